@@ -1,11 +1,13 @@
 use anyhow::{anyhow, Result};
-use common::log::{error, info};
+use common::log::{error, info, warn};
 use http::StatusCode;
 use itertools::Itertools;
 use mm2_net::transport::slurp_url;
-use std::fs::OpenOptions;
+use std::fs::{self, OpenOptions};
+use std::future::Future;
 use std::io::Write;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
 
 use crate::adex_config::AdexConfigImpl;
 use crate::error_anyhow;
@@ -17,16 +19,74 @@ pub(crate) async fn init_activation_scheme() -> Result<()> {
     let config_path = get_activation_scheme_path()?;
     info!("Start getting activation_scheme from: {config_path:?}");
 
+    let activation_scheme = get_activation_scheme_data().await?;
+    write_activation_scheme(&config_path, &activation_scheme)
+}
+
+/// Re-downloads the activation scheme if the cached one is missing or older than `max_age`, or if `force`d to.
+/// If the download fails, the stale cached scheme is kept.
+pub(crate) async fn refresh_activation_scheme(max_age: Duration, force: bool) -> Result<()> {
+    let config_path = get_activation_scheme_path()?;
+    refresh_activation_scheme_file(
+        &config_path,
+        max_age,
+        force,
+        SystemTime::now(),
+        get_activation_scheme_data(),
+    )
+    .await?;
+    Ok(())
+}
+
+/// Returns whether the activation scheme at `path` has been refreshed with the `download`ed data.
+pub(crate) async fn refresh_activation_scheme_file<F>(
+    path: &Path,
+    max_age: Duration,
+    force: bool,
+    now: SystemTime,
+    download: F,
+) -> Result<bool>
+where
+    F: Future<Output = Result<Vec<u8>>>,
+{
+    let modified = fs::metadata(path).and_then(|metadata| metadata.modified()).ok();
+    let is_stale = match modified {
+        // The modification time in the future is considered as fresh.
+        Some(modified) => now.duration_since(modified).map_or(false, |age| age > max_age),
+        None => true,
+    };
+    if !force && !is_stale {
+        return Ok(false);
+    }
+
+    if force {
+        info!("Refreshing activation scheme at {path:?}");
+    } else {
+        info!("Activation scheme at {path:?} is missing or older than {max_age:?}, refreshing");
+    }
+    match download.await {
+        Ok(activation_scheme) => {
+            write_activation_scheme(path, &activation_scheme)?;
+            Ok(true)
+        },
+        Err(error) if modified.is_some() => {
+            warn!("Failed to refresh activation_scheme, using the stale one: {error}");
+            Ok(false)
+        },
+        Err(error) => Err(error),
+    }
+}
+
+fn write_activation_scheme(path: &Path, activation_scheme: &[u8]) -> Result<()> {
     let mut writer = OpenOptions::new()
         .create(true)
         .truncate(true)
         .write(true)
-        .open(config_path)
+        .open(path)
         .map_err(|error| error_anyhow!("Failed to open activation_scheme file to write: {error}"))?;
 
-    let activation_scheme = get_activation_scheme_data().await?;
     writer
-        .write_all(&activation_scheme)
+        .write_all(activation_scheme)
         .map_err(|error| error_anyhow!("Failed to write activation_scheme: {error}"))
 }
 
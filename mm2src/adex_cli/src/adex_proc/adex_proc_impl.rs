@@ -3,12 +3,14 @@ use log::{debug, error, info, warn};
 use mm2_rpc::data::legacy::{BalanceResponse, CoinInitResponse, GetEnabledResponse, Mm2RpcResult, MmVersionResponse,
                             OrderbookRequest, OrderbookResponse, SellBuyRequest, SellBuyResponse, Status};
 use serde_json::{json, Value as Json};
+use uuid::Uuid;
 
 use super::command::{Command, Dummy, Method};
 use super::response_handler::ResponseHandler;
 use super::OrderbookConfig;
 use crate::activation_scheme_db::get_activation_scheme;
 use crate::adex_config::AdexConfig;
+use crate::rpc_data::SavedSwap;
 use crate::transport::Transport;
 use crate::{error_anyhow, error_bail, warn_anyhow};
 
@@ -133,6 +135,16 @@ impl<T: Transport, P: ResponseHandler, C: AdexConfig + 'static> AdexProc<'_, '_,
         request_legacy!(buy, Mm2RpcResult<SellBuyResponse>, self, on_buy_response)
     }
 
+    pub(crate) async fn get_swap_status(&self, uuid: &Uuid) -> Result<()> {
+        info!("Getting swap status, uuid: {uuid} ...");
+        let get_swap_status = Command::builder()
+            .userpass(self.get_rpc_password()?)
+            .method(Method::MySwapStatus)
+            .flatten_data(json!({ "params": { "uuid": uuid } }))
+            .build();
+        request_legacy!(get_swap_status, Mm2RpcResult<SavedSwap>, self, on_swap_status_response)
+    }
+
     pub(crate) async fn send_stop(&self) -> Result<()> {
         info!("Sending stop command");
         let stop_command = Command::<Dummy>::builder()
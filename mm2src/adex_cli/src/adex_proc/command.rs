@@ -27,6 +27,8 @@ pub(super) enum Method {
     GetOrderbook,
     Sell,
     Buy,
+    #[serde(rename = "my_swap_status")]
+    MySwapStatus,
 }
 
 #[derive(Serialize, Clone, Copy, Display)]
@@ -5,6 +5,7 @@ mod smart_fraction_fmt;
 pub(crate) use smart_fraction_fmt::SmartFractPrecision;
 
 use anyhow::{anyhow, Result};
+use chrono::{TimeZone, Utc};
 use itertools::Itertools;
 use log::{error, info};
 use mm2_rpc::data::legacy::{BalanceResponse, CoinInitResponse, GetEnabledResponse, Mm2RpcResult, MmVersionResponse,
@@ -17,6 +18,7 @@ use std::io::Write;
 use super::OrderbookConfig;
 use crate::adex_config::AdexConfig;
 use crate::error_anyhow;
+use crate::rpc_data::{SavedSwap, SwapType};
 use common::{write_safe::io::WriteSafeIO, write_safe_io, writeln_safe_io};
 
 pub(crate) trait ResponseHandler {
@@ -35,6 +37,7 @@ pub(crate) trait ResponseHandler {
     fn on_sell_response(&self, response: &Mm2RpcResult<SellBuyResponse>) -> Result<()>;
     fn on_buy_response(&self, response: &Mm2RpcResult<SellBuyResponse>) -> Result<()>;
     fn on_stop_response(&self, response: &Mm2RpcResult<Status>) -> Result<()>;
+    fn on_swap_status_response(&self, response: &Mm2RpcResult<SavedSwap>) -> Result<()>;
 }
 
 pub(crate) struct ResponseHandlerImpl<'a> {
@@ -190,6 +193,60 @@ impl ResponseHandler for ResponseHandlerImpl<'_> {
         writeln_safe_io!(self.writer.borrow_mut(), "Service stopped: {}", response.result);
         Ok(())
     }
+
+    /// Renders the swap events as a chronological timeline, marking the failed events and the current state.
+    fn on_swap_status_response(&self, response: &Mm2RpcResult<SavedSwap>) -> Result<()> {
+        let mut writer = self.writer.borrow_mut();
+        let swap = &response.result;
+        let swap_type = match swap.swap_type {
+            SwapType::Maker => "Maker",
+            SwapType::Taker => "Taker",
+        };
+        writeln_safe_io!(
+            writer,
+            "{swap_type} swap {}: {} -> {}",
+            swap.uuid,
+            swap.maker_coin.as_deref().unwrap_or("?"),
+            swap.taker_coin.as_deref().unwrap_or("?")
+        );
+
+        let is_error = |event_type: &str| swap.error_events.iter().any(|e| e == event_type);
+        let finished = swap
+            .events
+            .last()
+            .map_or(false, |last| last.event.event_type == "Finished");
+        for (i, event) in swap.events.iter().enumerate() {
+            let event_type = &event.event.event_type;
+            let mark = if is_error(event_type) {
+                "  <- failed"
+            } else if !finished && i + 1 == swap.events.len() {
+                "  <- current"
+            } else {
+                ""
+            };
+            writeln_safe_io!(writer, "{} {event_type}{mark}", format_timestamp_ms(event.timestamp));
+        }
+
+        let first_error = swap.events.iter().find(|event| is_error(&event.event.event_type));
+        match (first_error, finished) {
+            (Some(error), true) => writeln_safe_io!(writer, "Status: failed at {}", error.event.event_type),
+            (Some(error), false) => writeln_safe_io!(
+                writer,
+                "Status: failing at {}, still in progress",
+                error.event.event_type
+            ),
+            (None, true) => writeln_safe_io!(writer, "Status: finished successfully"),
+            (None, false) => writeln_safe_io!(writer, "Status: in progress"),
+        }
+        Ok(())
+    }
+}
+
+fn format_timestamp_ms(timestamp_ms: u64) -> String {
+    match Utc.timestamp_millis_opt(timestamp_ms as i64).single() {
+        Some(datetime) => datetime.format("%Y-%m-%d %H:%M:%S").to_string(),
+        None => timestamp_ms.to_string(),
+    }
 }
 
 struct SimpleCliTable<'a> {
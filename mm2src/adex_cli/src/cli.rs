@@ -7,8 +7,10 @@ use rpc::v1::types::H256 as H256Json;
 use std::collections::HashSet;
 use std::mem::take;
 use std::str::FromStr;
+use std::time::Duration;
 use uuid::Uuid;
 
+use crate::activation_scheme_db::refresh_activation_scheme;
 use crate::adex_config::{get_config, set_config, AdexConfig};
 use crate::adex_proc::{AdexProc, OrderbookConfig, ResponseHandler};
 use crate::scenarios::{get_status, init, start_process, stop_process};
@@ -18,6 +20,7 @@ const MM2_CONFIG_FILE_DEFAULT: &str = "MM2.json";
 const COINS_FILE_DEFAULT: &str = "coins";
 const ORDERBOOK_BIDS_LIMIT: &str = "20";
 const ORDERBOOK_ASKS_LIMIT: &str = "20";
+const ACTIVATION_SCHEME_MAX_AGE_HOURS: u64 = 24;
 
 #[derive(Subcommand)]
 enum Command {
@@ -51,6 +54,14 @@ enum Command {
     Enable {
         #[arg(name = "ASSET", help = "Asset to be included into the trading index")]
         asset: String,
+        #[arg(long, help = "Re-download the activation scheme regardless of its age")]
+        refresh: bool,
+        #[arg(
+            long,
+            help = "Re-download the activation scheme if the cached one is older than this number of hours",
+            default_value_t = ACTIVATION_SCHEME_MAX_AGE_HOURS
+        )]
+        scheme_max_age: u64,
     },
     #[command(about = "Gets balance of an asset")]
     Balance {
@@ -72,6 +83,11 @@ enum Command {
         #[command(flatten)]
         order_args: BuyOrderCli,
     },
+    #[command(about = "Shows the events timeline of a swap")]
+    SwapStatus {
+        #[arg(name = "UUID", help = "Uuid of the swap")]
+        uuid: Uuid,
+    },
 }
 
 #[derive(Subcommand)]
@@ -131,7 +147,15 @@ impl Cli {
                 set_config(*set_password, adex_uri.take())?
             },
             Command::Config(ConfigSubcommand::Get) => get_config(),
-            Command::Enable { asset } => proc.enable(asset).await?,
+            Command::Enable {
+                asset,
+                refresh,
+                scheme_max_age,
+            } => {
+                let max_age = Duration::from_secs(scheme_max_age.saturating_mul(3600));
+                refresh_activation_scheme(max_age, *refresh).await?;
+                proc.enable(asset).await?
+            },
             Command::Balance { asset } => proc.get_balance(asset).await?,
             Command::GetEnabled => proc.get_enabled().await?,
             Command::Orderbook { ref orderbook_args } => {
@@ -148,6 +172,7 @@ impl Cli {
             Command::Buy {
                 order_args: BuyOrderCli { order_cli },
             } => proc.buy(SellBuyRequest::from(order_cli)).await?,
+            Command::SwapStatus { uuid } => proc.get_swap_status(uuid).await?,
         }
         Ok(())
     }
@@ -5,6 +5,7 @@
 
 use mm2_rpc::data::legacy::{ElectrumProtocol, UtxoMergeParams};
 use serde::{Deserialize, Serialize};
+use uuid::Uuid;
 
 #[derive(Debug, Deserialize, Serialize)]
 #[serde(tag = "method", rename_all = "lowercase")]
@@ -68,3 +69,37 @@ pub(super) struct Server {
     disable_cert_verification: bool,
     pub timeout_sec: Option<u64>,
 }
+
+#[derive(Debug, Deserialize)]
+pub(crate) enum SwapType {
+    Maker,
+    Taker,
+}
+
+/// A saved maker or taker swap as returned by `my_swap_status`.
+/// Only the fields required to render the swap timeline are deserialized.
+#[derive(Debug, Deserialize)]
+pub(crate) struct SavedSwap {
+    #[serde(rename = "type")]
+    pub(crate) swap_type: SwapType,
+    pub(crate) uuid: Uuid,
+    pub(crate) maker_coin: Option<String>,
+    pub(crate) taker_coin: Option<String>,
+    pub(crate) events: Vec<SavedSwapEvent>,
+    /// The event types considered as failures.
+    #[serde(default)]
+    pub(crate) error_events: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct SavedSwapEvent {
+    /// Milliseconds since the Unix epoch.
+    pub(crate) timestamp: u64,
+    pub(crate) event: SwapEventData,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct SwapEventData {
+    #[serde(rename = "type")]
+    pub(crate) event_type: String,
+}
@@ -3,7 +3,8 @@ use std::time::Duration;
 use tokio::io::AsyncWriteExt;
 use tokio::net::{TcpListener, TcpStream};
 
-use crate::activation_scheme_db::{get_activation_scheme, get_activation_scheme_path, init_activation_scheme};
+use crate::activation_scheme_db::{get_activation_scheme, get_activation_scheme_path, init_activation_scheme,
+                                  refresh_activation_scheme_file};
 use crate::adex_config::AdexConfigImpl;
 use crate::adex_proc::ResponseHandlerImpl;
 use crate::cli::Cli;
@@ -171,6 +172,71 @@ async fn test_buy_morty_for_rick() {
     assert_eq!("Buy order uuid: 4685e133-dfb3-4b31-8d4c-0ffa79933c8e\n", result);
 }
 
+#[tokio::test]
+async fn test_swap_status() {
+    tokio::spawn(fake_mm2_server(
+        7792,
+        include_bytes!("http_mock_data/my_swap_status.http"),
+    ));
+    tokio::time::sleep(Duration::from_millis(FAKE_SERVER_WARMUP_TIMEOUT_MS)).await;
+    let mut buffer: Vec<u8> = vec![];
+    let response_handler = ResponseHandlerImpl {
+        writer: (&mut buffer as &mut dyn Write).into(),
+    };
+    let config = AdexConfigImpl::new("dummy", "http://127.0.0.1:7792");
+    let args = vec!["adex-cli", "swap-status", "2a5a3a7c-5e3c-4a71-9ba2-7a5bb6c1a1f2"];
+    Cli::execute(args.iter().map(|arg| arg.to_string()), &config, &response_handler)
+        .await
+        .unwrap();
+
+    let result = String::from_utf8(buffer).unwrap();
+    assert_eq!(SWAP_STATUS, result);
+}
+
+#[tokio::test]
+async fn test_refresh_stale_activation_scheme() {
+    const MAX_AGE: Duration = Duration::from_secs(3600);
+
+    let path = std::env::temp_dir().join("adex_cli_test_refresh_activation_scheme.json");
+    std::fs::write(&path, "stale").unwrap();
+    let now = std::time::SystemTime::now();
+    let download = |data: &'static str| async move { Ok(data.as_bytes().to_vec()) };
+
+    // The cache is fresh, nothing is downloaded.
+    let refreshed = refresh_activation_scheme_file(&path, MAX_AGE, false, now, download("fresh"))
+        .await
+        .unwrap();
+    assert!(!refreshed);
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "stale");
+
+    // Unless the refresh is forced.
+    let refreshed = refresh_activation_scheme_file(&path, MAX_AGE, true, now, download("forced"))
+        .await
+        .unwrap();
+    assert!(refreshed);
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "forced");
+    std::fs::write(&path, "stale").unwrap();
+
+    // Pretend the cache is older than `MAX_AGE`.
+    let later = now + MAX_AGE * 2;
+
+    // The download fails, the stale cache is kept.
+    let failed_download = async { Err(anyhow::anyhow!("Network is unreachable")) };
+    let refreshed = refresh_activation_scheme_file(&path, MAX_AGE, false, later, failed_download)
+        .await
+        .unwrap();
+    assert!(!refreshed);
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "stale");
+
+    let refreshed = refresh_activation_scheme_file(&path, MAX_AGE, false, later, download("fresh"))
+        .await
+        .unwrap();
+    assert!(refreshed);
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "fresh");
+
+    std::fs::remove_file(&path).unwrap();
+}
+
 async fn fake_mm2_server(port: u16, predefined_response: &'static [u8]) {
     let server = TcpListener::bind(("0.0.0.0", port))
         .await
@@ -253,3 +319,12 @@ unspendable_balance: 0
 required_confirmations: 3
 requires_notarization: No
 ";
+
+const SWAP_STATUS: &str = r"Taker swap 2a5a3a7c-5e3c-4a71-9ba2-7a5bb6c1a1f2: RICK -> MORTY
+2023-04-07 07:00:00 Started
+2023-04-07 07:00:30 Negotiated
+2023-04-07 07:00:35 TakerFeeSent
+2023-04-07 07:01:40 MakerPaymentValidateFailed  <- failed
+2023-04-07 07:01:41 Finished
+Status: failed at MakerPaymentValidateFailed
+";
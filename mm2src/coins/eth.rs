@@ -77,6 +77,7 @@ use ethereum_types::{Address, H160, H256, U256};
 use ethkey::{public_to_address, sign, verify_address, KeyPair, Public, Signature};
 use futures::compat::Future01CompatExt;
 use futures::future::{join, join_all, select_ok, try_join_all, Either, FutureExt, TryFutureExt};
+use futures::stream::BoxStream;
 use futures01::Future;
 use http::Uri;
 use kdf_walletconnect::{WalletConnectCtx, WalletConnectOps};
@@ -116,11 +117,11 @@ use super::{coin_conf, lp_coinfind_or_err, AsyncMutex, BalanceError, BalanceFut,
             PaymentInstructionArgs, PaymentInstructions, PaymentInstructionsErr, PrivKeyBuildPolicy,
             PrivKeyPolicyNotAllowed, RawTransactionError, RawTransactionFut, RawTransactionRequest, RawTransactionRes,
             RawTransactionResult, RefundPaymentArgs, RewardTarget, RpcClientType, RpcTransportEventHandler,
-            RpcTransportEventHandlerShared, SearchForSwapTxSpendInput, SendMakerPaymentSpendPreimageInput,
-            SendPaymentArgs, SignEthTransactionParams, SignRawTransactionEnum, SignRawTransactionRequest,
-            SignatureError, SignatureResult, SpendPaymentArgs, SwapOps, SwapTxFeePolicy, TradeFee, TradePreimageError,
-            TradePreimageFut, TradePreimageResult, TradePreimageValue, Transaction, TransactionDetails,
-            TransactionEnum, TransactionErr, TransactionFut, TransactionType, TxMarshalingErr,
+            RpcTransportEventHandlerShared, RpcTransportTrafficStats, SearchForSwapTxSpendInput,
+            SendMakerPaymentSpendPreimageInput, SendPaymentArgs, SignEthTransactionParams, SignRawTransactionEnum,
+            SignRawTransactionRequest, SignatureError, SignatureResult, SpendPaymentArgs, SwapOps, SwapTxFeePolicy,
+            TradeFee, TradePreimageError, TradePreimageFut, TradePreimageResult, TradePreimageValue, Transaction,
+            TransactionDetails, TransactionEnum, TransactionErr, TransactionFut, TransactionType, TxMarshalingErr,
             UnexpectedDerivationMethod, ValidateAddressResult, ValidateFeeArgs, ValidateInstructionsErr,
             ValidateOtherPubKeyErr, ValidatePaymentError, ValidatePaymentFut, ValidatePaymentInput, VerificationError,
             VerificationResult, WaitForHTLCTxSpendArgs, WatcherOps, WatcherReward, WatcherRewardError,
@@ -617,6 +618,33 @@ impl From<web3::Error> for Web3RpcError {
     }
 }
 
+/// Yielded by [`EthCoin::watch_allowance`] when the allowance of the `spender` drops below the `required` amount.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AllowanceDropped {
+    pub spender: Address,
+    pub allowance: U256,
+    pub required: U256,
+}
+
+/// Error returned by [`EthCoin::check_nonce_gap`].
+#[derive(Debug, Display)]
+pub enum NonceGapError {
+    /// The transaction with the `expected` nonce is not mined yet,
+    /// so the transactions queued up to the `actual` pending nonce are stuck behind it.
+    #[display(
+        fmt = "Nonce gap detected: the next nonce to be mined is {}, but the pending nonce is {}",
+        expected,
+        actual
+    )]
+    NonceGapDetected { expected: U256, actual: U256 },
+    #[display(fmt = "{}", _0)]
+    Web3RpcError(Web3RpcError),
+}
+
+impl From<Web3RpcError> for NonceGapError {
+    fn from(e: Web3RpcError) -> Self { NonceGapError::Web3RpcError(e) }
+}
+
 impl From<Web3RpcError> for RawTransactionError {
     fn from(e: Web3RpcError) -> Self {
         match e {
@@ -859,9 +887,14 @@ pub struct EthCoinImpl {
     /// platform coin for tokens and vice versa.
     derivation_method: Arc<EthDerivationMethod>,
     sign_message_prefix: Option<String>,
+    /// The primary swap contract, new swaps are always started with it.
     swap_contract_address: Address,
     swap_v2_contracts: Option<SwapV2Contracts>,
-    fallback_swap_contract: Option<Address>,
+    /// The swap contracts accepted besides `swap_contract_address` for the swaps started with them,
+    /// e.g. before the primary contract was migrated.
+    /// They are only used to negotiate and validate such swaps: the allowance is always read for
+    /// `swap_contract_address` since new payments are sent to it, and balances don't depend on a swap contract.
+    fallback_swap_contracts: Vec<Address>,
     contract_supports_watchers: bool,
     web3_instances: AsyncMutex<Vec<Web3Instance>>,
     decimals: u8,
@@ -902,6 +935,15 @@ pub struct Web3Instance {
     is_parity: bool,
 }
 
+/// Diagnostics of the transport to one of the RPC nodes, see [`EthCoin::rpc_transports_diagnostics`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct RpcTransportDiagnostics {
+    /// The number of event handlers attached to the transport.
+    pub event_handlers_count: usize,
+    /// The traffic counted by the coin's [`CoinTransportMetrics`] handler, if it's attached.
+    pub traffic: Option<RpcTransportTrafficStats>,
+}
+
 /// Information about a token that follows the ERC20 protocol on an EVM-based network.
 #[derive(Clone, Debug)]
 pub struct Erc20TokenDetails {
@@ -919,6 +961,50 @@ pub struct SwapV2Contracts {
     pub nft_maker_swap_v2_contract: Address,
 }
 
+/// `swap_contract_address` of an ETH activation request: either a single address,
+/// or a list where the first address is the primary contract and the rest are the fallbacks.
+/// The fallbacks are accepted for the swaps started with them, while new swaps always use the primary contract.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(untagged)]
+pub enum SwapContractAddresses {
+    Single(Address),
+    List(Vec<Address>),
+}
+
+impl From<Address> for SwapContractAddresses {
+    fn from(address: Address) -> Self { SwapContractAddresses::Single(address) }
+}
+
+impl SwapContractAddresses {
+    /// Splits the addresses into the primary swap contract and the fallbacks,
+    /// with the `fallback_swap_contract` of the request appended to the latter.
+    /// Returns an error if there are no addresses or any of them is zero.
+    pub fn into_primary_and_fallbacks(
+        self,
+        fallback_swap_contract: Option<Address>,
+    ) -> Result<(Address, Vec<Address>), String> {
+        let addresses = match self {
+            SwapContractAddresses::Single(address) => vec![address],
+            SwapContractAddresses::List(addresses) => addresses,
+        };
+        if addresses.iter().any(|address| *address == Address::default()) {
+            return Err("swap_contract_address can't be zero address".to_string());
+        }
+
+        let mut addresses = addresses.into_iter().chain(fallback_swap_contract);
+        let primary = addresses
+            .next()
+            .ok_or_else(|| "swap_contract_address can't be empty".to_string())?;
+        let mut fallbacks: Vec<Address> = Vec::new();
+        for address in addresses {
+            if address != primary && !fallbacks.contains(&address) {
+                fallbacks.push(address);
+            }
+        }
+        Ok((primary, fallbacks))
+    }
+}
+
 #[derive(Deserialize, Serialize)]
 #[serde(tag = "format")]
 pub enum EthAddressFormat {
@@ -1560,13 +1646,14 @@ impl SwapOps for EthCoin {
                     return Ok(Some(self.swap_contract_address.0.to_vec().into()));
                 }
 
-                if Some(other_addr) == self.fallback_swap_contract {
-                    return Ok(self.fallback_swap_contract.map(|addr| addr.0.to_vec().into()));
+                if self.fallback_swap_contracts.contains(&other_addr) {
+                    return Ok(Some(other_addr.0.to_vec().into()));
                 }
                 MmError::err(NegotiateSwapContractAddrErr::UnexpectedOtherAddr(bytes.into()))
             },
             None => self
-                .fallback_swap_contract
+                .fallback_swap_contracts
+                .first()
                 .map(|addr| Some(addr.0.to_vec().into()))
                 .ok_or_else(|| MmError::new(NegotiateSwapContractAddrErr::NoOtherAddrAndNoFallback)),
         }
@@ -1994,7 +2081,7 @@ impl WatcherOps for EthCoin {
             input.secret_hash.to_vec()
         };
         let expected_swap_contract_address = self.swap_contract_address;
-        let fallback_swap_contract = self.fallback_swap_contract;
+        let fallback_swap_contracts = self.fallback_swap_contracts.clone();
 
         let fut = async move {
             let tx_from_rpc = selfi.transaction(TransactionId::Hash(tx.tx_hash())).await?;
@@ -2016,10 +2103,10 @@ impl WatcherOps for EthCoin {
             })?;
 
             if swap_contract_address != expected_swap_contract_address
-                && Some(swap_contract_address) != fallback_swap_contract
+                && !fallback_swap_contracts.contains(&swap_contract_address)
             {
                 return MmError::err(ValidatePaymentError::WrongPaymentTx(format!(
-                    "{INVALID_CONTRACT_ADDRESS_ERR_LOG}: Payment tx {tx_from_rpc:?} was sent to wrong address, expected either {expected_swap_contract_address:?} or the fallback {fallback_swap_contract:?}"
+                    "{INVALID_CONTRACT_ADDRESS_ERR_LOG}: Payment tx {tx_from_rpc:?} was sent to wrong address, expected either {expected_swap_contract_address:?} or one of the fallbacks {fallback_swap_contracts:?}"
                 )));
             }
 
@@ -4840,6 +4927,49 @@ impl EthCoin {
         Box::new(fut.boxed().compat())
     }
 
+    /// Polls the allowance granted to the `spender` every `check_every` seconds and yields [`AllowanceDropped`]
+    /// each time it falls below the `required` amount, e.g. when the approval was changed by another dapp.
+    /// Failed allowance requests are yielded as errors and don't stop the polling.
+    pub fn watch_allowance(
+        &self,
+        spender: Address,
+        required: U256,
+        check_every: f64,
+    ) -> BoxStream<'static, Web3RpcResult<AllowanceDropped>> {
+        let coin = self.clone();
+        // The state is whether the allowance was sufficient on the previous check and whether to poll without a delay.
+        Box::pin(futures::stream::unfold(
+            (true, true),
+            move |(mut was_sufficient, mut poll_now)| {
+                let coin = coin.clone();
+                async move {
+                    loop {
+                        if !poll_now {
+                            Timer::sleep(check_every).await;
+                        }
+                        poll_now = false;
+
+                        let allowance = match coin.allowance(spender).compat().await {
+                            Ok(allowance) => allowance,
+                            Err(e) => return Some((Err(e), (was_sufficient, poll_now))),
+                        };
+                        let is_sufficient = allowance >= required;
+                        let dropped = was_sufficient && !is_sufficient;
+                        was_sufficient = is_sufficient;
+                        if dropped {
+                            let event = AllowanceDropped {
+                                spender,
+                                allowance,
+                                required,
+                            };
+                            return Some((Ok(event), (was_sufficient, poll_now)));
+                        }
+                    }
+                }
+            },
+        ))
+    }
+
     fn wait_for_required_allowance(
         &self,
         spender: Address,
@@ -5412,10 +5542,10 @@ impl EthCoin {
 
         let eth_fee_history_price_fut = async {
             match coin.eth_fee_history(U256::from(1u64), BlockNumber::Latest, &[]).await {
+                // The history of a single block starts from the latest one.
                 Ok(res) => res
-                    .base_fee_per_gas
-                    .first()
-                    .map(|val| increase_by_percent_one_gwei(*val, BASE_BLOCK_FEE_DIFF_PCT)),
+                    .base_fee_at_block(res.oldest_block)
+                    .map(|val| increase_by_percent_one_gwei(val, BASE_BLOCK_FEE_DIFF_PCT)),
                 Err(e) => {
                     debug!("Error {} on eth_feeHistory request", e);
                     None
@@ -5704,6 +5834,44 @@ impl EthCoin {
         };
         Box::new(Box::pin(fut).compat())
     }
+
+    /// Returns the diagnostics of the transports to the RPC nodes of the coin, e.g. to find a node
+    /// whose event handlers aren't attached or don't observe the traffic.
+    pub async fn rpc_transports_diagnostics(&self) -> Vec<RpcTransportDiagnostics> {
+        self.web3_instances
+            .lock()
+            .await
+            .iter()
+            .map(|instance| {
+                let transport = instance.web3.transport();
+                RpcTransportDiagnostics {
+                    event_handlers_count: transport.event_handlers_count(),
+                    traffic: transport.event_handler_traffic_stats(CoinTransportMetrics::NAME),
+                }
+            })
+            .collect()
+    }
+
+    /// Checks whether transactions of the given address are stuck behind a not mined one,
+    /// e.g. when a transaction with a lower nonce was dropped from the mempool or is underpriced.
+    /// Returns the pending nonce if there is at most one transaction waiting to be mined.
+    pub async fn check_nonce_gap(&self, addr: Address) -> MmResult<U256, NonceGapError> {
+        let (latest, pending) = self
+            .latest_and_pending_transaction_counts(addr)
+            .await
+            .map_to_mm(Web3RpcError::from)?;
+        if pending > latest + 1 {
+            warn!(
+                "{} nonce gap for {:?}: latest nonce {}, pending nonce {}",
+                self.ticker, addr, latest, pending
+            );
+            return MmError::err(NonceGapError::NonceGapDetected {
+                expected: latest,
+                actual: pending,
+            });
+        }
+        Ok(pending)
+    }
 }
 
 #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
@@ -6013,8 +6181,11 @@ impl MmCoin for EthCoin {
         Some(BytesJson::from(self.swap_contract_address.0.as_ref()))
     }
 
-    fn fallback_swap_contract(&self) -> Option<BytesJson> {
-        self.fallback_swap_contract.map(|a| BytesJson::from(a.0.as_ref()))
+    fn fallback_swap_contracts(&self) -> Vec<BytesJson> {
+        self.fallback_swap_contracts
+            .iter()
+            .map(|a| BytesJson::from(a.0.as_ref()))
+            .collect()
     }
 
     fn mature_confirmations(&self) -> Option<u32> { None }
@@ -6503,16 +6674,15 @@ pub async fn eth_coin_from_conf_and_request(
     let mut rng = small_rng();
     urls.as_mut_slice().shuffle(&mut rng);
 
-    let swap_contract_address: Address = try_s!(json::from_value(req["swap_contract_address"].clone()));
-    if swap_contract_address == Address::default() {
-        return ERR!("swap_contract_address can't be zero address");
-    }
+    let swap_contract_addresses: SwapContractAddresses = try_s!(json::from_value(req["swap_contract_address"].clone()));
     let fallback_swap_contract: Option<Address> = try_s!(json::from_value(req["fallback_swap_contract"].clone()));
     if let Some(fallback) = fallback_swap_contract {
         if fallback == Address::default() {
             return ERR!("fallback_swap_contract can't be zero address");
         }
     }
+    let (swap_contract_address, fallback_swap_contracts) =
+        try_s!(swap_contract_addresses.into_primary_and_fallbacks(fallback_swap_contract));
     let contract_supports_watchers = req["contract_supports_watchers"].as_bool().unwrap_or_default();
 
     let path_to_address = try_s!(json::from_value::<Option<HDPathAccountToAddressId>>(
@@ -6664,7 +6834,7 @@ pub async fn eth_coin_from_conf_and_request(
         sign_message_prefix,
         swap_contract_address,
         swap_v2_contracts: None,
-        fallback_swap_contract,
+        fallback_swap_contracts,
         contract_supports_watchers,
         decimals,
         ticker: ticker.into(),
@@ -7501,7 +7671,7 @@ impl EthCoin {
             sign_message_prefix: self.sign_message_prefix.clone(),
             swap_contract_address: self.swap_contract_address,
             swap_v2_contracts: self.swap_v2_contracts,
-            fallback_swap_contract: self.fallback_swap_contract,
+            fallback_swap_contracts: self.fallback_swap_contracts.clone(),
             contract_supports_watchers: self.contract_supports_watchers,
             web3_instances: AsyncMutex::new(self.web3_instances.lock().await.clone()),
             decimals: self.decimals,
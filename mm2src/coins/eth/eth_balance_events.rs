@@ -153,6 +153,8 @@ impl EventStreamer for EthBalanceEventStreamer {
         }
     }
 
+    fn config(&self) -> Option<Json> { Some(json!({ "stream_interval_seconds": self.interval })) }
+
     async fn handle(
         self,
         broadcaster: Broadcaster,
@@ -6,6 +6,7 @@ use super::web3_transport::FeeHistoryResult;
 use super::{web3_transport::Web3Transport, EthCoin};
 use common::{custom_futures::timeout::FutureTimerExt, log::debug};
 use compatible_time::Duration;
+#[cfg(test)] use mocktopus::macros::*;
 use serde_json::Value;
 use web3::types::{Address, Block, BlockId, BlockNumber, Bytes, CallRequest, FeeHistory, Filter, Log, Proof, SyncState,
                   Trace, TraceFilter, Transaction, TransactionId, TransactionReceipt, TransactionRequest, Work, H256,
@@ -14,6 +15,7 @@ use web3::{helpers, Transport};
 
 pub(crate) const ETH_RPC_REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
 
+#[cfg_attr(test, mockable)]
 impl EthCoin {
     async fn try_rpc_send(&self, method: &str, params: Vec<jsonrpc_core::Value>) -> Result<Value, web3::Error> {
         let mut clients = self.web3_instances.lock().await;
@@ -56,6 +58,54 @@ impl EthCoin {
 
         Err(error)
     }
+
+    /// Sends the given `calls` as a single JSON-RPC batch request, rotating the transports like [`EthCoin::try_rpc_send`].
+    /// Returns the results ordered as the `calls`.
+    async fn try_rpc_send_batch(
+        &self,
+        calls: Vec<(&str, Vec<jsonrpc_core::Value>)>,
+    ) -> Result<Vec<Result<Value, web3::Error>>, web3::Error> {
+        let mut clients = self.web3_instances.lock().await;
+
+        let mut error = web3::Error::Unreachable;
+        for (i, client) in clients.clone().into_iter().enumerate() {
+            let transport = client.web3.transport();
+            if let Web3Transport::Websocket(socket) = transport {
+                socket.maybe_spawn_connection_loop(self.clone());
+            }
+            let requests = calls
+                .iter()
+                .map(|(method, params)| transport.prepare(method, params.clone()))
+                .collect();
+
+            match transport.send_batch(requests).timeout(ETH_RPC_REQUEST_TIMEOUT).await {
+                Ok(results) if results.iter().any(|result| result.is_ok()) => {
+                    // Bring the live client to the front of rpc_clients
+                    clients.rotate_left(i);
+                    return Ok(results);
+                },
+                Ok(results) => {
+                    debug!("Batch request failed. Errors: {results:?}");
+                    if let Some(Err(err)) = results.into_iter().next() {
+                        error = err;
+                    }
+
+                    if let Web3Transport::Websocket(socket_transport) = transport {
+                        socket_transport.stop_connection_loop().await;
+                    };
+                },
+                Err(timeout_error) => {
+                    debug!("Timeout exceed for batch request. Error: {timeout_error}");
+
+                    if let Web3Transport::Websocket(socket_transport) = transport {
+                        socket_transport.stop_connection_loop().await;
+                    };
+                },
+            };
+        }
+
+        Err(error)
+    }
 }
 
 #[allow(dead_code)]
@@ -287,6 +337,35 @@ impl EthCoin {
             .and_then(|t| serde_json::from_value(t).map_err(Into::into))
     }
 
+    /// Get the `latest` (mined) and `pending` (mined + mempool) nonces in a single batch request
+    pub(crate) async fn latest_and_pending_transaction_counts(
+        &self,
+        address: Address,
+    ) -> Result<(U256, U256), web3::Error> {
+        let address = helpers::serialize(&address);
+        let calls = [BlockNumber::Latest, BlockNumber::Pending]
+            .iter()
+            .map(|block| {
+                ("eth_getTransactionCount", vec![
+                    address.clone(),
+                    helpers::serialize(block),
+                ])
+            })
+            .collect();
+
+        let mut counts = self
+            .try_rpc_send_batch(calls)
+            .await?
+            .into_iter()
+            .map(|result| result.and_then(|t| serde_json::from_value(t).map_err(Into::into)));
+        match (counts.next(), counts.next()) {
+            (Some(latest), Some(pending)) => Ok((latest?, pending?)),
+            _ => Err(web3::Error::InvalidResponse(
+                "Unexpected number of batch results".to_owned(),
+            )),
+        }
+    }
+
     /// Get transaction
     pub(crate) async fn transaction(&self, id: TransactionId) -> Result<Option<Transaction>, web3::Error> {
         let result = match id {
@@ -986,6 +986,240 @@ fn test_eth_validate_valid_and_invalid_pubkey() {
     assert!(coin.validate_other_pubkey(&[1u8; 8]).is_err());
 }
 
+#[test]
+fn test_decode_contract_call_short_input() {
+    let function = ERC20_CONTRACT.function("transfer").unwrap();
+
+    let err = decode_contract_call(function, &[0xa9, 0x05]).unwrap_err();
+    match err {
+        ethabi::Error::Other(msg) => assert!(msg.contains("at least 4 bytes"), "{}", msg),
+        e => panic!("Expected ethabi::Error::Other, found {:?}", e),
+    }
+
+    let approve = ERC20_CONTRACT.function("approve").unwrap();
+    let approve_call = approve
+        .encode_input(&[Token::Address(Address::default()), Token::Uint(1.into())])
+        .unwrap();
+    let err = decode_contract_call(function, &approve_call).unwrap_err();
+    match err {
+        ethabi::Error::Other(msg) => assert!(msg.contains("Unexpected contract call signature"), "{}", msg),
+        e => panic!("Expected ethabi::Error::Other, found {:?}", e),
+    }
+}
+
+#[test]
+fn test_watch_allowance_drop() {
+    use futures::StreamExt;
+
+    static ALLOWANCES: [u64; 4] = [100, 100, 50, 40];
+    static mut CALLS: usize = 0;
+
+    EthCoin::allowance.mock_safe(|_, _| {
+        let allowance = unsafe {
+            let allowance = ALLOWANCES[CALLS.min(ALLOWANCES.len() - 1)];
+            CALLS += 1;
+            allowance
+        };
+        MockResult::Return(Box::new(futures01::future::ok(allowance.into())))
+    });
+
+    let (_ctx, coin) = eth_coin_for_test(
+        EthCoinType::Erc20 {
+            platform: ETH.to_string(),
+            token_addr: Address::default(),
+        },
+        &["http://dummy.dummy"],
+        None,
+        ETH_SEPOLIA_CHAIN_ID,
+    );
+    let spender = Address::from_low_u64_be(1);
+
+    let mut stream = coin.watch_allowance(spender, 80.into(), 0.);
+    let event = block_on(stream.next()).unwrap().unwrap();
+    assert_eq!(event, AllowanceDropped {
+        spender,
+        allowance: 50.into(),
+        required: 80.into(),
+    });
+    // The allowance was already checked 3 times, the 4th check (still insufficient) must not emit one more event.
+    assert_eq!(unsafe { CALLS }, 3);
+}
+
+#[test]
+fn test_check_nonce_gap() {
+    let (_ctx, coin) = eth_coin_for_test(EthCoinType::Eth, &["http://dummy.dummy"], None, ETH_SEPOLIA_CHAIN_ID);
+    let address = Address::from_low_u64_be(1);
+
+    // One transaction waiting to be mined is not a gap.
+    EthCoin::latest_and_pending_transaction_counts
+        .mock_safe(|_, _| MockResult::Return(Box::pin(futures::future::ok((5.into(), 6.into())))));
+    assert_eq!(block_on(coin.check_nonce_gap(address)).unwrap(), U256::from(6));
+
+    EthCoin::latest_and_pending_transaction_counts
+        .mock_safe(|_, _| MockResult::Return(Box::pin(futures::future::ok((5.into(), 8.into())))));
+    let err = block_on(coin.check_nonce_gap(address)).unwrap_err().into_inner();
+    match err {
+        NonceGapError::NonceGapDetected { expected, actual } => {
+            assert_eq!(expected, U256::from(5));
+            assert_eq!(actual, U256::from(8));
+        },
+        e => panic!("Expected NonceGapDetected, found {:?}", e),
+    }
+}
+
+#[test]
+#[cfg(not(target_arch = "wasm32"))]
+fn test_latest_and_pending_transaction_counts_single_batch() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    let requests_count = Arc::new(AtomicUsize::new(0));
+    let requests_count_c = requests_count.clone();
+    let port = spawn_mock_http_server(move |request| {
+        requests_count_c.fetch_add(1, Ordering::SeqCst);
+        let calls: Vec<Json> = serde_json::from_slice(request).unwrap();
+        let outputs: Vec<Json> = calls
+            .iter()
+            .map(|call| {
+                assert_eq!(call["method"], "eth_getTransactionCount");
+                let count = if call["params"][1] == "pending" { "0x7" } else { "0x5" };
+                json!({"jsonrpc": "2.0", "id": call["id"], "result": count})
+            })
+            .collect();
+        serde_json::to_string(&outputs).unwrap()
+    });
+
+    let url = format!("http://127.0.0.1:{}", port);
+    let (_ctx, coin) = eth_coin_for_test(EthCoinType::Eth, &[&url], None, ETH_SEPOLIA_CHAIN_ID);
+    let nonces = block_on(coin.latest_and_pending_transaction_counts(Address::from_low_u64_be(1))).unwrap();
+    assert_eq!(nonces, (U256::from(5), U256::from(7)));
+    assert_eq!(requests_count.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn test_eth_activation_with_fallback_swap_contracts() {
+    let ctx = MmCtxBuilder::new().into_mm_arc();
+    let conf = json!({
+      "coin": "MATIC",
+      "name": "matic",
+      "fname": "Polygon",
+      "rpcport": 80,
+      "mm2": 1,
+      "avg_blocktime": 0.03,
+      "required_confirmations": 3,
+      "protocol": {
+        "type": "ETH",
+        "protocol_data": {
+            "chain_id": MATIC_CHAIN_ID
+        }
+      }
+    });
+
+    let primary = Address::from_str("0x9130b257d37a52e52f21054c4da3450c72f595ce").unwrap();
+    let first_fallback = Address::from_str("0x2b3b3a1c2b67b0e3e8b6b0e67e2ef4d7ad9c5d1a").unwrap();
+    let second_fallback = Address::from_str("0x7e6bbb19b9a14fd9d6a7bd0e4a1e0e5dcb7bd1a2").unwrap();
+    let request = json!({
+        "method": "enable",
+        "coin": "MATIC",
+        "urls": ["https://polygon-mainnet.g.alchemy.com/v2/9YYl6iMLmXXLoflMPHnMTC4Dcm2L2tFH"],
+        "swap_contract_address": [primary, first_fallback, second_fallback],
+    });
+
+    let priv_key_policy = PrivKeyBuildPolicy::IguanaPrivKey(IguanaPrivKey::from([1u8; 32]));
+    let coin = block_on(eth_coin_from_conf_and_request(
+        &ctx,
+        "MATIC",
+        &conf,
+        &request,
+        CoinProtocol::ETH {
+            chain_id: MATIC_CHAIN_ID,
+        },
+        priv_key_policy,
+    ))
+    .unwrap();
+
+    assert_eq!(coin.swap_contract_address, primary);
+    assert_eq!(coin.fallback_swap_contracts, vec![first_fallback, second_fallback]);
+    assert_eq!(MmCoin::fallback_swap_contracts(&coin), vec![
+        BytesJson::from(first_fallback.0.as_ref()),
+        BytesJson::from(second_fallback.0.as_ref()),
+    ]);
+    // New swaps are always started with the primary contract.
+    assert_eq!(
+        MmCoin::swap_contract_address(&coin),
+        Some(BytesJson::from(primary.0.as_ref()))
+    );
+    // But the swaps offered with any of the fallbacks are still accepted.
+    let negotiated = coin
+        .negotiate_swap_contract_addr(Some(second_fallback.as_bytes()))
+        .unwrap();
+    assert_eq!(negotiated, Some(BytesJson::from(second_fallback.0.as_ref())));
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[test]
+fn test_erc20_sender_trade_fee_ignores_fallback_allowance() {
+    const APPROVE_GAS_LIMIT: u64 = 60_000;
+    let fallback = Address::from_low_u64_be(2);
+
+    // Only the fallback swap contract is allowed to spend the tokens.
+    EthCoin::allowance.mock_safe(move |_, spender| {
+        let allowance = if spender == fallback { U256::MAX } else { U256::zero() };
+        MockResult::Return(Box::new(futures01::future::ok(allowance)))
+    });
+    EthCoin::get_gas_price.mock_safe(|_| MockResult::Return(Box::pin(futures::future::ok(GAS_PRICE.into()))));
+    EthCoin::estimate_gas_wrapper
+        .mock_safe(|_, _| MockResult::Return(Box::new(futures01::future::ok(APPROVE_GAS_LIMIT.into()))));
+
+    let (_ctx, coin) = eth_coin_for_test(
+        EthCoinType::Erc20 {
+            platform: ETH.to_string(),
+            token_addr: Address::default(),
+        },
+        &["http://dummy.dummy"],
+        Some(fallback),
+        ETH_SEPOLIA_CHAIN_ID,
+    );
+
+    // New payments are sent to the primary contract, so its `approve` is paid for.
+    let value = u256_to_big_decimal(1000.into(), 18).expect("u256_to_big_decimal");
+    let actual =
+        block_on(coin.get_sender_trade_fee(TradePreimageValue::Exact(value), FeeApproxStage::WithoutApprox, true))
+            .expect("!get_sender_trade_fee");
+    let gas_limit = gas_limit::ERC20_PAYMENT + gas_limit::ERC20_SENDER_REFUND + APPROVE_GAS_LIMIT;
+    let amount = u256_to_big_decimal((gas_limit * GAS_PRICE).into(), 18).expect("!u256_to_big_decimal");
+    assert_eq!(actual, TradeFee {
+        coin: ETH.to_owned(),
+        amount: amount.into(),
+        paid_from_trading_vol: false,
+    });
+}
+
+#[test]
+fn test_swap_contract_addresses_into_primary_and_fallbacks() {
+    let primary = Address::from_low_u64_be(1);
+    let fallback = Address::from_low_u64_be(2);
+
+    let single: SwapContractAddresses = json::from_value(json!(primary)).unwrap();
+    assert_eq!(single, SwapContractAddresses::Single(primary));
+    assert_eq!(
+        single.into_primary_and_fallbacks(Some(fallback)).unwrap(),
+        (primary, vec![fallback])
+    );
+
+    // Duplicates and the primary contract are not recorded as fallbacks.
+    let list: SwapContractAddresses = json::from_value(json!([primary, fallback, primary])).unwrap();
+    assert_eq!(
+        list.into_primary_and_fallbacks(Some(fallback)).unwrap(),
+        (primary, vec![fallback])
+    );
+
+    let empty = SwapContractAddresses::List(Vec::new());
+    assert!(empty.into_primary_and_fallbacks(Some(fallback)).is_err());
+
+    let with_zero = SwapContractAddresses::List(vec![primary, Address::default()]);
+    assert!(with_zero.into_primary_and_fallbacks(None).is_err());
+}
+
 #[test]
 fn test_get_enabled_erc20_by_contract_and_platform() {
     use super::erc20::get_enabled_erc20_by_platform_and_contract;
@@ -1103,6 +1337,29 @@ fn test_get_enabled_erc20_by_contract_and_platform() {
     assert_eq!(res.unwrap().platform_ticker(), ETH);
 }
 
+#[test]
+fn test_fee_history_base_fee_at_block() {
+    use crate::eth::web3_transport::FeeHistoryResult;
+
+    // Two blocks `100` and `101`, and the predicted base fee of the next block `102`.
+    let fee_history = FeeHistoryResult {
+        oldest_block: U256::from(100),
+        base_fee_per_gas: vec![U256::from(10), U256::from(11), U256::from(12)],
+        gas_used_ratio: vec![0.5, 0.6],
+        priority_rewards: None,
+    };
+
+    // The oldest block.
+    assert_eq!(fee_history.base_fee_at_block(U256::from(100)), Some(U256::from(10)));
+    assert_eq!(fee_history.base_fee_at_block(U256::from(101)), Some(U256::from(11)));
+    // The predicted next block.
+    assert_eq!(fee_history.base_fee_at_block(U256::from(102)), Some(U256::from(12)));
+    // Out of range blocks.
+    assert_eq!(fee_history.base_fee_at_block(U256::from(99)), None);
+    assert_eq!(fee_history.base_fee_at_block(U256::from(103)), None);
+    assert_eq!(fee_history.base_fee_at_block(U256::MAX), None);
+}
+
 #[test]
 #[cfg(not(target_arch = "wasm32"))]
 fn test_fee_history() {
@@ -1167,3 +1424,254 @@ fn test_h256_to_str() {
     let b: BytesJson = h.0.to_vec().into();
     println!("H256=0x{:02x}", b);
 }
+
+/// Spawns a simple HTTP server that responds to every request with the body returned by `respond`.
+/// Returns the port the server listens on.
+#[cfg(not(target_arch = "wasm32"))]
+fn spawn_mock_http_server<F>(respond: F) -> u16
+where
+    F: Fn(&[u8]) -> String + Send + 'static,
+{
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let mut stream = stream.unwrap();
+            let mut request = Vec::new();
+            let mut buf = [0u8; 1024];
+            // Read the request headers and the body entirely.
+            let body_start = loop {
+                let read = stream.read(&mut buf).unwrap();
+                request.extend_from_slice(&buf[..read]);
+                let request_str = String::from_utf8_lossy(&request).to_lowercase();
+                if let Some(headers_end) = request_str.find("\r\n\r\n") {
+                    let content_length = request_str
+                        .lines()
+                        .find_map(|line| line.strip_prefix("content-length:"))
+                        .map(|len| len.trim().parse::<usize>().unwrap())
+                        .unwrap_or_default();
+                    if request.len() >= headers_end + 4 + content_length {
+                        break headers_end + 4;
+                    }
+                }
+                if read == 0 {
+                    break request.len();
+                }
+            };
+            let body = respond(&request[body_start..]);
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            );
+            stream.write_all(response.as_bytes()).unwrap();
+        }
+    });
+    port
+}
+
+/// An [`RpcTransportEventHandler`] that counts the bytes sent and received by an RPC transport.
+/// The handler can be found by its `name` that is returned from [`RpcTransportEventHandler::debug_info`].
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug)]
+struct RpcTransportTrafficCounter {
+    name: String,
+    bytes_sent: AtomicU64,
+    bytes_received: AtomicU64,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl RpcTransportTrafficCounter {
+    fn new(name: String) -> RpcTransportTrafficCounter {
+        RpcTransportTrafficCounter {
+            name,
+            bytes_sent: AtomicU64::new(0),
+            bytes_received: AtomicU64::new(0),
+        }
+    }
+
+    fn into_shared(self) -> RpcTransportEventHandlerShared { Arc::new(self) }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl RpcTransportEventHandler for RpcTransportTrafficCounter {
+    fn debug_info(&self) -> String { self.name.clone() }
+
+    fn on_outgoing_request(&self, data: &[u8]) {
+        self.bytes_sent.fetch_add(data.len() as u64, AtomicOrdering::Relaxed);
+    }
+
+    fn on_incoming_response(&self, data: &[u8]) {
+        self.bytes_received
+            .fetch_add(data.len() as u64, AtomicOrdering::Relaxed);
+    }
+
+    fn on_connected(&self, _address: &str) -> Result<(), String> { Ok(()) }
+
+    fn on_disconnected(&self, _address: &str) -> Result<(), String> { Ok(()) }
+
+    fn traffic_stats(&self) -> Option<RpcTransportTrafficStats> {
+        Some(RpcTransportTrafficStats {
+            bytes_sent: self.bytes_sent.load(AtomicOrdering::Relaxed),
+            bytes_received: self.bytes_received.load(AtomicOrdering::Relaxed),
+        })
+    }
+}
+
+#[test]
+#[cfg(not(target_arch = "wasm32"))]
+fn test_web3_transport_event_handlers_traffic_stats() {
+    use web3::Transport;
+
+    const RESPONSE_BODY: &str = r#"{"jsonrpc":"2.0","result":"0x1","id":0}"#;
+
+    let port = spawn_mock_http_server(|_request| RESPONSE_BODY.to_owned());
+
+    let node = HttpTransportNode {
+        uri: format!("http://127.0.0.1:{}", port).parse().unwrap(),
+        komodo_proxy: false,
+    };
+    let ctx = MmCtxBuilder::new().into_mm_arc();
+    let mut event_handlers = rpc_event_handlers_for_eth_transport(&ctx, "ETH".to_owned());
+    event_handlers.push(RpcTransportTrafficCounter::new("counter_1".to_owned()).into_shared());
+    event_handlers.push(RpcTransportTrafficCounter::new("counter_2".to_owned()).into_shared());
+    let transport = Web3Transport::new_http_with_event_handlers(node, event_handlers);
+    assert_eq!(transport.event_handlers_count(), 3);
+
+    let (id, request) = transport.prepare("eth_blockNumber", vec![]);
+    let request_len = web3::helpers::to_string(&request).len() as u64;
+    let result = block_on(transport.send(id, request)).unwrap();
+    assert_eq!(result, json!("0x1"));
+
+    let expected = RpcTransportTrafficStats {
+        bytes_sent: request_len,
+        bytes_received: RESPONSE_BODY.len() as u64,
+    };
+    assert_eq!(transport.event_handler_traffic_stats("counter_1"), Some(expected));
+    assert_eq!(transport.event_handler_traffic_stats("counter_2"), Some(expected));
+    assert_eq!(
+        transport.event_handler_traffic_stats(CoinTransportMetrics::NAME),
+        Some(expected)
+    );
+    assert_eq!(transport.event_handler_traffic_stats("unknown"), None);
+}
+
+#[test]
+fn test_websocket_keepalive_config_rejects_zero() {
+    use crate::eth::v2_activation::{websocket_keepalive_config, EthNode};
+
+    let node = |ws_ping_interval_secs, ws_pong_timeout_secs| EthNode {
+        url: "wss://node.example".to_owned(),
+        komodo_proxy: false,
+        ws_ping_interval_secs,
+        ws_pong_timeout_secs,
+    };
+
+    let keepalive = websocket_keepalive_config(&node(Some(5), Some(7))).unwrap();
+    assert_eq!(keepalive.ping_interval, Duration::from_secs(5));
+    assert_eq!(keepalive.pong_timeout, Duration::from_secs(7));
+
+    for invalid in [node(Some(0), None), node(None, Some(0))] {
+        let err = websocket_keepalive_config(&invalid).unwrap_err().into_inner();
+        assert!(matches!(err, EthActivationV2Error::InvalidPayload(_)), "{}", err);
+    }
+}
+
+#[test]
+#[cfg(not(target_arch = "wasm32"))]
+fn test_websocket_transport_detects_dead_connection() {
+    use crate::eth::web3_transport::websocket_transport::WebsocketKeepaliveConfig;
+    use base64::Engine;
+    use common::custom_futures::timeout::FutureTimerExt;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+    use std::time::Duration;
+
+    const WEBSOCKET_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+
+    // A websocket server that accepts the handshake but never responds to pings.
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    std::thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut request = Vec::new();
+        let mut buf = [0u8; 1024];
+        while !request.windows(4).any(|window| window == b"\r\n\r\n") {
+            let read = stream.read(&mut buf).unwrap();
+            request.extend_from_slice(&buf[..read]);
+        }
+        let key = String::from_utf8_lossy(&request)
+            .lines()
+            .find_map(|line| {
+                let (name, value) = line.split_once(':')?;
+                name.eq_ignore_ascii_case("sec-websocket-key")
+                    .then(|| value.trim().to_owned())
+            })
+            .unwrap();
+        let key_hash = bitcrypto::sha1(format!("{}{}", key, WEBSOCKET_GUID).as_bytes());
+        let accept = base64::engine::general_purpose::STANDARD.encode(key_hash.take());
+        let response = format!(
+            "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Accept: {}\r\n\r\n",
+            accept
+        );
+        stream.write_all(response.as_bytes()).unwrap();
+
+        // Consume the incoming pings without responding until the connection is closed.
+        while let Ok(read) = stream.read(&mut buf) {
+            if read == 0 {
+                break;
+            }
+        }
+    });
+
+    let node = WebsocketTransportNode {
+        uri: format!("ws://127.0.0.1:{}", port).parse().unwrap(),
+    };
+    let transport =
+        WebsocketTransport::with_event_handlers(node, vec![]).with_keepalive_config(WebsocketKeepaliveConfig {
+            ping_interval: Duration::from_millis(100),
+            pong_timeout: Duration::from_millis(300),
+        });
+    assert!(!transport.last_request_failed.load(AtomicOrdering::SeqCst));
+
+    // The connection loop must stop by itself once the pong timeout expires.
+    block_on(transport.clone().start_connection_loop(None).timeout_secs(5.))
+        .expect("The dead connection should have been detected");
+    assert!(transport.last_request_failed.load(AtomicOrdering::SeqCst));
+}
+
+#[test]
+#[cfg(not(target_arch = "wasm32"))]
+fn test_web3_transport_send_batch() {
+    // Respond to a batch request in the reversed order to check if the results are matched by ids.
+    let port = spawn_mock_http_server(|request| {
+        let calls: Vec<Json> = serde_json::from_slice(request).unwrap();
+        let outputs: Vec<Json> = calls
+            .iter()
+            .rev()
+            .map(|call| json!({"jsonrpc": "2.0", "id": call["id"], "result": call["method"]}))
+            .collect();
+        serde_json::to_string(&outputs).unwrap()
+    });
+
+    let node = HttpTransportNode {
+        uri: format!("http://127.0.0.1:{}", port).parse().unwrap(),
+        komodo_proxy: false,
+    };
+    let transport = Web3Transport::new_http(node);
+
+    let methods = ["eth_getTransactionCount", "eth_getBalance", "eth_gasPrice"];
+    let requests = methods
+        .iter()
+        .map(|method| web3::Transport::prepare(&transport, method, vec![]))
+        .collect();
+    let results = block_on(transport.send_batch(requests));
+
+    let actual: Vec<Json> = results.into_iter().map(|result| result.unwrap()).collect();
+    let expected: Vec<Json> = methods.iter().map(|method| json!(method)).collect();
+    assert_eq!(actual, expected);
+    assert!(!transport.is_last_request_failed());
+}
@@ -6,10 +6,11 @@ use mm2_event_stream::{Broadcaster, Event, EventStreamer, NoDataIn, StreamHandle
 use async_trait::async_trait;
 use compatible_time::Instant;
 use futures::channel::oneshot;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+use serde_json::Value as Json;
 use std::convert::TryFrom;
 
-#[derive(Deserialize)]
+#[derive(Deserialize, Serialize)]
 #[serde(deny_unknown_fields)]
 /// Types of estimators available.
 /// Simple - simple internal gas price estimator based on historical data.
@@ -19,7 +20,7 @@ pub enum EstimatorType {
     Provider,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, Serialize)]
 #[serde(deny_unknown_fields, default)]
 pub struct EthFeeStreamingConfig {
     /// The time in seconds to wait before re-estimating the gas fees.
@@ -58,6 +59,8 @@ impl EventStreamer for EthFeeEventStreamer {
         }
     }
 
+    fn config(&self) -> Option<Json> { serde_json::to_value(&self.config).ok() }
+
     async fn handle(
         self,
         broadcaster: Broadcaster,
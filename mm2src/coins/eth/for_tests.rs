@@ -63,7 +63,7 @@ pub(crate) fn eth_coin_from_keypair(
         derivation_method: Arc::new(DerivationMethod::SingleAddress(my_address)),
         swap_contract_address: Address::from_str(ETH_SEPOLIA_SWAP_CONTRACT).unwrap(),
         swap_v2_contracts: None,
-        fallback_swap_contract,
+        fallback_swap_contracts: fallback_swap_contract.into_iter().collect(),
         contract_supports_watchers: false,
         ticker,
         web3_instances: AsyncMutex::new(web3_instances),
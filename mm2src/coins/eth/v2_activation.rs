@@ -22,7 +22,7 @@ use proxy_signature::RawMessage;
 use rpc_task::RpcTaskError;
 use std::sync::atomic::Ordering;
 use url::Url;
-use web3_transport::websocket_transport::WebsocketTransport;
+use web3_transport::websocket_transport::{WebsocketKeepaliveConfig, WebsocketTransport};
 
 #[derive(
     Clone, Debug, Deserialize, Display, EnumFromTrait, EnumFromStringify, PartialEq, Serialize, SerializeErrorType,
@@ -198,7 +198,8 @@ pub struct EthActivationV2Request {
     pub nodes: Vec<EthNode>,
     #[serde(default)]
     pub rpc_mode: EthRpcMode,
-    pub swap_contract_address: Address,
+    /// A single swap contract, or a list of the primary swap contract followed by the fallbacks.
+    pub swap_contract_address: SwapContractAddresses,
     #[serde(default)]
     pub swap_v2_contracts: Option<SwapV2Contracts>,
     pub fallback_swap_contract: Option<Address>,
@@ -220,6 +221,13 @@ pub struct EthNode {
     pub url: String,
     #[serde(default)]
     pub komodo_proxy: bool,
+    /// The interval of websocket keepalive pings in seconds. Ignored for non-websocket nodes.
+    #[serde(default)]
+    pub ws_ping_interval_secs: Option<u64>,
+    /// The time in seconds to wait for a pong before considering a websocket connection dead.
+    /// Ignored for non-websocket nodes.
+    #[serde(default)]
+    pub ws_pong_timeout_secs: Option<u64>,
 }
 
 #[derive(Display, Serialize, SerializeErrorType)]
@@ -475,7 +483,7 @@ impl EthCoin {
             sign_message_prefix: self.sign_message_prefix.clone(),
             swap_contract_address: self.swap_contract_address,
             swap_v2_contracts: self.swap_v2_contracts,
-            fallback_swap_contract: self.fallback_swap_contract,
+            fallback_swap_contracts: self.fallback_swap_contracts.clone(),
             contract_supports_watchers: self.contract_supports_watchers,
             decimals,
             ticker,
@@ -563,7 +571,7 @@ impl EthCoin {
             sign_message_prefix: self.sign_message_prefix.clone(),
             swap_contract_address: self.swap_contract_address,
             swap_v2_contracts: self.swap_v2_contracts,
-            fallback_swap_contract: self.fallback_swap_contract,
+            fallback_swap_contracts: self.fallback_swap_contracts.clone(),
             contract_supports_watchers: self.contract_supports_watchers,
             web3_instances: AsyncMutex::new(self.web3_instances.lock().await.clone()),
             decimals: self.decimals,
@@ -595,13 +603,6 @@ pub async fn eth_coin_from_conf_and_request_v2(
     priv_key_build_policy: EthPrivKeyBuildPolicy,
     chain_spec: ChainSpec,
 ) -> MmResult<EthCoin, EthActivationV2Error> {
-    if req.swap_contract_address == Address::default() {
-        return Err(EthActivationV2Error::InvalidSwapContractAddr(
-            "swap_contract_address can't be zero address".to_string(),
-        )
-        .into());
-    }
-
     if ctx.use_trading_proto_v2() {
         let contracts = req.swap_v2_contracts.as_ref().ok_or_else(|| {
             EthActivationV2Error::InvalidPayload(
@@ -627,6 +628,11 @@ pub async fn eth_coin_from_conf_and_request_v2(
             .into());
         }
     }
+    let (swap_contract_address, fallback_swap_contracts) = req
+        .swap_contract_address
+        .clone()
+        .into_primary_and_fallbacks(req.fallback_swap_contract)
+        .map_to_mm(EthActivationV2Error::InvalidSwapContractAddr)?;
 
     let (priv_key_policy, derivation_method) = build_address_and_priv_key_policy(
         ctx,
@@ -700,9 +706,9 @@ pub async fn eth_coin_from_conf_and_request_v2(
         coin_type,
         chain_spec,
         sign_message_prefix,
-        swap_contract_address: req.swap_contract_address,
+        swap_contract_address,
         swap_v2_contracts: req.swap_v2_contracts,
-        fallback_swap_contract: req.fallback_swap_contract,
+        fallback_swap_contracts,
         contract_supports_watchers: req.contract_supports_watchers,
         decimals: ETH_DECIMALS,
         ticker: ticker.to_string(),
@@ -897,7 +903,7 @@ fn create_transport(
     event_handlers: &[RpcTransportEventHandlerShared],
 ) -> MmResult<Web3Transport, EthActivationV2Error> {
     match uri.scheme_str() {
-        Some("ws") | Some("wss") => Ok(create_websocket_transport(ctx, uri, eth_node, event_handlers)),
+        Some("ws") | Some("wss") => create_websocket_transport(ctx, uri, eth_node, event_handlers),
         Some("http") | Some("https") => Ok(create_http_transport(ctx, uri, eth_node, event_handlers)),
         _ => MmError::err(EthActivationV2Error::InvalidPayload(format!(
             "Invalid node address '{uri}'. Only http(s) and ws(s) nodes are supported"
@@ -910,12 +916,14 @@ fn create_websocket_transport(
     uri: &Uri,
     eth_node: &EthNode,
     event_handlers: &[RpcTransportEventHandlerShared],
-) -> Web3Transport {
+) -> MmResult<Web3Transport, EthActivationV2Error> {
     const TMP_SOCKET_CONNECTION: Duration = Duration::from_secs(20);
 
     let node = WebsocketTransportNode { uri: uri.clone() };
+    let keepalive = websocket_keepalive_config(eth_node)?;
 
-    let mut websocket_transport = WebsocketTransport::with_event_handlers(node, event_handlers.to_owned());
+    let mut websocket_transport =
+        WebsocketTransport::with_event_handlers(node, event_handlers.to_owned()).with_keepalive_config(keepalive);
 
     if eth_node.komodo_proxy {
         websocket_transport.proxy_sign_keypair = Some(P2PContext::fetch_from_mm_arc(ctx).keypair().clone());
@@ -930,7 +938,34 @@ fn create_websocket_transport(
     let settings = AbortSettings::info_on_abort(format!("connection loop stopped for {:?}", uri));
     ctx.spawner().spawn_with_settings(fut, settings);
 
-    Web3Transport::Websocket(websocket_transport)
+    Ok(Web3Transport::Websocket(websocket_transport))
+}
+
+/// Builds the websocket keepalive config of the node, rejecting zero intervals
+/// as they would make the keepalive loop ping or time out continuously.
+pub(super) fn websocket_keepalive_config(
+    eth_node: &EthNode,
+) -> MmResult<WebsocketKeepaliveConfig, EthActivationV2Error> {
+    let mut keepalive = WebsocketKeepaliveConfig::default();
+    if let Some(ping_interval_secs) = eth_node.ws_ping_interval_secs {
+        if ping_interval_secs == 0 {
+            return MmError::err(EthActivationV2Error::InvalidPayload(format!(
+                "'ws_ping_interval_secs' of the node '{}' must be greater than 0",
+                eth_node.url
+            )));
+        }
+        keepalive.ping_interval = Duration::from_secs(ping_interval_secs);
+    }
+    if let Some(pong_timeout_secs) = eth_node.ws_pong_timeout_secs {
+        if pong_timeout_secs == 0 {
+            return MmError::err(EthActivationV2Error::InvalidPayload(format!(
+                "'ws_pong_timeout_secs' of the node '{}' must be greater than 0",
+                eth_node.url
+            )));
+        }
+        keepalive.pong_timeout = Duration::from_secs(pong_timeout_secs);
+    }
+    Ok(keepalive)
 }
 
 fn create_http_transport(
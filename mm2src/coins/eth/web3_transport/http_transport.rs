@@ -1,11 +1,14 @@
 use crate::eth::{web3_transport::Web3SendOut, RpcTransportEventHandler, RpcTransportEventHandlerShared, Web3RpcError};
 use common::APPLICATION_JSON;
 use common::X_AUTH_PAYLOAD;
+use futures::future::BoxFuture;
 use http::header::CONTENT_TYPE;
-use jsonrpc_core::{Call, Response};
+use jsonrpc_core::{Call, Id, Output, Request, Response};
 use mm2_p2p::Keypair;
 use proxy_signature::RawMessage;
 use serde_json::Value as Json;
+use std::collections::HashMap;
+use std::fmt;
 use std::ops::Deref;
 use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::Arc;
@@ -34,6 +37,52 @@ where
     }
 }
 
+/// Deserialize bytes RPC batch response into the list of `Result`s ordered as the given `ids`.
+pub(crate) fn de_rpc_batch_response<T>(
+    response: T,
+    rpc_url: &str,
+    ids: &[RequestId],
+) -> Result<Vec<Result<Json, Error>>, Error>
+where
+    T: Deref<Target = [u8]> + std::fmt::Debug,
+{
+    let response = serde_json::from_slice(&response).map_err(|e| {
+        Error::InvalidResponse(format!(
+            "url: {}, Error deserializing response: {}, raw response: {}",
+            rpc_url,
+            e,
+            String::from_utf8_lossy(&response)
+        ))
+    })?;
+
+    let outputs = match response {
+        Response::Batch(outputs) => outputs,
+        Response::Single(output) => {
+            return Err(Error::InvalidResponse(format!(
+                "Expected batch, got single: {:?}",
+                output
+            )))
+        },
+    };
+
+    // The batch outputs may come in any order, so match them with the requests by ids.
+    let mut outputs: HashMap<Id, Output> = outputs
+        .into_iter()
+        .map(|output| (output.id().clone(), output))
+        .collect();
+    let results = ids
+        .iter()
+        .map(|id| match outputs.remove(&Id::Num(*id as u64)) {
+            Some(output) => to_result_from_output(output),
+            None => Err(Error::InvalidResponse(format!(
+                "No response for the request with id {}",
+                id
+            ))),
+        })
+        .collect();
+    Ok(results)
+}
+
 #[derive(Clone, Debug)]
 pub struct HttpTransport {
     id: Arc<AtomicUsize>,
@@ -72,6 +121,18 @@ impl HttpTransport {
             last_request_failed: Arc::new(AtomicBool::new(false)),
         }
     }
+
+    #[inline]
+    pub(crate) fn event_handlers(&self) -> &[RpcTransportEventHandlerShared] { &self.event_handlers }
+
+    /// Sends the given `requests` as a single JSON-RPC batch request.
+    /// The results are ordered as the given `requests`.
+    pub(crate) fn send_batch(
+        &self,
+        requests: Vec<(RequestId, Call)>,
+    ) -> BoxFuture<'static, Result<Vec<Result<Json, Error>>, Error>> {
+        Box::pin(send_batch_request(requests, self.clone()))
+    }
 }
 
 impl Transport for HttpTransport {
@@ -93,6 +154,24 @@ impl Transport for HttpTransport {
 
 #[cfg(not(target_arch = "wasm32"))]
 async fn send_request(request: Call, transport: HttpTransport) -> Result<Json, Error> {
+    let body = send_payload(&request, to_string(&request), call_description(&request), &transport).await?;
+    de_rpc_response(body, &transport.node.uri.to_string()).map_err(|err| {
+        request_failed_error(
+            &request,
+            Web3RpcError::InvalidResponse(format!("Server: '{}', error: {}", transport.node.uri, err)),
+        )
+    })
+}
+
+/// Sends the `serialized_request` payload and returns the raw response body.
+/// `request` and `request_description` are used for error reporting only.
+#[cfg(not(target_arch = "wasm32"))]
+async fn send_payload<R: fmt::Debug>(
+    request: &R,
+    serialized_request: String,
+    request_description: String,
+    transport: &HttpTransport,
+) -> Result<Vec<u8>, Error> {
     use common::executor::Timer;
     use common::log::warn;
     use futures::future::{select, Either};
@@ -102,7 +181,6 @@ async fn send_request(request: Call, transport: HttpTransport) -> Result<Json, E
 
     const REQUEST_TIMEOUT_S: f64 = 20.;
 
-    let serialized_request = to_string(&request);
     let request_bytes = serialized_request.as_bytes();
 
     transport.event_handlers.on_outgoing_request(request_bytes);
@@ -120,10 +198,10 @@ async fn send_request(request: Call, transport: HttpTransport) -> Result<Json, E
             request_bytes.len(),
             common::PROXY_REQUEST_EXPIRATION_SEC,
         )
-        .map_err(|e| request_failed_error(&request, Web3RpcError::Internal(e.to_string())))?;
+        .map_err(|e| request_failed_error(request, Web3RpcError::Internal(e.to_string())))?;
 
         let proxy_sign_serialized = serde_json::to_string(&proxy_sign)
-            .map_err(|e| request_failed_error(&request, Web3RpcError::Internal(e.to_string())))?;
+            .map_err(|e| request_failed_error(request, Web3RpcError::Internal(e.to_string())))?;
 
         req.headers_mut()
             .insert(X_AUTH_PAYLOAD, proxy_sign_serialized.parse().unwrap());
@@ -135,24 +213,19 @@ async fn send_request(request: Call, transport: HttpTransport) -> Result<Json, E
     let res = match rc {
         Either::Left((r, _t)) => r,
         Either::Right((_t, _r)) => {
-            let (method, id) = match &request {
-                Call::MethodCall(m) => (m.method.clone(), m.id.clone()),
-                Call::Notification(n) => (n.method.clone(), jsonrpc_core::Id::Null),
-                Call::Invalid { id } => ("Invalid call".to_string(), id.clone()),
-            };
             let error = format!(
-                "Error requesting '{}': {}s timeout expired, method: '{}', id: {:?}",
-                transport.node.uri, REQUEST_TIMEOUT_S, method, id
+                "Error requesting '{}': {}s timeout expired, {}",
+                transport.node.uri, REQUEST_TIMEOUT_S, request_description
             );
             warn!("{}", error);
-            return Err(request_failed_error(&request, Web3RpcError::Transport(error)));
+            return Err(request_failed_error(request, Web3RpcError::Transport(error)));
         },
     };
 
     let (status, _headers, body) = match res {
         Ok(r) => r,
         Err(err) => {
-            return Err(request_failed_error(&request, Web3RpcError::Transport(err.to_string())));
+            return Err(request_failed_error(request, Web3RpcError::Transport(err.to_string())));
         },
     };
 
@@ -160,7 +233,7 @@ async fn send_request(request: Call, transport: HttpTransport) -> Result<Json, E
 
     if !status.is_success() {
         return Err(request_failed_error(
-            &request,
+            request,
             Web3RpcError::Transport(format!(
                 "Server: '{}', response !200: {}, {}",
                 transport.node.uri,
@@ -170,22 +243,28 @@ async fn send_request(request: Call, transport: HttpTransport) -> Result<Json, E
         ));
     }
 
-    let res = match de_rpc_response(body, &transport.node.uri.to_string()) {
-        Ok(r) => r,
-        Err(err) => {
-            return Err(request_failed_error(
-                &request,
-                Web3RpcError::InvalidResponse(format!("Server: '{}', error: {}", transport.node.uri, err)),
-            ));
-        },
-    };
-
-    Ok(res)
+    Ok(body)
 }
 
 #[cfg(target_arch = "wasm32")]
 async fn send_request(request: Call, transport: HttpTransport) -> Result<Json, Error> {
-    let serialized_request = to_string(&request);
+    let body = send_payload(&request, to_string(&request), &transport).await?;
+    de_rpc_response(body, &transport.node.uri.to_string()).map_err(|e| {
+        request_failed_error(
+            &request,
+            Web3RpcError::InvalidResponse(format!("Server: '{}', error: {}", transport.node.uri, e)),
+        )
+    })
+}
+
+/// Sends the `serialized_request` payload and returns the raw response body.
+/// `request` is used for error reporting only.
+#[cfg(target_arch = "wasm32")]
+async fn send_payload<R: fmt::Debug>(
+    request: &R,
+    serialized_request: String,
+    transport: &HttpTransport,
+) -> Result<Vec<u8>, Error> {
     let request_bytes = serialized_request.as_bytes();
 
     let proxy_sign_header = if let Some(proxy_sign_keypair) = &transport.proxy_sign_keypair {
@@ -195,10 +274,10 @@ async fn send_request(request: Call, transport: HttpTransport) -> Result<Json, E
             request_bytes.len(),
             common::PROXY_REQUEST_EXPIRATION_SEC,
         )
-        .map_err(|e| request_failed_error(&request, Web3RpcError::Internal(e.to_string())))?;
+        .map_err(|e| request_failed_error(request, Web3RpcError::Internal(e.to_string())))?;
 
         let proxy_sign_serialized = serde_json::to_string(&proxy_sign)
-            .map_err(|e| request_failed_error(&request, Web3RpcError::Internal(e.to_string())))?;
+            .map_err(|e| request_failed_error(request, Web3RpcError::Internal(e.to_string())))?;
 
         Some(proxy_sign_serialized)
     } else {
@@ -213,13 +292,13 @@ async fn send_request(request: Call, transport: HttpTransport) -> Result<Json, E
     )
     .await
     {
-        Ok(response_json) => Ok(response_json),
+        Ok(response) => Ok(response.into_bytes()),
         Err(Error::Transport(e)) => Err(request_failed_error(
-            &request,
+            request,
             Web3RpcError::Transport(format!("Server: '{}', error: {}", transport.node.uri, e)),
         )),
         Err(e) => Err(request_failed_error(
-            &request,
+            request,
             Web3RpcError::InvalidResponse(format!("Server: '{}', error: {}", transport.node.uri, e)),
         )),
     }
@@ -231,7 +310,7 @@ async fn send_request_once(
     uri: &http::Uri,
     event_handlers: &Vec<RpcTransportEventHandlerShared>,
     proxy_sign_header: Option<String>,
-) -> Result<Json, Error> {
+) -> Result<String, Error> {
     use http::header::ACCEPT;
     use mm2_net::wasm::http::FetchRequest;
 
@@ -263,19 +342,46 @@ async fn send_request_once(
     // account for incoming traffic
     event_handlers.on_incoming_response(response_str.as_bytes());
 
-    let response: Response = serde_json::from_str(&response_str).map_err(|e| {
-        Error::InvalidResponse(format!(
-            "Error deserializing response: {}, raw response: {:?}",
-            e, response_str
-        ))
-    })?;
-    match response {
-        Response::Single(output) => to_result_from_output(output),
-        Response::Batch(_) => Err(Error::InvalidResponse("Expected single, got batch.".to_owned())),
-    }
+    Ok(response_str)
+}
+
+async fn send_batch_request(
+    requests: Vec<(RequestId, Call)>,
+    transport: HttpTransport,
+) -> Result<Vec<Result<Json, Error>>, Error> {
+    let ids: Vec<RequestId> = requests.iter().map(|(id, _)| *id).collect();
+    let request = Request::Batch(requests.into_iter().map(|(_, call)| call).collect());
+
+    #[cfg(not(target_arch = "wasm32"))]
+    let body = {
+        let request_description = match &request {
+            Request::Batch(calls) => calls.iter().map(call_description).collect::<Vec<_>>().join("; "),
+            Request::Single(call) => call_description(call),
+        };
+        send_payload(&request, to_string(&request), request_description, &transport).await?
+    };
+    #[cfg(target_arch = "wasm32")]
+    let body = send_payload(&request, to_string(&request), &transport).await?;
+
+    de_rpc_batch_response(body, &transport.node.uri.to_string(), &ids).map_err(|e| {
+        request_failed_error(
+            &request,
+            Web3RpcError::InvalidResponse(format!("Server: '{}', error: {}", transport.node.uri, e)),
+        )
+    })
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn call_description(call: &Call) -> String {
+    let (method, id) = match call {
+        Call::MethodCall(m) => (m.method.clone(), m.id.clone()),
+        Call::Notification(n) => (n.method.clone(), jsonrpc_core::Id::Null),
+        Call::Invalid { id } => ("Invalid call".to_string(), id.clone()),
+    };
+    format!("method: '{}', id: {:?}", method, id)
 }
 
-fn request_failed_error(request: &Call, error: Web3RpcError) -> Error {
+fn request_failed_error<R: fmt::Debug>(request: &R, error: Web3RpcError) -> Error {
     let error = format!("request {:?} failed: {}", request, error);
     Error::Transport(TransportError::Message(error))
 }
@@ -25,7 +25,7 @@ struct MetamaskTransportInner {
     eth_config: MetamaskEthConfig,
     eip1193: Eip1193Provider,
     // TODO use `event_handlers` properly.
-    _event_handlers: Vec<RpcTransportEventHandlerShared>,
+    event_handlers: Vec<RpcTransportEventHandlerShared>,
 }
 
 impl MetamaskTransport {
@@ -37,13 +37,16 @@ impl MetamaskTransport {
         let inner = MetamaskTransportInner {
             eth_config,
             eip1193,
-            _event_handlers: event_handlers,
+            event_handlers,
         };
         Ok(MetamaskTransport {
             inner: Arc::new(inner),
             last_request_failed: Arc::new(AtomicBool::new(false)),
         })
     }
+
+    #[inline]
+    pub(crate) fn event_handlers(&self) -> &[RpcTransportEventHandlerShared] { &self.inner.event_handlers }
 }
 
 impl Transport for MetamaskTransport {
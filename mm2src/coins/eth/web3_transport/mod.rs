@@ -7,7 +7,7 @@ use serde_json::Value;
 use std::sync::atomic::Ordering;
 use web3::{Error, RequestId, Transport};
 
-use crate::RpcTransportEventHandlerShared;
+use crate::{RpcTransportEventHandler, RpcTransportEventHandlerShared, RpcTransportTrafficStats};
 
 pub(crate) mod http_transport;
 #[cfg(target_arch = "wasm32")] pub(crate) mod metamask_transport;
@@ -58,10 +58,77 @@ impl Web3Transport {
         }
     }
 
+    fn event_handlers(&self) -> &[RpcTransportEventHandlerShared] {
+        match self {
+            Web3Transport::Http(http) => http.event_handlers(),
+            Web3Transport::Websocket(websocket) => websocket.event_handlers(),
+            #[cfg(target_arch = "wasm32")]
+            Web3Transport::Metamask(metamask) => metamask.event_handlers(),
+        }
+    }
+
+    /// Returns the number of event handlers attached to the transport.
+    pub fn event_handlers_count(&self) -> usize { self.event_handlers().len() }
+
+    /// Returns the traffic stats of the event handler with the given `name` (see [`RpcTransportEventHandler::debug_info`]).
+    /// Returns `None` if there is no such handler or it doesn't keep track of the traffic.
+    pub fn event_handler_traffic_stats(&self, name: &str) -> Option<RpcTransportTrafficStats> {
+        self.event_handlers()
+            .iter()
+            .find(|handler| handler.debug_info() == name)
+            .and_then(|handler| handler.traffic_stats())
+    }
+
     #[cfg(all(test, not(target_arch = "wasm32")))]
     pub fn new_http(node: http_transport::HttpTransportNode) -> Web3Transport {
         http_transport::HttpTransport::new(node).into()
     }
+
+    /// Sends the given `requests` as a single JSON-RPC batch request and returns the results ordered as the `requests`.
+    ///
+    /// Falls back to sending the requests one by one if the transport doesn't support batching
+    /// (Metamask, or Websocket through komodo-defi-proxy).
+    /// Marks the last request as failed if the whole batch failed.
+    pub fn send_batch(&self, requests: Vec<(RequestId, Call)>) -> BoxFuture<'static, Vec<Result<Json, Error>>> {
+        let selfi = self.clone();
+        let fut = async move {
+            if requests.is_empty() {
+                return Vec::new();
+            }
+            let requests_count = requests.len();
+
+            let batch_result = match &selfi {
+                Web3Transport::Http(http) => http.send_batch(requests).await,
+                Web3Transport::Websocket(websocket) if websocket.proxy_sign_keypair.is_none() => {
+                    websocket.send_batch(requests).await
+                },
+                _ => Ok(selfi.send_sequentially(requests).await),
+            };
+            // Propagate the error of the whole batch to each request.
+            let results = batch_result.unwrap_or_else(|e| vec![Err(e); requests_count]);
+
+            selfi.set_last_request_failed(results.iter().all(|result| result.is_err()));
+
+            results
+        };
+
+        Box::pin(fut)
+    }
+
+    async fn send_sequentially(&self, requests: Vec<(RequestId, Call)>) -> Vec<Result<Json, Error>> {
+        let mut results = Vec::with_capacity(requests.len());
+        for (id, request) in requests {
+            let result = match self {
+                Web3Transport::Http(http) => http.send(id, request),
+                Web3Transport::Websocket(websocket) => websocket.send(id, request),
+                #[cfg(target_arch = "wasm32")]
+                Web3Transport::Metamask(metamask) => metamask.send(id, request),
+            }
+            .await;
+            results.push(result);
+        }
+        results
+    }
 }
 
 impl Transport for Web3Transport {
@@ -120,3 +187,18 @@ pub struct FeeHistoryResult {
     #[serde(rename = "reward")]
     pub priority_rewards: Option<Vec<Vec<U256>>>,
 }
+
+impl FeeHistoryResult {
+    /// Returns the base fee of the given `block_number`.
+    ///
+    /// `base_fee_per_gas` contains `N + 1` entries for `N` requested blocks starting from `oldest_block`,
+    /// where the last entry is the predicted base fee of the next block after the newest one.
+    /// Returns `None` if `block_number` is out of that range.
+    pub fn base_fee_at_block(&self, block_number: U256) -> Option<U256> {
+        let index = block_number.checked_sub(self.oldest_block)?;
+        if index >= U256::from(self.base_fee_per_gas.len()) {
+            return None;
+        }
+        self.base_fee_per_gas.get(index.as_usize()).copied()
+    }
+}
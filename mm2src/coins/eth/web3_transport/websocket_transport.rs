@@ -5,7 +5,7 @@
 //! less bandwidth. This efficiency is achieved by avoiding the handling of TCP handshakes (connection reusability)
 //! for each request.
 
-use super::http_transport::de_rpc_response;
+use super::http_transport::{de_rpc_batch_response, de_rpc_response};
 use crate::eth::eth_rpc::ETH_RPC_REQUEST_TIMEOUT;
 use crate::eth::web3_transport::Web3SendOut;
 use crate::eth::{EthCoin, RpcTransportEventHandlerShared};
@@ -15,10 +15,11 @@ use common::log;
 use compatible_time::{Duration, Instant};
 use futures::channel::mpsc::{UnboundedReceiver, UnboundedSender};
 use futures::channel::oneshot;
+use futures::future::{BoxFuture, Either};
 use futures::lock::Mutex as AsyncMutex;
 use futures_ticker::Ticker;
 use futures_util::{FutureExt, SinkExt, StreamExt};
-use jsonrpc_core::Call;
+use jsonrpc_core::{Call, Request};
 use mm2_p2p::Keypair;
 use proxy_signature::{ProxySign, RawMessage};
 use std::sync::atomic::AtomicBool;
@@ -32,7 +33,28 @@ use web3::{helpers::build_request, RequestId, Transport};
 
 const MAX_ATTEMPTS: u32 = 3;
 const SLEEP_DURATION: f64 = 1.;
-const KEEPALIVE_DURATION: Duration = Duration::from_secs(10);
+const DEFAULT_PING_INTERVAL: Duration = Duration::from_secs(30);
+const DEFAULT_PONG_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Application-level keepalive settings of [`WebsocketTransport`].
+///
+/// Some proxies and load-balancers drop idle connections silently, so a lightweight JSON-RPC request (ping)
+/// is sent every `ping_interval`. Any message received from the node is considered as a pong.
+/// If no message is received within `pong_timeout` after a ping, the connection is considered dead.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct WebsocketKeepaliveConfig {
+    pub(crate) ping_interval: Duration,
+    pub(crate) pong_timeout: Duration,
+}
+
+impl Default for WebsocketKeepaliveConfig {
+    fn default() -> Self {
+        WebsocketKeepaliveConfig {
+            ping_interval: DEFAULT_PING_INTERVAL,
+            pong_timeout: DEFAULT_PONG_TIMEOUT,
+        }
+    }
+}
 
 #[derive(Clone, Debug)]
 pub(crate) struct WebsocketTransportNode {
@@ -48,6 +70,7 @@ pub struct WebsocketTransport {
     pub(crate) proxy_sign_keypair: Option<Keypair>,
     controller_channel: Arc<ControllerChannel>,
     connection_guard: Arc<AsyncMutex<()>>,
+    keepalive: WebsocketKeepaliveConfig,
 }
 
 #[derive(Debug)]
@@ -64,7 +87,9 @@ enum ControllerMessage {
 #[derive(Debug)]
 struct WsRequest {
     serialized_request: String,
-    request_id: RequestId,
+    /// The ids of the requests whose response is awaited, there are many ids for a batch request.
+    /// The ids are sorted so that a response can be matched by the ids it contains.
+    request_ids: Vec<RequestId>,
     response_notifier: oneshot::Sender<Vec<u8>>,
 }
 
@@ -93,9 +118,29 @@ impl WebsocketTransport {
             connection_guard: Arc::new(AsyncMutex::new(())),
             proxy_sign_keypair: None,
             last_request_failed: Arc::new(AtomicBool::new(false)),
+            keepalive: WebsocketKeepaliveConfig::default(),
         }
     }
 
+    /// Sends the given `requests` as a single JSON-RPC batch request.
+    /// The results are ordered as the given `requests`.
+    ///
+    /// Note that komodo-defi-proxy signatures are not attached to batch requests.
+    pub(crate) fn send_batch(
+        &self,
+        requests: Vec<(RequestId, Call)>,
+    ) -> BoxFuture<'static, Result<Vec<Result<serde_json::Value, Error>>, Error>> {
+        Box::pin(send_batch_request(self.clone(), requests, self.event_handlers.clone()))
+    }
+
+    pub(crate) fn with_keepalive_config(mut self, keepalive: WebsocketKeepaliveConfig) -> Self {
+        self.keepalive = keepalive;
+        self
+    }
+
+    #[inline]
+    pub(crate) fn event_handlers(&self) -> &[RpcTransportEventHandlerShared] { &self.event_handlers }
+
     async fn handle_keepalive(&self, wsocket: &mut WebSocketStream, expires_at: Option<Instant>) -> OuterAction {
         const SIMPLE_REQUEST: &str = r#"{"jsonrpc":"2.0","method":"net_version","params":[],"id": 0 }"#;
 
@@ -136,16 +181,16 @@ impl WebsocketTransport {
         &self,
         request: Option<ControllerMessage>,
         wsocket: &mut WebSocketStream,
-        response_notifiers: &mut TimedMap<usize, oneshot::Sender<Vec<u8>>>,
+        response_notifiers: &mut TimedMap<Vec<RequestId>, oneshot::Sender<Vec<u8>>>,
     ) -> OuterAction {
         match request {
             Some(ControllerMessage::Request(WsRequest {
-                request_id,
+                request_ids,
                 serialized_request,
                 response_notifier,
             })) => {
                 response_notifiers.insert_expirable(
-                    request_id,
+                    request_ids.clone(),
                     response_notifier,
                     // Since request will be cancelled when timeout occurs, we are free to drop its state.
                     ETH_RPC_REQUEST_TIMEOUT,
@@ -171,7 +216,7 @@ impl WebsocketTransport {
                 }
 
                 if should_continue {
-                    let _ = response_notifiers.remove(&request_id);
+                    let _ = response_notifiers.remove(&request_ids);
                     return OuterAction::Continue;
                 }
             },
@@ -187,23 +232,32 @@ impl WebsocketTransport {
     async fn handle_response(
         &self,
         message: Option<Result<tokio_tungstenite_wasm::Message, tokio_tungstenite_wasm::Error>>,
-        response_notifiers: &mut TimedMap<usize, oneshot::Sender<Vec<u8>>>,
+        response_notifiers: &mut TimedMap<Vec<RequestId>, oneshot::Sender<Vec<u8>>>,
     ) -> OuterAction {
         match message {
             Some(Ok(tokio_tungstenite_wasm::Message::Text(inc_event))) => {
                 if let Ok(inc_event) = serde_json::from_str::<serde_json::Value>(&inc_event) {
-                    if !inc_event.is_object() {
-                        return OuterAction::Continue;
-                    }
-
-                    if let Some(id) = inc_event.get("id") {
-                        let request_id = id.as_u64().unwrap_or_default() as usize;
+                    let notifier = match &inc_event {
+                        serde_json::Value::Object(_) => inc_event.get("id").and_then(|id| {
+                            let request_id = id.as_u64().unwrap_or_default() as usize;
+                            response_notifiers.remove(&vec![request_id])
+                        }),
+                        // A batch response is matched by the full set of its ids.
+                        serde_json::Value::Array(outputs) => {
+                            let mut request_ids: Vec<RequestId> = outputs
+                                .iter()
+                                .filter_map(|output| Some(output.get("id")?.as_u64()? as usize))
+                                .collect();
+                            request_ids.sort_unstable();
+                            response_notifiers.remove(&request_ids)
+                        },
+                        _ => return OuterAction::Continue,
+                    };
 
-                        if let Some(notifier) = response_notifiers.remove(&request_id) {
-                            let mut res_bytes: Vec<u8> = Vec::new();
-                            if serde_json::to_writer(&mut res_bytes, &inc_event).is_ok() {
-                                notifier.send(res_bytes).expect("receiver channel must be alive");
-                            }
+                    if let Some(notifier) = notifier {
+                        let mut res_bytes: Vec<u8> = Vec::new();
+                        if serde_json::to_writer(&mut res_bytes, &inc_event).is_ok() {
+                            notifier.send(res_bytes).expect("receiver channel must be alive");
                         }
                     }
                 }
@@ -248,7 +302,7 @@ impl WebsocketTransport {
         let _guard = self.connection_guard.lock().await;
 
         // List of awaiting requests
-        let mut response_notifiers: TimedMap<RequestId, oneshot::Sender<Vec<u8>>> =
+        let mut response_notifiers: TimedMap<Vec<RequestId>, oneshot::Sender<Vec<u8>>> =
             TimedMap::new_with_map_kind(timed_map::MapKind::FxHashMap).expiration_tick_cap(30);
 
         let mut wsocket = match self
@@ -262,20 +316,43 @@ impl WebsocketTransport {
             },
         };
 
-        let mut keepalive_interval = Ticker::new(KEEPALIVE_DURATION);
+        let mut keepalive_interval = Ticker::new(self.keepalive.ping_interval);
         let mut req_rx = self.controller_channel.rx.lock().await;
+        // The time when the first unanswered ping was sent.
+        let mut ping_sent_at: Option<Instant> = None;
 
         loop {
+            let pong_timeout = match ping_sent_at {
+                Some(sent_at) => {
+                    let remaining = self.keepalive.pong_timeout.saturating_sub(sent_at.elapsed());
+                    Either::Left(Timer::sleep(remaining.as_secs_f64()))
+                },
+                None => Either::Right(futures::future::pending::<()>()),
+            };
+
             futures_util::select! {
                 _ = keepalive_interval.next().fuse() => {
                     match self.handle_keepalive(&mut wsocket, expires_at).await {
-                        OuterAction::None => {},
+                        OuterAction::None => {
+                            ping_sent_at.get_or_insert_with(Instant::now);
+                        },
                         OuterAction::Continue => continue,
                         OuterAction::Break => break,
                         OuterAction::Return => return,
                     }
                 }
 
+                _ = pong_timeout.fuse() => {
+                    log::warn!(
+                        "No pong received from {} within {:?}, considering the connection dead",
+                        self.node.uri,
+                        self.keepalive.pong_timeout
+                    );
+                    // The connection loop will be restarted on the next request (see `maybe_spawn_connection_loop`).
+                    self.last_request_failed.store(true, Ordering::SeqCst);
+                    return;
+                }
+
                 request = req_rx.next().fuse() => {
                     match self.handle_send_request(request, &mut wsocket, &mut response_notifiers).await {
                         OuterAction::None => {},
@@ -286,6 +363,9 @@ impl WebsocketTransport {
                 }
 
                 message = wsocket.next().fuse() => {
+                    if let Some(Ok(_)) = message {
+                        ping_sent_at = None;
+                    }
                     match self.handle_response(message, &mut response_notifiers).await {
                         OuterAction::None => {},
                         OuterAction::Continue => continue,
@@ -363,7 +443,7 @@ async fn send_request(
 
     let mut tx = transport.controller_channel.tx.clone();
     tx.send(ControllerMessage::Request(WsRequest {
-        request_id,
+        request_ids: vec![request_id],
         serialized_request,
         response_notifier: notification_sender,
     }))
@@ -381,6 +461,46 @@ async fn send_request(
     ))))
 }
 
+async fn send_batch_request(
+    transport: WebsocketTransport,
+    requests: Vec<(RequestId, Call)>,
+    event_handlers: Vec<RpcTransportEventHandlerShared>,
+) -> Result<Vec<Result<serde_json::Value, Error>>, Error> {
+    if requests.is_empty() {
+        return Ok(Vec::new());
+    }
+    let ids: Vec<RequestId> = requests.iter().map(|(id, _)| *id).collect();
+    // The response notifier is registered by all the request ids of the batch.
+    let mut request_ids = ids.clone();
+    request_ids.sort_unstable();
+
+    let request = Request::Batch(requests.into_iter().map(|(_, call)| call).collect());
+    let serialized_request = to_string(&request);
+
+    let (notification_sender, notification_receiver) = oneshot::channel::<Vec<u8>>();
+
+    event_handlers.on_outgoing_request(serialized_request.as_bytes());
+
+    let mut tx = transport.controller_channel.tx.clone();
+    tx.send(ControllerMessage::Request(WsRequest {
+        request_ids,
+        serialized_request,
+        response_notifier: notification_sender,
+    }))
+    .await
+    .map_err(|e| Error::Transport(TransportError::Message(e.to_string())))?;
+
+    if let Ok(response) = notification_receiver.await {
+        event_handlers.on_incoming_response(&response);
+        return de_rpc_batch_response(response, &transport.node.uri.to_string(), &ids);
+    };
+
+    Err(Error::Transport(TransportError::Message(format!(
+        "Sending {:?} failed.",
+        request
+    ))))
+}
+
 impl Transport for WebsocketTransport {
     type Out = Web3SendOut;
 
@@ -3,6 +3,7 @@ pub(crate) mod ln_db;
 pub mod ln_errors;
 pub mod ln_events;
 mod ln_filesystem_persister;
+mod ln_graph_deltas;
 pub mod ln_p2p;
 pub mod ln_platform;
 pub(crate) mod ln_serialization;
@@ -59,7 +60,7 @@ use ln_p2p::PeerManager;
 use ln_platform::Platform;
 use ln_serialization::{ChannelDetailsForRPC, PublicKeyForRPC};
 use ln_sql::SqliteLightningDB;
-use ln_storage::{NetworkGraph, NodesAddressesMapShared, Scorer, TrustedNodesShared};
+use ln_storage::{LightningStorage, NetworkGraph, NodesAddressesMapShared, PersisterHealth, Scorer, TrustedNodesShared};
 use ln_utils::{ChainMonitor, ChannelManager, Router};
 use mm2_core::mm_ctx::MmArc;
 use mm2_err_handle::prelude::*;
@@ -163,6 +164,26 @@ impl LightningCoin {
     #[inline]
     fn my_node_id(&self) -> String { self.channel_manager.get_our_node_id().to_string() }
 
+    /// Checks that the whole persistence stack is healthy: the storage directories are writable
+    /// and the DB is responsive.
+    pub async fn persister_health(&self) -> PersisterHealth {
+        let mut health = match self.persister.health_check().await {
+            Ok(health) => health,
+            Err(e) => PersisterHealth {
+                errors: vec![format!("Storage health check failed: {}", e)],
+                ..Default::default()
+            },
+        };
+        match self.db.check_db_responsive().await {
+            Ok(()) => health.db_responsive = Some(true),
+            Err(e) => {
+                health.db_responsive = Some(false);
+                health.errors.push(format!("DB health check failed: {}", e));
+            },
+        }
+        health
+    }
+
     pub(crate) async fn list_channels(&self) -> Vec<ChannelDetails> {
         let channel_manager = self.channel_manager.clone();
         async_blocking(move || channel_manager.list_channels()).await
@@ -215,6 +236,7 @@ impl LightningCoin {
             InvoiceDescription::Hash(h) => hex::encode(h.0.into_inner()),
         };
         let amt_msat = invoice.amount_milli_satoshis().map(|a| a as i64);
+        let invoice_str = invoice.to_string();
 
         let selfi = self.clone();
         match max_total_cltv_expiry_delta {
@@ -232,7 +254,8 @@ impl LightningCoin {
             None => async_blocking(move || selfi.invoice_payer.pay_invoice(&invoice)).await?,
         };
 
-        let payment_info = PaymentInfo::new(payment_hash, payment_type, description, amt_msat);
+        let payment_info =
+            PaymentInfo::new(payment_hash, payment_type, description, amt_msat).with_invoice(invoice_str);
         // So this only updates the payment in db if the user is retrying to pay an invoice payment that has failed
         self.db.add_or_update_payment_in_db(&payment_info).await?;
         Ok(payment_info)
@@ -1294,7 +1317,7 @@ impl MmCoin for LightningCoin {
 
     fn swap_contract_address(&self) -> Option<BytesJson> { None }
 
-    fn fallback_swap_contract(&self) -> Option<BytesJson> { None }
+    fn fallback_swap_contracts(&self) -> Vec<BytesJson> { Vec::new() }
 
     fn mature_confirmations(&self) -> Option<u32> { None }
 
@@ -1,5 +1,8 @@
+use crate::lightning::ln_filesystem_persister::FsyncPolicy;
+use crate::lightning::ln_sql::DEFAULT_BUSY_TIMEOUT;
 use crate::utxo::BlockchainNetwork;
 use lightning::util::config::{ChannelConfig, ChannelHandshakeConfig, ChannelHandshakeLimits, UserConfig};
+use std::time::Duration;
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct PlatformCoinConfirmationTargets {
@@ -283,6 +286,24 @@ pub struct LightningCoinConf {
     pub our_channels_configs: Option<OurChannelsConfigs>,
     pub counterparty_channel_config_limits: Option<CounterpartyLimits>,
     pub sign_message_prefix: Option<String>,
+    /// When the channel monitor writes are flushed to the disk, defaults to [`FsyncPolicy::Always`].
+    #[serde(default)]
+    pub monitor_fsync_policy: FsyncPolicy,
+    /// Enables the write-ahead log journal mode of the lightning DB, disabled by default.
+    /// See [`crate::lightning::ln_sql::SqliteLightningDB::with_wal_mode`] for the trade-offs.
+    #[serde(default)]
+    pub db_wal_mode: bool,
+    /// The time in milliseconds to wait for a lock on the lightning DB held by another connection to be released,
+    /// defaults to [`DEFAULT_BUSY_TIMEOUT`].
+    pub db_busy_timeout_ms: Option<u64>,
+}
+
+impl LightningCoinConf {
+    /// The time to wait for a lock on the lightning DB, see [`LightningCoinConf::db_busy_timeout_ms`].
+    pub fn db_busy_timeout(&self) -> Duration {
+        self.db_busy_timeout_ms
+            .map_or(DEFAULT_BUSY_TIMEOUT, Duration::from_millis)
+    }
 }
 
 impl From<LightningCoinConf> for UserConfig {
@@ -3,8 +3,11 @@ use common::{now_sec_i64, PagingOptionsEnum};
 use db_common::sqlite::rusqlite::types::FromSqlError;
 use derive_more::Display;
 use lightning::ln::{PaymentHash, PaymentPreimage};
+use lightning::util::events::ClosureReason;
 use secp256k1v24::PublicKey;
-use serde::{Deserialize, Serialize};
+use serde::ser::SerializeMap;
+use serde::{Deserialize, Serialize, Serializer};
+use std::borrow::Cow;
 use std::str::FromStr;
 use uuid::Uuid;
 
@@ -19,14 +22,16 @@ pub struct DBChannelDetails {
     pub funding_value: Option<i64>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub closing_tx: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub closure_reason: Option<String>,
+    #[serde(flatten)]
+    pub closure_reason: Option<ChannelCloseReason>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub claiming_tx: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub claimed_balance: Option<f64>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub funding_generated_in_block: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub short_channel_id: Option<u64>,
     pub is_outbound: bool,
     pub is_public: bool,
     pub is_closed: bool,
@@ -51,6 +56,7 @@ impl DBChannelDetails {
             funding_tx: None,
             funding_value: None,
             funding_generated_in_block: None,
+            short_channel_id: None,
             closing_tx: None,
             closure_reason: None,
             claiming_tx: None,
@@ -64,6 +70,141 @@ impl DBChannelDetails {
     }
 }
 
+/// The reason a channel was closed, mirrors lightning's [`ClosureReason`].
+/// Stored in the DB as the variant name (see [`ChannelCloseReason::kind`]) plus an optional detail message.
+#[derive(Clone, Debug, Display, PartialEq)]
+pub enum ChannelCloseReason {
+    #[display(fmt = "counterparty force-closed with message {}", peer_msg)]
+    CounterpartyForceClosed { peer_msg: String },
+    #[display(fmt = "user manually force-closed the channel")]
+    HolderForceClosed,
+    #[display(fmt = "the channel was cooperatively closed")]
+    CooperativeClosure,
+    #[display(fmt = "commitment or closing transaction was confirmed on chain.")]
+    CommitmentTxConfirmed,
+    #[display(fmt = "funding transaction failed to confirm within 2016 blocks")]
+    FundingTimedOut,
+    #[display(fmt = "processing error: {}", err)]
+    ProcessingError { err: String },
+    #[display(fmt = "the peer disconnected prior to the channel being funded")]
+    DisconnectedPeer,
+    #[display(fmt = "the ChannelManager read from disk was stale compared to ChannelMonitor(s)")]
+    OutdatedChannelManager,
+    /// A legacy free-form reason that doesn't match any of the known ones.
+    #[display(fmt = "{}", detail)]
+    Unknown { detail: String },
+}
+
+impl ChannelCloseReason {
+    /// The variant name that is stored in the `closure_reason` column.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            ChannelCloseReason::CounterpartyForceClosed { .. } => "CounterpartyForceClosed",
+            ChannelCloseReason::HolderForceClosed => "HolderForceClosed",
+            ChannelCloseReason::CooperativeClosure => "CooperativeClosure",
+            ChannelCloseReason::CommitmentTxConfirmed => "CommitmentTxConfirmed",
+            ChannelCloseReason::FundingTimedOut => "FundingTimedOut",
+            ChannelCloseReason::ProcessingError { .. } => "ProcessingError",
+            ChannelCloseReason::DisconnectedPeer => "DisconnectedPeer",
+            ChannelCloseReason::OutdatedChannelManager => "OutdatedChannelManager",
+            ChannelCloseReason::Unknown { .. } => "Unknown",
+        }
+    }
+
+    /// The detail message that is stored in the `closure_reason_detail` column.
+    pub fn detail(&self) -> Option<&str> {
+        match self {
+            ChannelCloseReason::CounterpartyForceClosed { peer_msg } => Some(peer_msg),
+            ChannelCloseReason::ProcessingError { err } => Some(err),
+            ChannelCloseReason::Unknown { detail } => Some(detail),
+            _ => None,
+        }
+    }
+
+    /// Restores the reason from the stored kind and detail. Returns `None` if the kind is unknown.
+    pub fn from_kind(kind: &str, detail: Option<String>) -> Option<Self> {
+        let reason = match kind {
+            "CounterpartyForceClosed" => ChannelCloseReason::CounterpartyForceClosed {
+                peer_msg: detail.unwrap_or_default(),
+            },
+            "HolderForceClosed" => ChannelCloseReason::HolderForceClosed,
+            "CooperativeClosure" => ChannelCloseReason::CooperativeClosure,
+            "CommitmentTxConfirmed" => ChannelCloseReason::CommitmentTxConfirmed,
+            "FundingTimedOut" => ChannelCloseReason::FundingTimedOut,
+            "ProcessingError" => ChannelCloseReason::ProcessingError {
+                err: detail.unwrap_or_default(),
+            },
+            "DisconnectedPeer" => ChannelCloseReason::DisconnectedPeer,
+            "OutdatedChannelManager" => ChannelCloseReason::OutdatedChannelManager,
+            "Unknown" => ChannelCloseReason::Unknown {
+                detail: detail.unwrap_or_default(),
+            },
+            _ => return None,
+        };
+        Some(reason)
+    }
+
+    /// Parses a free-form reason stored by older versions, which is the `Display` output of lightning's
+    /// [`ClosureReason`]. Unrecognized strings are kept as [`ChannelCloseReason::Unknown`].
+    pub fn from_legacy_str(reason: &str) -> Self {
+        const COUNTERPARTY_FORCE_CLOSED_PREFIX: &str = "counterparty force-closed with message ";
+        const PROCESSING_ERROR_PREFIX: &str = "processing error: ";
+
+        if let Some(peer_msg) = reason.strip_prefix(COUNTERPARTY_FORCE_CLOSED_PREFIX) {
+            return ChannelCloseReason::CounterpartyForceClosed {
+                peer_msg: peer_msg.to_owned(),
+            };
+        }
+        if let Some(err) = reason.strip_prefix(PROCESSING_ERROR_PREFIX) {
+            return ChannelCloseReason::ProcessingError { err: err.to_owned() };
+        }
+        let known = [
+            ChannelCloseReason::HolderForceClosed,
+            ChannelCloseReason::CooperativeClosure,
+            ChannelCloseReason::CommitmentTxConfirmed,
+            ChannelCloseReason::FundingTimedOut,
+            ChannelCloseReason::DisconnectedPeer,
+            ChannelCloseReason::OutdatedChannelManager,
+        ];
+        known
+            .iter()
+            .find(|known_reason| known_reason.to_string() == reason)
+            .cloned()
+            .unwrap_or_else(|| ChannelCloseReason::Unknown {
+                detail: reason.to_owned(),
+            })
+    }
+}
+
+/// Serialized as the human readable `closure_reason` string that the API has always returned,
+/// plus the `closure_reason_kind` that can be used to categorize the reason.
+/// Meant to be flattened into [`DBChannelDetails`].
+impl Serialize for ChannelCloseReason {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(2))?;
+        map.serialize_entry("closure_reason", &self.to_string())?;
+        map.serialize_entry("closure_reason_kind", self.kind())?;
+        map.end()
+    }
+}
+
+impl From<ClosureReason> for ChannelCloseReason {
+    fn from(reason: ClosureReason) -> Self {
+        match reason {
+            ClosureReason::CounterpartyForceClosed { peer_msg } => {
+                ChannelCloseReason::CounterpartyForceClosed { peer_msg }
+            },
+            ClosureReason::HolderForceClosed => ChannelCloseReason::HolderForceClosed,
+            ClosureReason::CooperativeClosure => ChannelCloseReason::CooperativeClosure,
+            ClosureReason::CommitmentTxConfirmed => ChannelCloseReason::CommitmentTxConfirmed,
+            ClosureReason::FundingTimedOut => ChannelCloseReason::FundingTimedOut,
+            ClosureReason::ProcessingError { err } => ChannelCloseReason::ProcessingError { err },
+            ClosureReason::DisconnectedPeer => ChannelCloseReason::DisconnectedPeer,
+            ClosureReason::OutdatedChannelManager => ChannelCloseReason::OutdatedChannelManager,
+        }
+    }
+}
+
 #[derive(Clone, Deserialize)]
 pub enum ChannelType {
     Outbound,
@@ -138,6 +279,8 @@ pub struct PaymentInfo {
     pub status: HTLCStatus,
     pub created_at: i64,
     pub last_updated: i64,
+    /// The bolt11 invoice this payment was made for or generated with, if any (e.g. not available for keysend).
+    pub invoice: Option<String>,
 }
 
 impl PaymentInfo {
@@ -158,9 +301,16 @@ impl PaymentInfo {
             status: HTLCStatus::Pending,
             created_at: now_sec_i64(),
             last_updated: now_sec_i64(),
+            invoice: None,
         }
     }
 
+    #[inline]
+    pub fn with_invoice(mut self, invoice: String) -> Self {
+        self.invoice = Some(invoice);
+        self
+    }
+
     #[inline]
     pub fn with_preimage(mut self, preimage: PaymentPreimage) -> Self {
         self.preimage = Some(preimage);
@@ -174,6 +324,41 @@ impl PaymentInfo {
     }
 
     pub(crate) fn is_outbound(&self) -> bool { matches!(self.payment_type, PaymentType::OutboundPayment { .. }) }
+
+    /// Formats the payment as a row of [`LightningDB::export_payments_csv`] following [`PAYMENTS_CSV_HEADER`],
+    /// without the trailing newline.
+    pub fn to_csv_row(&self) -> String {
+        let (direction, destination) = match &self.payment_type {
+            PaymentType::OutboundPayment { destination } => ("outbound", destination.to_string()),
+            PaymentType::InboundPayment => ("inbound", String::new()),
+        };
+        let fields = [
+            hex::encode(self.payment_hash.0),
+            direction.to_owned(),
+            self.amt_msat.map(|amt| amt.to_string()).unwrap_or_default(),
+            self.fee_paid_msat.map(|fee| fee.to_string()).unwrap_or_default(),
+            self.status.to_string(),
+            destination,
+            self.created_at.to_string(),
+        ];
+        fields
+            .iter()
+            .map(|field| escape_csv_field(field))
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+}
+
+/// The header row of [`LightningDB::export_payments_csv`].
+pub const PAYMENTS_CSV_HEADER: &str = "hash,direction,amount_msat,fee_msat,status,destination,timestamp";
+
+/// Quotes the field if it contains a separator, a quote or a line break, doubling the quotes inside (RFC 4180).
+pub(crate) fn escape_csv_field(field: &str) -> Cow<'_, str> {
+    if field.contains(|c| matches!(c, ',' | '"' | '\n' | '\r')) {
+        Cow::Owned(format!("\"{}\"", field.replace('"', "\"\"")))
+    } else {
+        Cow::Borrowed(field)
+    }
 }
 
 #[derive(Clone)]
@@ -206,10 +391,21 @@ pub trait LightningDB {
     /// Checks if tables have been initialized or not in DB.
     async fn is_db_initialized(&self) -> Result<bool, Self::Error>;
 
+    /// Runs a trivial query to check that the DB is responsive.
+    async fn check_db_responsive(&self) -> Result<(), Self::Error>;
+
     /// Inserts a new channel record in the DB. The record's data is completed using add_funding_tx_to_db,
     /// add_closing_tx_to_db, add_claiming_tx_to_db when this information is available.
     async fn add_channel_to_db(&self, details: &DBChannelDetails) -> Result<(), Self::Error>;
 
+    /// Checks that the channel record can be inserted without writing it to the DB: the `counterparty_node_id`
+    /// must be a valid public key and no channel with the same uuid must exist.
+    async fn validate_channel_for_insert(&self, details: &DBChannelDetails) -> Result<(), Self::Error>;
+
+    /// Inserts multiple channel records in the DB in a single transaction. If any of the records can't be inserted
+    /// (e.g. a channel with the same uuid already exists), none of them are inserted.
+    async fn add_channels_to_db(&self, channels: Vec<DBChannelDetails>) -> Result<(), Self::Error>;
+
     /// Updates a channel's DB record with the channel's funding transaction information.
     async fn add_funding_tx_to_db(
         &self,
@@ -227,7 +423,7 @@ pub trait LightningDB {
     async fn update_channel_to_closed(
         &self,
         uuid: Uuid,
-        closure_reason: String,
+        closure_reason: ChannelCloseReason,
         close_at: i64,
     ) -> Result<(), Self::Error>;
 
@@ -237,6 +433,9 @@ pub trait LightningDB {
     /// when initializing the persister.
     async fn get_closed_channels_with_no_closing_tx(&self) -> Result<Vec<DBChannelDetails>, Self::Error>;
 
+    /// Updates a channel's DB record with the channel's short channel id (scid) once the funding transaction is confirmed.
+    async fn add_short_channel_id_to_db(&self, uuid: Uuid, short_channel_id: u64) -> Result<(), Self::Error>;
+
     /// Updates a channel's DB record with the channel's closing transaction hash.
     async fn add_closing_tx_to_db(&self, uuid: Uuid, closing_tx: String) -> Result<(), Self::Error>;
 
@@ -252,6 +451,24 @@ pub trait LightningDB {
     /// Gets a channel record from DB by the channel's uuid.
     async fn get_channel_from_db(&self, uuid: Uuid) -> Result<Option<DBChannelDetails>, Self::Error>;
 
+    /// Gets a channel record from DB by the channel's short channel id (scid).
+    async fn get_channel_by_short_channel_id(
+        &self,
+        short_channel_id: u64,
+    ) -> Result<Option<DBChannelDetails>, Self::Error>;
+
+    /// Gets the channel records from DB matching the given uuids in a single batched read.
+    /// The found channels are returned in the order of `uuids`, the uuids with no matching channel are skipped.
+    async fn get_channels_from_db(&self, uuids: &[Uuid]) -> Result<Vec<DBChannelDetails>, Self::Error>;
+
+    /// Gets the list of channels whose funding transaction was generated in a block within the `from..=to` range.
+    /// Channels with no funding block height saved yet are not included.
+    async fn get_channels_funded_in_block_range(
+        &self,
+        from: u64,
+        to: u64,
+    ) -> Result<Vec<DBChannelDetails>, Self::Error>;
+
     /// Gets the list of closed channels that match the provided filter criteria. The number of requested records is
     /// specified by the limit parameter, the starting record to list from is specified by the paging parameter. The
     /// total number of matched records along with the number of skipped records are also returned in the result.
@@ -262,6 +479,9 @@ pub trait LightningDB {
         limit: usize,
     ) -> Result<GetClosedChannelsResult, Self::Error>;
 
+    /// Gets the number of channel records in the DB. Only closed or open channels are counted if `closed` is specified.
+    async fn get_channel_count(&self, closed: Option<bool>) -> Result<u64, Self::Error>;
+
     /// Inserts a new payment record in the DB.
     async fn add_payment_to_db(&self, info: &PaymentInfo) -> Result<(), Self::Error>;
 
@@ -296,6 +516,12 @@ pub trait LightningDB {
     /// Gets a payment's record from DB by the payment's hash.
     async fn get_payment_from_db(&self, hash: PaymentHash) -> Result<Option<PaymentInfo>, Self::Error>;
 
+    /// Gets up to `limit` payments ordered by their last update time (newest first).
+    async fn get_payments_ordered_by_time(&self, limit: usize) -> Result<Vec<PaymentInfo>, Self::Error>;
+
+    /// Gets all outbound payments sent to the provided destination node, ordered by last update time (newest first).
+    async fn get_payments_to_destination(&self, dest: PublicKey) -> Result<Vec<PaymentInfo>, Self::Error>;
+
     /// Gets the list of payments that match the provided filter criteria. The number of requested records is specified
     /// by the limit parameter, the starting record to list from is specified by the paging parameter. The total number
     /// of matched records along with the number of skipped records are also returned in the result.
@@ -305,4 +531,19 @@ pub trait LightningDB {
         paging: PagingOptionsEnum<PaymentHash>,
         limit: usize,
     ) -> Result<GetPaymentsResult, Self::Error>;
+
+    /// Gets the number of payment records in the DB. Only outbound or inbound payments are counted if `is_outbound`
+    /// is specified.
+    async fn get_payment_count(&self, is_outbound: Option<bool>) -> Result<u64, Self::Error>;
+
+    /// Calls `f` for every payment record in the DB in insertion order, reading the records one by one
+    /// instead of loading them all into memory. Only outbound or inbound payments are visited if `is_outbound`
+    /// is specified.
+    async fn for_each_payment<F>(&self, is_outbound: Option<bool>, f: F) -> Result<(), Self::Error>
+    where
+        F: FnMut(PaymentInfo) + Send + 'static;
+
+    /// Exports all the payments in the DB as CSV starting with the [`PAYMENTS_CSV_HEADER`] row.
+    /// The records are visited one by one using [`LightningDB::for_each_payment`].
+    async fn export_payments_csv(&self) -> Result<String, Self::Error>;
 }
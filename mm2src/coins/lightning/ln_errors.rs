@@ -1,7 +1,9 @@
+use crate::lightning::ln_filesystem_persister::is_disk_full_io_error;
 use crate::utxo::rpc_clients::UtxoRpcError;
 use crate::PrivKeyPolicyNotAllowed;
 use common::executor::AbortedError;
 use common::HttpStatusCode;
+use db_common::sqlite::is_disk_full_error;
 use db_common::sqlite::rusqlite::Error as SqlError;
 use derive_more::Display;
 use http::StatusCode;
@@ -36,6 +38,9 @@ pub enum EnableLightningError {
     RpcError(String),
     #[display(fmt = "DB error {}", _0)]
     DbError(String),
+    /// The disk is full. Unlike `IOError` and `DbError`, the operation can be retried once some space is freed.
+    #[display(fmt = "Disk is full: {}", _0)]
+    DiskFull(String),
     #[display(fmt = "Rpc task error: {}", _0)]
     RpcTaskError(String),
     ConnectToNodeError(String),
@@ -50,6 +55,7 @@ impl HttpStatusCode for EnableLightningError {
             | EnableLightningError::RpcError(_)
             | EnableLightningError::PrivKeyPolicyNotAllowed(_) => StatusCode::BAD_REQUEST,
             EnableLightningError::UnsupportedMode(_, _) => StatusCode::NOT_IMPLEMENTED,
+            EnableLightningError::DiskFull(_) => StatusCode::SERVICE_UNAVAILABLE,
             EnableLightningError::InvalidAddress(_)
             | EnableLightningError::InvalidPath(_)
             | EnableLightningError::SystemTimeError(_)
@@ -64,11 +70,21 @@ impl HttpStatusCode for EnableLightningError {
 }
 
 impl From<std::io::Error> for EnableLightningError {
-    fn from(err: std::io::Error) -> EnableLightningError { EnableLightningError::IOError(err.to_string()) }
+    fn from(err: std::io::Error) -> EnableLightningError {
+        if is_disk_full_io_error(&err) {
+            return EnableLightningError::DiskFull(err.to_string());
+        }
+        EnableLightningError::IOError(err.to_string())
+    }
 }
 
 impl From<SqlError> for EnableLightningError {
-    fn from(err: SqlError) -> EnableLightningError { EnableLightningError::DbError(err.to_string()) }
+    fn from(err: SqlError) -> EnableLightningError {
+        if is_disk_full_error(&err) {
+            return EnableLightningError::DiskFull(err.to_string());
+        }
+        EnableLightningError::DbError(err.to_string())
+    }
 }
 
 impl From<UtxoRpcError> for EnableLightningError {
@@ -91,6 +107,9 @@ impl From<AbortedError> for EnableLightningError {
 pub enum SaveChannelClosingError {
     #[display(fmt = "DB error: {}", _0)]
     DbError(String),
+    /// The disk is full. Unlike `DbError`, saving the channel closing can be retried once some space is freed.
+    #[display(fmt = "Disk is full: {}", _0)]
+    DiskFull(String),
     #[display(fmt = "Channel with uuid {} not found in DB", _0)]
     ChannelNotFound(Uuid),
     #[display(fmt = "Funding transaction hash is Null in DB")]
@@ -104,7 +123,12 @@ pub enum SaveChannelClosingError {
 }
 
 impl From<SqlError> for SaveChannelClosingError {
-    fn from(err: SqlError) -> SaveChannelClosingError { SaveChannelClosingError::DbError(err.to_string()) }
+    fn from(err: SqlError) -> SaveChannelClosingError {
+        if is_disk_full_error(&err) {
+            return SaveChannelClosingError::DiskFull(err.to_string());
+        }
+        SaveChannelClosingError::DbError(err.to_string())
+    }
 }
 
 impl From<TryFromIntError> for SaveChannelClosingError {
@@ -1,5 +1,5 @@
 use super::*;
-use crate::lightning::ln_db::{DBChannelDetails, HTLCStatus, LightningDB, PaymentType};
+use crate::lightning::ln_db::{ChannelCloseReason, DBChannelDetails, HTLCStatus, LightningDB, PaymentType};
 use crate::lightning::ln_errors::{SaveChannelClosingError, SaveChannelClosingResult};
 use crate::lightning::ln_sql::SqliteLightningDB;
 use bitcoin::blockdata::script::Script;
@@ -89,7 +89,7 @@ impl EventHandler for LightningEventHandler {
                 channel_id,
                 user_channel_id,
                 reason,
-            } => self.handle_channel_closed(channel_id, user_channel_id, reason.to_string()),
+            } => self.handle_channel_closed(channel_id, user_channel_id, reason.into()),
 
             // Todo: Add spent UTXOs to RecentlySpentOutPoints if it's not discarded
             Event::DiscardFunding { channel_id, transaction } => info!(
@@ -151,7 +151,11 @@ impl EventHandler for LightningEventHandler {
             Event::ProbeSuccessful { .. } => (),
             Event::ProbeFailed { .. } => (),
             Event::HTLCIntercepted { .. } => (),
-            Event::ChannelReady { user_channel_id, .. } => info!("{}: {}", CHANNEL_READY_LOG, Uuid::from_u128(user_channel_id)),
+            Event::ChannelReady {
+                channel_id,
+                user_channel_id,
+                ..
+            } => self.handle_channel_ready(channel_id, user_channel_id),
         }
     }
 }
@@ -229,7 +233,7 @@ async fn save_channel_closing_details(
     db: SqliteLightningDB,
     platform: Arc<Platform>,
     uuid: Uuid,
-    reason: String,
+    reason: ChannelCloseReason,
 ) -> SaveChannelClosingResult<()> {
     db.update_channel_to_closed(uuid, reason, now_sec_i64()).await?;
 
@@ -454,7 +458,7 @@ impl LightningEventHandler {
         self.platform.spawner().spawn_with_settings(fut, settings);
     }
 
-    fn handle_channel_closed(&self, channel_id: [u8; 32], user_channel_id: u128, reason: String) {
+    fn handle_channel_closed(&self, channel_id: [u8; 32], user_channel_id: u128, reason: ChannelCloseReason) {
         info!(
             "Channel: {} closed for the following reason: {}",
             hex::encode(channel_id),
@@ -477,6 +481,31 @@ impl LightningEventHandler {
         self.platform.spawner().spawn_with_settings(fut, settings);
     }
 
+    fn handle_channel_ready(&self, channel_id: [u8; 32], user_channel_id: u128) {
+        let uuid = Uuid::from_u128(user_channel_id);
+        info!("{}: {}", CHANNEL_READY_LOG, uuid);
+
+        let short_channel_id = self
+            .channel_manager
+            .list_channels()
+            .into_iter()
+            .find(|chan| chan.channel_id == channel_id)
+            .and_then(|chan| chan.short_channel_id);
+        let short_channel_id = match short_channel_id {
+            Some(scid) => scid,
+            None => return,
+        };
+
+        let db = self.db.clone();
+        let fut = async move {
+            db.add_short_channel_id_to_db(uuid, short_channel_id)
+                .await
+                .error_log_with_msg("Unable to add short channel id to DB!");
+        };
+        let settings = AbortSettings::default().critical_timout_s(CRITICAL_FUTURE_TIMEOUT);
+        self.platform.spawner().spawn_with_settings(fut, settings);
+    }
+
     fn handle_payment_failed(&self, payment_hash: PaymentHash) {
         info!(
             "Handling PaymentFailed event for payment_hash: {}",
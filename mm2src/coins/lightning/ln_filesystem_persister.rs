@@ -1,25 +1,32 @@
-use crate::lightning::ln_storage::{LightningStorage, NetworkGraph, NodesAddressesMap, NodesAddressesMapShared, Scorer,
-                                   TrustedNodesShared};
+use crate::lightning::ln_graph_deltas::{deltas_file_header, read_deltas_file, NetworkGraphDigests, NetworkGraphRecords};
+use crate::lightning::ln_storage::{LightningStorage, NetworkGraph, NodesAddressesMap, NodesAddressesMapShared,
+                                   PersisterHealth, Scorer, TrustedNodesShared};
 use async_trait::async_trait;
 use bitcoin::blockdata::constants::genesis_block;
 use bitcoin::{BlockHash, Network, Txid};
-use bitcoin_hashes::hex::FromHex;
+use bitcoin_hashes::hex::{FromHex, ToHex};
+use bitcoin_hashes::{sha256, Hash};
 use common::async_blocking;
-use common::log::LogState;
-use lightning::chain::channelmonitor::ChannelMonitor;
+use common::log::{warn, LogState};
+use crypto::{decrypt_with_slip21, encrypt_with_slip21, EncryptedData};
+use lightning::chain::chainmonitor::{MonitorUpdateId, Persist};
+use lightning::chain::channelmonitor::{ChannelMonitor, ChannelMonitorUpdate};
 use lightning::chain::keysinterface::{KeysInterface, Sign};
+use lightning::chain::transaction::OutPoint;
+use lightning::chain::ChannelMonitorUpdateStatus;
 use lightning::routing::scoring::{ProbabilisticScorer, ProbabilisticScoringParameters};
 use lightning::util::persist::KVStorePersister;
-use lightning::util::ser::{ReadableArgs, Writeable};
-use mm2_io::fs::{check_dir_operations, invalid_data_err, read_json, write_json};
+use lightning::util::ser::{ReadableArgs, Writeable, Writer};
+use mm2_io::fs::{check_dir_operations, invalid_data_err, read_json, write, write_json};
 use secp256k1v24::PublicKey;
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::fs;
 use std::io::{BufReader, BufWriter, Cursor};
 use std::net::SocketAddr;
 use std::ops::Deref;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::{Arc, Mutex};
 
 #[cfg(target_family = "unix")] use std::os::unix::io::AsRawFd;
@@ -28,17 +35,78 @@ use std::sync::{Arc, Mutex};
 use {std::ffi::OsStr, std::os::windows::ffi::OsStrExt};
 
 const USE_TMP_FILE: bool = true;
+/// The first byte of an encrypted nodes addresses file. Plaintext files are JSON objects and always start with `{`.
+const ENCRYPTED_NODES_ADDRESSES_HEADER: u8 = 0x01;
+const NODES_ADDRESSES_DERIVATION_PATH: &str = "lightning/nodes_addresses";
+/// The network graph is written whole every `NETWORK_GRAPH_DELTAS_PER_SNAPSHOT` persists, or sooner if the deltas file
+/// grows bigger than the last snapshot. The persists in between only append the channels and nodes that changed
+/// to the deltas file. The background processor persists the graph hourly, so a snapshot is written about once a day.
+const NETWORK_GRAPH_DELTAS_PER_SNAPSHOT: u64 = 24;
+/// `ERROR_HANDLE_DISK_FULL` and `ERROR_DISK_FULL` system error codes.
+#[cfg(target_family = "windows")]
+const WINDOWS_DISK_FULL_ERRORS: [i32; 2] = [39, 112];
+
+/// Flushes a written file to the disk, the first argument is the destination path of the write.
+pub type SyncFileFn = dyn Fn(&Path, &fs::File) -> std::io::Result<()> + Send + Sync;
+
+fn sync_file_to_disk(_dest_file: &Path, file: &fs::File) -> std::io::Result<()> { file.sync_all() }
 
 pub struct LightningFilesystemPersister {
     main_path: PathBuf,
     backup_path: Option<PathBuf>,
+    /// The master secret used to derive the keys for encrypting the nodes addresses file.
+    /// The file is stored as plaintext JSON if it's not set.
+    nodes_addresses_key: Option<[u8; 64]>,
+    /// When the `ChannelMonitor` writes are flushed to the disk, the other writes are always flushed.
+    monitor_fsync_policy: FsyncPolicy,
+    /// The number of `ChannelMonitor` writes done by this persister, used by [`FsyncPolicy::EveryN`].
+    monitor_writes_count: AtomicU64,
+    /// The `ChannelMonitor` files written without `fsync` since the last flushed write, see [`FsyncPolicy::EveryN`].
+    dirty_monitor_files: Mutex<BTreeSet<PathBuf>>,
+    sync_file: Arc<SyncFileFn>,
+    /// The state of the network graph files, `None` until the graph is read or written.
+    /// It's also reset if appending a delta fails, so that the next persist writes a snapshot.
+    network_graph_files: Mutex<Option<NetworkGraphFiles>>,
+}
+
+/// The network graph snapshot and the deltas appended to it since, see [`NETWORK_GRAPH_DELTAS_PER_SNAPSHOT`].
+struct NetworkGraphFiles {
+    snapshot_digest: sha256::Hash,
+    snapshot_len: u64,
+    deltas_len: u64,
+    deltas_count: u64,
+    /// The digests of the graph records as of the last persist.
+    digests: NetworkGraphDigests,
 }
 
 impl LightningFilesystemPersister {
     /// Initialize a new LightningPersister and set the path to the individual channels'
     /// files.
     #[inline]
-    pub fn new(main_path: PathBuf, backup_path: Option<PathBuf>) -> Self { Self { main_path, backup_path } }
+    pub fn new(main_path: PathBuf, backup_path: Option<PathBuf>, nodes_addresses_key: Option<[u8; 64]>) -> Self {
+        Self {
+            main_path,
+            backup_path,
+            nodes_addresses_key,
+            monitor_fsync_policy: FsyncPolicy::default(),
+            monitor_writes_count: AtomicU64::new(0),
+            dirty_monitor_files: Mutex::new(BTreeSet::new()),
+            sync_file: Arc::new(sync_file_to_disk),
+            network_graph_files: Mutex::new(None),
+        }
+    }
+
+    /// Sets when the `ChannelMonitor` writes are flushed to the disk, see [`FsyncPolicy`] for the trade-offs.
+    pub fn with_monitor_fsync_policy(mut self, monitor_fsync_policy: FsyncPolicy) -> Self {
+        self.monitor_fsync_policy = monitor_fsync_policy;
+        self
+    }
+
+    #[cfg(test)]
+    fn with_sync_file(mut self, sync_file: Arc<SyncFileFn>) -> Self {
+        self.sync_file = sync_file;
+        self
+    }
 
     /// Get the directory which was provided when this persister was initialized.
     #[inline]
@@ -67,6 +135,12 @@ impl LightningFilesystemPersister {
         path
     }
 
+    pub fn network_graph_deltas_path(&self) -> PathBuf {
+        let mut path = self.main_path();
+        path.push("network_graph_deltas");
+        path
+    }
+
     pub fn scorer_path(&self) -> PathBuf {
         let mut path = self.main_path();
         path.push("scorer");
@@ -155,25 +229,313 @@ impl LightningFilesystemPersister {
         }
         Ok(res)
     }
+
+    /// Lists the funding outpoints of the channels with a stored `ChannelMonitor`.
+    /// The outpoints are parsed from the `<txid>_<index>` file names, the files are not read.
+    /// Unlike [`LightningFilesystemPersister::read_channelmonitors`], files with other names are skipped.
+    pub fn list_monitor_outpoints(&self) -> Result<Vec<OutPoint>, std::io::Error> {
+        let path = self.monitors_path();
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+        let mut outpoints = Vec::new();
+        for file_option in fs::read_dir(path)? {
+            let file = file_option?;
+            if let Some(outpoint) = file.file_name().to_str().and_then(monitor_outpoint_from_file_name) {
+                outpoints.push(outpoint);
+            }
+        }
+        Ok(outpoints)
+    }
+
+    /// Serializes the nodes addresses to JSON and encrypts them if an encryption key is configured.
+    fn encode_nodes_addresses(&self, nodes_addresses: &HashMap<String, SocketAddr>) -> std::io::Result<Vec<u8>> {
+        let json = serde_json::to_vec(nodes_addresses).map_err(|e| invalid_data_err("Error", e))?;
+        let key = match &self.nodes_addresses_key {
+            Some(key) => key,
+            None => return Ok(json),
+        };
+
+        let encrypted = encrypt_with_slip21(&json, key, NODES_ADDRESSES_DERIVATION_PATH)
+            .map_err(|e| invalid_data_err("Error encrypting nodes addresses", e))?;
+        let mut content = vec![ENCRYPTED_NODES_ADDRESSES_HEADER];
+        serde_json::to_writer(&mut content, &encrypted).map_err(|e| invalid_data_err("Error", e))?;
+        Ok(content)
+    }
+
+    /// Decodes the nodes addresses file content, decrypting it first if it starts with the encryption header.
+    fn decode_nodes_addresses(&self, content: &[u8]) -> std::io::Result<HashMap<String, SocketAddr>> {
+        let json = match content.split_first() {
+            Some((&ENCRYPTED_NODES_ADDRESSES_HEADER, encrypted)) => {
+                let key = self.nodes_addresses_key.as_ref().ok_or_else(|| {
+                    invalid_data_err(
+                        "Error",
+                        "Nodes addresses file is encrypted but no encryption key is set",
+                    )
+                })?;
+                let encrypted: EncryptedData =
+                    serde_json::from_slice(encrypted).map_err(|e| invalid_data_err("Error", e))?;
+                decrypt_with_slip21(&encrypted, key)
+                    .map_err(|e| invalid_data_err("Error decrypting nodes addresses", e))?
+            },
+            _ => content.to_vec(),
+        };
+        serde_json::from_slice(&json).map_err(|e| invalid_data_err("Error", e))
+    }
 }
 
-impl KVStorePersister for LightningFilesystemPersister {
-    fn persist<W: Writeable>(&self, key: &str, object: &W) -> std::io::Result<()> {
+impl LightningFilesystemPersister {
+    /// Writes the `object` to the file of the given `key` in the main and the backup directories.
+    /// The files are still replaced atomically if `fsync` is false, but the write may be lost on a power loss.
+    fn persist_with_fsync<W: Writeable>(&self, key: &str, object: &W, fsync: bool) -> std::io::Result<()> {
+        if key == "network_graph" {
+            return self.persist_network_graph(object, fsync);
+        }
+
         let mut dest_file = self.main_path();
         dest_file.push(key);
         drop_mutability!(dest_file);
-        write_to_file(dest_file, object)?;
+        let sync_file = fsync.then_some(&*self.sync_file);
+        write_to_file(dest_file, object, sync_file)?;
 
-        if !matches!(key, "network_graph" | "scorer") {
+        if key != "scorer" {
             if let Some(mut dest_file) = self.backup_path() {
                 dest_file.push(key);
                 drop_mutability!(dest_file);
-                write_to_file(dest_file, object)?;
+                write_to_file(dest_file, object, sync_file)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Appends the channels and nodes that changed since the last persist to the deltas file,
+    /// or writes a new snapshot of the graph, see [`NETWORK_GRAPH_DELTAS_PER_SNAPSHOT`].
+    /// The network graph isn't backed up, it can always be synced from the peers again.
+    fn persist_network_graph<W: Writeable>(&self, network_graph: &W, fsync: bool) -> std::io::Result<()> {
+        let encoded = network_graph.encode();
+        let records = NetworkGraphRecords::decode(&encoded)?;
+        let sync_file = fsync.then_some(&*self.sync_file);
+        let mut files = self.network_graph_files.lock().unwrap();
+
+        if let Some(state) = files.as_mut() {
+            if state.deltas_count < NETWORK_GRAPH_DELTAS_PER_SNAPSHOT && state.deltas_len < state.snapshot_len {
+                let is_first_delta = state.deltas_count == 0;
+                let mut content = if is_first_delta {
+                    deltas_file_header(&state.snapshot_digest)
+                } else {
+                    Vec::new()
+                };
+                content.extend_from_slice(&records.delta_since(&state.digests).encode_framed());
+
+                match append_to_file(&self.network_graph_deltas_path(), &content, is_first_delta, sync_file) {
+                    Ok(()) => {
+                        state.deltas_len += content.len() as u64;
+                        state.deltas_count += 1;
+                        state.digests = records.digests();
+                        return Ok(());
+                    },
+                    // The deltas file may end with a partly written delta now, so nothing can be appended to it.
+                    Err(e) => warn!(
+                        "Error appending to the network graph deltas file, writing a snapshot: {}",
+                        e
+                    ),
+                }
             }
         }
 
+        *files = None;
+        write_to_file(self.network_graph_path(), &RawBytes(&encoded), sync_file)?;
+        // The deltas of the old snapshot are ignored on load even if the removal fails.
+        match fs::remove_file(self.network_graph_deltas_path()) {
+            Ok(()) => (),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => (),
+            Err(e) => return Err(e),
+        }
+        *files = Some(NetworkGraphFiles {
+            snapshot_digest: sha256::Hash::hash(&encoded),
+            snapshot_len: encoded.len() as u64,
+            deltas_len: 0,
+            deltas_count: 0,
+            digests: records.digests(),
+        });
         Ok(())
     }
+
+    /// Counts a new `ChannelMonitor` write and returns whether it must be flushed according to the fsync policy.
+    fn next_monitor_write_fsync(&self) -> bool {
+        let write_number = self.monitor_writes_count.fetch_add(1, Ordering::Relaxed) + 1;
+        self.monitor_fsync_policy.should_fsync(write_number)
+    }
+
+    /// Writes a `ChannelMonitor` to the file of the given `key` according to the fsync policy.
+    /// A flushed write also flushes the monitor files written without `fsync` since the previous flushed write.
+    fn persist_monitor<W: Writeable>(&self, key: &str, monitor: &W) -> std::io::Result<()> {
+        let fsync = self.next_monitor_write_fsync();
+        self.persist_with_fsync(key, monitor, fsync)?;
+
+        let mut dirty_files = self.dirty_monitor_files.lock().unwrap();
+        let files = std::iter::once(self.main_path().join(key)).chain(self.backup_path().map(|path| path.join(key)));
+        if !fsync {
+            dirty_files.extend(files);
+            return Ok(());
+        }
+        for file in files {
+            dirty_files.remove(&file);
+        }
+        // The files that couldn't be flushed are retried on the next flushed write.
+        dirty_files.retain(|dest_file| match flush_file(dest_file, &*self.sync_file) {
+            Ok(()) => false,
+            Err(e) => {
+                warn!("Error flushing the channel monitor file {}: {}", dest_file.display(), e);
+                true
+            },
+        });
+        Ok(())
+    }
+}
+
+impl KVStorePersister for LightningFilesystemPersister {
+    fn persist<W: Writeable>(&self, key: &str, object: &W) -> std::io::Result<()> {
+        self.persist_with_fsync(key, object, true)
+    }
+}
+
+/// Checks whether the `error` is caused by running out of disk space.
+/// Such an error is transient, the same write can succeed once some space is freed.
+pub fn is_disk_full_io_error(error: &std::io::Error) -> bool {
+    match error.raw_os_error() {
+        #[cfg(target_family = "unix")]
+        Some(code) => code == libc::ENOSPC,
+        #[cfg(target_family = "windows")]
+        Some(code) => WINDOWS_DISK_FULL_ERRORS.contains(&code),
+        _ => false,
+    }
+}
+
+/// The key under which `KVStorePersister` stores the `ChannelMonitor` of the channel with the given funding outpoint.
+fn channel_monitor_key(funding_txo: OutPoint) -> String {
+    format!("monitors/{}_{}", funding_txo.txid.to_hex(), funding_txo.index)
+}
+
+/// Parses the funding outpoint from a `ChannelMonitor` file name written by [`channel_monitor_key`].
+/// Returns `None` for any other file, e.g. an interrupted `.tmp` write.
+fn monitor_outpoint_from_file_name(file_name: &str) -> Option<OutPoint> {
+    let (txid, index) = file_name.split_once('_')?;
+    if txid.len() != 64 {
+        return None;
+    }
+    Some(OutPoint {
+        txid: Txid::from_hex(txid).ok()?,
+        index: index.parse().ok()?,
+    })
+}
+
+/// Maps the result of a `ChannelMonitor` write to the status reported to the `ChainMonitor`.
+/// A write that failed because the disk is full is reported as `InProgress` rather than `PermanentFailure`
+/// (which force-closes the channel), so that it can be retried later by `retry_pending_monitor_updates`.
+fn channel_monitor_update_status(funding_txo: OutPoint, result: std::io::Result<()>) -> ChannelMonitorUpdateStatus {
+    match result {
+        Ok(()) => ChannelMonitorUpdateStatus::Completed,
+        Err(e) if is_disk_full_io_error(&e) => {
+            common::log::error!(
+                "Disk is full, ChannelMonitor {}:{} will be persisted later: {}",
+                funding_txo.txid,
+                funding_txo.index,
+                e
+            );
+            ChannelMonitorUpdateStatus::InProgress
+        },
+        Err(e) => {
+            common::log::error!(
+                "Failed to persist ChannelMonitor {}:{}: {}",
+                funding_txo.txid,
+                funding_txo.index,
+                e
+            );
+            ChannelMonitorUpdateStatus::PermanentFailure
+        },
+    }
+}
+
+/// When the `ChannelMonitor` writes are flushed to the disk with `fsync`.
+/// Set with the `monitor_fsync_policy` coin config field, e.g. `"always"`, `{"every_n": 10}` or `"never"`.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum FsyncPolicy {
+    /// Every write is flushed before it's reported as completed, so a completed update survives a power loss.
+    /// This is the only policy that keeps the guarantee the `ChainMonitor` relies on, and it's the default.
+    Always,
+    /// Only every N-th write is flushed, along with all the monitor files written since the previous flushed write.
+    /// So up to N - 1 completed updates, across all the channels, can be lost on a power loss (a process crash is fine,
+    /// the OS still writes them out). Since the files written in between are replaced without being flushed, some
+    /// filesystems can also leave such a file empty after a power loss rather than with its previous state.
+    /// Either may lead to broadcasting a revoked state and losing the channel funds. Suits high-throughput routing
+    /// nodes with a reliable power supply. `EveryN(0)` is the same as `Always`.
+    EveryN(u32),
+    /// No write is flushed explicitly, the OS decides when the data reaches the disk. Any number of completed
+    /// updates can be lost on a power loss, and the monitor files can be left empty, so this is only suitable
+    /// for tests and throwaway nodes.
+    Never,
+}
+
+impl Default for FsyncPolicy {
+    fn default() -> Self { FsyncPolicy::Always }
+}
+
+impl FsyncPolicy {
+    /// Whether the write with the given 1-based sequence number must be flushed.
+    fn should_fsync(&self, write_number: u64) -> bool {
+        match *self {
+            FsyncPolicy::Always => true,
+            FsyncPolicy::EveryN(n) => n == 0 || write_number % n as u64 == 0,
+            FsyncPolicy::Never => false,
+        }
+    }
+}
+
+/// Persists `ChannelMonitor`s to the same files as the `KVStorePersister` blanket implementation of `Persist`,
+/// but doesn't treat a full disk as a permanent failure.
+/// The writes are flushed to the disk according to the `monitor_fsync_policy` of the `persister`.
+pub struct ChannelMonitorPersister {
+    persister: Arc<LightningFilesystemPersister>,
+}
+
+impl ChannelMonitorPersister {
+    pub fn new(persister: Arc<LightningFilesystemPersister>) -> Self { ChannelMonitorPersister { persister } }
+
+    /// Writes the `monitor` of the channel with the given funding outpoint to the disk.
+    pub fn persist_channel_monitor<W: Writeable>(
+        &self,
+        funding_txo: OutPoint,
+        monitor: &W,
+    ) -> ChannelMonitorUpdateStatus {
+        let result = self
+            .persister
+            .persist_monitor(&channel_monitor_key(funding_txo), monitor);
+        channel_monitor_update_status(funding_txo, result)
+    }
+}
+
+impl<ChannelSigner: Sign> Persist<ChannelSigner> for ChannelMonitorPersister {
+    fn persist_new_channel(
+        &self,
+        funding_txo: OutPoint,
+        monitor: &ChannelMonitor<ChannelSigner>,
+        _update_id: MonitorUpdateId,
+    ) -> ChannelMonitorUpdateStatus {
+        self.persist_channel_monitor(funding_txo, monitor)
+    }
+
+    fn update_persisted_channel(
+        &self,
+        funding_txo: OutPoint,
+        _update: &Option<ChannelMonitorUpdate>,
+        monitor: &ChannelMonitor<ChannelSigner>,
+        _update_id: MonitorUpdateId,
+    ) -> ChannelMonitorUpdateStatus {
+        self.persist_channel_monitor(funding_txo, monitor)
+    }
 }
 
 #[cfg(target_family = "windows")]
@@ -192,7 +554,8 @@ fn path_to_windows_str<T: AsRef<OsStr>>(path: T) -> Vec<winapi::shared::ntdef::W
     path.as_ref().encode_wide().chain(Some(0)).collect()
 }
 
-fn write_to_file<W: Writeable>(dest_file: PathBuf, data: &W) -> std::io::Result<()> {
+/// The file is flushed to the disk with `sync_file` if it's set, otherwise it's only replaced atomically.
+fn write_to_file<W: Writeable>(dest_file: PathBuf, data: &W, sync_file: Option<&SyncFileFn>) -> std::io::Result<()> {
     let mut tmp_file = dest_file.clone();
     tmp_file.set_extension("tmp");
     drop_mutability!(tmp_file);
@@ -207,16 +570,21 @@ fn write_to_file<W: Writeable>(dest_file: PathBuf, data: &W) -> std::io::Result<
         // rust stdlib 1.36 or higher.
         let mut buf = BufWriter::new(fs::File::create(&tmp_file)?);
         data.write(&mut buf)?;
-        buf.into_inner()?.sync_all()?;
+        let file = buf.into_inner()?;
+        if let Some(sync_file) = sync_file {
+            sync_file(&dest_file, &file)?;
+        }
     }
     // Fsync the parent directory on Unix.
     #[cfg(target_family = "unix")]
     {
         let parent_directory = dest_file.parent().unwrap();
         fs::rename(&tmp_file, &dest_file)?;
-        let dir_file = fs::OpenOptions::new().read(true).open(parent_directory)?;
-        unsafe {
-            libc::fsync(dir_file.as_raw_fd());
+        if sync_file.is_some() {
+            let dir_file = fs::OpenOptions::new().read(true).open(parent_directory)?;
+            unsafe {
+                libc::fsync(dir_file.as_raw_fd());
+            }
         }
     }
     #[cfg(target_family = "windows")]
@@ -245,6 +613,53 @@ fn write_to_file<W: Writeable>(dest_file: PathBuf, data: &W) -> std::io::Result<
     Ok(())
 }
 
+/// Flushes the `dest_file` that was written without `fsync` to the disk, along with its directory on Unix.
+/// A file that was removed since doesn't need to be flushed.
+fn flush_file(dest_file: &Path, sync_file: &SyncFileFn) -> std::io::Result<()> {
+    // Windows requires write access to flush a file.
+    let file = match fs::OpenOptions::new().write(true).open(dest_file) {
+        Ok(file) => file,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(e),
+    };
+    sync_file(dest_file, &file)?;
+    #[cfg(target_family = "unix")]
+    {
+        let dir_file = fs::OpenOptions::new().read(true).open(dest_file.parent().unwrap())?;
+        unsafe {
+            libc::fsync(dir_file.as_raw_fd());
+        }
+    }
+    Ok(())
+}
+
+/// Appends the `content` to the `dest_file`, the file is truncated first if `truncate` is set.
+fn append_to_file(
+    dest_file: &Path,
+    content: &[u8],
+    truncate: bool,
+    sync_file: Option<&SyncFileFn>,
+) -> std::io::Result<()> {
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .write(true)
+        .append(!truncate)
+        .truncate(truncate)
+        .open(dest_file)?;
+    std::io::Write::write_all(&mut file, content)?;
+    if let Some(sync_file) = sync_file {
+        sync_file(dest_file, &file)?;
+    }
+    Ok(())
+}
+
+/// Writes already serialized data as is, unlike `Vec<u8>` which is written prefixed with its length.
+struct RawBytes<'a>(&'a [u8]);
+
+impl Writeable for RawBytes<'_> {
+    fn write<W: Writer>(&self, writer: &mut W) -> Result<(), std::io::Error> { writer.write_all(self.0) }
+}
+
 #[async_trait]
 impl LightningStorage for LightningFilesystemPersister {
     type Error = std::io::Error;
@@ -293,16 +708,42 @@ impl LightningStorage for LightningFilesystemPersister {
         .await
     }
 
+    async fn health_check(&self) -> Result<PersisterHealth, Self::Error> {
+        let main_path = self.main_path();
+        let backup_path = self.backup_path();
+        async_blocking(move || {
+            let mut health = PersisterHealth::default();
+            match check_dir_operations(&main_path) {
+                Ok(()) => health.main_dir_writable = true,
+                Err(e) => health
+                    .errors
+                    .push(format!("Main dir {} check failed: {}", main_path.display(), e)),
+            }
+            if let Some(backup_path) = backup_path {
+                let backup_dir_writable = match check_dir_operations(&backup_path) {
+                    Ok(()) => true,
+                    Err(e) => {
+                        health
+                            .errors
+                            .push(format!("Backup dir {} check failed: {}", backup_path.display(), e));
+                        false
+                    },
+                };
+                health.backup_dir_writable = Some(backup_dir_writable);
+            }
+            Ok(health)
+        })
+        .await
+    }
+
     async fn get_nodes_addresses(&self) -> Result<NodesAddressesMap, Self::Error> {
         let path = self.nodes_addresses_path();
         if !path.exists() {
             return Ok(HashMap::new());
         }
 
-        let nodes_addresses: HashMap<String, SocketAddr> = read_json(&path)
-            .await
-            .map_err(|e| invalid_data_err("Error", e))?
-            .ok_or_else(|| std::io::Error::from(std::io::ErrorKind::NotFound))?;
+        let content = async_blocking(move || fs::read(path)).await?;
+        let nodes_addresses = self.decode_nodes_addresses(&content)?;
 
         nodes_addresses
             .iter()
@@ -322,18 +763,16 @@ impl LightningStorage for LightningFilesystemPersister {
             .iter()
             .map(|(pubkey, addr)| (pubkey.to_string(), *addr))
             .collect();
+        let content = self.encode_nodes_addresses(&nodes_addresses)?;
 
-        write_json(&nodes_addresses, &path, USE_TMP_FILE)
-            .await
-            .map_err(|e| invalid_data_err("Error", e))?;
-
-        if let Some(path) = backup_path {
-            write_json(&nodes_addresses, &path, USE_TMP_FILE)
-                .await
-                .map_err(|e| invalid_data_err("Error", e))?;
-        }
-
-        Ok(())
+        async_blocking(move || {
+            write(&path, &content, USE_TMP_FILE)?;
+            if let Some(path) = backup_path {
+                write(&path, &content, USE_TMP_FILE)?;
+            }
+            Ok(())
+        })
+        .await
     }
 
     async fn get_network_graph(&self, network: Network, logger: Arc<LogState>) -> Result<NetworkGraph, Self::Error> {
@@ -341,12 +780,44 @@ impl LightningStorage for LightningFilesystemPersister {
         if !path.exists() {
             return Ok(NetworkGraph::new(genesis_block(network).header.block_hash(), logger));
         }
-        async_blocking(move || {
-            let file = fs::File::open(path)?;
+        let deltas_path = self.network_graph_deltas_path();
+        let (network_graph, files) = async_blocking(move || {
             common::log::info!("Reading the saved lightning network graph from file, this can take some time!");
-            NetworkGraph::read(&mut BufReader::new(file), logger).map_err(|e| invalid_data_err("Error", e))
+            let snapshot = fs::read(path)?;
+            let snapshot_digest = sha256::Hash::hash(&snapshot);
+            let mut records = NetworkGraphRecords::decode(&snapshot)?;
+
+            let deltas_content = match fs::read(deltas_path) {
+                Ok(content) => content,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+                Err(e) => return Err(e),
+            };
+            let deltas = read_deltas_file(&snapshot_digest, &deltas_content);
+            let deltas_count = deltas.deltas.len() as u64;
+            for delta in deltas.deltas {
+                records.apply(delta);
+            }
+
+            let network_graph = NetworkGraph::read(&mut Cursor::new(records.encode()), logger)
+                .map_err(|e| invalid_data_err("Error", e))?;
+            // Nothing can be appended after a partly written delta, the next persist writes a snapshot then.
+            let files = (!deltas.truncated).then(|| NetworkGraphFiles {
+                snapshot_digest,
+                snapshot_len: snapshot.len() as u64,
+                deltas_len: if deltas_count == 0 {
+                    0
+                } else {
+                    deltas_content.len() as u64
+                },
+                deltas_count,
+                digests: records.digests(),
+            });
+            Ok((network_graph, files))
         })
-        .await
+        .await?;
+
+        *self.network_graph_files.lock().unwrap() = files;
+        Ok(network_graph)
     }
 
     async fn get_scorer(&self, network_graph: Arc<NetworkGraph>, logger: Arc<LogState>) -> Result<Scorer, Self::Error> {
@@ -398,3 +869,307 @@ impl LightningStorage for LightningFilesystemPersister {
             .map_err(|e| invalid_data_err("Error", e))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lightning::ln_errors::EnableLightningError;
+    use common::block_on;
+    use parking_lot::Mutex as PaMutex;
+    use secp256k1v24::{Secp256k1, SecretKey};
+
+    fn test_persister_dir(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join("ln_filesystem_persister").join(name);
+        let _ = fs::remove_dir_all(&path);
+        path
+    }
+
+    fn test_nodes_addresses() -> NodesAddressesMap {
+        let secp = Secp256k1::new();
+        let pubkey = PublicKey::from_secret_key(&secp, &SecretKey::from_slice(&[1; 32]).unwrap());
+        let mut nodes_addresses = HashMap::new();
+        nodes_addresses.insert(pubkey, "127.0.0.1:9735".parse().unwrap());
+        nodes_addresses
+    }
+
+    #[test]
+    fn test_save_get_encrypted_nodes_addresses() {
+        let main_path = test_persister_dir("encrypted_nodes_addresses");
+        let persister = LightningFilesystemPersister::new(main_path, None, Some([7; 64]));
+        block_on(persister.init_fs()).unwrap();
+
+        let nodes_addresses = test_nodes_addresses();
+        block_on(persister.save_nodes_addresses(Arc::new(PaMutex::new(nodes_addresses.clone())))).unwrap();
+
+        let content = fs::read(persister.nodes_addresses_path()).unwrap();
+        assert_eq!(content[0], ENCRYPTED_NODES_ADDRESSES_HEADER);
+        assert!(serde_json::from_slice::<HashMap<String, SocketAddr>>(&content).is_err());
+        let (pubkey, _) = nodes_addresses.iter().next().unwrap();
+        assert!(!String::from_utf8_lossy(&content).contains(&pubkey.to_string()));
+
+        let actual = block_on(persister.get_nodes_addresses()).unwrap();
+        assert_eq!(nodes_addresses, actual);
+
+        // The encrypted file can't be read without the key.
+        let persister = LightningFilesystemPersister::new(persister.main_path(), None, None);
+        block_on(persister.get_nodes_addresses()).unwrap_err();
+    }
+
+    #[test]
+    fn test_get_plaintext_nodes_addresses_with_key() {
+        let main_path = test_persister_dir("plaintext_nodes_addresses");
+        let persister = LightningFilesystemPersister::new(main_path.clone(), None, None);
+        block_on(persister.init_fs()).unwrap();
+
+        let nodes_addresses = test_nodes_addresses();
+        block_on(persister.save_nodes_addresses(Arc::new(PaMutex::new(nodes_addresses.clone())))).unwrap();
+        let content = fs::read(persister.nodes_addresses_path()).unwrap();
+        serde_json::from_slice::<HashMap<String, SocketAddr>>(&content).unwrap();
+
+        // Plaintext files saved before the key was configured are still readable.
+        let persister = LightningFilesystemPersister::new(main_path, None, Some([7; 64]));
+        let actual = block_on(persister.get_nodes_addresses()).unwrap();
+        assert_eq!(nodes_addresses, actual);
+    }
+
+    #[test]
+    fn test_list_monitor_outpoints() {
+        let path = test_persister_dir("list_monitor_outpoints");
+        let persister = LightningFilesystemPersister::new(path, None, None);
+        assert!(persister.list_monitor_outpoints().unwrap().is_empty());
+
+        fs::create_dir_all(persister.monitors_path()).unwrap();
+        let mut outpoints: Vec<_> = (0..3u16)
+            .map(|index| OutPoint {
+                txid: Txid::from_hex(&format!("{:02x}", index + 1).repeat(32)).unwrap(),
+                index,
+            })
+            .collect();
+        // The file bodies aren't valid `ChannelMonitor`s, listing the outpoints must not read them.
+        for outpoint in outpoints.iter() {
+            persister
+                .persist(&channel_monitor_key(*outpoint), &vec![0u8; 4])
+                .unwrap();
+        }
+        let tmp_file = format!("{}.tmp", channel_monitor_key(outpoints[0]));
+        for other_file in [tmp_file.as_str(), "monitors/checkval", "monitors/not_a_monitor"] {
+            fs::write(persister.main_path().join(other_file), b"garbage").unwrap();
+        }
+
+        let mut listed = persister.list_monitor_outpoints().unwrap();
+        listed.sort_by_key(|outpoint| outpoint.index);
+        outpoints.sort_by_key(|outpoint| outpoint.index);
+        assert_eq!(listed, outpoints);
+    }
+
+    #[test]
+    fn test_fsync_policy() {
+        let txid = Txid::from_hex(&"ab".repeat(32)).unwrap();
+        // Returns the indexes of the monitor writes that were flushed to the disk.
+        let synced_writes = |name: &str, fsync_policy: Option<FsyncPolicy>, writes: u16| {
+            let synced = Arc::new(Mutex::new(Vec::new()));
+            let synced_hook = synced.clone();
+            let sync_file: Arc<SyncFileFn> = Arc::new(move |dest_file: &Path, file: &fs::File| {
+                synced_hook.lock().unwrap().push(dest_file.to_path_buf());
+                file.sync_all()
+            });
+            let mut persister =
+                LightningFilesystemPersister::new(test_persister_dir(name), None, None).with_sync_file(sync_file);
+            if let Some(fsync_policy) = fsync_policy {
+                persister = persister.with_monitor_fsync_policy(fsync_policy);
+            }
+            let persister = Arc::new(persister);
+            block_on(persister.init_fs()).unwrap();
+
+            let monitor_persister = ChannelMonitorPersister::new(persister.clone());
+            for index in 0..writes {
+                let funding_txo = OutPoint { txid, index };
+                assert!(matches!(
+                    monitor_persister.persist_channel_monitor(funding_txo, &vec![1u8; 8]),
+                    ChannelMonitorUpdateStatus::Completed
+                ));
+            }
+            let synced = synced.lock().unwrap();
+            (0..writes)
+                .filter(|index| {
+                    let mut monitor_file = persister.main_path();
+                    monitor_file.push(channel_monitor_key(OutPoint { txid, index: *index }));
+                    synced.contains(&monitor_file)
+                })
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(synced_writes("fsync_default", None, 4), vec![0, 1, 2, 3]);
+        assert_eq!(synced_writes("fsync_always", Some(FsyncPolicy::Always), 4), vec![
+            0, 1, 2, 3
+        ]);
+        // Every 3rd write also flushes the files of the two writes before it, the 10th write isn't flushed yet.
+        assert_eq!(
+            synced_writes("fsync_every_3", Some(FsyncPolicy::EveryN(3)), 10),
+            (0..9).collect::<Vec<_>>()
+        );
+        assert!(synced_writes("fsync_every_3_pending", Some(FsyncPolicy::EveryN(3)), 2).is_empty());
+        assert_eq!(synced_writes("fsync_every_0", Some(FsyncPolicy::EveryN(0)), 3), vec![
+            0, 1, 2
+        ]);
+        assert!(synced_writes("fsync_never", Some(FsyncPolicy::Never), 4).is_empty());
+
+        let policies: Vec<FsyncPolicy> = serde_json::from_value(json!(["always", {"every_n": 10}, "never"])).unwrap();
+        assert_eq!(policies, vec![
+            FsyncPolicy::Always,
+            FsyncPolicy::EveryN(10),
+            FsyncPolicy::Never
+        ]);
+    }
+
+    #[test]
+    #[cfg(target_family = "unix")]
+    fn test_disk_full_is_transient() {
+        let funding_txo = OutPoint {
+            txid: Txid::from_hex(&"ab".repeat(32)).unwrap(),
+            index: 1,
+        };
+
+        let disk_full = std::io::Error::from_raw_os_error(libc::ENOSPC);
+        assert!(is_disk_full_io_error(&disk_full));
+        assert!(matches!(
+            EnableLightningError::from(std::io::Error::from_raw_os_error(libc::ENOSPC)),
+            EnableLightningError::DiskFull(_)
+        ));
+        assert!(matches!(
+            channel_monitor_update_status(funding_txo, Err(disk_full)),
+            ChannelMonitorUpdateStatus::InProgress
+        ));
+
+        let permission_denied = std::io::Error::from_raw_os_error(libc::EACCES);
+        assert!(!is_disk_full_io_error(&permission_denied));
+        assert!(matches!(
+            EnableLightningError::from(std::io::Error::from_raw_os_error(libc::EACCES)),
+            EnableLightningError::IOError(_)
+        ));
+        assert!(matches!(
+            channel_monitor_update_status(funding_txo, Err(permission_denied)),
+            ChannelMonitorUpdateStatus::PermanentFailure
+        ));
+
+        assert!(matches!(
+            channel_monitor_update_status(funding_txo, Ok(())),
+            ChannelMonitorUpdateStatus::Completed
+        ));
+    }
+
+    #[test]
+    fn test_network_graph_deltas_reconstruction() {
+        use lightning::ln::features::ChannelFeatures;
+        use lightning::ln::msgs::UnsignedChannelUpdate;
+        use lightning::routing::gossip::NodeId;
+
+        const NODES_COUNT: u8 = 50;
+
+        let main_path = test_persister_dir("network_graph_deltas");
+        let persister = LightningFilesystemPersister::new(main_path.clone(), None, None);
+        block_on(persister.init_fs()).unwrap();
+
+        let logger = Arc::new(LogState::in_memory());
+        let genesis_hash = genesis_block(Network::Testnet).header.block_hash();
+        let network_graph = NetworkGraph::new(genesis_hash, logger.clone());
+        let secp = Secp256k1::new();
+        let node_ids: Vec<_> = (1..=NODES_COUNT)
+            .map(|i| PublicKey::from_secret_key(&secp, &SecretKey::from_slice(&[i; 32]).unwrap()))
+            .collect();
+        let timestamp = common::now_sec();
+        let add_channel = |short_channel_id: u64, node_one: usize, node_two: usize| {
+            network_graph
+                .add_channel_from_partial_announcement(
+                    short_channel_id,
+                    timestamp,
+                    ChannelFeatures::empty(),
+                    node_ids[node_one],
+                    node_ids[node_two],
+                )
+                .unwrap()
+        };
+        let update_channel = |short_channel_id: u64, timestamp: u64, fee_base_msat: u32| {
+            network_graph
+                .update_channel_unsigned(&UnsignedChannelUpdate {
+                    chain_hash: genesis_hash,
+                    short_channel_id,
+                    timestamp: timestamp as u32,
+                    flags: 0,
+                    cltv_expiry_delta: 144,
+                    htlc_minimum_msat: 1,
+                    htlc_maximum_msat: 1_000_000,
+                    fee_base_msat,
+                    fee_proportional_millionths: 1,
+                    excess_data: Vec::new(),
+                })
+                .unwrap()
+        };
+
+        // A chain of channels between the nodes.
+        for node in 1..NODES_COUNT as usize - 1 {
+            add_channel(node as u64, node, node + 1);
+        }
+        persister.persist("network_graph", &network_graph).unwrap();
+        let snapshot = fs::read(persister.network_graph_path()).unwrap();
+        assert!(!persister.network_graph_deltas_path().exists());
+
+        // A new channel with a new node, two updates of the same channel and a closed channel with its node removed.
+        add_channel(100, 0, NODES_COUNT as usize - 1);
+        persister.persist("network_graph", &network_graph).unwrap();
+        update_channel(2, timestamp, 1000);
+        persister.persist("network_graph", &network_graph).unwrap();
+        update_channel(2, timestamp + 1, 2000);
+        network_graph.channel_failed(1, true);
+        persister.persist("network_graph", &network_graph).unwrap();
+
+        assert_eq!(fs::read(persister.network_graph_path()).unwrap(), snapshot);
+        assert!(persister.network_graph_deltas_path().exists());
+
+        let reader = LightningFilesystemPersister::new(main_path, None, None);
+        let reconstructed = block_on(reader.get_network_graph(Network::Testnet, logger)).unwrap();
+        assert_eq!(
+            NetworkGraphRecords::decode(&reconstructed.encode()).unwrap(),
+            NetworkGraphRecords::decode(&network_graph.encode()).unwrap()
+        );
+        let read_only = reconstructed.read_only();
+        assert!(read_only.channels().get(&1).is_none());
+        assert!(read_only.nodes().get(&NodeId::from_pubkey(&node_ids[1])).is_none());
+        let channel_update = read_only.channels().get(&2).unwrap().one_to_two.as_ref().unwrap();
+        assert_eq!(channel_update.fees.base_msat, 2000);
+        drop(read_only);
+
+        // The reader keeps appending to the same deltas file until the next snapshot is due.
+        for _ in 3..NETWORK_GRAPH_DELTAS_PER_SNAPSHOT {
+            reader.persist("network_graph", &reconstructed).unwrap();
+        }
+        assert_eq!(fs::read(reader.network_graph_path()).unwrap(), snapshot);
+        reader.persist("network_graph", &reconstructed).unwrap();
+        assert_eq!(fs::read(reader.network_graph_path()).unwrap(), reconstructed.encode());
+        assert!(!reader.network_graph_deltas_path().exists());
+    }
+
+    #[test]
+    fn test_health_check() {
+        let main_path = test_persister_dir("health_check");
+        let backup_path = test_persister_dir("health_check_backup");
+        let persister = LightningFilesystemPersister::new(main_path.clone(), Some(backup_path), None);
+        block_on(persister.init_fs()).unwrap();
+
+        let health = block_on(persister.health_check()).unwrap();
+        assert!(health.is_healthy(), "{:?}", health);
+        assert_eq!(health.backup_dir_writable, Some(true));
+        assert!(health.errors.is_empty());
+
+        // Replace the main dir with a regular file, so that nothing can be written to it even by the root user.
+        fs::remove_dir_all(&main_path).unwrap();
+        fs::write(&main_path, b"not a directory").unwrap();
+        let health = block_on(persister.health_check()).unwrap();
+        fs::remove_file(&main_path).unwrap();
+
+        assert!(!health.is_healthy());
+        assert!(!health.main_dir_writable);
+        assert_eq!(health.backup_dir_writable, Some(true));
+        assert_eq!(health.errors.len(), 1);
+    }
+}
@@ -0,0 +1,295 @@
+//! The network graph is persisted as a snapshot written with `NetworkGraph::write` plus a deltas file.
+//! Between the snapshots, only the channels and nodes that changed since the previous persist are appended
+//! to the deltas file, and the graph is reconstructed on load by applying the deltas on top of the snapshot.
+
+use bitcoin_hashes::{sha256, Hash};
+use lightning::routing::gossip::{ChannelInfo, NodeInfo};
+use lightning::util::ser::Readable;
+use mm2_io::fs::invalid_data_err;
+use std::collections::{BTreeMap, HashMap};
+use std::io::{Cursor, Read};
+
+/// The length of the serialization version prefix and the genesis block hash that a serialized graph starts with.
+const GRAPH_HEADER_LEN: usize = 2 + 32;
+/// The length of a serialized `NodeId`, which is a compressed public key.
+const NODE_ID_LEN: usize = 33;
+/// The deltas file starts with the SHA256 of the snapshot that its deltas are applied to.
+const DELTAS_FILE_HEADER_LEN: usize = 32;
+
+type NodeIdBytes = [u8; NODE_ID_LEN];
+
+/// A serialized `NetworkGraph` split into its channel and node records, the records are kept serialized.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct NetworkGraphRecords {
+    /// The serialization version prefix and the genesis block hash.
+    header: Vec<u8>,
+    channels: BTreeMap<u64, Vec<u8>>,
+    nodes: BTreeMap<NodeIdBytes, Vec<u8>>,
+    /// The TLV fields that end the graph, e.g. the last rapid gossip sync timestamp.
+    trailer: Vec<u8>,
+}
+
+impl NetworkGraphRecords {
+    /// Splits the output of `NetworkGraph::write` into records.
+    pub(crate) fn decode(bytes: &[u8]) -> std::io::Result<Self> {
+        let mut reader = Cursor::new(bytes);
+        let mut header = vec![0; GRAPH_HEADER_LEN];
+        reader.read_exact(&mut header)?;
+
+        let mut channels = BTreeMap::new();
+        for _ in 0..read_u64(&mut reader)? {
+            let short_channel_id = read_u64(&mut reader)?;
+            channels.insert(short_channel_id, read_record::<ChannelInfo>(&mut reader)?);
+        }
+
+        let mut nodes = BTreeMap::new();
+        for _ in 0..read_u64(&mut reader)? {
+            let node_id = read_node_id(&mut reader)?;
+            nodes.insert(node_id, read_record::<NodeInfo>(&mut reader)?);
+        }
+
+        let mut trailer = Vec::new();
+        reader.read_to_end(&mut trailer)?;
+
+        Ok(NetworkGraphRecords {
+            header,
+            channels,
+            nodes,
+            trailer,
+        })
+    }
+
+    /// Serializes the records in the `NetworkGraph::write` format, so that they can be read with `NetworkGraph::read`.
+    pub(crate) fn encode(&self) -> Vec<u8> {
+        let mut bytes = self.header.clone();
+        bytes.extend_from_slice(&(self.channels.len() as u64).to_be_bytes());
+        for (short_channel_id, record) in &self.channels {
+            bytes.extend_from_slice(&short_channel_id.to_be_bytes());
+            bytes.extend_from_slice(record);
+        }
+        bytes.extend_from_slice(&(self.nodes.len() as u64).to_be_bytes());
+        for (node_id, record) in &self.nodes {
+            bytes.extend_from_slice(node_id);
+            bytes.extend_from_slice(record);
+        }
+        bytes.extend_from_slice(&self.trailer);
+        bytes
+    }
+
+    pub(crate) fn digests(&self) -> NetworkGraphDigests {
+        NetworkGraphDigests {
+            channels: self
+                .channels
+                .iter()
+                .map(|(short_channel_id, record)| (*short_channel_id, sha256::Hash::hash(record)))
+                .collect(),
+            nodes: self
+                .nodes
+                .iter()
+                .map(|(node_id, record)| (*node_id, sha256::Hash::hash(record)))
+                .collect(),
+        }
+    }
+
+    /// Returns the records that were added, changed or removed since the graph had the `previous` digests.
+    /// A channel or node record changes whenever a gossip message for it is applied, e.g. a channel update
+    /// with a newer timestamp.
+    pub(crate) fn delta_since(&self, previous: &NetworkGraphDigests) -> NetworkGraphDelta {
+        NetworkGraphDelta {
+            header: self.header.clone(),
+            trailer: self.trailer.clone(),
+            changed_channels: changed_records(&self.channels, &previous.channels),
+            removed_channels: removed_records(&self.channels, &previous.channels),
+            changed_nodes: changed_records(&self.nodes, &previous.nodes),
+            removed_nodes: removed_records(&self.nodes, &previous.nodes),
+        }
+    }
+
+    pub(crate) fn apply(&mut self, delta: NetworkGraphDelta) {
+        self.header = delta.header;
+        self.trailer = delta.trailer;
+        for short_channel_id in delta.removed_channels {
+            self.channels.remove(&short_channel_id);
+        }
+        self.channels.extend(delta.changed_channels);
+        for node_id in delta.removed_nodes {
+            self.nodes.remove(&node_id);
+        }
+        self.nodes.extend(delta.changed_nodes);
+    }
+}
+
+/// The digests of the graph records as of the last persist, used to find the records that changed since then
+/// without keeping a second copy of the graph in memory.
+#[derive(Debug, Default)]
+pub(crate) struct NetworkGraphDigests {
+    channels: HashMap<u64, sha256::Hash>,
+    nodes: HashMap<NodeIdBytes, sha256::Hash>,
+}
+
+/// The graph records that changed between two persists.
+#[derive(Debug, Default, PartialEq)]
+pub(crate) struct NetworkGraphDelta {
+    header: Vec<u8>,
+    trailer: Vec<u8>,
+    changed_channels: BTreeMap<u64, Vec<u8>>,
+    removed_channels: Vec<u64>,
+    changed_nodes: BTreeMap<NodeIdBytes, Vec<u8>>,
+    removed_nodes: Vec<NodeIdBytes>,
+}
+
+impl NetworkGraphDelta {
+    fn encode(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        write_bytes(&mut bytes, &self.header);
+        write_bytes(&mut bytes, &self.trailer);
+        bytes.extend_from_slice(&(self.changed_channels.len() as u64).to_be_bytes());
+        for (short_channel_id, record) in &self.changed_channels {
+            bytes.extend_from_slice(&short_channel_id.to_be_bytes());
+            write_bytes(&mut bytes, record);
+        }
+        bytes.extend_from_slice(&(self.removed_channels.len() as u64).to_be_bytes());
+        for short_channel_id in &self.removed_channels {
+            bytes.extend_from_slice(&short_channel_id.to_be_bytes());
+        }
+        bytes.extend_from_slice(&(self.changed_nodes.len() as u64).to_be_bytes());
+        for (node_id, record) in &self.changed_nodes {
+            bytes.extend_from_slice(node_id);
+            write_bytes(&mut bytes, record);
+        }
+        bytes.extend_from_slice(&(self.removed_nodes.len() as u64).to_be_bytes());
+        for node_id in &self.removed_nodes {
+            bytes.extend_from_slice(node_id);
+        }
+        bytes
+    }
+
+    fn decode(bytes: &[u8]) -> std::io::Result<Self> {
+        let mut reader = Cursor::new(bytes);
+        let mut delta = NetworkGraphDelta {
+            header: read_bytes(&mut reader)?,
+            trailer: read_bytes(&mut reader)?,
+            ..Default::default()
+        };
+        for _ in 0..read_u64(&mut reader)? {
+            let short_channel_id = read_u64(&mut reader)?;
+            delta
+                .changed_channels
+                .insert(short_channel_id, read_bytes(&mut reader)?);
+        }
+        for _ in 0..read_u64(&mut reader)? {
+            delta.removed_channels.push(read_u64(&mut reader)?);
+        }
+        for _ in 0..read_u64(&mut reader)? {
+            let node_id = read_node_id(&mut reader)?;
+            delta.changed_nodes.insert(node_id, read_bytes(&mut reader)?);
+        }
+        for _ in 0..read_u64(&mut reader)? {
+            delta.removed_nodes.push(read_node_id(&mut reader)?);
+        }
+        Ok(delta)
+    }
+
+    /// Serializes the delta prefixed with its length, as it's appended to the deltas file.
+    pub(crate) fn encode_framed(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        write_bytes(&mut bytes, &self.encode());
+        bytes
+    }
+}
+
+/// The content of a deltas file.
+#[derive(Debug, Default)]
+pub(crate) struct NetworkGraphDeltas {
+    pub(crate) deltas: Vec<NetworkGraphDelta>,
+    /// Whether the file ends with a partly written or a corrupted delta, e.g. because of a crash during an append.
+    /// Nothing can be appended to such a file.
+    pub(crate) truncated: bool,
+}
+
+/// Returns the header of a deltas file with the deltas for the snapshot with the given digest.
+pub(crate) fn deltas_file_header(snapshot_digest: &sha256::Hash) -> Vec<u8> { snapshot_digest.as_inner().to_vec() }
+
+/// Reads the deltas from the `content` of a deltas file written for the snapshot with the given digest.
+/// No deltas are returned if the file was written for another snapshot, e.g. if the persister stopped after
+/// writing a new snapshot but before removing the old deltas file.
+pub(crate) fn read_deltas_file(snapshot_digest: &sha256::Hash, content: &[u8]) -> NetworkGraphDeltas {
+    let mut result = NetworkGraphDeltas::default();
+    if content.len() < DELTAS_FILE_HEADER_LEN || content[..DELTAS_FILE_HEADER_LEN] != snapshot_digest.as_inner()[..] {
+        return result;
+    }
+
+    let mut reader = Cursor::new(&content[DELTAS_FILE_HEADER_LEN..]);
+    while (reader.position() as usize) < reader.get_ref().len() {
+        match read_bytes(&mut reader).and_then(|delta| NetworkGraphDelta::decode(&delta)) {
+            Ok(delta) => result.deltas.push(delta),
+            Err(_) => {
+                result.truncated = true;
+                break;
+            },
+        }
+    }
+    result
+}
+
+fn changed_records<K: Copy + Ord + std::hash::Hash>(
+    records: &BTreeMap<K, Vec<u8>>,
+    previous: &HashMap<K, sha256::Hash>,
+) -> BTreeMap<K, Vec<u8>> {
+    records
+        .iter()
+        .filter(|(key, record)| previous.get(key) != Some(&sha256::Hash::hash(record)))
+        .map(|(key, record)| (*key, record.clone()))
+        .collect()
+}
+
+fn removed_records<K: Copy + Ord + std::hash::Hash>(
+    records: &BTreeMap<K, Vec<u8>>,
+    previous: &HashMap<K, sha256::Hash>,
+) -> Vec<K> {
+    let mut removed: Vec<K> = previous
+        .keys()
+        .filter(|key| !records.contains_key(key))
+        .copied()
+        .collect();
+    removed.sort();
+    removed
+}
+
+/// Reads a record of the type `T` and returns it still serialized.
+fn read_record<T: Readable>(reader: &mut Cursor<&[u8]>) -> std::io::Result<Vec<u8>> {
+    let start = reader.position() as usize;
+    T::read(reader).map_err(|e| invalid_data_err("Error decoding the network graph", e))?;
+    Ok(reader.get_ref()[start..reader.position() as usize].to_vec())
+}
+
+fn read_u64(reader: &mut Cursor<&[u8]>) -> std::io::Result<u64> {
+    let mut buf = [0; 8];
+    reader.read_exact(&mut buf)?;
+    Ok(u64::from_be_bytes(buf))
+}
+
+fn read_node_id(reader: &mut Cursor<&[u8]>) -> std::io::Result<NodeIdBytes> {
+    let mut node_id = [0; NODE_ID_LEN];
+    reader.read_exact(&mut node_id)?;
+    Ok(node_id)
+}
+
+/// Reads bytes prefixed with their length.
+fn read_bytes(reader: &mut Cursor<&[u8]>) -> std::io::Result<Vec<u8>> {
+    let mut len = [0; 4];
+    reader.read_exact(&mut len)?;
+    let len = u32::from_be_bytes(len) as usize;
+    // Check the length before allocating, it can be garbage if the file is corrupted.
+    if len > reader.get_ref().len() - reader.position() as usize {
+        return Err(std::io::ErrorKind::UnexpectedEof.into());
+    }
+    let mut bytes = vec![0; len];
+    reader.read_exact(&mut bytes)?;
+    Ok(bytes)
+}
+
+fn write_bytes(bytes: &mut Vec<u8>, data: &[u8]) {
+    bytes.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    bytes.extend_from_slice(data);
+}
@@ -1,9 +1,11 @@
 use super::*;
 use crate::lightning::ln_errors::{SaveChannelClosingError, SaveChannelClosingResult};
-use crate::lightning::ln_utils::RpcBestBlock;
+use crate::lightning::ln_filesystem_persister::ChannelMonitorPersister;
+use crate::lightning::ln_utils::{retry_pending_monitor_updates, RpcBestBlock};
 use crate::utxo::rpc_clients::{BlockHashOrHeight, ConfirmedTransactionInfo, ElectrumBlockHeader, ElectrumClient,
                                ElectrumNonce, EstimateFeeMethod, UtxoRpcClientEnum, UtxoRpcResult};
 use crate::utxo::spv::SimplePaymentVerification;
+use crate::utxo::utxo_common::fee_rate::TxFeeRate;
 use crate::utxo::utxo_standard::UtxoStandardCoin;
 use crate::utxo::GetConfirmedTxError;
 use crate::{MarketCoinOps, MmCoin, WaitForHTLCTxSpendArgs, WeakSpawner};
@@ -93,12 +95,19 @@ pub async fn ln_best_block_update_loop(
     platform: Arc<Platform>,
     db: SqliteLightningDB,
     chain_monitor: Arc<ChainMonitor>,
+    monitor_persister: Arc<ChannelMonitorPersister>,
     channel_manager: Arc<ChannelManager>,
     best_header_listener: ElectrumClient,
     best_block: RpcBestBlock,
 ) {
     let mut current_best_block = best_block;
     loop {
+        // ChannelMonitor updates that couldn't be persisted because the disk was full are retried until they succeed.
+        let chain_monitor_for_retry = chain_monitor.clone();
+        let monitor_persister_for_retry = monitor_persister.clone();
+        async_blocking(move || retry_pending_monitor_updates(&chain_monitor_for_retry, &monitor_persister_for_retry))
+            .await;
+
         // Transactions confirmations check can be done at every CHECK_FOR_NEW_BEST_BLOCK_INTERVAL instead of at every new block
         // in case a transaction confirmation fails due to electrums being down. This way there will be no need to wait for a new
         // block to confirm such transaction and causing delays.
@@ -591,7 +600,7 @@ impl FeeEstimator for Platform {
         // https://docs.rs/lightning/0.0.101/lightning/chain/chaininterface/trait.FeeEstimator.html#tymethod.get_est_sat_per_1000_weight
         // This has changed in rust-lightning v0.0.110 as LDK currently wraps get_est_sat_per_1000_weight to ensure that the value returned is
         // no smaller than 253. https://github.com/lightningdevkit/rust-lightning/pull/1552
-        (fee_rate as f64 / 4.0).ceil() as u32
+        TxFeeRate::from_sat_per_kb(fee_rate).sat_per_kwu() as u32
     }
 }
 
@@ -194,6 +194,8 @@ pub struct PaymentInfoForRPC {
     status: HTLCStatus,
     created_at: i64,
     last_updated: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    invoice: Option<String>,
 }
 
 impl From<PaymentInfo> for PaymentInfoForRPC {
@@ -207,6 +209,7 @@ impl From<PaymentInfo> for PaymentInfoForRPC {
             status: info.status,
             created_at: info.created_at,
             last_updated: info.last_updated,
+            invoice: info.invoice,
         }
     }
 }
@@ -1,23 +1,47 @@
 #![allow(deprecated)] // TODO: remove this once rusqlite is >= 0.29
 
-use crate::lightning::ln_db::{ChannelType, ChannelVisibility, ClosedChannelsFilter, DBChannelDetails,
-                              DBPaymentsFilter, GetClosedChannelsResult, GetPaymentsResult, HTLCStatus, LightningDB,
-                              PaymentInfo, PaymentType};
+use crate::lightning::ln_db::{ChannelCloseReason, ChannelType, ChannelVisibility, ClosedChannelsFilter,
+                              DBChannelDetails, DBPaymentsFilter, GetClosedChannelsResult, GetPaymentsResult,
+                              HTLCStatus, LightningDB, PaymentInfo, PaymentType, PAYMENTS_CSV_HEADER};
 use async_trait::async_trait;
 use common::{async_blocking, now_sec_i64, PagingOptionsEnum};
 use db_common::owned_named_params;
+use db_common::sqlite::rusqlite::ffi;
 use db_common::sqlite::rusqlite::types::Type;
-use db_common::sqlite::rusqlite::{params, Error as SqlError, Row, ToSql};
+use db_common::sqlite::rusqlite::{params, params_from_iter, Connection, Error as SqlError, Row, ToSql};
 use db_common::sqlite::sql_builder::SqlBuilder;
 use db_common::sqlite::{h256_option_slice_from_row, h256_slice_from_row, offset_by_id, query_single_row,
                         sql_text_conversion_err, string_from_row, validate_table_name, AsSqlNamedParams,
                         OwnedSqlNamedParams, SqlNamedParams, SqliteConnShared, CHECK_TABLE_EXISTS_SQL};
 use lightning::ln::{PaymentHash, PaymentPreimage};
 use secp256k1v24::PublicKey;
+use std::collections::HashMap;
 use std::convert::TryInto;
 use std::str::FromStr;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::time::Duration;
 use uuid::Uuid;
 
+/// The default time to wait for a lock held by another connection to be released before failing with `SQLITE_BUSY`.
+pub const DEFAULT_BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The maximum number of host parameters in a single statement supported by every SQLite version.
+const MAX_SQL_VARIABLES: usize = 999;
+
+/// The maximum length of a bolt11 invoice string stored with a payment, the largest alphanumeric payload a QR code can hold.
+/// Invoices with many routing hints can get long, but they still have to be shareable.
+const MAX_INVOICE_LENGTH: usize = 7089;
+
+/// Acquires the connection lock even if the mutex was poisoned by a panic in another thread holding it.
+/// A panic can't leave the connection in an inconsistent state as every write is a single statement or transaction,
+/// so there is no reason to cascade the failure to every subsequent DB operation.
+fn lock_connection(conn: &Mutex<Connection>) -> MutexGuard<'_, Connection> {
+    conn.lock().unwrap_or_else(|poisoned| {
+        common::log::warn!("Lightning DB connection mutex was poisoned by a panic, recovering it");
+        poisoned.into_inner()
+    })
+}
+
 fn channels_history_table(ticker: &str) -> String { ticker.to_owned() + "_channels_history" }
 
 fn payments_history_table(ticker: &str) -> String { ticker.to_owned() + "_payments_history" }
@@ -37,6 +61,8 @@ fn create_channels_history_table_sql(for_coin: &str) -> Result<String, SqlError>
             funding_generated_in_block Integer,
             closing_tx VARCHAR(255),
             closure_reason TEXT,
+            closure_reason_detail TEXT,
+            short_channel_id INTEGER,
             claiming_tx VARCHAR(255),
             claimed_balance REAL,
             is_outbound INTEGER NOT NULL,
@@ -51,6 +77,85 @@ fn create_channels_history_table_sql(for_coin: &str) -> Result<String, SqlError>
     Ok(sql)
 }
 
+/// Nullable columns of the channels history table that may be missing from tables created by older schema versions.
+/// New columns must be appended here (in addition to `create_channels_history_table_sql`) so they are added
+/// to existing databases on `init_db`.
+const CHANNELS_HISTORY_MIGRATED_COLUMNS: &[(&str, &str)] = &[
+    ("funding_tx", "VARCHAR(255)"),
+    ("funding_value", "INTEGER"),
+    ("funding_generated_in_block", "Integer"),
+    ("closing_tx", "VARCHAR(255)"),
+    ("closure_reason", "TEXT"),
+    ("claiming_tx", "VARCHAR(255)"),
+    ("claimed_balance", "REAL"),
+    ("closed_at", "INTEGER"),
+    ("closure_reason_detail", "TEXT"),
+    ("short_channel_id", "INTEGER"),
+];
+
+fn table_columns(conn: &Connection, table_name: &str) -> Result<Vec<String>, SqlError> {
+    validate_table_name(table_name)?;
+    let sql = format!("PRAGMA table_info({});", table_name);
+    let mut stmt = conn.prepare(&sql)?;
+    let columns = stmt
+        .query_map([], |row| row.get::<_, String>(1))?
+        .collect::<Result<_, _>>()?;
+    Ok(columns)
+}
+
+/// Adds the columns listed in `columns` that are missing from `table_name`.
+/// Existing rows are preserved, new columns are filled with `NULL`.
+fn add_missing_columns(conn: &Connection, table_name: &str, columns: &[(&str, &str)]) -> Result<(), SqlError> {
+    let existing = table_columns(conn, table_name)?;
+    for (column, column_type) in columns {
+        if existing.iter().any(|c| c == column) {
+            continue;
+        }
+        let sql = format!("ALTER TABLE {} ADD COLUMN {} {};", table_name, column, column_type);
+        conn.execute(&sql, [])?;
+    }
+    Ok(())
+}
+
+/// Converts the free-form closure reasons stored by older versions to the `closure_reason` kind
+/// and `closure_reason_detail` columns. Rows that are already converted are left untouched.
+fn migrate_legacy_closure_reasons(conn: &mut Connection, table_name: &str) -> Result<(), SqlError> {
+    validate_table_name(table_name)?;
+    let select_sql = format!(
+        "SELECT uuid, closure_reason FROM {} WHERE closure_reason IS NOT NULL AND closure_reason_detail IS NULL;",
+        table_name
+    );
+    let legacy: Vec<(String, ChannelCloseReason)> = {
+        let mut stmt = conn.prepare(&select_sql)?;
+        let rows = stmt.query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))?;
+        let mut legacy = Vec::new();
+        for row in rows {
+            let (uuid, reason) = row?;
+            if ChannelCloseReason::from_kind(&reason, None).is_none() {
+                legacy.push((uuid, ChannelCloseReason::from_legacy_str(&reason)));
+            }
+        }
+        legacy
+    };
+    if legacy.is_empty() {
+        return Ok(());
+    }
+
+    let update_sql = format!(
+        "UPDATE {} SET closure_reason = ?1, closure_reason_detail = ?2 WHERE uuid = ?3;",
+        table_name
+    );
+    let sql_transaction = conn.transaction()?;
+    for (uuid, reason) in legacy {
+        sql_transaction.execute(&update_sql, params!(reason.kind(), reason.detail(), uuid))?;
+    }
+    sql_transaction.commit()
+}
+
+/// Columns of the payments history table that may be missing from tables created by older schema versions,
+/// existing rows get the column defaults for them.
+const PAYMENTS_HISTORY_MIGRATED_COLUMNS: &[(&str, &str)] = &[("invoice", "TEXT")];
+
 fn create_payments_history_table_sql(for_coin: &str) -> Result<String, SqlError> {
     let table_name = payments_history_table(for_coin);
     validate_table_name(&table_name)?;
@@ -67,7 +172,8 @@ fn create_payments_history_table_sql(for_coin: &str) -> Result<String, SqlError>
             is_outbound INTEGER NOT NULL,
             status VARCHAR(255) NOT NULL,
             created_at INTEGER NOT NULL,
-            last_updated INTEGER NOT NULL
+            last_updated INTEGER NOT NULL,
+            invoice TEXT
         );",
         table_name
     );
@@ -75,12 +181,49 @@ fn create_payments_history_table_sql(for_coin: &str) -> Result<String, SqlError>
     Ok(sql)
 }
 
+fn create_payments_is_outbound_index_sql(for_coin: &str) -> Result<String, SqlError> {
+    let table_name = payments_history_table(for_coin);
+    validate_table_name(&table_name)?;
+
+    let sql = format!(
+        "CREATE INDEX IF NOT EXISTS {}_is_outbound_idx ON {} (is_outbound);",
+        table_name, table_name
+    );
+
+    Ok(sql)
+}
+
+/// Whether the `failure` is a violation of a `PRIMARY KEY` or `UNIQUE` constraint,
+/// other constraint violations (e.g. `NOT NULL`) aren't caused by a duplicate record.
+fn is_duplicate_failure(failure: &ffi::Error) -> bool {
+    matches!(
+        failure.extended_code,
+        ffi::SQLITE_CONSTRAINT_PRIMARYKEY | ffi::SQLITE_CONSTRAINT_UNIQUE
+    )
+}
+
+/// Rejects a malformed `counterparty_node_id`, so that it doesn't fail later when the channel record is read back.
+fn validate_counterparty_node_id(channel_detail: &DBChannelDetails) -> Result<(), SqlError> {
+    PublicKey::from_str(&channel_detail.counterparty_node_id)
+        .map(|_| ())
+        .map_err(|e| {
+            SqlError::ToSqlConversionFailure(
+                format!(
+                    "Invalid counterparty_node_id {}: {}",
+                    channel_detail.counterparty_node_id, e
+                )
+                .into(),
+            )
+        })
+}
+
 fn insert_channel_sql(
     for_coin: &str,
     channel_detail: &DBChannelDetails,
 ) -> Result<(String, OwnedSqlNamedParams), SqlError> {
     let table_name = channels_history_table(for_coin);
     validate_table_name(&table_name)?;
+    validate_counterparty_node_id(channel_detail)?;
 
     let sql = format!(
         "INSERT INTO {} (
@@ -129,12 +272,32 @@ fn payment_info_to_owned_named_params(payment_info: &PaymentInfo) -> OwnedSqlNam
         ":status": status,
         ":created_at": payment_info.created_at,
         ":last_updated": payment_info.last_updated,
+        ":invoice": payment_info.invoice.clone(),
+    }
+}
+
+/// Rejects invoices that can't be a valid bolt11 invoice because of their length, so that an arbitrary blob can't be
+/// stored in the payments history.
+fn validate_payment_invoice(payment_info: &PaymentInfo) -> Result<(), SqlError> {
+    match &payment_info.invoice {
+        Some(invoice) if invoice.is_empty() || invoice.len() > MAX_INVOICE_LENGTH => {
+            Err(SqlError::ToSqlConversionFailure(
+                format!(
+                    "Invalid invoice length {}, expected 1..={}",
+                    invoice.len(),
+                    MAX_INVOICE_LENGTH
+                )
+                .into(),
+            ))
+        },
+        _ => Ok(()),
     }
 }
 
 fn insert_payment_sql(for_coin: &str, payment_info: &PaymentInfo) -> Result<(String, OwnedSqlNamedParams), SqlError> {
     let table_name = payments_history_table(for_coin);
     validate_table_name(&table_name)?;
+    validate_payment_invoice(payment_info)?;
 
     let sql = format!(
         "INSERT INTO {} (
@@ -147,9 +310,10 @@ fn insert_payment_sql(for_coin: &str, payment_info: &PaymentInfo) -> Result<(Str
             is_outbound,
             status,
             created_at,
-            last_updated
+            last_updated,
+            invoice
         ) VALUES (
-            :payment_hash, :destination, :description, :preimage, :amount_msat, :fee_paid_msat, :is_outbound, :status, :created_at, :last_updated
+            :payment_hash, :destination, :description, :preimage, :amount_msat, :fee_paid_msat, :is_outbound, :status, :created_at, :last_updated, :invoice
         )",
         table_name
     );
@@ -160,6 +324,7 @@ fn insert_payment_sql(for_coin: &str, payment_info: &PaymentInfo) -> Result<(Str
 fn upsert_payment_sql(for_coin: &str, payment_info: &PaymentInfo) -> Result<(String, OwnedSqlNamedParams), SqlError> {
     let table_name = payments_history_table(for_coin);
     validate_table_name(&table_name)?;
+    validate_payment_invoice(payment_info)?;
 
     let sql = format!(
         "INSERT OR REPLACE INTO {} (
@@ -172,9 +337,10 @@ fn upsert_payment_sql(for_coin: &str, payment_info: &PaymentInfo) -> Result<(Str
             is_outbound,
             status,
             created_at,
-            last_updated
+            last_updated,
+            invoice
         ) VALUES (
-            :payment_hash, :destination, :description, :preimage, :amount_msat, :fee_paid_msat, :is_outbound, :status, :created_at, :last_updated
+            :payment_hash, :destination, :description, :preimage, :amount_msat, :fee_paid_msat, :is_outbound, :status, :created_at, :last_updated, :invoice
         )",
         table_name
     );
@@ -269,7 +435,9 @@ fn select_channel_by_uuid_sql(for_coin: &str) -> Result<String, SqlError> {
             is_public,
             is_closed,
             created_at,
-            closed_at
+            closed_at,
+            closure_reason_detail,
+            short_channel_id
         FROM
             {}
         WHERE
@@ -280,6 +448,76 @@ fn select_channel_by_uuid_sql(for_coin: &str) -> Result<String, SqlError> {
     Ok(sql)
 }
 
+/// Builds a query selecting the channels whose uuids match any of `count` positional parameters.
+fn select_channels_by_uuids_sql(for_coin: &str, count: usize) -> Result<String, SqlError> {
+    let table_name = channels_history_table(for_coin);
+    validate_table_name(&table_name)?;
+
+    let placeholders = (1..=count).map(|i| format!("?{}", i)).collect::<Vec<_>>().join(", ");
+    let sql = format!(
+        "SELECT
+            uuid,
+            channel_id,
+            counterparty_node_id,
+            funding_tx,
+            funding_value,
+            funding_generated_in_block,
+            closing_tx,
+            closure_reason,
+            claiming_tx,
+            claimed_balance,
+            is_outbound,
+            is_public,
+            is_closed,
+            created_at,
+            closed_at,
+            closure_reason_detail,
+            short_channel_id
+        FROM
+            {}
+        WHERE
+            uuid IN ({})",
+        table_name, placeholders
+    );
+
+    Ok(sql)
+}
+
+fn select_channels_funded_in_block_range_sql(for_coin: &str) -> Result<String, SqlError> {
+    let table_name = channels_history_table(for_coin);
+    validate_table_name(&table_name)?;
+
+    let sql = format!(
+        "SELECT
+            uuid,
+            channel_id,
+            counterparty_node_id,
+            funding_tx,
+            funding_value,
+            funding_generated_in_block,
+            closing_tx,
+            closure_reason,
+            claiming_tx,
+            claimed_balance,
+            is_outbound,
+            is_public,
+            is_closed,
+            created_at,
+            closed_at,
+            closure_reason_detail,
+            short_channel_id
+        FROM
+            {}
+        WHERE
+            funding_generated_in_block IS NOT NULL AND funding_generated_in_block BETWEEN ?1 AND ?2
+        ORDER BY
+            funding_generated_in_block ASC;",
+        table_name
+    );
+
+    Ok(sql)
+}
+
 fn select_payment_by_hash_sql(for_coin: &str) -> Result<String, SqlError> {
     let table_name = payments_history_table(for_coin);
     validate_table_name(&table_name)?;
@@ -295,7 +533,8 @@ fn select_payment_by_hash_sql(for_coin: &str) -> Result<String, SqlError> {
             status,
             is_outbound,
             created_at,
-            last_updated
+            last_updated,
+            invoice
         FROM
             {}
         WHERE
@@ -306,6 +545,116 @@ fn select_payment_by_hash_sql(for_coin: &str) -> Result<String, SqlError> {
     Ok(sql)
 }
 
+fn select_payments_to_destination_sql(for_coin: &str) -> Result<String, SqlError> {
+    let table_name = payments_history_table(for_coin);
+    validate_table_name(&table_name)?;
+
+    let sql = format!(
+        "SELECT
+            payment_hash,
+            destination,
+            description,
+            preimage,
+            amount_msat,
+            fee_paid_msat,
+            status,
+            is_outbound,
+            created_at,
+            last_updated,
+            invoice
+        FROM
+            {}
+        WHERE
+            destination = ?1 AND is_outbound = 1
+        ORDER BY
+            last_updated DESC, id DESC;",
+        table_name
+    );
+
+    Ok(sql)
+}
+
+fn select_payments_ordered_by_time_sql(for_coin: &str) -> Result<String, SqlError> {
+    let table_name = payments_history_table(for_coin);
+    validate_table_name(&table_name)?;
+
+    let sql = format!(
+        "SELECT
+            payment_hash,
+            destination,
+            description,
+            preimage,
+            amount_msat,
+            fee_paid_msat,
+            status,
+            is_outbound,
+            created_at,
+            last_updated,
+            invoice
+        FROM
+            {}
+        ORDER BY
+            last_updated DESC, id DESC
+        LIMIT ?1;",
+        table_name
+    );
+
+    Ok(sql)
+}
+
+fn count_channels_sql(for_coin: &str) -> Result<String, SqlError> {
+    let table_name = channels_history_table(for_coin);
+    validate_table_name(&table_name)?;
+
+    let sql = format!(
+        "SELECT COUNT(id) FROM {} WHERE ?1 IS NULL OR is_closed = ?1;",
+        table_name
+    );
+
+    Ok(sql)
+}
+
+fn select_payments_by_direction_sql(for_coin: &str) -> Result<String, SqlError> {
+    let table_name = payments_history_table(for_coin);
+    validate_table_name(&table_name)?;
+
+    let sql = format!(
+        "SELECT
+            payment_hash,
+            destination,
+            description,
+            preimage,
+            amount_msat,
+            fee_paid_msat,
+            status,
+            is_outbound,
+            created_at,
+            last_updated,
+            invoice
+        FROM
+            {}
+        WHERE
+            ?1 IS NULL OR is_outbound = ?1
+        ORDER BY
+            id;",
+        table_name
+    );
+
+    Ok(sql)
+}
+
+fn count_payments_sql(for_coin: &str) -> Result<String, SqlError> {
+    let table_name = payments_history_table(for_coin);
+    validate_table_name(&table_name)?;
+
+    let sql = format!(
+        "SELECT COUNT(id) FROM {} WHERE ?1 IS NULL OR is_outbound = ?1;",
+        table_name
+    );
+
+    Ok(sql)
+}
+
 fn channel_details_from_row(row: &Row<'_>) -> Result<DBChannelDetails, SqlError> {
     let channel_details = DBChannelDetails {
         uuid: Uuid::parse_str(&row.get::<_, String>(0)?)
@@ -316,7 +665,10 @@ fn channel_details_from_row(row: &Row<'_>) -> Result<DBChannelDetails, SqlError>
         funding_value: row.get(4)?,
         funding_generated_in_block: row.get(5)?,
         closing_tx: row.get(6)?,
-        closure_reason: row.get(7)?,
+        closure_reason: match row.get::<_, Option<String>>(7)? {
+            Some(kind) => Some(closure_reason_from_columns(&kind, row.get(15)?)),
+            None => None,
+        },
         claiming_tx: row.get(8)?,
         claimed_balance: row.get(9)?,
         is_outbound: row.get(10)?,
@@ -324,10 +676,18 @@ fn channel_details_from_row(row: &Row<'_>) -> Result<DBChannelDetails, SqlError>
         is_closed: row.get(12)?,
         created_at: row.get(13)?,
         closed_at: row.get(14)?,
+        // Stored as a signed integer, the cast only reinterprets the bits so every scid round-trips.
+        short_channel_id: row.get::<_, Option<i64>>(16)?.map(|scid| scid as u64),
     };
     Ok(channel_details)
 }
 
+/// Restores the closure reason from the `closure_reason` and `closure_reason_detail` columns.
+/// Falls back to parsing a legacy free-form reason in case the DB hasn't been migrated yet.
+fn closure_reason_from_columns(kind: &str, detail: Option<String>) -> ChannelCloseReason {
+    ChannelCloseReason::from_kind(kind, detail).unwrap_or_else(|| ChannelCloseReason::from_legacy_str(kind))
+}
+
 fn payment_info_from_row(row: &Row<'_>) -> Result<PaymentInfo, SqlError> {
     let is_outbound = row.get::<_, bool>(7)?;
     let payment_type = if is_outbound {
@@ -348,6 +708,7 @@ fn payment_info_from_row(row: &Row<'_>) -> Result<PaymentInfo, SqlError> {
         status: HTLCStatus::from_str(&row.get::<_, String>(6)?)?,
         created_at: row.get(8)?,
         last_updated: row.get(9)?,
+        invoice: row.get(10)?,
     };
     Ok(payment_info)
 }
@@ -386,13 +747,32 @@ fn update_channel_to_closed_sql(for_coin: &str) -> Result<String, SqlError> {
     validate_table_name(&table_name)?;
 
     let sql = format!(
-        "UPDATE {} SET closure_reason = ?1, is_closed = ?2, closed_at = ?3 WHERE uuid = ?4;",
+        "UPDATE {} SET closure_reason = ?1, closure_reason_detail = ?2, is_closed = ?3, closed_at = ?4 WHERE uuid = ?5;",
         table_name
     );
 
     Ok(sql)
 }
 
+fn update_short_channel_id_sql(for_coin: &str) -> Result<String, SqlError> {
+    let table_name = channels_history_table(for_coin);
+    validate_table_name(&table_name)?;
+
+    let sql = format!("UPDATE {} SET short_channel_id = ?1 WHERE uuid = ?2;", table_name);
+
+    Ok(sql)
+}
+
+fn select_channel_by_short_channel_id_sql(for_coin: &str) -> Result<String, SqlError> {
+    let table_name = channels_history_table(for_coin);
+    validate_table_name(&table_name)?;
+
+    let mut builder = SqlBuilder::select_from(table_name);
+    add_fields_to_get_channels_sql_builder(&mut builder);
+    builder.and_where("short_channel_id = ?1");
+    Ok(builder.sql().expect("valid sql"))
+}
+
 fn update_closing_tx_sql(for_coin: &str) -> Result<String, SqlError> {
     let table_name = channels_history_table(for_coin);
     validate_table_name(&table_name)?;
@@ -427,7 +807,9 @@ fn add_fields_to_get_channels_sql_builder(sql_builder: &mut SqlBuilder) {
         .field("is_public")
         .field("is_closed")
         .field("created_at")
-        .field("closed_at");
+        .field("closed_at")
+        .field("closure_reason_detail")
+        .field("short_channel_id");
 }
 
 fn finalize_get_channels_sql_builder(sql_builder: &mut SqlBuilder, offset: usize, limit: usize) {
@@ -472,7 +854,11 @@ fn apply_get_channels_filter<'a>(
     }
 
     if let Some(closure_reason) = &filter.closure_reason {
-        builder.and_where(format!("closure_reason LIKE '%{}%'", closure_reason));
+        // Matches both the reason kind and its detail message.
+        builder.and_where(
+            "(closure_reason LIKE '%' || :closure_reason || '%' OR closure_reason_detail LIKE '%' || :closure_reason || '%')",
+        );
+        params.push((":closure_reason", closure_reason));
     }
 
     if let Some(claiming_tx) = &filter.claiming_tx {
@@ -529,10 +915,12 @@ fn finalize_get_payments_sql_builder(sql_builder: &mut SqlBuilder, offset: usize
         .field("status")
         .field("is_outbound")
         .field("created_at")
-        .field("last_updated");
+        .field("last_updated")
+        .field("invoice");
     sql_builder.offset(offset);
     sql_builder.limit(limit);
     sql_builder.order_desc("last_updated");
+    sql_builder.order_desc("id");
 }
 
 fn apply_get_payments_filter<'a>(
@@ -606,6 +994,8 @@ fn update_claiming_tx_sql(for_coin: &str) -> Result<String, SqlError> {
 pub struct SqliteLightningDB {
     db_ticker: String,
     sqlite_connection: SqliteConnShared,
+    busy_timeout: Duration,
+    wal_mode: bool,
 }
 
 impl SqliteLightningDB {
@@ -616,8 +1006,52 @@ impl SqliteLightningDB {
         Ok(Self {
             db_ticker,
             sqlite_connection,
+            busy_timeout: DEFAULT_BUSY_TIMEOUT,
+            wal_mode: false,
+        })
+    }
+
+    /// Sets the time to wait for a lock held by another connection to be released, applied on `init_db`.
+    pub fn with_busy_timeout(mut self, busy_timeout: Duration) -> Self {
+        self.busy_timeout = busy_timeout;
+        self
+    }
+
+    /// Enables the write-ahead log journal mode on `init_db`, which allows history queries to run concurrently
+    /// with persistence writes instead of being serialized by the default rollback journal.
+    ///
+    /// Durability trade-offs: a committed transaction is only appended to the `-wal` file and is moved to the main
+    /// DB file on checkpoints, so the `-wal` and `-shm` files must be kept (and backed up) along with the DB file.
+    /// WAL also doesn't work on network filesystems. The journal mode is persistent and stays enabled for the DB file.
+    /// WAL isn't applicable to in-memory DBs, which keep the `memory` journal mode.
+    pub fn with_wal_mode(mut self, wal_mode: bool) -> Self {
+        self.wal_mode = wal_mode;
+        self
+    }
+
+    /// Returns a handle to the tables of another `ticker` sharing this DB connection and settings,
+    /// so that lightning on multiple chains can be persisted without setting up a DB per chain.
+    pub fn for_ticker(&self, ticker: String) -> Result<Self, SqlError> {
+        Ok(Self {
+            busy_timeout: self.busy_timeout,
+            wal_mode: self.wal_mode,
+            ..Self::new(ticker, self.sqlite_connection.clone())?
         })
     }
+
+    /// Creates the tables of every ticker in `tickers` and returns the handles to them by ticker.
+    pub async fn init_tickers<I>(&self, tickers: I) -> Result<HashMap<String, Self>, SqlError>
+    where
+        I: IntoIterator<Item = String>,
+    {
+        let mut dbs = HashMap::new();
+        for ticker in tickers {
+            let db = self.for_ticker(ticker.clone())?;
+            db.init_db().await?;
+            dbs.insert(ticker, db);
+        }
+        Ok(dbs)
+    }
 }
 
 #[async_trait]
@@ -627,12 +1061,31 @@ impl LightningDB for SqliteLightningDB {
     async fn init_db(&self) -> Result<(), Self::Error> {
         let sqlite_connection = self.sqlite_connection.clone();
 
+        let channels_history_table = channels_history_table(self.db_ticker.as_str());
+        let payments_history_table = payments_history_table(self.db_ticker.as_str());
         let sql_channels_history = create_channels_history_table_sql(self.db_ticker.as_str())?;
         let sql_payments_history = create_payments_history_table_sql(self.db_ticker.as_str())?;
+        let sql_payments_is_outbound_index = create_payments_is_outbound_index_sql(self.db_ticker.as_str())?;
+        let busy_timeout = self.busy_timeout;
+        let wal_mode = self.wal_mode;
         async_blocking(move || {
-            let conn = sqlite_connection.lock().unwrap();
+            let mut conn = lock_connection(&sqlite_connection);
+            conn.busy_timeout(busy_timeout)?;
+            if wal_mode {
+                let journal_mode: String = conn.query_row("PRAGMA journal_mode = WAL;", [], |row| row.get(0))?;
+                if !journal_mode.eq_ignore_ascii_case("wal") && !journal_mode.eq_ignore_ascii_case("memory") {
+                    common::log::warn!(
+                        "Couldn't enable WAL journal mode for lightning DB, current mode: {}",
+                        journal_mode
+                    );
+                }
+            }
             conn.execute(&sql_channels_history, []).map(|_| ())?;
             conn.execute(&sql_payments_history, []).map(|_| ())?;
+            add_missing_columns(&conn, &channels_history_table, CHANNELS_HISTORY_MIGRATED_COLUMNS)?;
+            migrate_legacy_closure_reasons(&mut conn, &channels_history_table)?;
+            add_missing_columns(&conn, &payments_history_table, PAYMENTS_HISTORY_MIGRATED_COLUMNS)?;
+            conn.execute(&sql_payments_is_outbound_index, []).map(|_| ())?;
             Ok(())
         })
         .await
@@ -646,7 +1099,7 @@ impl LightningDB for SqliteLightningDB {
 
         let sqlite_connection = self.sqlite_connection.clone();
         async_blocking(move || {
-            let conn = sqlite_connection.lock().unwrap();
+            let conn = lock_connection(&sqlite_connection);
             let channels_history_initialized =
                 query_single_row(&conn, CHECK_TABLE_EXISTS_SQL, [channels_history_table], string_from_row)?;
             let payments_history_initialized =
@@ -656,19 +1109,69 @@ impl LightningDB for SqliteLightningDB {
         .await
     }
 
+    async fn check_db_responsive(&self) -> Result<(), Self::Error> {
+        let sqlite_connection = self.sqlite_connection.clone();
+        async_blocking(move || {
+            let conn = lock_connection(&sqlite_connection);
+            conn.query_row("SELECT 1;", [], |row| row.get::<_, i64>(0))?;
+            Ok(())
+        })
+        .await
+    }
+
     async fn add_channel_to_db(&self, details: &DBChannelDetails) -> Result<(), Self::Error> {
         let for_coin = self.db_ticker.clone();
         let (sql, params) = insert_channel_sql(&for_coin, details)?;
 
         let sqlite_connection = self.sqlite_connection.clone();
         async_blocking(move || {
-            let conn = sqlite_connection.lock().unwrap();
+            let conn = lock_connection(&sqlite_connection);
             conn.execute_named(&sql, &params.as_sql_named_params())?;
             Ok(())
         })
         .await
     }
 
+    async fn validate_channel_for_insert(&self, details: &DBChannelDetails) -> Result<(), Self::Error> {
+        validate_counterparty_node_id(details)?;
+
+        if self.get_channel_from_db(details.uuid).await?.is_some() {
+            return Err(SqlError::SqliteFailure(
+                ffi::Error::new(ffi::SQLITE_CONSTRAINT),
+                Some(format!("Channel with uuid {} already exists", details.uuid)),
+            ));
+        }
+        Ok(())
+    }
+
+    async fn add_channels_to_db(&self, channels: Vec<DBChannelDetails>) -> Result<(), Self::Error> {
+        let for_coin = self.db_ticker.clone();
+
+        let sqlite_connection = self.sqlite_connection.clone();
+        async_blocking(move || {
+            let mut conn = lock_connection(&sqlite_connection);
+            // The transaction is rolled back on drop if any insert fails.
+            let sql_transaction = conn.transaction()?;
+            for details in channels.iter() {
+                let (sql, params) = insert_channel_sql(&for_coin, details)?;
+                sql_transaction
+                    .execute_named(&sql, &params.as_sql_named_params())
+                    .map_err(|e| match e {
+                        SqlError::SqliteFailure(failure, _) if is_duplicate_failure(&failure) => {
+                            SqlError::SqliteFailure(
+                                failure,
+                                Some(format!("Channel with uuid {} already exists", details.uuid)),
+                            )
+                        },
+                        e => e,
+                    })?;
+            }
+            sql_transaction.commit()?;
+            Ok(())
+        })
+        .await
+    }
+
     async fn add_funding_tx_to_db(
         &self,
         uuid: Uuid,
@@ -680,7 +1183,7 @@ impl LightningDB for SqliteLightningDB {
 
         let sqlite_connection = self.sqlite_connection.clone();
         async_blocking(move || {
-            let mut conn = sqlite_connection.lock().unwrap();
+            let mut conn = lock_connection(&sqlite_connection);
             let sql_transaction = conn.transaction()?;
             let params = params!(funding_tx, funding_value, funding_generated_in_block, uuid.to_string());
             sql_transaction.execute(&update_funding_tx_sql(&for_coin)?, params)?;
@@ -695,7 +1198,7 @@ impl LightningDB for SqliteLightningDB {
 
         let sqlite_connection = self.sqlite_connection.clone();
         async_blocking(move || {
-            let mut conn = sqlite_connection.lock().unwrap();
+            let mut conn = lock_connection(&sqlite_connection);
             let sql_transaction = conn.transaction()?;
             let params = params!(block_height, funding_tx);
             sql_transaction.execute(&update_funding_tx_block_height_sql(&for_coin)?, params)?;
@@ -708,7 +1211,7 @@ impl LightningDB for SqliteLightningDB {
     async fn update_channel_to_closed(
         &self,
         uuid: Uuid,
-        closure_reason: String,
+        closure_reason: ChannelCloseReason,
         closed_at: i64,
     ) -> Result<(), Self::Error> {
         let for_coin = self.db_ticker.clone();
@@ -716,9 +1219,15 @@ impl LightningDB for SqliteLightningDB {
 
         let sqlite_connection = self.sqlite_connection.clone();
         async_blocking(move || {
-            let mut conn = sqlite_connection.lock().unwrap();
+            let mut conn = lock_connection(&sqlite_connection);
             let sql_transaction = conn.transaction()?;
-            let params = params!(closure_reason, is_closed, closed_at, uuid.to_string());
+            let params = params!(
+                closure_reason.kind(),
+                closure_reason.detail(),
+                is_closed,
+                closed_at,
+                uuid.to_string()
+            );
             sql_transaction.execute(&update_channel_to_closed_sql(&for_coin)?, params)?;
             sql_transaction.commit()?;
             Ok(())
@@ -735,7 +1244,7 @@ impl LightningDB for SqliteLightningDB {
 
         let sqlite_connection = self.sqlite_connection.clone();
         async_blocking(move || {
-            let conn = sqlite_connection.lock().unwrap();
+            let conn = lock_connection(&sqlite_connection);
 
             let mut stmt = conn.prepare(&sql)?;
             let result = stmt
@@ -746,12 +1255,26 @@ impl LightningDB for SqliteLightningDB {
         .await
     }
 
+    async fn add_short_channel_id_to_db(&self, uuid: Uuid, short_channel_id: u64) -> Result<(), Self::Error> {
+        let for_coin = self.db_ticker.clone();
+        let short_channel_id = short_channel_id as i64;
+
+        let sqlite_connection = self.sqlite_connection.clone();
+        async_blocking(move || {
+            let conn = lock_connection(&sqlite_connection);
+            let params = params!(short_channel_id, uuid.to_string());
+            conn.execute(&update_short_channel_id_sql(&for_coin)?, params)?;
+            Ok(())
+        })
+        .await
+    }
+
     async fn add_closing_tx_to_db(&self, uuid: Uuid, closing_tx: String) -> Result<(), Self::Error> {
         let for_coin = self.db_ticker.clone();
 
         let sqlite_connection = self.sqlite_connection.clone();
         async_blocking(move || {
-            let mut conn = sqlite_connection.lock().unwrap();
+            let mut conn = lock_connection(&sqlite_connection);
             let sql_transaction = conn.transaction()?;
             let params = params!(closing_tx, uuid.to_string());
             sql_transaction.execute(&update_closing_tx_sql(&for_coin)?, params)?;
@@ -771,7 +1294,7 @@ impl LightningDB for SqliteLightningDB {
 
         let sqlite_connection = self.sqlite_connection.clone();
         async_blocking(move || {
-            let mut conn = sqlite_connection.lock().unwrap();
+            let mut conn = lock_connection(&sqlite_connection);
             let sql_transaction = conn.transaction()?;
             let params = params!(claiming_tx, claimed_balance, closing_tx);
             sql_transaction.execute(&update_claiming_tx_sql(&for_coin)?, params)?;
@@ -787,24 +1310,90 @@ impl LightningDB for SqliteLightningDB {
 
         let sqlite_connection = self.sqlite_connection.clone();
         async_blocking(move || {
-            let conn = sqlite_connection.lock().unwrap();
+            let conn = lock_connection(&sqlite_connection);
             query_single_row(&conn, &sql, params, channel_details_from_row)
         })
         .await
     }
 
-    async fn get_closed_channels_by_filter(
+    async fn get_channel_by_short_channel_id(
         &self,
-        filter: Option<ClosedChannelsFilter>,
-        paging: PagingOptionsEnum<Uuid>,
-        limit: usize,
-    ) -> Result<GetClosedChannelsResult, Self::Error> {
-        let mut sql_builder = get_channels_builder_preimage(self.db_ticker.as_str())?;
+        short_channel_id: u64,
+    ) -> Result<Option<DBChannelDetails>, Self::Error> {
+        let params = [short_channel_id as i64];
+        let sql = select_channel_by_short_channel_id_sql(self.db_ticker.as_str())?;
 
         let sqlite_connection = self.sqlite_connection.clone();
         async_blocking(move || {
-            let conn = sqlite_connection.lock().unwrap();
-
+            let conn = lock_connection(&sqlite_connection);
+            query_single_row(&conn, &sql, params, channel_details_from_row)
+        })
+        .await
+    }
+
+    async fn get_channels_from_db(&self, uuids: &[Uuid]) -> Result<Vec<DBChannelDetails>, Self::Error> {
+        if uuids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let for_coin = self.db_ticker.clone();
+        let uuids = uuids.to_vec();
+        let sqlite_connection = self.sqlite_connection.clone();
+        async_blocking(move || {
+            let conn = lock_connection(&sqlite_connection);
+
+            let mut channels = Vec::with_capacity(uuids.len());
+            for chunk in uuids.chunks(MAX_SQL_VARIABLES) {
+                let sql = select_channels_by_uuids_sql(&for_coin, chunk.len())?;
+                let params: Vec<_> = chunk.iter().map(|uuid| uuid.to_string()).collect();
+                let mut stmt = conn.prepare(&sql)?;
+                let rows = stmt.query_map(params_from_iter(params.iter()), channel_details_from_row)?;
+                for row in rows {
+                    channels.push(row?);
+                }
+            }
+
+            // Keep the order of the requested uuids, the DB doesn't guarantee any order for `IN` queries.
+            channels.sort_by_key(|chan: &DBChannelDetails| uuids.iter().position(|uuid| *uuid == chan.uuid));
+            Ok(channels)
+        })
+        .await
+    }
+
+    async fn get_channels_funded_in_block_range(
+        &self,
+        from: u64,
+        to: u64,
+    ) -> Result<Vec<DBChannelDetails>, Self::Error> {
+        let from = from as i64;
+        let to = to as i64;
+        let sql = select_channels_funded_in_block_range_sql(self.db_ticker.as_str())?;
+
+        let sqlite_connection = self.sqlite_connection.clone();
+        async_blocking(move || {
+            let conn = lock_connection(&sqlite_connection);
+
+            let mut stmt = conn.prepare(&sql)?;
+            let result = stmt
+                .query_map(params!(from, to), channel_details_from_row)?
+                .collect::<Result<_, _>>()?;
+            Ok(result)
+        })
+        .await
+    }
+
+    async fn get_closed_channels_by_filter(
+        &self,
+        filter: Option<ClosedChannelsFilter>,
+        paging: PagingOptionsEnum<Uuid>,
+        limit: usize,
+    ) -> Result<GetClosedChannelsResult, Self::Error> {
+        let mut sql_builder = get_channels_builder_preimage(self.db_ticker.as_str())?;
+
+        let sqlite_connection = self.sqlite_connection.clone();
+        async_blocking(move || {
+            let conn = lock_connection(&sqlite_connection);
+
             let mut total_builder = sql_builder.clone();
             total_builder.count("id");
             let total_sql = total_builder.sql().expect("valid sql");
@@ -852,13 +1441,25 @@ impl LightningDB for SqliteLightningDB {
         .await
     }
 
+    async fn get_channel_count(&self, closed: Option<bool>) -> Result<u64, Self::Error> {
+        let sql = count_channels_sql(self.db_ticker.as_str())?;
+
+        let sqlite_connection = self.sqlite_connection.clone();
+        async_blocking(move || {
+            let conn = lock_connection(&sqlite_connection);
+            let count: i64 = conn.query_row(&sql, params!(closed), |row| row.get(0))?;
+            Ok(count.try_into().expect("count should be always above zero"))
+        })
+        .await
+    }
+
     async fn add_payment_to_db(&self, info: &PaymentInfo) -> Result<(), Self::Error> {
         let for_coin = self.db_ticker.clone();
         let (sql, params) = insert_payment_sql(&for_coin, info)?;
 
         let sqlite_connection = self.sqlite_connection.clone();
         async_blocking(move || {
-            let conn = sqlite_connection.lock().unwrap();
+            let conn = lock_connection(&sqlite_connection);
             conn.execute_named(&sql, &params.as_sql_named_params())?;
             Ok(())
         })
@@ -871,7 +1472,7 @@ impl LightningDB for SqliteLightningDB {
 
         let sqlite_connection = self.sqlite_connection.clone();
         async_blocking(move || {
-            let conn = sqlite_connection.lock().unwrap();
+            let conn = lock_connection(&sqlite_connection);
             conn.execute_named(&sql, &params.as_sql_named_params())?;
             Ok(())
         })
@@ -890,7 +1491,7 @@ impl LightningDB for SqliteLightningDB {
 
         let sqlite_connection = self.sqlite_connection.clone();
         async_blocking(move || {
-            let mut conn = sqlite_connection.lock().unwrap();
+            let mut conn = lock_connection(&sqlite_connection);
             let sql_transaction = conn.transaction()?;
             let params = params!(preimage, last_updated, payment_hash);
             sql_transaction.execute(&update_payment_preimage_sql(&for_coin)?, params)?;
@@ -908,7 +1509,7 @@ impl LightningDB for SqliteLightningDB {
 
         let sqlite_connection = self.sqlite_connection.clone();
         async_blocking(move || {
-            let mut conn = sqlite_connection.lock().unwrap();
+            let mut conn = lock_connection(&sqlite_connection);
             let sql_transaction = conn.transaction()?;
             let params = params!(status, last_updated, payment_hash);
             sql_transaction.execute(&update_payment_status_sql(&for_coin)?, params)?;
@@ -931,7 +1532,7 @@ impl LightningDB for SqliteLightningDB {
 
         let sqlite_connection = self.sqlite_connection.clone();
         async_blocking(move || {
-            let mut conn = sqlite_connection.lock().unwrap();
+            let mut conn = lock_connection(&sqlite_connection);
             let sql_transaction = conn.transaction()?;
             let params = params!(preimage, status, last_updated, payment_hash);
             sql_transaction.execute(&update_claimable_payment_sql(&for_coin)?, params)?;
@@ -956,7 +1557,7 @@ impl LightningDB for SqliteLightningDB {
 
         let sqlite_connection = self.sqlite_connection.clone();
         async_blocking(move || {
-            let mut conn = sqlite_connection.lock().unwrap();
+            let mut conn = lock_connection(&sqlite_connection);
             let sql_transaction = conn.transaction()?;
             let params = params!(preimage, fee_paid_msat, status, last_updated, payment_hash);
             sql_transaction.execute(&update_sent_payment_sql(&for_coin)?, params)?;
@@ -972,12 +1573,46 @@ impl LightningDB for SqliteLightningDB {
 
         let sqlite_connection = self.sqlite_connection.clone();
         async_blocking(move || {
-            let conn = sqlite_connection.lock().unwrap();
+            let conn = lock_connection(&sqlite_connection);
             query_single_row(&conn, &sql, params, payment_info_from_row)
         })
         .await
     }
 
+    async fn get_payments_to_destination(&self, dest: PublicKey) -> Result<Vec<PaymentInfo>, Self::Error> {
+        let params = [dest.to_string()];
+        let sql = select_payments_to_destination_sql(self.db_ticker.as_str())?;
+
+        let sqlite_connection = self.sqlite_connection.clone();
+        async_blocking(move || {
+            let conn = lock_connection(&sqlite_connection);
+
+            let mut stmt = conn.prepare(&sql)?;
+            let result = stmt
+                .query_map(params, payment_info_from_row)?
+                .collect::<Result<_, _>>()?;
+            Ok(result)
+        })
+        .await
+    }
+
+    async fn get_payments_ordered_by_time(&self, limit: usize) -> Result<Vec<PaymentInfo>, Self::Error> {
+        let limit = limit as i64;
+        let sql = select_payments_ordered_by_time_sql(self.db_ticker.as_str())?;
+
+        let sqlite_connection = self.sqlite_connection.clone();
+        async_blocking(move || {
+            let conn = lock_connection(&sqlite_connection);
+
+            let mut stmt = conn.prepare(&sql)?;
+            let result = stmt
+                .query_map([limit], payment_info_from_row)?
+                .collect::<Result<_, _>>()?;
+            Ok(result)
+        })
+        .await
+    }
+
     async fn get_payments_by_filter(
         &self,
         filter: Option<DBPaymentsFilter>,
@@ -988,7 +1623,7 @@ impl LightningDB for SqliteLightningDB {
 
         let sqlite_connection = self.sqlite_connection.clone();
         async_blocking(move || {
-            let conn = sqlite_connection.lock().unwrap();
+            let conn = lock_connection(&sqlite_connection);
 
             let mut total_builder = sql_builder.clone();
             total_builder.count("id");
@@ -1006,7 +1641,7 @@ impl LightningDB for SqliteLightningDB {
                         &sql_builder,
                         params,
                         "payment_hash",
-                        "last_updated DESC",
+                        "last_updated DESC, id DESC",
                         "payment_hash = ?1",
                     )?;
                     match maybe_offset {
@@ -1043,14 +1678,61 @@ impl LightningDB for SqliteLightningDB {
         })
         .await
     }
+
+    async fn get_payment_count(&self, is_outbound: Option<bool>) -> Result<u64, Self::Error> {
+        let sql = count_payments_sql(self.db_ticker.as_str())?;
+
+        let sqlite_connection = self.sqlite_connection.clone();
+        async_blocking(move || {
+            let conn = lock_connection(&sqlite_connection);
+            let count: i64 = conn.query_row(&sql, params!(is_outbound), |row| row.get(0))?;
+            Ok(count.try_into().expect("count should be always above zero"))
+        })
+        .await
+    }
+
+    async fn for_each_payment<F>(&self, is_outbound: Option<bool>, mut f: F) -> Result<(), Self::Error>
+    where
+        F: FnMut(PaymentInfo) + Send + 'static,
+    {
+        let sql = select_payments_by_direction_sql(self.db_ticker.as_str())?;
+
+        let sqlite_connection = self.sqlite_connection.clone();
+        async_blocking(move || {
+            let conn = lock_connection(&sqlite_connection);
+
+            let mut stmt = conn.prepare(&sql)?;
+            let mut rows = stmt.query(params!(is_outbound))?;
+            while let Some(row) = rows.next()? {
+                f(payment_info_from_row(row)?);
+            }
+            Ok(())
+        })
+        .await
+    }
+
+    async fn export_payments_csv(&self) -> Result<String, Self::Error> {
+        let csv = Arc::new(Mutex::new(format!("{}\n", PAYMENTS_CSV_HEADER)));
+        let csv_clone = csv.clone();
+        self.for_each_payment(None, move |payment| {
+            let mut csv = csv_clone.lock().unwrap_or_else(PoisonError::into_inner);
+            csv.push_str(&payment.to_csv_row());
+            csv.push('\n');
+        })
+        .await?;
+
+        let csv = std::mem::take(&mut *csv.lock().unwrap_or_else(PoisonError::into_inner));
+        Ok(csv)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::lightning::ln_db::DBChannelDetails;
+    use crate::lightning::ln_db::{escape_csv_field, DBChannelDetails};
+    use crate::lightning::ln_errors::{EnableLightningError, SaveChannelClosingError};
     use common::{block_on, new_uuid};
-    use db_common::sqlite::rusqlite::{self, Connection};
+    use db_common::sqlite::{is_constraint_error, rusqlite};
     use rand::distributions::Alphanumeric;
     use rand::{Rng, RngCore};
     use secp256k1v24::{Secp256k1, SecretKey};
@@ -1084,20 +1766,16 @@ mod tests {
                     rng.fill_bytes(&mut bytes);
                     Some(hex::encode(bytes))
                 },
-                closure_reason: {
-                    Some(
-                        rng.sample_iter(&Alphanumeric)
-                            .take(30)
-                            .map(char::from)
-                            .collect::<String>(),
-                    )
-                },
+                closure_reason: Some(ChannelCloseReason::ProcessingError {
+                    err: rng.sample_iter(&Alphanumeric).take(30).map(char::from).collect(),
+                }),
                 claiming_tx: {
                     rng.fill_bytes(&mut bytes);
                     Some(hex::encode(bytes))
                 },
                 claimed_balance: Some(rng.gen::<f64>()),
                 funding_generated_in_block: Some(rng.gen::<i64>()),
+                short_channel_id: None,
                 is_outbound: rng.gen::<bool>(),
                 is_public: rng.gen::<bool>(),
                 is_closed: rand::random(),
@@ -1149,6 +1827,7 @@ mod tests {
                 status,
                 created_at: rng.gen::<i64>(),
                 last_updated: rng.gen::<i64>(),
+                invoice: None,
             };
             payments.push(info);
         }
@@ -1173,6 +1852,212 @@ mod tests {
         assert!(initialized);
     }
 
+    #[test]
+    fn test_check_db_responsive() {
+        let db = SqliteLightningDB::new(
+            "check_db_responsive".into(),
+            Arc::new(Mutex::new(Connection::open_in_memory().unwrap())),
+        )
+        .unwrap();
+        block_on(db.check_db_responsive()).unwrap();
+    }
+
+    #[test]
+    fn test_recover_poisoned_connection() {
+        let db = SqliteLightningDB::new(
+            "poisoned_connection".into(),
+            Arc::new(Mutex::new(Connection::open_in_memory().unwrap())),
+        )
+        .unwrap();
+
+        block_on(db.init_db()).unwrap();
+
+        let sqlite_connection = db.sqlite_connection.clone();
+        std::thread::spawn(move || {
+            let _conn = sqlite_connection.lock().unwrap();
+            panic!("Panic while holding the lightning DB connection");
+        })
+        .join()
+        .unwrap_err();
+        assert!(db.sqlite_connection.is_poisoned());
+
+        block_on(db.check_db_responsive()).unwrap();
+        let channel = generate_random_channels(1).pop().unwrap();
+        block_on(db.add_channel_to_db(&channel)).unwrap();
+        let actual = block_on(db.get_channel_from_db(channel.uuid)).unwrap().unwrap();
+        assert_eq!(channel.uuid, actual.uuid);
+    }
+
+    #[test]
+    fn test_busy_timeout_waits_for_lock() {
+        let path = std::env::temp_dir().join("lightning_busy_timeout.db");
+        let _ = std::fs::remove_file(&path);
+
+        let db = SqliteLightningDB::new(
+            "busy_timeout".into(),
+            Arc::new(Mutex::new(Connection::open(&path).unwrap())),
+        )
+        .unwrap()
+        .with_busy_timeout(Duration::from_secs(10));
+        block_on(db.init_db()).unwrap();
+
+        // Hold a write lock on the DB from another connection for a while.
+        let mut other_conn = Connection::open(&path).unwrap();
+        let (locked_tx, locked_rx) = std::sync::mpsc::channel();
+        let lock_holder = std::thread::spawn(move || {
+            let transaction = other_conn
+                .transaction_with_behavior(rusqlite::TransactionBehavior::Exclusive)
+                .unwrap();
+            locked_tx.send(()).unwrap();
+            std::thread::sleep(Duration::from_millis(500));
+            transaction.commit().unwrap();
+        });
+        locked_rx.recv().unwrap();
+
+        // The write must wait for the lock to be released instead of failing with `SQLITE_BUSY` immediately.
+        let channel = DBChannelDetails::new(
+            new_uuid(),
+            [0; 32],
+            PublicKey::from_str("038863cf8ab91046230f561cd5b386cbff8309fa02e3f0c3ed161a3aeb64a643b9").unwrap(),
+            true,
+            true,
+        );
+        block_on(db.add_channel_to_db(&channel)).unwrap();
+        lock_holder.join().unwrap();
+
+        assert_eq!(block_on(db.get_channel_from_db(channel.uuid)).unwrap(), Some(channel));
+    }
+
+    #[test]
+    fn test_wal_mode_concurrent_read_write() {
+        let path = std::env::temp_dir().join("lightning_wal_mode.db");
+        for suffix in ["", "-wal", "-shm"] {
+            let _ = std::fs::remove_file(format!("{}{}", path.display(), suffix));
+        }
+
+        let db = SqliteLightningDB::new(
+            "wal_mode".into(),
+            Arc::new(Mutex::new(Connection::open(&path).unwrap())),
+        )
+        .unwrap()
+        .with_busy_timeout(Duration::from_millis(0))
+        .with_wal_mode(true);
+        block_on(db.init_db()).unwrap();
+
+        let journal_mode: String = db
+            .sqlite_connection
+            .lock()
+            .unwrap()
+            .query_row("PRAGMA journal_mode;", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(journal_mode.to_lowercase(), "wal");
+
+        let channel = DBChannelDetails::new(
+            new_uuid(),
+            [0; 32],
+            PublicKey::from_str("038863cf8ab91046230f561cd5b386cbff8309fa02e3f0c3ed161a3aeb64a643b9").unwrap(),
+            true,
+            true,
+        );
+        block_on(db.add_channel_to_db(&channel)).unwrap();
+
+        // Keep a read transaction open on another connection, the writer must not be blocked by it
+        // even though it doesn't wait for locks at all.
+        let mut reader = Connection::open(&path).unwrap();
+        let read_transaction = reader.transaction().unwrap();
+        let count: i64 = read_transaction
+            .query_row("SELECT COUNT(*) FROM wal_mode_channels_history;", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(count, 1);
+
+        let mut second_channel = channel.clone();
+        second_channel.uuid = new_uuid();
+        block_on(db.add_channel_to_db(&second_channel)).unwrap();
+
+        // The reader still sees its snapshot.
+        let count: i64 = read_transaction
+            .query_row("SELECT COUNT(*) FROM wal_mode_channels_history;", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(count, 1);
+        read_transaction.commit().unwrap();
+    }
+
+    #[test]
+    fn test_wal_mode_in_memory() {
+        let db = SqliteLightningDB::new(
+            "wal_mode_in_memory".into(),
+            Arc::new(Mutex::new(Connection::open_in_memory().unwrap())),
+        )
+        .unwrap()
+        .with_wal_mode(true);
+        block_on(db.init_db()).unwrap();
+        assert!(block_on(db.is_db_initialized()).unwrap());
+    }
+
+    #[test]
+    fn test_init_sql_adds_missing_channels_history_columns() {
+        let conn = Connection::open_in_memory().unwrap();
+        // Legacy schema without funding_generated_in_block, claiming_tx and claimed_balance columns.
+        conn.execute(
+            "CREATE TABLE migrate_columns_channels_history (
+                id INTEGER NOT NULL PRIMARY KEY,
+                uuid VARCHAR(255) NOT NULL UNIQUE,
+                channel_id VARCHAR(255) NOT NULL,
+                counterparty_node_id VARCHAR(255) NOT NULL,
+                funding_tx VARCHAR(255),
+                funding_value INTEGER,
+                closing_tx VARCHAR(255),
+                closure_reason TEXT,
+                is_outbound INTEGER NOT NULL,
+                is_public INTEGER NOT NULL,
+                is_closed INTEGER NOT NULL,
+                created_at INTEGER NOT NULL,
+                closed_at INTEGER
+            );",
+            [],
+        )
+        .unwrap();
+        let uuid = new_uuid();
+        conn.execute(
+            "INSERT INTO migrate_columns_channels_history
+                (uuid, channel_id, counterparty_node_id, funding_tx, funding_value, is_outbound, is_public, is_closed, created_at)
+            VALUES (?1, ?2, ?3, ?4, ?5, 1, 0, 0, 1655806080);",
+            params![
+                uuid.to_string(),
+                hex::encode([0; 32]),
+                "038863cf8ab91046230f561cd5b386cbff8309fa02e3f0c3ed161a3aeb64a643b9",
+                "9cdafd6d42dcbdc06b0b5bce1866deb82630581285bbfb56870577300c0a8c6e",
+                3000
+            ],
+        )
+        .unwrap();
+
+        let db = SqliteLightningDB::new("migrate_columns".into(), Arc::new(Mutex::new(conn))).unwrap();
+        block_on(db.init_db()).unwrap();
+
+        let columns = {
+            let conn = db.sqlite_connection.lock().unwrap();
+            table_columns(&conn, "migrate_columns_channels_history").unwrap()
+        };
+        for (column, _) in CHANNELS_HISTORY_MIGRATED_COLUMNS {
+            assert!(columns.iter().any(|c| c == column), "column {} is missing", column);
+        }
+
+        let channel = block_on(db.get_channel_from_db(uuid)).unwrap().unwrap();
+        assert_eq!(channel.channel_id, hex::encode([0; 32]));
+        assert_eq!(
+            channel.funding_tx.as_deref(),
+            Some("9cdafd6d42dcbdc06b0b5bce1866deb82630581285bbfb56870577300c0a8c6e")
+        );
+        assert_eq!(channel.funding_value, Some(3000));
+        assert_eq!(channel.funding_generated_in_block, None);
+        assert_eq!(channel.claiming_tx, None);
+        assert_eq!(channel.claimed_balance, None);
+
+        // Running the migration again on an up-to-date table must not fail.
+        block_on(db.init_db()).unwrap();
+    }
+
     #[test]
     fn test_add_get_channel_sql() {
         let db = SqliteLightningDB::new(
@@ -1231,9 +2116,8 @@ mod tests {
         assert_eq!(expected_channel_details, actual_channel_details);
 
         let current_time = now_sec_i64();
-        block_on(db.update_channel_to_closed(uuid_2, "the channel was cooperatively closed".into(), current_time))
-            .unwrap();
-        expected_channel_details.closure_reason = Some("the channel was cooperatively closed".into());
+        block_on(db.update_channel_to_closed(uuid_2, ChannelCloseReason::CooperativeClosure, current_time)).unwrap();
+        expected_channel_details.closure_reason = Some(ChannelCloseReason::CooperativeClosure);
         expected_channel_details.is_closed = true;
         expected_channel_details.closed_at = Some(current_time);
 
@@ -1245,8 +2129,7 @@ mod tests {
         assert_eq!(closed_channels.channels.len(), 1);
         assert_eq!(expected_channel_details, closed_channels.channels[0]);
 
-        block_on(db.update_channel_to_closed(uuid_1, "the channel was cooperatively closed".into(), now_sec_i64()))
-            .unwrap();
+        block_on(db.update_channel_to_closed(uuid_1, ChannelCloseReason::CooperativeClosure, now_sec_i64())).unwrap();
         let closed_channels =
             block_on(db.get_closed_channels_by_filter(None, PagingOptionsEnum::default(), 10)).unwrap();
         assert_eq!(closed_channels.channels.len(), 2);
@@ -1282,6 +2165,493 @@ mod tests {
         assert_eq!(expected_channel_details, actual_channel_details);
     }
 
+    #[test]
+    fn test_channel_close_reasons() {
+        let db = SqliteLightningDB::new(
+            "channel_close_reasons".into(),
+            Arc::new(Mutex::new(Connection::open_in_memory().unwrap())),
+        )
+        .unwrap();
+
+        block_on(db.init_db()).unwrap();
+
+        let reasons = vec![
+            ChannelCloseReason::CooperativeClosure,
+            ChannelCloseReason::CounterpartyForceClosed {
+                peer_msg: "channel is not usable".into(),
+            },
+            ChannelCloseReason::HolderForceClosed,
+            ChannelCloseReason::CommitmentTxConfirmed,
+        ];
+        let channels = generate_random_channels(reasons.len() as u64);
+        for (channel, reason) in channels.iter().zip(reasons.iter()) {
+            block_on(db.add_channel_to_db(channel)).unwrap();
+            block_on(db.update_channel_to_closed(channel.uuid, reason.clone(), now_sec_i64())).unwrap();
+            let actual = block_on(db.get_channel_from_db(channel.uuid)).unwrap().unwrap();
+            assert_eq!(actual.closure_reason.as_ref(), Some(reason));
+        }
+
+        let conn = db.sqlite_connection.lock().unwrap();
+        let (kind, detail): (String, Option<String>) = conn
+            .query_row(
+                "SELECT closure_reason, closure_reason_detail FROM channel_close_reasons_channels_history WHERE uuid = ?1;",
+                [channels[1].uuid.to_string()],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        assert_eq!(kind, "CounterpartyForceClosed");
+        assert_eq!(detail.as_deref(), Some("channel is not usable"));
+        drop(conn);
+
+        let filter_by_reason = |closure_reason: &str| {
+            let filter = ClosedChannelsFilter {
+                channel_id: None,
+                counterparty_node_id: None,
+                funding_tx: None,
+                from_funding_value: None,
+                to_funding_value: None,
+                closing_tx: None,
+                closure_reason: Some(closure_reason.to_owned()),
+                claiming_tx: None,
+                from_claimed_balance: None,
+                to_claimed_balance: None,
+                channel_type: None,
+                channel_visibility: None,
+            };
+            block_on(db.get_closed_channels_by_filter(Some(filter), PagingOptionsEnum::default(), 10))
+                .unwrap()
+                .channels
+                .into_iter()
+                .map(|channel| channel.uuid)
+                .collect::<Vec<_>>()
+        };
+        // By the reason kind.
+        assert_eq!(filter_by_reason("Cooperative"), vec![channels[0].uuid]);
+        // By the detail message.
+        assert_eq!(filter_by_reason("not usable"), vec![channels[1].uuid]);
+        // The filter value is bound as a parameter rather than interpolated into the query.
+        assert!(filter_by_reason("%' OR 1=1 --").is_empty());
+
+        // The API keeps returning the human readable reason, the kind is returned separately.
+        let channel = block_on(db.get_channel_from_db(channels[1].uuid)).unwrap().unwrap();
+        let json = serde_json::to_value(&channel).unwrap();
+        assert_eq!(
+            json["closure_reason"],
+            "counterparty force-closed with message channel is not usable"
+        );
+        assert_eq!(json["closure_reason_kind"], "CounterpartyForceClosed");
+    }
+
+    #[test]
+    fn test_channel_close_reason_from_legacy_str() {
+        let reasons = vec![
+            ChannelCloseReason::CooperativeClosure,
+            ChannelCloseReason::CounterpartyForceClosed {
+                peer_msg: "channel is not usable".into(),
+            },
+            ChannelCloseReason::HolderForceClosed,
+            ChannelCloseReason::CommitmentTxConfirmed,
+            ChannelCloseReason::FundingTimedOut,
+            ChannelCloseReason::ProcessingError {
+                err: "invalid commitment signature".into(),
+            },
+            ChannelCloseReason::DisconnectedPeer,
+            ChannelCloseReason::OutdatedChannelManager,
+        ];
+        for reason in reasons {
+            // The legacy strings are the `Display` output of lightning's `ClosureReason`.
+            assert_eq!(ChannelCloseReason::from_legacy_str(&reason.to_string()), reason);
+            assert_eq!(
+                ChannelCloseReason::from_kind(reason.kind(), reason.detail().map(String::from)),
+                Some(reason)
+            );
+        }
+
+        assert_eq!(
+            ChannelCloseReason::from_legacy_str("some random reason"),
+            ChannelCloseReason::Unknown {
+                detail: "some random reason".into()
+            }
+        );
+        assert_eq!(ChannelCloseReason::from_kind("some random reason", None), None);
+    }
+
+    #[test]
+    fn test_init_sql_migrates_legacy_closure_reasons() {
+        let conn = Connection::open_in_memory().unwrap();
+        // Legacy schema storing free-form closure reasons.
+        conn.execute(
+            "CREATE TABLE migrate_reasons_channels_history (
+                id INTEGER NOT NULL PRIMARY KEY,
+                uuid VARCHAR(255) NOT NULL UNIQUE,
+                channel_id VARCHAR(255) NOT NULL,
+                counterparty_node_id VARCHAR(255) NOT NULL,
+                closure_reason TEXT,
+                is_outbound INTEGER NOT NULL,
+                is_public INTEGER NOT NULL,
+                is_closed INTEGER NOT NULL,
+                created_at INTEGER NOT NULL
+            );",
+            [],
+        )
+        .unwrap();
+        let legacy_reasons = [
+            (new_uuid(), "the channel was cooperatively closed"),
+            (
+                new_uuid(),
+                "counterparty force-closed with message channel is not usable",
+            ),
+            (new_uuid(), "commitment or closing transaction was confirmed on chain."),
+            (new_uuid(), "some random reason"),
+        ];
+        for (uuid, reason) in legacy_reasons.iter() {
+            conn.execute(
+                "INSERT INTO migrate_reasons_channels_history
+                    (uuid, channel_id, counterparty_node_id, closure_reason, is_outbound, is_public, is_closed, created_at)
+                VALUES (?1, ?2, ?3, ?4, 1, 0, 1, 1655806080);",
+                params![
+                    uuid.to_string(),
+                    hex::encode([0; 32]),
+                    "038863cf8ab91046230f561cd5b386cbff8309fa02e3f0c3ed161a3aeb64a643b9",
+                    reason
+                ],
+            )
+            .unwrap();
+        }
+
+        let db = SqliteLightningDB::new("migrate_reasons".into(), Arc::new(Mutex::new(conn))).unwrap();
+        block_on(db.init_db()).unwrap();
+
+        let expected = [
+            ChannelCloseReason::CooperativeClosure,
+            ChannelCloseReason::CounterpartyForceClosed {
+                peer_msg: "channel is not usable".into(),
+            },
+            ChannelCloseReason::CommitmentTxConfirmed,
+            ChannelCloseReason::Unknown {
+                detail: "some random reason".into(),
+            },
+        ];
+        for ((uuid, _), expected) in legacy_reasons.iter().zip(expected.iter()) {
+            let channel = block_on(db.get_channel_from_db(*uuid)).unwrap().unwrap();
+            assert_eq!(channel.closure_reason.as_ref(), Some(expected));
+        }
+
+        let conn = db.sqlite_connection.lock().unwrap();
+        let kind: String = conn
+            .query_row(
+                "SELECT closure_reason FROM migrate_reasons_channels_history WHERE uuid = ?1;",
+                [legacy_reasons[0].0.to_string()],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(kind, "CooperativeClosure");
+    }
+
+    #[test]
+    fn test_get_channel_and_payment_count() {
+        let db = SqliteLightningDB::new(
+            "channel_and_payment_count".into(),
+            Arc::new(Mutex::new(Connection::open_in_memory().unwrap())),
+        )
+        .unwrap();
+
+        block_on(db.init_db()).unwrap();
+
+        assert_eq!(block_on(db.get_channel_count(None)).unwrap(), 0);
+        assert_eq!(block_on(db.get_payment_count(None)).unwrap(), 0);
+
+        let channels = generate_random_channels(7);
+        for channel in channels.iter() {
+            block_on(db.add_channel_to_db(channel)).unwrap();
+        }
+        for channel in channels.iter().take(3) {
+            block_on(db.update_channel_to_closed(channel.uuid, ChannelCloseReason::CooperativeClosure, now_sec_i64()))
+                .unwrap();
+        }
+        assert_eq!(block_on(db.get_channel_count(None)).unwrap(), 7);
+        assert_eq!(block_on(db.get_channel_count(Some(true))).unwrap(), 3);
+        assert_eq!(block_on(db.get_channel_count(Some(false))).unwrap(), 4);
+
+        let secp = Secp256k1::new();
+        let destination = PublicKey::from_secret_key(&secp, &SecretKey::from_slice(&[1; 32]).unwrap());
+        let mut payments = generate_random_payments(5);
+        for (i, payment) in payments.iter_mut().enumerate() {
+            payment.payment_type = if i < 2 {
+                PaymentType::OutboundPayment { destination }
+            } else {
+                PaymentType::InboundPayment
+            };
+            block_on(db.add_payment_to_db(payment)).unwrap();
+        }
+        assert_eq!(block_on(db.get_payment_count(None)).unwrap(), 5);
+        assert_eq!(block_on(db.get_payment_count(Some(true))).unwrap(), 2);
+        assert_eq!(block_on(db.get_payment_count(Some(false))).unwrap(), 3);
+    }
+
+    #[test]
+    fn test_add_get_channel_by_short_channel_id() {
+        let db = SqliteLightningDB::new(
+            "short_channel_id".into(),
+            Arc::new(Mutex::new(Connection::open_in_memory().unwrap())),
+        )
+        .unwrap();
+
+        block_on(db.init_db()).unwrap();
+
+        let channels = generate_random_channels(2);
+        for channel in channels.iter() {
+            block_on(db.add_channel_to_db(channel)).unwrap();
+        }
+        let actual = block_on(db.get_channel_from_db(channels[0].uuid)).unwrap().unwrap();
+        assert_eq!(actual.short_channel_id, None);
+
+        let scid = 0x0a_0000_0001_0002;
+        block_on(db.add_short_channel_id_to_db(channels[0].uuid, scid)).unwrap();
+        let actual = block_on(db.get_channel_by_short_channel_id(scid)).unwrap().unwrap();
+        assert_eq!(actual.uuid, channels[0].uuid);
+        assert_eq!(actual.short_channel_id, Some(scid));
+
+        // Scids above `i64::MAX` must round-trip too.
+        block_on(db.add_short_channel_id_to_db(channels[1].uuid, u64::MAX)).unwrap();
+        let actual = block_on(db.get_channel_by_short_channel_id(u64::MAX)).unwrap().unwrap();
+        assert_eq!(actual.uuid, channels[1].uuid);
+        assert_eq!(actual.short_channel_id, Some(u64::MAX));
+
+        assert!(block_on(db.get_channel_by_short_channel_id(scid + 1))
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_validate_channel_for_insert() {
+        let db = SqliteLightningDB::new(
+            "validate_channel_for_insert".into(),
+            Arc::new(Mutex::new(Connection::open_in_memory().unwrap())),
+        )
+        .unwrap();
+
+        block_on(db.init_db()).unwrap();
+
+        let channel = DBChannelDetails::new(
+            new_uuid(),
+            [0; 32],
+            PublicKey::from_str("038863cf8ab91046230f561cd5b386cbff8309fa02e3f0c3ed161a3aeb64a643b9").unwrap(),
+            true,
+            true,
+        );
+        block_on(db.validate_channel_for_insert(&channel)).unwrap();
+        // The validation must not write anything.
+        assert_eq!(block_on(db.get_channel_from_db(channel.uuid)).unwrap(), None);
+
+        block_on(db.add_channel_to_db(&channel)).unwrap();
+        let err = block_on(db.validate_channel_for_insert(&channel)).unwrap_err();
+        assert!(is_constraint_error(&err), "{err:?}");
+        assert!(err.to_string().contains("already exists"), "{err}");
+
+        let mut malformed = channel.clone();
+        malformed.uuid = new_uuid();
+        malformed.counterparty_node_id = "not a pubkey".into();
+        let err = block_on(db.validate_channel_for_insert(&malformed)).unwrap_err();
+        assert!(matches!(err, SqlError::ToSqlConversionFailure(_)), "{err:?}");
+        assert!(err.to_string().contains("Invalid counterparty_node_id"), "{err}");
+    }
+
+    #[test]
+    fn test_add_channel_with_invalid_counterparty_node_id() {
+        let db = SqliteLightningDB::new(
+            "add_channel_invalid_node_id".into(),
+            Arc::new(Mutex::new(Connection::open_in_memory().unwrap())),
+        )
+        .unwrap();
+
+        block_on(db.init_db()).unwrap();
+
+        let mut channel = DBChannelDetails::new(
+            new_uuid(),
+            [0; 32],
+            PublicKey::from_str("038863cf8ab91046230f561cd5b386cbff8309fa02e3f0c3ed161a3aeb64a643b9").unwrap(),
+            true,
+            true,
+        );
+        channel.counterparty_node_id = "038863cf8ab91046230f561cd5b386cbff8309fa02e3f0c3ed161a3aeb64a643".into();
+
+        let err = block_on(db.add_channel_to_db(&channel)).unwrap_err();
+        assert!(matches!(err, SqlError::ToSqlConversionFailure(_)), "{err:?}");
+        assert!(err.to_string().contains("Invalid counterparty_node_id"), "{err}");
+        assert_eq!(block_on(db.get_channel_from_db(channel.uuid)).unwrap(), None);
+
+        let err = block_on(db.add_channels_to_db(vec![channel.clone()])).unwrap_err();
+        assert!(matches!(err, SqlError::ToSqlConversionFailure(_)), "{err:?}");
+        assert_eq!(block_on(db.get_channel_from_db(channel.uuid)).unwrap(), None);
+    }
+
+    #[test]
+    fn test_multiple_tickers_in_one_db() {
+        let db = SqliteLightningDB::new(
+            "tBTC-TEST-lightning".into(),
+            Arc::new(Mutex::new(Connection::open_in_memory().unwrap())),
+        )
+        .unwrap();
+
+        let tickers = ["tBTC-TEST-lightning".to_owned(), "tLTC-TEST-lightning".to_owned()];
+        let dbs = block_on(db.init_tickers(tickers.clone())).unwrap();
+        assert_eq!(dbs.len(), 2);
+
+        // The same uuid is added under each ticker, which would collide if the tickers shared the tables.
+        let uuid = new_uuid();
+        let counterparty_node_id =
+            PublicKey::from_str("038863cf8ab91046230f561cd5b386cbff8309fa02e3f0c3ed161a3aeb64a643b9").unwrap();
+        for (i, ticker) in tickers.iter().enumerate() {
+            let channel = DBChannelDetails::new(uuid, [i as u8; 32], counterparty_node_id, true, true);
+            block_on(dbs[ticker].add_channel_to_db(&channel)).unwrap();
+        }
+
+        for (i, ticker) in tickers.iter().enumerate() {
+            let channel = block_on(dbs[ticker].get_channel_from_db(uuid)).unwrap().unwrap();
+            assert_eq!(channel.channel_id, hex::encode([i as u8; 32]));
+        }
+        // The handle the tickers were initialized from keeps working with its own ticker's tables.
+        let channel = block_on(db.get_channel_from_db(uuid)).unwrap().unwrap();
+        assert_eq!(channel.channel_id, hex::encode([0; 32]));
+    }
+
+    #[test]
+    fn test_add_channels_to_db() {
+        let db = SqliteLightningDB::new(
+            "add_channels_to_db".into(),
+            Arc::new(Mutex::new(Connection::open_in_memory().unwrap())),
+        )
+        .unwrap();
+
+        block_on(db.init_db()).unwrap();
+
+        let channels = generate_random_channels(10);
+        let new_channels: Vec<_> = channels
+            .iter()
+            .map(|chan| {
+                let counterparty_node_id = PublicKey::from_str(&chan.counterparty_node_id).unwrap();
+                DBChannelDetails::new(
+                    chan.uuid,
+                    [1; 32],
+                    counterparty_node_id,
+                    chan.is_outbound,
+                    chan.is_public,
+                )
+            })
+            .collect();
+        block_on(db.add_channels_to_db(new_channels[..5].to_vec())).unwrap();
+        for channel in &new_channels[..5] {
+            let actual = block_on(db.get_channel_from_db(channel.uuid)).unwrap().unwrap();
+            assert_eq!(channel, &actual);
+        }
+
+        // The batch contains a channel that is already in the DB, the whole batch must be rolled back.
+        let mut batch = new_channels[5..].to_vec();
+        batch.push(new_channels[2].clone());
+        let err = block_on(db.add_channels_to_db(batch)).unwrap_err();
+        assert!(is_constraint_error(&err));
+        assert!(err.to_string().contains(&new_channels[2].uuid.to_string()), "{}", err);
+        for channel in &new_channels[5..] {
+            assert!(block_on(db.get_channel_from_db(channel.uuid)).unwrap().is_none());
+        }
+    }
+
+    #[test]
+    fn test_add_channels_to_db_non_duplicate_constraint_error() {
+        let connection = Arc::new(Mutex::new(Connection::open_in_memory().unwrap()));
+        let db = SqliteLightningDB::new("add_channels_constraint".into(), connection.clone()).unwrap();
+
+        block_on(db.init_db()).unwrap();
+        // `RAISE(ABORT)` fails the insert with a constraint error that isn't caused by a duplicate channel.
+        lock_connection(&connection)
+            .execute_batch(&format!(
+                "CREATE TRIGGER reject_inserts BEFORE INSERT ON {} BEGIN SELECT RAISE(ABORT, 'insert rejected'); END;",
+                channels_history_table(&db.db_ticker)
+            ))
+            .unwrap();
+
+        let channels = generate_random_channels(1);
+        let err = block_on(db.add_channels_to_db(channels)).unwrap_err();
+        assert!(is_constraint_error(&err), "{err:?}");
+        assert!(err.to_string().contains("insert rejected"), "{err}");
+        assert!(!err.to_string().contains("already exists"), "{err}");
+    }
+
+    #[test]
+    fn test_get_channels_from_db() {
+        let db = SqliteLightningDB::new(
+            "get_channels_from_db".into(),
+            Arc::new(Mutex::new(Connection::open_in_memory().unwrap())),
+        )
+        .unwrap();
+
+        block_on(db.init_db()).unwrap();
+
+        let channels = generate_random_channels(5);
+        for channel in &channels {
+            block_on(db.add_channel_to_db(channel)).unwrap();
+        }
+
+        let missing_uuid = new_uuid();
+        let requested = [channels[3].uuid, missing_uuid, channels[0].uuid, channels[4].uuid];
+        let actual: Vec<_> = block_on(db.get_channels_from_db(&requested))
+            .unwrap()
+            .into_iter()
+            .map(|chan| chan.uuid)
+            .collect();
+        let expected = vec![channels[3].uuid, channels[0].uuid, channels[4].uuid];
+        assert_eq!(expected, actual);
+
+        let actual = block_on(db.get_channels_from_db(&[missing_uuid])).unwrap();
+        assert!(actual.is_empty());
+
+        let actual = block_on(db.get_channels_from_db(&[])).unwrap();
+        assert!(actual.is_empty());
+    }
+
+    #[test]
+    fn test_get_channels_funded_in_block_range() {
+        let db = SqliteLightningDB::new(
+            "funded_in_block_range".into(),
+            Arc::new(Mutex::new(Connection::open_in_memory().unwrap())),
+        )
+        .unwrap();
+
+        block_on(db.init_db()).unwrap();
+
+        let mut channels = generate_random_channels(6);
+        let blocks = [100, 150, 200, 250, 300];
+        for (channel, block) in channels.iter_mut().zip(blocks.iter()) {
+            channel.funding_generated_in_block = Some(*block);
+            block_on(db.add_channel_to_db(channel)).unwrap();
+            block_on(db.add_funding_tx_to_db(
+                channel.uuid,
+                channel.funding_tx.clone().unwrap(),
+                channel.funding_value.unwrap(),
+                *block,
+            ))
+            .unwrap();
+        }
+        // The last channel is not funded yet.
+        block_on(db.add_channel_to_db(&channels[5])).unwrap();
+
+        let actual: Vec<_> = block_on(db.get_channels_funded_in_block_range(150, 250))
+            .unwrap()
+            .into_iter()
+            .map(|chan| chan.uuid)
+            .collect();
+        let expected = vec![channels[1].uuid, channels[2].uuid, channels[3].uuid];
+        assert_eq!(expected, actual);
+
+        let actual = block_on(db.get_channels_funded_in_block_range(0, u32::MAX as u64)).unwrap();
+        assert_eq!(5, actual.len());
+        assert!(actual.iter().all(|chan| chan.funding_generated_in_block.is_some()));
+
+        let actual = block_on(db.get_channels_funded_in_block_range(301, 400)).unwrap();
+        assert!(actual.is_empty());
+    }
+
     #[test]
     fn test_add_get_payment_sql() {
         let db = SqliteLightningDB::new(
@@ -1305,6 +2675,7 @@ mod tests {
             status: HTLCStatus::Failed,
             created_at: now_sec_i64(),
             last_updated: now_sec_i64(),
+            invoice: None,
         };
         block_on(db.add_payment_to_db(&expected_payment_info)).unwrap();
 
@@ -1372,6 +2743,348 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_get_payments_to_destination() {
+        let db = SqliteLightningDB::new(
+            "payments_to_destination".into(),
+            Arc::new(Mutex::new(Connection::open_in_memory().unwrap())),
+        )
+        .unwrap();
+
+        block_on(db.init_db()).unwrap();
+
+        let secp = Secp256k1::new();
+        let dest_1 = PublicKey::from_secret_key(&secp, &SecretKey::from_slice(&[1; 32]).unwrap());
+        let dest_2 = PublicKey::from_secret_key(&secp, &SecretKey::from_slice(&[2; 32]).unwrap());
+
+        let mut payments = generate_random_payments(10);
+        for (i, payment) in payments.iter_mut().enumerate() {
+            payment.payment_type = match i % 3 {
+                0 => PaymentType::OutboundPayment { destination: dest_1 },
+                1 => PaymentType::OutboundPayment { destination: dest_2 },
+                _ => PaymentType::InboundPayment,
+            };
+            block_on(db.add_payment_to_db(payment)).unwrap();
+        }
+
+        payments.sort_by(|a, b| b.last_updated.cmp(&a.last_updated));
+        let expected: Vec<_> = payments
+            .iter()
+            .filter(|p| p.payment_type == PaymentType::OutboundPayment { destination: dest_1 })
+            .cloned()
+            .collect();
+        let actual = block_on(db.get_payments_to_destination(dest_1)).unwrap();
+        assert_eq!(4, actual.len());
+        assert_eq!(expected, actual);
+
+        let actual = block_on(db.get_payments_to_destination(dest_2)).unwrap();
+        assert_eq!(3, actual.len());
+        assert!(actual
+            .iter()
+            .all(|p| p.payment_type == PaymentType::OutboundPayment { destination: dest_2 }));
+    }
+
+    #[test]
+    fn test_get_payments_ordered_by_time() {
+        let db = SqliteLightningDB::new(
+            "payments_ordered_by_time".into(),
+            Arc::new(Mutex::new(Connection::open_in_memory().unwrap())),
+        )
+        .unwrap();
+
+        block_on(db.init_db()).unwrap();
+
+        let mut payments = generate_random_payments(5);
+        for (i, payment) in payments.iter_mut().enumerate() {
+            payment.created_at = i as i64;
+            payment.last_updated = i as i64;
+            block_on(db.add_payment_to_db(payment)).unwrap();
+        }
+
+        let actual: Vec<_> = block_on(db.get_payments_ordered_by_time(10))
+            .unwrap()
+            .into_iter()
+            .map(|p| p.payment_hash)
+            .collect();
+        let expected: Vec<_> = payments.iter().rev().map(|p| p.payment_hash).collect();
+        assert_eq!(expected, actual);
+
+        // Updating a payment must bump its last_updated timestamp and move it to the top.
+        block_on(db.update_payment_status_in_db(payments[0].payment_hash, &HTLCStatus::Failed)).unwrap();
+        let actual = block_on(db.get_payments_ordered_by_time(2)).unwrap();
+        assert_eq!(2, actual.len());
+        assert_eq!(payments[0].payment_hash, actual[0].payment_hash);
+        assert_eq!(payments[0].created_at, actual[0].created_at);
+        assert!(actual[0].last_updated > payments[4].last_updated);
+    }
+
+    #[test]
+    fn test_for_each_payment() {
+        let db = SqliteLightningDB::new(
+            "for_each_payment".into(),
+            Arc::new(Mutex::new(Connection::open_in_memory().unwrap())),
+        )
+        .unwrap();
+
+        block_on(db.init_db()).unwrap();
+
+        let mut payments = generate_random_payments(500);
+        for (i, payment) in payments.iter_mut().enumerate() {
+            payment.amt_msat = Some(i as i64 * 1000);
+            block_on(db.add_payment_to_db(payment)).unwrap();
+        }
+
+        let sum_with_visitor = |is_outbound: Option<bool>| {
+            let sum = Arc::new(Mutex::new(0i64));
+            let sum_clone = sum.clone();
+            block_on(db.for_each_payment(is_outbound, move |payment| {
+                *sum_clone.lock().unwrap() += payment.amt_msat.unwrap_or_default();
+            }))
+            .unwrap();
+            let sum = *sum.lock().unwrap();
+            sum
+        };
+
+        let collected = block_on(db.get_payments_by_filter(
+            None,
+            PagingOptionsEnum::PageNumber(NonZeroUsize::new(1).unwrap()),
+            payments.len(),
+        ))
+        .unwrap()
+        .payments;
+        assert_eq!(collected.len(), payments.len());
+        let collected_sum = |is_outbound: Option<bool>| -> i64 {
+            collected
+                .iter()
+                .filter(|payment| is_outbound.map_or(true, |is_outbound| payment.is_outbound() == is_outbound))
+                .map(|payment| payment.amt_msat.unwrap_or_default())
+                .sum()
+        };
+
+        for is_outbound in [None, Some(true), Some(false)] {
+            assert_eq!(
+                sum_with_visitor(is_outbound),
+                collected_sum(is_outbound),
+                "{is_outbound:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_export_payments_csv() {
+        let db = SqliteLightningDB::new(
+            "export_payments_csv".into(),
+            Arc::new(Mutex::new(Connection::open_in_memory().unwrap())),
+        )
+        .unwrap();
+
+        block_on(db.init_db()).unwrap();
+
+        let destination = PublicKey::from_secret_key(&Secp256k1::new(), &SecretKey::from_slice(&[1; 32]).unwrap());
+        let mut outbound = PaymentInfo::new(
+            PaymentHash([1; 32]),
+            PaymentType::OutboundPayment { destination },
+            "outbound, with a comma".into(),
+            Some(2000),
+        )
+        .with_status(HTLCStatus::Succeeded);
+        outbound.fee_paid_msat = Some(10);
+        outbound.created_at = 1_700_000_000;
+        let mut inbound = PaymentInfo::new(
+            PaymentHash([2; 32]),
+            PaymentType::InboundPayment,
+            "inbound".into(),
+            None,
+        );
+        inbound.created_at = 1_700_000_001;
+        block_on(db.add_payment_to_db(&outbound)).unwrap();
+        block_on(db.add_payment_to_db(&inbound)).unwrap();
+
+        let csv = block_on(db.export_payments_csv()).unwrap();
+        let lines: Vec<_> = csv.lines().collect();
+        assert_eq!(lines, vec![
+            "hash,direction,amount_msat,fee_msat,status,destination,timestamp".to_owned(),
+            format!(
+                "{},outbound,2000,10,Succeeded,{},1700000000",
+                hex::encode([1; 32]),
+                destination
+            ),
+            format!("{},inbound,,,Pending,,1700000001", hex::encode([2; 32])),
+        ]);
+        assert_eq!(escape_csv_field("a,\"b\""), "\"a,\"\"b\"\"\"");
+    }
+
+    #[test]
+    fn test_get_payments_by_direction_newest_first() {
+        let db = SqliteLightningDB::new(
+            "payments_by_direction".into(),
+            Arc::new(Mutex::new(Connection::open_in_memory().unwrap())),
+        )
+        .unwrap();
+
+        block_on(db.init_db()).unwrap();
+
+        let conn = db.sqlite_connection.lock().unwrap();
+        let index: Option<String> = query_single_row(
+            &conn,
+            "SELECT name FROM sqlite_master WHERE type = 'index' AND name = ?1;",
+            ["payments_by_direction_payments_history_is_outbound_idx"],
+            string_from_row,
+        )
+        .unwrap();
+        assert!(index.is_some());
+        drop(conn);
+
+        let secp = Secp256k1::new();
+        let destination = PublicKey::from_secret_key(&secp, &SecretKey::from_slice(&[1; 32]).unwrap());
+        // All the payments share the same timestamps, so the insertion order must break the tie.
+        let mut payments = generate_random_payments(8);
+        for (i, payment) in payments.iter_mut().enumerate() {
+            payment.payment_type = if i % 2 == 0 {
+                PaymentType::OutboundPayment { destination }
+            } else {
+                PaymentType::InboundPayment
+            };
+            payment.created_at = 100;
+            payment.last_updated = 100;
+            block_on(db.add_payment_to_db(payment)).unwrap();
+        }
+
+        let filter = DBPaymentsFilter {
+            is_outbound: Some(true),
+            destination: None,
+            description: None,
+            status: None,
+            from_amount_msat: None,
+            to_amount_msat: None,
+            from_fee_paid_msat: None,
+            to_fee_paid_msat: None,
+            from_timestamp: None,
+            to_timestamp: None,
+        };
+        let actual: Vec<_> = block_on(db.get_payments_by_filter(
+            Some(filter.clone()),
+            PagingOptionsEnum::PageNumber(NonZeroUsize::new(1).unwrap()),
+            10,
+        ))
+        .unwrap()
+        .payments
+        .into_iter()
+        .map(|p| p.payment_hash)
+        .collect();
+        let expected: Vec<_> = payments.iter().step_by(2).rev().map(|p| p.payment_hash).collect();
+        assert_eq!(expected, actual);
+
+        let inbound_filter = DBPaymentsFilter {
+            is_outbound: Some(false),
+            ..filter
+        };
+        let actual: Vec<_> = block_on(db.get_payments_by_filter(
+            Some(inbound_filter),
+            PagingOptionsEnum::PageNumber(NonZeroUsize::new(1).unwrap()),
+            10,
+        ))
+        .unwrap()
+        .payments
+        .into_iter()
+        .map(|p| p.payment_hash)
+        .collect();
+        let expected: Vec<_> = payments
+            .iter()
+            .skip(1)
+            .step_by(2)
+            .rev()
+            .map(|p| p.payment_hash)
+            .collect();
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn test_init_sql_adds_missing_payments_history_columns() {
+        let conn = Connection::open_in_memory().unwrap();
+        // Legacy schema without the invoice column.
+        conn.execute(
+            "CREATE TABLE migrate_invoice_payments_history (
+                id INTEGER NOT NULL PRIMARY KEY,
+                payment_hash VARCHAR(255) NOT NULL UNIQUE,
+                destination VARCHAR(255),
+                description VARCHAR(641) NOT NULL,
+                preimage VARCHAR(255),
+                amount_msat INTEGER,
+                fee_paid_msat INTEGER,
+                is_outbound INTEGER NOT NULL,
+                status VARCHAR(255) NOT NULL,
+                created_at INTEGER NOT NULL,
+                last_updated INTEGER NOT NULL
+            );",
+            [],
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO migrate_invoice_payments_history
+                (payment_hash, description, is_outbound, status, created_at, last_updated)
+            VALUES (?1, 'legacy payment', 0, 'Succeeded', 1700000000, 1700000100);",
+            [hex::encode([3; 32])],
+        )
+        .unwrap();
+
+        let db = SqliteLightningDB::new("migrate_invoice".into(), Arc::new(Mutex::new(conn))).unwrap();
+        block_on(db.init_db()).unwrap();
+
+        let payment = block_on(db.get_payment_from_db(PaymentHash([3; 32]))).unwrap().unwrap();
+        assert_eq!(payment.description, "legacy payment");
+        assert_eq!(payment.status, HTLCStatus::Succeeded);
+        assert_eq!(payment.created_at, 1_700_000_000);
+        assert_eq!(payment.last_updated, 1_700_000_100);
+        assert_eq!(payment.invoice, None);
+    }
+
+    #[test]
+    fn test_add_get_payment_invoice() {
+        let db = SqliteLightningDB::new(
+            "payment_invoice".into(),
+            Arc::new(Mutex::new(Connection::open_in_memory().unwrap())),
+        )
+        .unwrap();
+
+        block_on(db.init_db()).unwrap();
+
+        let mut payments = generate_random_payments(3);
+        let invoice = "lnbcrt10u1p3l7l6ypp5wq3zkmhgkr7xn9ej4ujhhxe96c2q3xn8vm9kxcyvs2f8vdrzgrsq".to_string();
+        payments[0].invoice = Some(invoice.clone());
+        payments[1].invoice = None;
+
+        block_on(db.add_payment_to_db(&payments[0])).unwrap();
+        block_on(db.add_payment_to_db(&payments[1])).unwrap();
+
+        let actual = block_on(db.get_payment_from_db(payments[0].payment_hash))
+            .unwrap()
+            .unwrap();
+        assert_eq!(actual.invoice, Some(invoice));
+        assert_eq!(actual, payments[0]);
+        let actual = block_on(db.get_payment_from_db(payments[1].payment_hash))
+            .unwrap()
+            .unwrap();
+        assert_eq!(actual.invoice, None);
+        assert_eq!(actual, payments[1]);
+
+        // Upserting replaces the whole record including the invoice.
+        payments[0].invoice = None;
+        block_on(db.add_or_update_payment_in_db(&payments[0])).unwrap();
+        let actual = block_on(db.get_payment_from_db(payments[0].payment_hash))
+            .unwrap()
+            .unwrap();
+        assert_eq!(actual.invoice, None);
+
+        payments[2].invoice = Some("l".repeat(MAX_INVOICE_LENGTH + 1));
+        block_on(db.add_payment_to_db(&payments[2])).unwrap_err();
+        payments[2].invoice = Some(String::new());
+        block_on(db.add_or_update_payment_in_db(&payments[2])).unwrap_err();
+        assert!(block_on(db.get_payment_from_db(payments[2].payment_hash))
+            .unwrap()
+            .is_none());
+    }
+
     #[test]
     fn test_get_payments_by_filter() {
         let db = SqliteLightningDB::new(
@@ -1493,6 +3206,25 @@ mod tests {
         assert_eq!(expected_payments, actual_payments);
     }
 
+    #[test]
+    fn test_disk_full_db_error_is_transient() {
+        let disk_full = || SqlError::SqliteFailure(rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_FULL), None);
+        assert!(matches!(
+            SaveChannelClosingError::from(disk_full()),
+            SaveChannelClosingError::DiskFull(_)
+        ));
+        assert!(matches!(
+            EnableLightningError::from(disk_full()),
+            EnableLightningError::DiskFull(_)
+        ));
+
+        let constraint = SqlError::SqliteFailure(rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_CONSTRAINT), None);
+        assert!(matches!(
+            SaveChannelClosingError::from(constraint),
+            SaveChannelClosingError::DbError(_)
+        ));
+    }
+
     #[test]
     fn test_invalid_lightning_db_name() {
         let db = SqliteLightningDB::new("123".into(), Mutex::new(Connection::open_in_memory().unwrap()).into());
@@ -5,6 +5,7 @@ use lightning::routing::gossip;
 use lightning::routing::scoring::ProbabilisticScorer;
 use parking_lot::Mutex as PaMutex;
 use secp256k1v24::PublicKey;
+use serde::Serialize;
 use std::collections::{HashMap, HashSet};
 use std::net::SocketAddr;
 use std::sync::{Arc, Mutex};
@@ -16,6 +17,26 @@ pub type TrustedNodesShared = Arc<PaMutex<HashSet<PublicKey>>>;
 pub type NetworkGraph = gossip::NetworkGraph<Arc<LogState>>;
 pub type Scorer = Mutex<ProbabilisticScorer<Arc<NetworkGraph>, Arc<LogState>>>;
 
+/// The health of the lightning persistence stack, can be used by readiness probes.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct PersisterHealth {
+    /// Whether a probe file can be written to and read back from the main lightning data directory.
+    pub main_dir_writable: bool,
+    /// Whether a probe file can be written to and read back from the backup directory,
+    /// `None` if no backup path is configured.
+    pub backup_dir_writable: Option<bool>,
+    /// Whether the lightning sqlite DB responds to queries, `None` if the DB wasn't checked.
+    pub db_responsive: Option<bool>,
+    /// The errors encountered by the failed checks.
+    pub errors: Vec<String>,
+}
+
+impl PersisterHealth {
+    pub fn is_healthy(&self) -> bool {
+        self.main_dir_writable && self.backup_dir_writable.unwrap_or(true) && self.db_responsive.unwrap_or(true)
+    }
+}
+
 #[async_trait]
 pub trait LightningStorage {
     type Error;
@@ -25,6 +46,9 @@ pub trait LightningStorage {
 
     async fn is_fs_initialized(&self) -> Result<bool, Self::Error>;
 
+    /// Does a lightweight write probe of the storage directories. The DB part of the result is left unchecked.
+    async fn health_check(&self) -> Result<PersisterHealth, Self::Error>;
+
     async fn get_nodes_addresses(&self) -> Result<NodesAddressesMap, Self::Error>;
 
     async fn save_nodes_addresses(&self, nodes_addresses: NodesAddressesMapShared) -> Result<(), Self::Error>;
@@ -1,11 +1,12 @@
 use super::*;
 use crate::lightning::ln_db::LightningDB;
+use crate::lightning::ln_filesystem_persister::{ChannelMonitorPersister, FsyncPolicy};
 use crate::lightning::ln_platform::{get_best_header, ln_best_block_update_loop, update_best_block};
 use crate::lightning::ln_sql::SqliteLightningDB;
 use crate::lightning::ln_storage::{LightningStorage, NodesAddressesMap};
 use crate::utxo::rpc_clients::ElectrumBlockHeader;
 use bitcoin::hash_types::BlockHash;
-use bitcoin_hashes::{sha256d, Hash};
+use bitcoin_hashes::{sha256d, sha512, Hash, HashEngine};
 use common::executor::SpawnFuture;
 use common::log::LogState;
 use lightning::chain::keysinterface::{InMemorySigner, KeysManager};
@@ -23,6 +24,7 @@ use std::collections::hash_map::Entry;
 use std::fs::File;
 use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
 pub const PAYMENT_RETRY_ATTEMPTS: usize = 5;
 
@@ -32,7 +34,7 @@ pub type ChainMonitor = chainmonitor::ChainMonitor<
     Arc<Platform>,
     Arc<Platform>,
     Arc<LogState>,
-    Arc<LightningFilesystemPersister>,
+    Arc<ChannelMonitorPersister>,
 >;
 
 pub type ChannelManager = SimpleArcChannelManager<ChainMonitor, Platform, Platform, LogState>;
@@ -68,15 +70,37 @@ fn ln_data_backup_dir(path: Option<String>, platform_coin_address: &str, ticker:
     })
 }
 
+/// Derives the master secret for encrypting the nodes addresses file from the platform coin's private key,
+/// so that the same secret is derived on every start without being stored anywhere.
+pub fn nodes_addresses_encryption_key(platform: &Platform) -> EnableLightningResult<[u8; 64]> {
+    let secret: [u8; 32] = platform
+        .coin
+        .as_ref()
+        .priv_key_policy
+        .activated_key_or_err()?
+        .private()
+        .secret
+        .into();
+    let mut engine = sha512::Hash::engine();
+    engine.input(b"lightning/nodes_addresses");
+    engine.input(&secret);
+    Ok(sha512::Hash::from_engine(engine).into_inner())
+}
+
 pub async fn init_persister(
     ctx: &MmArc,
     platform_coin_address: &str,
     ticker: String,
     backup_path: Option<String>,
+    nodes_addresses_key: Option<[u8; 64]>,
+    monitor_fsync_policy: FsyncPolicy,
 ) -> EnableLightningResult<Arc<LightningFilesystemPersister>> {
     let ln_data_dir = ln_data_dir(ctx, platform_coin_address, &ticker);
     let ln_data_backup_dir = ln_data_backup_dir(backup_path, platform_coin_address, &ticker);
-    let persister = Arc::new(LightningFilesystemPersister::new(ln_data_dir, ln_data_backup_dir));
+    let persister = Arc::new(
+        LightningFilesystemPersister::new(ln_data_dir, ln_data_backup_dir, nodes_addresses_key)
+            .with_monitor_fsync_policy(monitor_fsync_policy),
+    );
 
     let is_initialized = persister.is_fs_initialized().await?;
     if !is_initialized {
@@ -90,15 +114,18 @@ pub async fn init_db(
     ctx: &MmArc,
     platform_coin_address: &str,
     ticker: String,
+    wal_mode: bool,
+    busy_timeout: Duration,
 ) -> EnableLightningResult<SqliteLightningDB> {
     let conn = ctx
         .address_db(platform_coin_address)
         .map_err(|e| EnableLightningError::IOError(e.to_string()))?;
-    let db = SqliteLightningDB::new(ticker, Arc::new(Mutex::new(conn)))?;
+    let db = SqliteLightningDB::new(ticker, Arc::new(Mutex::new(conn)))?
+        .with_wal_mode(wal_mode)
+        .with_busy_timeout(busy_timeout);
 
-    if !db.is_db_initialized().await? {
-        db.init_db().await?;
-    }
+    // `init_db` is idempotent and must run on every start to apply the connection settings and column migrations.
+    db.init_db().await?;
 
     Ok(db)
 }
@@ -135,12 +162,13 @@ pub async fn init_channel_manager(
     let broadcaster = platform.clone();
 
     // Initialize the ChainMonitor
+    let monitor_persister = Arc::new(ChannelMonitorPersister::new(persister.clone()));
     let chain_monitor: Arc<ChainMonitor> = Arc::new(chainmonitor::ChainMonitor::new(
         Some(platform.clone()),
         broadcaster.clone(),
         logger.clone(),
         fee_estimator.clone(),
-        persister.clone(),
+        monitor_persister.clone(),
     ));
 
     // Read ChannelMonitor state from disk, important for lightning node is restarting and has at least 1 channel
@@ -254,6 +282,7 @@ pub async fn init_channel_manager(
         platform.clone(),
         db,
         chain_monitor.clone(),
+        monitor_persister,
         channel_manager.clone(),
         rpc_client.clone(),
         best_block,
@@ -261,6 +290,25 @@ pub async fn init_channel_manager(
     Ok((chain_monitor, channel_manager))
 }
 
+/// Persists the `ChannelMonitor`s whose updates were left `InProgress` because the disk was full,
+/// and notifies the `chain_monitor` about every update that is completed now.
+pub fn retry_pending_monitor_updates(chain_monitor: &ChainMonitor, monitor_persister: &ChannelMonitorPersister) {
+    for (funding_txo, update_ids) in chain_monitor.list_pending_monitor_updates() {
+        let status = match chain_monitor.get_monitor(funding_txo) {
+            Ok(monitor) => monitor_persister.persist_channel_monitor(funding_txo, &*monitor),
+            Err(_) => continue,
+        };
+        if !matches!(status, ChannelMonitorUpdateStatus::Completed) {
+            continue;
+        }
+        for update_id in update_ids {
+            if let Err(e) = chain_monitor.channel_monitor_updated(funding_txo, update_id) {
+                error!("Error completing ChannelMonitor update for {:?}: {:?}", funding_txo, e);
+            }
+        }
+    }
+}
+
 pub async fn get_open_channels_nodes_addresses(
     persister: Arc<LightningFilesystemPersister>,
     channel_manager: Arc<ChannelManager>,
@@ -82,8 +82,8 @@ use std::collections::HashSet;
 use std::num::{NonZeroUsize, TryFromIntError};
 use std::ops::{Add, AddAssign, Deref};
 use std::str::FromStr;
-use std::sync::atomic::AtomicBool;
 use std::sync::atomic::Ordering as AtomicOrdering;
+use std::sync::atomic::{AtomicBool, AtomicU64};
 use std::sync::Arc;
 use std::time::Duration;
 use std::{fmt, iter};
@@ -2465,9 +2465,19 @@ pub struct TransactionDetails {
     /// Type of transactions, default is StandardTransfer
     #[serde(default)]
     transaction_type: TransactionType,
+    /// The history records stored before the empty memos were normalized contain an empty string instead of `None`.
+    #[serde(default, deserialize_with = "deserialize_empty_memo_as_none")]
     memo: Option<String>,
 }
 
+fn deserialize_empty_memo_as_none<'de, D>(deserializer: D) -> Result<Option<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let memo = Option::<String>::deserialize(deserializer)?;
+    Ok(memo.filter(|memo| !memo.is_empty()))
+}
+
 #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 #[serde(untagged)]
 pub enum TransactionData {
@@ -3568,8 +3578,8 @@ pub trait MmCoin: SwapOps + WatcherOps + MarketCoinOps + Send + Sync + 'static {
     /// Get swap contract address if the coin uses it in Atomic Swaps.
     fn swap_contract_address(&self) -> Option<BytesJson>;
 
-    /// Get fallback swap contract address if the coin uses it in Atomic Swaps.
-    fn fallback_swap_contract(&self) -> Option<BytesJson>;
+    /// Get the fallback swap contract addresses, in the order of preference, if the coin uses them in Atomic Swaps.
+    fn fallback_swap_contracts(&self) -> Vec<BytesJson>;
 
     /// The minimum number of confirmations at which a transaction is considered mature.
     fn mature_confirmations(&self) -> Option<u32>;
@@ -4687,6 +4697,16 @@ pub trait RpcTransportEventHandler {
     fn on_connected(&self, address: &str) -> Result<(), String>;
 
     fn on_disconnected(&self, address: &str) -> Result<(), String>;
+
+    /// Returns the traffic counters if the handler keeps track of them.
+    fn traffic_stats(&self) -> Option<RpcTransportTrafficStats> { None }
+}
+
+/// The amount of traffic observed by an [`RpcTransportEventHandler`].
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct RpcTransportTrafficStats {
+    pub bytes_sent: u64,
+    pub bytes_received: u64,
 }
 
 pub type SharableRpcTransportEventHandler = dyn RpcTransportEventHandler + Send + Sync;
@@ -4706,6 +4726,8 @@ impl RpcTransportEventHandler for RpcTransportEventHandlerShared {
     fn on_connected(&self, address: &str) -> Result<(), String> { self.as_ref().on_connected(address) }
 
     fn on_disconnected(&self, address: &str) -> Result<(), String> { self.as_ref().on_disconnected(address) }
+
+    fn traffic_stats(&self) -> Option<RpcTransportTrafficStats> { self.as_ref().traffic_stats() }
 }
 
 impl RpcTransportEventHandler for Box<SharableRpcTransportEventHandler> {
@@ -4718,6 +4740,8 @@ impl RpcTransportEventHandler for Box<SharableRpcTransportEventHandler> {
     fn on_connected(&self, address: &str) -> Result<(), String> { self.as_ref().on_connected(address) }
 
     fn on_disconnected(&self, address: &str) -> Result<(), String> { self.as_ref().on_disconnected(address) }
+
+    fn traffic_stats(&self) -> Option<RpcTransportTrafficStats> { self.as_ref().traffic_stats() }
 }
 
 impl<T: RpcTransportEventHandler> RpcTransportEventHandler for Vec<T> {
@@ -4789,14 +4813,22 @@ pub struct CoinTransportMetrics {
     ticker: String,
     /// RPC client type.
     client: String,
+    /// The traffic counters shared by the clones of the handler.
+    bytes_sent: Arc<AtomicU64>,
+    bytes_received: Arc<AtomicU64>,
 }
 
 impl CoinTransportMetrics {
+    /// The name returned from [`RpcTransportEventHandler::debug_info`].
+    pub const NAME: &'static str = "CoinTransportMetrics";
+
     fn new(metrics: MetricsWeak, ticker: String, client: RpcClientType) -> CoinTransportMetrics {
         CoinTransportMetrics {
             metrics,
             ticker,
             client: client.to_string(),
+            bytes_sent: Arc::new(AtomicU64::new(0)),
+            bytes_received: Arc::new(AtomicU64::new(0)),
         }
     }
 
@@ -4804,9 +4836,10 @@ impl CoinTransportMetrics {
 }
 
 impl RpcTransportEventHandler for CoinTransportMetrics {
-    fn debug_info(&self) -> String { "CoinTransportMetrics".into() }
+    fn debug_info(&self) -> String { Self::NAME.into() }
 
     fn on_outgoing_request(&self, data: &[u8]) {
+        self.bytes_sent.fetch_add(data.len() as u64, AtomicOrdering::Relaxed);
         mm_counter!(self.metrics, "rpc_client.traffic.out", data.len() as u64,
             "coin" => self.ticker.to_owned(), "client" => self.client.to_owned());
         mm_counter!(self.metrics, "rpc_client.request.count", 1,
@@ -4814,6 +4847,8 @@ impl RpcTransportEventHandler for CoinTransportMetrics {
     }
 
     fn on_incoming_response(&self, data: &[u8]) {
+        self.bytes_received
+            .fetch_add(data.len() as u64, AtomicOrdering::Relaxed);
         mm_counter!(self.metrics, "rpc_client.traffic.in", data.len() as u64,
             "coin" => self.ticker.to_owned(), "client" => self.client.to_owned());
         mm_counter!(self.metrics, "rpc_client.response.count", 1,
@@ -4823,6 +4858,13 @@ impl RpcTransportEventHandler for CoinTransportMetrics {
     fn on_connected(&self, _address: &str) -> Result<(), String> { Ok(()) }
 
     fn on_disconnected(&self, _address: &str) -> Result<(), String> { Ok(()) }
+
+    fn traffic_stats(&self) -> Option<RpcTransportTrafficStats> {
+        Some(RpcTransportTrafficStats {
+            bytes_sent: self.bytes_sent.load(AtomicOrdering::Relaxed),
+            bytes_received: self.bytes_received.load(AtomicOrdering::Relaxed),
+        })
+    }
 }
 
 #[async_trait]
@@ -1320,8 +1320,11 @@ impl MmCoin for Qrc20Coin {
         Some(BytesJson::from(self.swap_contract_address.0.as_ref()))
     }
 
-    fn fallback_swap_contract(&self) -> Option<BytesJson> {
-        self.fallback_swap_contract.map(|a| BytesJson::from(a.0.as_ref()))
+    fn fallback_swap_contracts(&self) -> Vec<BytesJson> {
+        self.fallback_swap_contract
+            .iter()
+            .map(|a| BytesJson::from(a.0.as_ref()))
+            .collect()
     }
 
     fn mature_confirmations(&self) -> Option<u32> { Some(self.utxo.conf.mature_confirmations) }
@@ -115,7 +115,8 @@ pub async fn generate_invoice(
         PaymentType::InboundPayment,
         req.description,
         req.amount_in_msat.map(|a| a as i64),
-    );
+    )
+    .with_invoice(invoice.to_string());
     // Note: Although the preimage can be recreated from the keymanager and the invoice secret, the payment info is added to db at invoice generation stage
     // to save the description. Although it's not ideal to keep track of invoices before they are paid since they may never be paid, but this is the only way
     // to have the invoice description saved in the db.
@@ -0,0 +1,64 @@
+use crate::lightning::ln_storage::PersisterHealth;
+use crate::{lp_coinfind_or_err, CoinFindError, MmCoinEnum};
+use common::HttpStatusCode;
+use http::StatusCode;
+use mm2_core::mm_ctx::MmArc;
+use mm2_err_handle::prelude::*;
+
+type PersisterHealthResult<T> = Result<T, MmError<PersisterHealthError>>;
+
+#[derive(Debug, Deserialize, Display, Serialize, SerializeErrorType)]
+#[serde(tag = "error_type", content = "error_data")]
+pub enum PersisterHealthError {
+    #[display(fmt = "Lightning network is not supported for {}", _0)]
+    UnsupportedCoin(String),
+    #[display(fmt = "No such coin {}", _0)]
+    NoSuchCoin(String),
+}
+
+impl HttpStatusCode for PersisterHealthError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            PersisterHealthError::UnsupportedCoin(_) => StatusCode::BAD_REQUEST,
+            PersisterHealthError::NoSuchCoin(_) => StatusCode::NOT_FOUND,
+        }
+    }
+}
+
+impl From<CoinFindError> for PersisterHealthError {
+    fn from(e: CoinFindError) -> Self {
+        match e {
+            CoinFindError::NoSuchCoin { coin } => PersisterHealthError::NoSuchCoin(coin),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct PersisterHealthReq {
+    pub coin: String,
+}
+
+#[derive(Serialize)]
+pub struct PersisterHealthResponse {
+    pub healthy: bool,
+    #[serde(flatten)]
+    pub health: PersisterHealth,
+}
+
+/// Returns the health of the lightning persistence stack of the coin, failed checks are reported
+/// in the response instead of as an RPC error so that readiness probes can inspect them.
+pub async fn get_persister_health(
+    ctx: MmArc,
+    req: PersisterHealthReq,
+) -> PersisterHealthResult<PersisterHealthResponse> {
+    let ln_coin = match lp_coinfind_or_err(&ctx, &req.coin).await? {
+        MmCoinEnum::LightningCoin(c) => c,
+        e => return MmError::err(PersisterHealthError::UnsupportedCoin(e.ticker().to_string())),
+    };
+    let health = ln_coin.persister_health().await;
+
+    Ok(PersisterHealthResponse {
+        healthy: health.is_healthy(),
+        health,
+    })
+}
@@ -4,6 +4,7 @@ mod generate_invoice;
 mod get_channel_details;
 mod get_claimable_balances;
 mod get_payment_details;
+mod get_persister_health;
 mod list_channels;
 mod list_payments_by_filter;
 mod open_channel;
@@ -22,6 +23,7 @@ pub mod channels {
 
 pub mod nodes {
     pub use super::connect_to_node::*;
+    pub use super::get_persister_health::*;
     pub use super::trusted_nodes::*;
 }
 
@@ -50,6 +50,7 @@ impl From<TendermintCoinRpcError> for StakingInfoError {
             | TendermintCoinRpcError::PerformError(e)
             | TendermintCoinRpcError::RpcClientError(e)
             | TendermintCoinRpcError::NotFound(e) => StakingInfoError::Transport(e),
+            e @ TendermintCoinRpcError::ConfirmationTimeout { .. } => StakingInfoError::Transport(e.to_string()),
             TendermintCoinRpcError::Prost(e) | TendermintCoinRpcError::InternalError(e) => StakingInfoError::Internal(e),
             TendermintCoinRpcError::UnexpectedAccountType { .. } => StakingInfoError::Internal(
                 "RPC client got an unexpected error 'TendermintCoinRpcError::UnexpectedAccountType', this isn't normal."
@@ -3,7 +3,9 @@ mod nucleus;
 
 use std::{convert::TryFrom, str::FromStr};
 
+use cosmrs::proto::cosmos::base::v1beta1::Coin as ProtoCoin;
 use cosmrs::{tx::Msg, AccountId, Any, Coin, ErrorReport};
+use derive_more::Display;
 use iris::htlc::{IrisClaimHtlcMsg, IrisCreateHtlcMsg};
 use nucleus::htlc::{NucleusClaimHtlcMsg, NucleusCreateHtlcMsg};
 
@@ -21,6 +23,18 @@ pub(crate) const HTLC_STATE_COMPLETED: i32 = 1;
 /// Defines a refunded state.
 pub(crate) const HTLC_STATE_REFUNDED: i32 = 2;
 
+/// Length of a hex encoded SHA256 `hash_lock`.
+const HASH_LOCK_HEX_LENGTH: usize = 64;
+
+/// Describes why a `hash_lock` is malformed.
+#[derive(Debug, Display, PartialEq)]
+pub(crate) enum InvalidHashLock {
+    #[display(fmt = "hash_lock must be {} hex characters, got {}", HASH_LOCK_HEX_LENGTH, _0)]
+    InvalidLength(usize),
+    #[display(fmt = "hash_lock '{}' is not a valid hex string: {}", hash_lock, reason)]
+    InvalidHex { hash_lock: String, reason: String },
+}
+
 /// Indicates whether this is an IRIS or Nucleus HTLC.
 #[derive(Copy, Clone)]
 pub(crate) enum HtlcType {
@@ -65,6 +79,33 @@ pub enum CustomTendermintMsgType {
     ClaimHtlcAmount,
     /// Claim HTLC for reciever.
     SignClaimHtlc,
+    /// Any other Cosmos SDK message (e.g. a staking delegation or a governance vote), pre-encoded as protobuf.
+    Custom { type_url: String, value: Vec<u8> },
+}
+
+impl CustomTendermintMsgType {
+    /// Returns the pre-encoded message of the `Custom` type, which can be passed to
+    /// `TendermintCoin::send_custom_msg`. HTLC messages are built by the coin itself, so `None` is returned for them.
+    pub fn to_any(&self) -> Option<Any> {
+        match self {
+            CustomTendermintMsgType::Custom { type_url, value } => Some(Any {
+                type_url: type_url.clone(),
+                value: value.clone(),
+            }),
+            CustomTendermintMsgType::SendHtlcAmount
+            | CustomTendermintMsgType::ClaimHtlcAmount
+            | CustomTendermintMsgType::SignClaimHtlc => None,
+        }
+    }
+}
+
+impl From<Any> for CustomTendermintMsgType {
+    fn from(msg: Any) -> Self {
+        CustomTendermintMsgType::Custom {
+            type_url: msg.type_url,
+            value: msg.value,
+        }
+    }
 }
 
 /// Defines the state of an HTLC.
@@ -243,6 +284,21 @@ impl CreateHtlcProto {
             Self::Nucleus(inner) => &inner.hash_lock,
         }
     }
+
+    /// Checks that the inner field `hash_lock` is a hex encoded SHA256 hash.
+    pub(crate) fn validate_hash_lock(&self) -> Result<(), InvalidHashLock> {
+        let hash_lock = self.hash_lock();
+        if hash_lock.len() != HASH_LOCK_HEX_LENGTH {
+            return Err(InvalidHashLock::InvalidLength(hash_lock.len()));
+        }
+
+        hex::decode(hash_lock).map_err(|e| InvalidHashLock::InvalidHex {
+            hash_lock: hash_lock.to_owned(),
+            reason: e.to_string(),
+        })?;
+
+        Ok(())
+    }
 }
 
 /// Generic enum for abstracting multiple types of claim HTLC protos.
@@ -300,4 +356,119 @@ impl QueryHtlcResponse {
             Self::Nucleus(inner) => Some(&inner.htlc.as_ref()?.hash_lock),
         }
     }
+
+    /// Returns the inner field `amount`.
+    pub(crate) fn amount(&self) -> Option<&[ProtoCoin]> {
+        match self {
+            Self::Iris(inner) => Some(&inner.htlc.as_ref()?.amount),
+            Self::Nucleus(inner) => Some(&inner.htlc.as_ref()?.amount),
+        }
+    }
+
+    /// Checks whether the locked amount equals to the `expected` one.
+    /// Returns `false` if there is no HTLC data.
+    pub(crate) fn amount_matches(&self, expected: &[Coin]) -> bool {
+        let Some(amount) = self.amount() else {
+            return false;
+        };
+        amount.len() == expected.len()
+            && amount.iter().zip(expected).all(|(actual, expected)| {
+                actual.denom == expected.denom.to_string() && actual.amount.parse::<u128>() == Ok(expected.amount)
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use iris::htlc_proto::IrisHtlcProto;
+    use nucleus::htlc_proto::NucleusHtlcProto;
+
+    fn test_amount() -> Vec<ProtoCoin> {
+        vec![
+            ProtoCoin {
+                denom: "unim".to_string(),
+                amount: "100000".to_string(),
+            },
+            ProtoCoin {
+                denom: "uiris".to_string(),
+                amount: "25".to_string(),
+            },
+        ]
+    }
+
+    fn create_htlc_proto_with_hash_lock(hash_lock: &str) -> CreateHtlcProto {
+        CreateHtlcProto::Iris(IrisCreateHtlcProto {
+            hash_lock: hash_lock.to_owned(),
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn test_validate_hash_lock() {
+        let valid = "5d02d1e3e3fcd3e0a6ba6bdfe8e6b2e8f1b4ec3aaa8cbc5f3c56ad6a1b8c9e0a";
+        create_htlc_proto_with_hash_lock(valid).validate_hash_lock().unwrap();
+        create_htlc_proto_with_hash_lock(&valid.to_uppercase())
+            .validate_hash_lock()
+            .unwrap();
+
+        let nucleus = CreateHtlcProto::Nucleus(NucleusCreateHtlcProto {
+            hash_lock: valid.to_owned(),
+            ..Default::default()
+        });
+        nucleus.validate_hash_lock().unwrap();
+
+        assert_eq!(
+            create_htlc_proto_with_hash_lock("").validate_hash_lock(),
+            Err(InvalidHashLock::InvalidLength(0))
+        );
+        assert_eq!(
+            create_htlc_proto_with_hash_lock(&valid[..62]).validate_hash_lock(),
+            Err(InvalidHashLock::InvalidLength(62))
+        );
+        assert_eq!(
+            create_htlc_proto_with_hash_lock(&format!("{valid}00")).validate_hash_lock(),
+            Err(InvalidHashLock::InvalidLength(66))
+        );
+
+        let non_hex = format!("zz{}", &valid[2..]);
+        assert!(matches!(
+            create_htlc_proto_with_hash_lock(&non_hex).validate_hash_lock(),
+            Err(InvalidHashLock::InvalidHex { .. })
+        ));
+    }
+
+    #[test]
+    fn test_query_htlc_response_amount() {
+        let iris_response = IrisQueryHtlcResponseProto {
+            htlc: Some(IrisHtlcProto {
+                amount: test_amount(),
+                ..Default::default()
+            }),
+        };
+        let decoded = QueryHtlcResponse::decode(HtlcType::Iris, &iris_response.encode_to_vec()).unwrap();
+        assert_eq!(decoded.amount().unwrap(), test_amount().as_slice());
+
+        let nucleus_response = NucleusQueryHtlcResponseProto {
+            htlc: Some(NucleusHtlcProto {
+                amount: test_amount(),
+                ..Default::default()
+            }),
+        };
+        let decoded = QueryHtlcResponse::decode(HtlcType::Nucleus, &nucleus_response.encode_to_vec()).unwrap();
+        assert_eq!(decoded.amount().unwrap(), test_amount().as_slice());
+
+        let coin = |denom: &str, amount| Coin {
+            denom: denom.parse().unwrap(),
+            amount,
+        };
+        assert!(decoded.amount_matches(&[coin("unim", 100000), coin("uiris", 25)]));
+        assert!(!decoded.amount_matches(&[coin("unim", 100000), coin("uiris", 26)]));
+        assert!(!decoded.amount_matches(&[coin("unim", 100000)]));
+
+        let empty_response = NucleusQueryHtlcResponseProto { htlc: None };
+        let decoded = QueryHtlcResponse::decode(HtlcType::Nucleus, &empty_response.encode_to_vec()).unwrap();
+        assert!(decoded.amount().is_none());
+        assert!(!decoded.amount_matches(&[]));
+    }
 }
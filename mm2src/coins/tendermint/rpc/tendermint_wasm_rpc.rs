@@ -1,6 +1,8 @@
+use common::custom_futures::repeatable::{Ready, Retry};
 use common::{APPLICATION_JSON, PROXY_REQUEST_EXPIRATION_SEC, X_AUTH_PAYLOAD};
 use cosmrs::tendermint::block::Height;
 use derive_more::Display;
+use futures::Future;
 use http::header::{ACCEPT, CONTENT_TYPE};
 use http::uri::InvalidUri;
 use http::{StatusCode, Uri};
@@ -17,7 +19,12 @@ use tendermint_rpc::error::Error as TendermintRpcError;
 pub use tendermint_rpc::query::Query as TendermintQuery;
 use tendermint_rpc::request::SimpleRequest;
 pub use tendermint_rpc::Order;
-use tendermint_rpc::Response;
+use tendermint_rpc::{Method, Request, Response};
+
+/// The number of attempts to perform an idempotent request in case of transient errors.
+const QUERY_ATTEMPTS: usize = 3;
+/// The time in seconds to wait before retrying an idempotent request.
+const QUERY_RETRY_INTERVAL: f64 = 0.5;
 
 #[derive(Debug, Clone)]
 pub struct HttpClient {
@@ -54,6 +61,51 @@ impl From<TendermintRpcError> for PerformError {
     fn from(err: TendermintRpcError) -> Self { PerformError::TendermintRpc(err) }
 }
 
+impl PerformError {
+    /// Whether the request failed due to a transient network or node failure, so it may succeed on retry.
+    /// Definitive errors (e.g. the node rejected the request) are never retried.
+    fn is_transient(&self) -> bool {
+        match self {
+            PerformError::Slurp(SlurpError::Transport { .. } | SlurpError::Timeout { .. }) => true,
+            PerformError::StatusCode { status_code, .. } => {
+                status_code.is_server_error() || *status_code == StatusCode::TOO_MANY_REQUESTS
+            },
+            PerformError::Slurp(_) | PerformError::TendermintRpc(_) | PerformError::Internal(_) => false,
+        }
+    }
+}
+
+/// Whether the request can be safely sent again, i.e. it doesn't broadcast anything.
+fn is_idempotent(method: Method) -> bool {
+    !matches!(
+        method,
+        Method::BroadcastTxAsync | Method::BroadcastTxSync | Method::BroadcastTxCommit | Method::BroadcastEvidence
+    )
+}
+
+/// Sends a request with `send`, retrying on transient errors if the request is idempotent.
+async fn send_with_retry<SendFn, Fut>(method: Method, send: SendFn) -> Result<String, PerformError>
+where
+    SendFn: Fn() -> Fut,
+    Fut: Future<Output = Result<String, PerformError>>,
+{
+    let attempts = if is_idempotent(method) { QUERY_ATTEMPTS } else { 1 };
+    let result = repeatable!(async {
+        match send().await {
+            Err(e) if e.is_transient() => Retry(e),
+            result => Ready(result),
+        }
+    })
+    .repeat_every_secs(QUERY_RETRY_INTERVAL)
+    .attempts(attempts)
+    .await;
+
+    match result {
+        Ok(result) => result,
+        Err(e) => Err(e.into_error()),
+    }
+}
+
 impl HttpClient {
     pub(crate) fn new(url: &str, proxy_sign_keypair: Option<Keypair>) -> Result<Self, HttpClientInitError> {
         Uri::from_str(url)?;
@@ -73,7 +125,14 @@ impl HttpClient {
     where
         R: SimpleRequest,
     {
-        let body_bytes = request.into_json().into_bytes();
+        let method = request.method();
+        let body = request.into_json();
+        let response_str = send_with_retry(method, || self.send(body.as_bytes().to_vec())).await?;
+        Ok(R::Response::from_string(response_str)?.into())
+    }
+
+    /// Sends the request `body` once, returning the response body on success.
+    async fn send(&self, body_bytes: Vec<u8>) -> Result<String, PerformError> {
         let body_size = body_bytes.len();
 
         let mut req = FetchRequest::post(&self.uri).cors().body_bytes(body_bytes);
@@ -98,7 +157,7 @@ impl HttpClient {
                 response: response_str,
             });
         }
-        Ok(R::Response::from_string(response_str)?.into())
+        Ok(response_str)
     }
 
     /// `/abci_info`: get information about the ABCI application.
@@ -141,4 +200,54 @@ mod tests {
         let client = HttpClient::new("http://34.80.202.172:26657", None).unwrap();
         client.abci_info().await.unwrap();
     }
+
+    #[wasm_bindgen_test]
+    async fn test_send_with_retry() {
+        use futures::future::ready;
+        use std::cell::Cell;
+
+        fn transport_error() -> PerformError {
+            PerformError::Slurp(SlurpError::Transport {
+                uri: "http://localhost:26657".to_owned(),
+                error: "Failed to fetch".to_owned(),
+            })
+        }
+
+        // Fails once with a transient error, then succeeds.
+        let attempts = Cell::new(0);
+        let fail_once = || {
+            attempts.set(attempts.get() + 1);
+            if attempts.get() == 1 {
+                ready(Err(transport_error()))
+            } else {
+                ready(Ok("response".to_owned()))
+            }
+        };
+
+        let response = send_with_retry(Method::AbciQuery, fail_once).await.unwrap();
+        assert_eq!(response, "response");
+        assert_eq!(attempts.get(), 2);
+
+        // Broadcasts must not be retried.
+        attempts.set(0);
+        let err = send_with_retry(Method::BroadcastTxCommit, fail_once).await.unwrap_err();
+        assert!(
+            matches!(err, PerformError::Slurp(SlurpError::Transport { .. })),
+            "{err}"
+        );
+        assert_eq!(attempts.get(), 1);
+
+        // Definitive errors must not be retried.
+        attempts.set(0);
+        let bad_request = || {
+            attempts.set(attempts.get() + 1);
+            ready(Err(PerformError::StatusCode {
+                status_code: StatusCode::BAD_REQUEST,
+                response: String::new(),
+            }))
+        };
+        let err = send_with_retry(Method::AbciQuery, bad_request).await.unwrap_err();
+        assert!(matches!(err, PerformError::StatusCode { .. }), "{err}");
+        assert_eq!(attempts.get(), 1);
+    }
 }
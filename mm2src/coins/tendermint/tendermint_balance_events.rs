@@ -1,22 +1,218 @@
 use async_trait::async_trait;
+use common::executor::Timer;
 use common::{http_uri_to_ws_address, log, PROXY_REQUEST_EXPIRATION_SEC};
+use compatible_time::Instant;
 use futures::channel::oneshot;
+use futures::future::{self, Either};
+use futures::{Future, Sink, Stream};
 use futures_util::{SinkExt, StreamExt};
 use jsonrpc_core::{Id as RpcId, Params as RpcParams, Value as RpcValue, Version as RpcVersion};
 use mm2_event_stream::{Broadcaster, Event, EventStreamer, NoDataIn, StreamHandlerInput, StreamerId};
 use mm2_number::BigDecimal;
 use proxy_signature::RawMessage;
+use serde_json::Value as Json;
 use std::collections::{HashMap, HashSet};
+use std::fmt;
+use tokio_tungstenite_wasm::Message;
 
 use super::TendermintCoin;
 use crate::{tendermint::TendermintCommons, utxo::utxo_common::big_decimal_from_sat_unsigned, MarketCoinOps};
 
+/// A lightweight JSON-RPC request sent to keep an idle websocket connection alive.
+const PING_REQUEST: &str = r#"{"jsonrpc":"2.0","method":"health","params":{},"id":1}"#;
+/// The time in seconds to wait before trying to connect again after a failed connection attempt.
+const RECONNECT_INTERVAL: f64 = 5.;
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields, default)]
+struct TendermintBalanceStreamingConfig {
+    /// The time in seconds of inactivity on the websocket connection after which a ping is sent to the node.
+    /// Some nodes and proxies drop idle connections silently.
+    pub ping_interval_seconds: f64,
+    /// The time in seconds to wait for any message from the node after a ping before reconnecting.
+    pub pong_timeout_seconds: f64,
+}
+
+impl Default for TendermintBalanceStreamingConfig {
+    fn default() -> Self {
+        Self {
+            ping_interval_seconds: 30.,
+            pong_timeout_seconds: 10.,
+        }
+    }
+}
+
 pub struct TendermintBalanceEventStreamer {
     coin: TendermintCoin,
+    config: TendermintBalanceStreamingConfig,
 }
 
 impl TendermintBalanceEventStreamer {
-    pub fn new(coin: TendermintCoin) -> Self { Self { coin } }
+    pub fn try_new(config: Option<Json>, coin: TendermintCoin) -> serde_json::Result<Self> {
+        let config = config.map(serde_json::from_value).unwrap_or(Ok(Default::default()))?;
+        Ok(Self { coin, config })
+    }
+}
+
+fn generate_subscription_query(
+    query_filter: String,
+    proxy_sign_keypair: &Option<mm2_p2p::Keypair>,
+    uri: &http::Uri,
+) -> String {
+    let mut params = serde_json::Map::with_capacity(1);
+    params.insert("query".to_owned(), RpcValue::String(query_filter));
+
+    let mut q = json!({
+        "id": RpcId::Num(0),
+        "jsonrpc": Some(RpcVersion::V2),
+        "method": "subscribe".to_owned(),
+        "params": RpcParams::Map(params),
+    });
+
+    const BODY_SIZE: usize = 0;
+    if let Some(proxy_sign_keypair) = proxy_sign_keypair {
+        if let Ok(proxy_sign) = RawMessage::sign(proxy_sign_keypair, uri, BODY_SIZE, PROXY_REQUEST_EXPIRATION_SEC) {
+            q["proxy_sign"] = serde_json::to_value(proxy_sign).expect("This should never happen");
+        }
+    };
+
+    serde_json::to_string(&q).expect("This should never happen")
+}
+
+/// A websocket connection to the node that transparently survives transient drops.
+///
+/// `connect` opens a new connection and returns the subscription queries to send over it.
+/// Once the connection is dropped (or the node doesn't answer a keepalive ping in time),
+/// a new connection is opened and the subscriptions are sent again.
+struct SubscriptionSocket<Connect, Sleep, Ws> {
+    connect: Connect,
+    /// Returns a future that completes after the given number of seconds, it's `Timer::sleep` outside of the tests.
+    sleep: Sleep,
+    config: TendermintBalanceStreamingConfig,
+    ws: Option<Ws>,
+    last_message_at: Instant,
+    /// The time when the unanswered ping was sent, if any.
+    ping_sent_at: Option<Instant>,
+}
+
+impl<Connect, Fut, Sleep, SleepFut, Ws, E> SubscriptionSocket<Connect, Sleep, Ws>
+where
+    Connect: FnMut() -> Fut,
+    Fut: Future<Output = Result<(Ws, Vec<Message>), String>>,
+    Sleep: FnMut(f64) -> SleepFut,
+    SleepFut: Future<Output = ()>,
+    Ws: Stream<Item = Result<Message, E>> + Sink<Message> + Unpin,
+    E: fmt::Display,
+    <Ws as Sink<Message>>::Error: fmt::Display,
+{
+    fn new(connect: Connect, sleep: Sleep, config: TendermintBalanceStreamingConfig) -> Self {
+        SubscriptionSocket {
+            connect,
+            sleep,
+            config,
+            ws: None,
+            last_message_at: Instant::now(),
+            ping_sent_at: None,
+        }
+    }
+
+    /// Connects to the node and sends the subscription queries.
+    async fn connect_and_subscribe(&mut self) -> Option<Ws> {
+        let (mut ws, subscriptions) = match (self.connect)().await {
+            Ok(connection) => connection,
+            Err(e) => {
+                log::error!("{e}");
+                (self.sleep)(RECONNECT_INTERVAL).await;
+                return None;
+            },
+        };
+
+        for subscription in subscriptions {
+            if let Err(e) = ws.send(subscription).await {
+                log::error!("Couldn't subscribe: {e}");
+                (self.sleep)(RECONNECT_INTERVAL).await;
+                return None;
+            }
+        }
+
+        self.last_message_at = Instant::now();
+        self.ping_sent_at = None;
+        Some(ws)
+    }
+
+    /// Returns the next subscription event received from the node, reconnecting and resubscribing if needed.
+    /// The other messages, such as the subscription confirmations and the ping responses, are skipped.
+    async fn next_event(&mut self) -> Json {
+        loop {
+            let ws = match self.ws.as_mut() {
+                Some(ws) => ws,
+                None => {
+                    self.ws = self.connect_and_subscribe().await;
+                    continue;
+                },
+            };
+
+            let timeout = match self.ping_sent_at {
+                Some(ping_sent_at) => self.config.pong_timeout_seconds - ping_sent_at.elapsed().as_secs_f64(),
+                None => self.config.ping_interval_seconds - self.last_message_at.elapsed().as_secs_f64(),
+            };
+            let timer = (self.sleep)(timeout.max(0.));
+            futures::pin_mut!(timer);
+            let message = match future::select(ws.next(), timer).await {
+                Either::Left((message, _)) => Some(message),
+                Either::Right(_) => None,
+            };
+
+            match message {
+                Some(Some(Ok(message))) => {
+                    // Any message from the node is considered as a pong.
+                    self.last_message_at = Instant::now();
+                    self.ping_sent_at = None;
+                    match message {
+                        Message::Text(data) => {
+                            if let Some(event) = parse_subscription_event(&data) {
+                                return event;
+                            }
+                        },
+                        Message::Close(_) => {
+                            log::warn!("The node closed the websocket connection, reconnecting");
+                            self.ws = None;
+                        },
+                        _ => (),
+                    }
+                },
+                Some(Some(Err(e))) => {
+                    log::error!("Server returned an unknown message type - {e}");
+                    self.ws = None;
+                },
+                Some(None) => {
+                    log::warn!("The websocket connection was dropped, reconnecting");
+                    self.ws = None;
+                },
+                None if self.ping_sent_at.is_some() => {
+                    log::warn!(
+                        "No pong received within {}s, reconnecting",
+                        self.config.pong_timeout_seconds
+                    );
+                    self.ws = None;
+                },
+                None => match ws.send(Message::Text(PING_REQUEST.to_owned())).await {
+                    Ok(_) => self.ping_sent_at = Some(Instant::now()),
+                    Err(e) => {
+                        log::error!("Couldn't send a ping: {e}");
+                        self.ws = None;
+                    },
+                },
+            }
+        }
+    }
+}
+
+/// Parses a message received from the node if it's an event of one of the subscriptions.
+/// The events carry the query results in `result.events`, the responses to the requests have no events.
+fn parse_subscription_event(data: &str) -> Option<Json> {
+    let json: Json = serde_json::from_str(data).ok()?;
+    json["result"]["events"].is_object().then_some(json)
 }
 
 #[async_trait]
@@ -29,6 +225,8 @@ impl EventStreamer for TendermintBalanceEventStreamer {
         }
     }
 
+    fn config(&self) -> Option<Json> { serde_json::to_value(&self.config).ok() }
+
     async fn handle(
         self,
         broadcaster: Broadcaster,
@@ -43,148 +241,260 @@ impl EventStreamer for TendermintBalanceEventStreamer {
         let account_id = coin.account_id.to_string();
         let mut current_balances: HashMap<String, BigDecimal> = HashMap::new();
 
-        fn generate_subscription_query(
-            query_filter: String,
-            proxy_sign_keypair: &Option<mm2_p2p::Keypair>,
-            uri: &http::Uri,
-        ) -> String {
-            let mut params = serde_json::Map::with_capacity(1);
-            params.insert("query".to_owned(), RpcValue::String(query_filter));
-
-            let mut q = json!({
-                "id": RpcId::Num(0),
-                "jsonrpc": Some(RpcVersion::V2),
-                "method": "subscribe".to_owned(),
-                "params": RpcParams::Map(params),
-            });
-
-            const BODY_SIZE: usize = 0;
-            if let Some(proxy_sign_keypair) = proxy_sign_keypair {
-                if let Ok(proxy_sign) =
-                    RawMessage::sign(proxy_sign_keypair, uri, BODY_SIZE, PROXY_REQUEST_EXPIRATION_SEC)
-                {
-                    q["proxy_sign"] = serde_json::to_value(proxy_sign).expect("This should never happen");
-                }
-            };
+        let connect = || {
+            let coin = coin.clone();
+            let account_id = account_id.clone();
+            async move {
+                let client = coin.rpc_client().await.map_err(|e| e.to_string())?;
 
-            serde_json::to_string(&q).expect("This should never happen")
-        }
+                // Filter received and spent TX events
+                let subscriptions = vec![
+                    format!("coin_received.receiver = '{}'", account_id),
+                    format!("coin_spent.spender = '{}'", account_id),
+                ]
+                .into_iter()
+                .map(|filter| {
+                    Message::Text(generate_subscription_query(
+                        filter,
+                        client.proxy_sign_keypair(),
+                        &client.uri(),
+                    ))
+                })
+                .collect();
+
+                let socket_address = format!("{}/{}", http_uri_to_ws_address(client.uri()), "websocket");
+                let ws = tokio_tungstenite_wasm::connect(&socket_address)
+                    .await
+                    .map_err(|e| format!("Couldn't connect to '{socket_address}': {e}"))?;
+                Ok((ws, subscriptions))
+            }
+        };
+        let mut socket = SubscriptionSocket::new(connect, Timer::sleep, self.config);
 
         loop {
-            let client = match coin.rpc_client().await {
-                Ok(client) => client,
-                Err(e) => {
-                    log::error!("{e}");
-                    continue;
-                },
-            };
+            // Here, we receive raw data from the socket.
+            // To examine this data, you can use tools like wscat/websocat or visit
+            // https://pastebin.pl/view/499cbf2c for sample data.
+            let json_val = socket.next_event().await;
+            let transfers: Vec<String> =
+                serde_json::from_value(json_val["result"]["events"]["transfer.amount"].clone()).unwrap_or_default();
 
-            let receiver_q = generate_subscription_query(
-                format!("coin_received.receiver = '{}'", account_id),
-                client.proxy_sign_keypair(),
-                &client.uri(),
-            );
-            let receiver_q = tokio_tungstenite_wasm::Message::Text(receiver_q);
-
-            let spender_q = generate_subscription_query(
-                format!("coin_spent.spender = '{}'", account_id),
-                client.proxy_sign_keypair(),
-                &client.uri(),
-            );
-            let spender_q = tokio_tungstenite_wasm::Message::Text(spender_q);
-
-            let socket_address = format!("{}/{}", http_uri_to_ws_address(client.uri()), "websocket");
-
-            let mut wsocket = match tokio_tungstenite_wasm::connect(&socket_address).await {
-                Ok(ws) => ws,
-                Err(e) => {
-                    log::error!("Couldn't connect to '{socket_address}': {e}");
-                    continue;
-                },
-            };
+            let denoms: HashSet<String> = transfers
+                .iter()
+                .map(|t| {
+                    let amount: String = t.chars().take_while(|c| c.is_numeric()).collect();
+                    let denom = &t[amount.len()..];
+                    denom.to_owned()
+                })
+                .collect();
 
-            // Filter received TX events
-            if let Err(e) = wsocket.send(receiver_q.clone()).await {
-                log::error!("{e}");
-                continue;
-            }
+            let mut balance_updates = vec![];
+            for denom in denoms {
+                if let Some((ticker, decimals)) = coin.active_ticker_and_decimals_from_denom(&denom) {
+                    let balance_denom = match coin.account_balance_for_denom(&coin.account_id, denom).await {
+                        Ok(balance_denom) => balance_denom,
+                        Err(e) => {
+                            log::error!("Failed getting balance for '{ticker}'. Error: {e}");
+                            let e = serde_json::to_value(e).expect("Serialization should't fail.");
+                            broadcaster.broadcast(Event::err(streamer_id.clone(), e));
 
-            // Filter spent TX events
-            if let Err(e) = wsocket.send(spender_q.clone()).await {
-                log::error!("{e}");
-                continue;
-            }
+                            continue;
+                        },
+                    };
 
-            while let Some(message) = wsocket.next().await {
-                let msg = match message {
-                    Ok(tokio_tungstenite_wasm::Message::Text(data)) => data.clone(),
-                    Ok(tokio_tungstenite_wasm::Message::Close(_)) => break,
-                    Err(err) => {
-                        log::error!("Server returned an unknown message type - {err}");
-                        break;
-                    },
-                    _ => continue,
-                };
-
-                // Here, we receive raw data from the socket.
-                // To examine this data, you can use tools like wscat/websocat or visit
-                // https://pastebin.pl/view/499cbf2c for sample data.
-                if let Ok(json_val) = serde_json::from_str::<serde_json::Value>(&msg) {
-                    let transfers: Vec<String> =
-                        serde_json::from_value(json_val["result"]["events"]["transfer.amount"].clone())
-                            .unwrap_or_default();
-
-                    let denoms: HashSet<String> = transfers
-                        .iter()
-                        .map(|t| {
-                            let amount: String = t.chars().take_while(|c| c.is_numeric()).collect();
-                            let denom = &t[amount.len()..];
-                            denom.to_owned()
-                        })
-                        .collect();
-
-                    let mut balance_updates = vec![];
-                    for denom in denoms {
-                        if let Some((ticker, decimals)) = coin.active_ticker_and_decimals_from_denom(&denom) {
-                            let balance_denom = match coin.account_balance_for_denom(&coin.account_id, denom).await {
-                                Ok(balance_denom) => balance_denom,
-                                Err(e) => {
-                                    log::error!("Failed getting balance for '{ticker}'. Error: {e}");
-                                    let e = serde_json::to_value(e).expect("Serialization should't fail.");
-                                    broadcaster.broadcast(Event::err(streamer_id.clone(), e));
-
-                                    continue;
-                                },
-                            };
-
-                            let balance_decimal = big_decimal_from_sat_unsigned(balance_denom, decimals);
-
-                            // Only broadcast when balance is changed
-                            let mut broadcast = false;
-                            if let Some(balance) = current_balances.get_mut(&ticker) {
-                                if *balance != balance_decimal {
-                                    *balance = balance_decimal.clone();
-                                    broadcast = true;
-                                }
-                            } else {
-                                current_balances.insert(ticker.clone(), balance_decimal.clone());
-                                broadcast = true;
-                            }
+                    let balance_decimal = big_decimal_from_sat_unsigned(balance_denom, decimals);
 
-                            if broadcast {
-                                balance_updates.push(json!({
-                                    "ticker": ticker,
-                                    "balance": { "spendable": balance_decimal, "unspendable": BigDecimal::default() }
-                                }));
-                            }
+                    // Only broadcast when balance is changed
+                    let mut broadcast = false;
+                    if let Some(balance) = current_balances.get_mut(&ticker) {
+                        if *balance != balance_decimal {
+                            *balance = balance_decimal.clone();
+                            broadcast = true;
                         }
+                    } else {
+                        current_balances.insert(ticker.clone(), balance_decimal.clone());
+                        broadcast = true;
                     }
 
-                    if !balance_updates.is_empty() {
-                        broadcaster.broadcast(Event::new(streamer_id.clone(), json!(balance_updates)));
+                    if broadcast {
+                        balance_updates.push(json!({
+                            "ticker": ticker,
+                            "balance": { "spendable": balance_decimal, "unspendable": BigDecimal::default() }
+                        }));
                     }
                 }
             }
+
+            if !balance_updates.is_empty() {
+                broadcaster.broadcast(Event::new(streamer_id.clone(), json!(balance_updates)));
+            }
+        }
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+    use common::block_on;
+    use common::executor::spawn;
+    use futures::channel::mpsc::{self, UnboundedReceiver, UnboundedSender};
+    use futures::lock::Mutex as AsyncMutex;
+    use std::pin::Pin;
+    use std::sync::{Arc, Mutex};
+    use std::task::{Context, Poll};
+
+    /// The client side of a mock websocket connection, records the messages sent to the node.
+    struct MockWs {
+        connection: usize,
+        from_node: UnboundedReceiver<Result<Message, String>>,
+        to_node: UnboundedSender<Message>,
+        sent: Arc<Mutex<Vec<(usize, Message)>>>,
+    }
+
+    impl Stream for MockWs {
+        type Item = Result<Message, String>;
+
+        fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+            self.from_node.poll_next_unpin(cx)
+        }
+    }
+
+    impl Sink<Message> for MockWs {
+        type Error = String;
+
+        fn poll_ready(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn start_send(self: Pin<&mut Self>, item: Message) -> Result<(), Self::Error> {
+            self.sent.lock().unwrap().push((self.connection, item.clone()));
+            self.to_node.unbounded_send(item).map_err(|e| e.to_string())
+        }
+
+        fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn poll_close(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    fn subscription_event(label: &str) -> String {
+        let event = json!({
+            "jsonrpc": "2.0",
+            "id": 0,
+            "result": { "query": "", "data": {}, "events": { "label": [label] } }
+        });
+        event.to_string()
+    }
+
+    /// A response to a request, e.g. a subscription confirmation or a ping response.
+    fn response(id: u64) -> String { json!({ "jsonrpc": "2.0", "id": id, "result": {} }).to_string() }
+
+    /// A mock node that confirms both subscriptions and then sends an event labeled with the connection number.
+    /// The first connection is dropped right after the event. On the second connection, only the first ping
+    /// is answered and followed by another event. The node never drops the other connections by itself.
+    async fn mock_node(
+        connection: usize,
+        mut from_client: UnboundedReceiver<Message>,
+        to_client: UnboundedSender<Result<Message, String>>,
+    ) {
+        for _ in 0..2 {
+            if from_client.next().await.is_none() {
+                return;
+            }
+            to_client.unbounded_send(Ok(Message::Text(response(0)))).ok();
+        }
+        to_client
+            .unbounded_send(Ok(Message::Text(subscription_event(&connection.to_string()))))
+            .ok();
+
+        match connection {
+            1 => return,
+            2 => {
+                if from_client.next().await.is_some() {
+                    to_client.unbounded_send(Ok(Message::Text(response(1)))).ok();
+                    to_client
+                        .unbounded_send(Ok(Message::Text(subscription_event("after-ping"))))
+                        .ok();
+                }
+            },
+            _ => (),
         }
+        while from_client.next().await.is_some() {}
+    }
+
+    #[test]
+    fn test_subscriptions_restored_after_drop() {
+        let connections = Arc::new(Mutex::new(0));
+        let sent = Arc::new(Mutex::new(Vec::new()));
+
+        let connect = || {
+            let connection = {
+                let mut connections = connections.lock().unwrap();
+                *connections += 1;
+                *connections
+            };
+            let (to_client, from_node) = mpsc::unbounded();
+            let (to_node, from_client) = mpsc::unbounded();
+            spawn(mock_node(connection, from_client, to_client));
+
+            let ws = MockWs {
+                connection,
+                from_node,
+                to_node,
+                sent: sent.clone(),
+            };
+            let queries = vec![Message::Text("sub-1".to_owned()), Message::Text("sub-2".to_owned())];
+            future::ready(Ok((ws, queries)))
+        };
+        // The keepalive timer only elapses when the test sends a tick, so no real time is involved.
+        let (ticks, ticks_rx) = mpsc::unbounded::<()>();
+        let ticks_rx = Arc::new(AsyncMutex::new(ticks_rx));
+        let sleep = move |_: f64| {
+            let ticks_rx = ticks_rx.clone();
+            async move {
+                ticks_rx.lock().await.next().await;
+            }
+        };
+        let mut socket = SubscriptionSocket::new(connect, sleep, TendermintBalanceStreamingConfig::default());
+        let mut next_event_label = || block_on(socket.next_event())["result"]["events"]["label"][0].clone();
+
+        // The first connection is dropped after the first event, the second event comes through the new connection.
+        // The subscription confirmations aren't returned as events.
+        assert_eq!(next_event_label(), "1");
+        assert_eq!(next_event_label(), "2");
+
+        // The connection is idle, so a ping is sent. The ping response isn't returned as an event.
+        ticks.unbounded_send(()).unwrap();
+        assert_eq!(next_event_label(), "after-ping");
+        assert_eq!(*connections.lock().unwrap(), 2);
+
+        // The second ping isn't answered before the pong timeout, so the subscriptions are sent on a new connection.
+        ticks.unbounded_send(()).unwrap();
+        ticks.unbounded_send(()).unwrap();
+        assert_eq!(next_event_label(), "3");
+        assert_eq!(*connections.lock().unwrap(), 3);
+
+        let subscription = |connection: usize, number: usize| (connection, Message::Text(format!("sub-{number}")));
+        let ping = (2, Message::Text(PING_REQUEST.to_owned()));
+        assert_eq!(*sent.lock().unwrap(), vec![
+            subscription(1, 1),
+            subscription(1, 2),
+            subscription(2, 1),
+            subscription(2, 2),
+            ping.clone(),
+            ping,
+            subscription(3, 1),
+            subscription(3, 2),
+        ]);
+    }
+
+    #[test]
+    fn test_parse_subscription_event() {
+        assert!(parse_subscription_event(&subscription_event("1")).is_some());
+        assert!(parse_subscription_event(&response(1)).is_none());
+        assert!(parse_subscription_event("pong").is_none());
     }
 }
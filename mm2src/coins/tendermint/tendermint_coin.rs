@@ -217,6 +217,16 @@ pub struct ActivatedTokenInfo {
     pub ticker: String,
 }
 
+/// Defines how many blocks a broadcasted transaction must be confirmed by
+/// and how long we are allowed to wait for it.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq)]
+pub struct TendermintConfirmationPolicy {
+    /// Number of blocks required, including the one the transaction was included in.
+    pub confirmations: u64,
+    /// As seconds
+    pub timeout_secs: u64,
+}
+
 pub struct TendermintConf {
     avg_blocktime: u8,
     /// Derivation path of the coin.
@@ -224,6 +234,9 @@ pub struct TendermintConf {
     /// where the full `BIP44` address has the following structure:
     /// `m/purpose'/coin_type'/account'/change/address_index`.
     derivation_path: Option<HDPathToCoin>,
+    /// Optional policy for waiting on additional confirmations of the broadcasted transactions
+    /// before reporting them as successful.
+    confirmation_policy: Option<TendermintConfirmationPolicy>,
 }
 
 impl TendermintConf {
@@ -248,9 +261,16 @@ impl TendermintConf {
             kind: TendermintInitErrorKind::ErrorDeserializingDerivationPath(e.to_string()),
         })?;
 
+        let confirmation_policy =
+            json::from_value(conf["wait_for_confirmations"].clone()).map_to_mm(|e| TendermintInitError {
+                ticker: ticker.to_string(),
+                kind: TendermintInitErrorKind::ErrorDeserializingConfirmationPolicy(e.to_string()),
+            })?;
+
         Ok(TendermintConf {
             avg_blocktime,
             derivation_path,
+            confirmation_policy,
         })
     }
 }
@@ -414,6 +434,10 @@ pub struct TendermintCoinImpl {
     pub ctx: MmWeak,
     pub(crate) wallet_type: TendermintWalletConnectionType,
     pub(crate) protocol_info: TendermintProtocolInfo,
+    pub(crate) confirmation_policy: Option<TendermintConfirmationPolicy>,
+    /// Locally cached accounts used for signing, keyed by address.
+    /// The sequence is incremented after each successful broadcast and re-synced from the node on mismatch only.
+    account_sequences: AsyncMutex<HashMap<String, BaseAccount>>,
 }
 
 #[derive(Clone)]
@@ -451,6 +475,8 @@ pub enum TendermintInitErrorKind {
     ErrorDeserializingDerivationPath(String),
     #[display(fmt = "Error deserializing 'path_to_address': {}", _0)]
     ErrorDeserializingPathToAddress(String),
+    #[display(fmt = "Error deserializing 'wait_for_confirmations': {}", _0)]
+    ErrorDeserializingConfirmationPolicy(String),
     PrivKeyPolicyNotAllowed(PrivKeyPolicyNotAllowed),
     RpcError(String),
     #[display(fmt = "avg_blocktime is missing in coin configuration")]
@@ -479,6 +505,17 @@ pub enum TendermintCoinRpcError {
         prefix: String,
     },
     NotFound(String),
+    #[display(
+        fmt = "Tx {} did not reach {} confirmations within {} seconds",
+        tx_hash,
+        confirmations,
+        timeout_secs
+    )]
+    ConfirmationTimeout {
+        tx_hash: String,
+        confirmations: u64,
+        timeout_secs: u64,
+    },
 }
 
 #[derive(Clone, Debug, Display, PartialEq, Serialize)]
@@ -533,6 +570,7 @@ impl From<TendermintCoinRpcError> for BalanceError {
             TendermintCoinRpcError::UnexpectedAccountType { prefix } => {
                 BalanceError::Internal(format!("Account type '{prefix}' is not supported for HTLCs"))
             },
+            e @ TendermintCoinRpcError::ConfirmationTimeout { .. } => BalanceError::Internal(e.to_string()),
         }
     }
 }
@@ -549,6 +587,7 @@ impl From<TendermintCoinRpcError> for ValidatePaymentError {
             TendermintCoinRpcError::UnexpectedAccountType { prefix } => {
                 ValidatePaymentError::InvalidParameter(format!("Account type '{prefix}' is not supported for HTLCs"))
             },
+            e @ TendermintCoinRpcError::ConfirmationTimeout { .. } => ValidatePaymentError::Transport(e.to_string()),
         }
     }
 }
@@ -778,6 +817,8 @@ impl TendermintCoin {
             protocol_info,
             ctx: ctx.weak(),
             wallet_type,
+            confirmation_policy: conf.confirmation_policy,
+            account_sequences: AsyncMutex::new(HashMap::new()),
         })))
     }
 
@@ -991,6 +1032,28 @@ impl TendermintCoin {
         }
     }
 
+    /// Signs and broadcasts a pre-encoded Cosmos SDK message (see `CustomTendermintMsgType::Custom`),
+    /// so that new message types can be sent without adding a dedicated method for each of them.
+    /// Returns the hash and the raw bytes of the broadcasted transaction.
+    pub async fn send_custom_msg(
+        &self,
+        msg: Any,
+        memo: &str,
+        withdraw_fee: Option<WithdrawFee>,
+        timeout: Duration,
+    ) -> Result<(String, Raw), TransactionErr> {
+        let current_block = try_tx_s!(self.latest_block_height().await);
+        let timeout_height = current_block + TIMEOUT_HEIGHT_DELTA;
+
+        let fee = try_tx_s!(
+            self.calculate_fee(msg.clone(), timeout_height, memo, withdraw_fee)
+                .await
+        );
+
+        self.common_send_raw_tx_bytes(msg, fee, timeout_height, memo, timeout)
+            .await
+    }
+
     async fn get_tx_raw(
         &self,
         account_info: &BaseAccount,
@@ -1030,7 +1093,15 @@ impl TendermintCoin {
         timeout_height: u64,
         memo: &str,
     ) -> Result<(String, Raw), TransactionErr> {
-        let mut account_info = try_tx_s!(self.account_info(&self.account_id).await);
+        let address = self.account_id.to_string();
+        // Keep the cache locked until the broadcast is done so that concurrent sends never sign with the same sequence.
+        let mut account_sequences = self.account_sequences.lock().await;
+        let mut account_info = match account_sequences.get(&address) {
+            Some(account_info) => account_info.clone(),
+            None => try_tx_s!(self.account_info(&self.account_id).await),
+        };
+
+        let mut resynced = false;
         loop {
             let tx_raw = try_tx_s!(
                 self.get_tx_raw(&account_info, tx_payload.clone(), fee.clone(), timeout_height, memo,)
@@ -1038,24 +1109,34 @@ impl TendermintCoin {
             );
 
             // Attempt to send the transaction bytes
-            match self.send_raw_tx_bytes(try_tx_s!(&tx_raw.to_bytes())).compat().await {
+            match self.broadcast_tx_bytes(try_tx_s!(tx_raw.to_bytes())).await {
                 Ok(tx_id) => {
+                    account_info.sequence += 1;
+                    account_sequences.insert(address, account_info);
                     return Ok((tx_id, tx_raw));
                 },
-                Err(e) => {
-                    // Handle sequence number mismatch and retry
-                    if e.contains(ACCOUNT_SEQUENCE_ERR) {
-                        account_info.sequence = try_tx_s!(parse_expected_sequence_number(&e));
-                        debug!("Account sequence mismatch, retrying...");
-                        continue;
+                // Re-sync the sequence from the node and retry once.
+                Err(e) if e.contains(ACCOUNT_SEQUENCE_ERR) && !resynced => {
+                    debug!("Account sequence mismatch, re-syncing and retrying...");
+                    resynced = true;
+                    account_info = try_tx_s!(self.account_info(&self.account_id).await);
+                    // The node we query might lag behind the one that rejected the tx.
+                    if let Ok(expected_sequence) = parse_expected_sequence_number(&e) {
+                        account_info.sequence = expected_sequence;
                     }
-
+                },
+                Err(e) => {
+                    account_sequences.remove(&address);
                     return Err(TransactionErr::Plain(ERRL!("Transaction failed: {}", e)));
                 },
             }
         }
     }
 
+    async fn broadcast_tx_bytes(&self, tx_bytes: Vec<u8>) -> Result<String, String> {
+        self.send_raw_tx_bytes(&tx_bytes).compat().await
+    }
+
     async fn send_unsigned_tx_externally(
         &self,
         tx_payload: Any,
@@ -1983,6 +2064,9 @@ impl TendermintCoin {
 
         let create_htlc_msg_proto = CreateHtlcProto::decode(htlc_type, tx.body.messages[0].value.as_slice())
             .map_to_mm(|e| ValidatePaymentError::WrongPaymentTx(e.to_string()))?;
+        create_htlc_msg_proto
+            .validate_hash_lock()
+            .map_to_mm(|e| ValidatePaymentError::WrongPaymentTx(e.to_string()))?;
         let create_htlc_msg = CreateHtlcMsg::try_from(create_htlc_msg_proto)
             .map_to_mm(|e| ValidatePaymentError::WrongPaymentTx(e.to_string()))?;
 
@@ -2030,13 +2114,20 @@ impl TendermintCoin {
             .htlc_state()
             .or_mm_err(|| ValidatePaymentError::InvalidRpcResponse(format!("No HTLC data for {}", htlc_id)))?;
 
-        match htlc_state {
-            HTLC_STATE_OPEN => Ok(()),
-            unexpected_state => MmError::err(ValidatePaymentError::UnexpectedPaymentState(format!(
-                "{}",
-                unexpected_state
-            ))),
+        if htlc_state != HTLC_STATE_OPEN {
+            return MmError::err(ValidatePaymentError::UnexpectedPaymentState(format!("{}", htlc_state)));
         }
+
+        if !htlc_response.amount_matches(&amount) {
+            return MmError::err(ValidatePaymentError::WrongPaymentTx(format!(
+                "Incorrect HTLC {} amount {:?}, expected {:?}",
+                htlc_id,
+                htlc_response.amount(),
+                amount
+            )));
+        }
+
+        Ok(())
     }
 
     fn validate_standard_dex_fee(
@@ -2356,6 +2447,75 @@ impl TendermintCoin {
         }
     }
 
+    /// Returns the height of the block that includes the transaction.
+    /// If tx doesn't exists on chain, then returns `None`.
+    async fn get_tx_height_or_none(&self, hash: String) -> MmResult<Option<u64>, TendermintCoinRpcError> {
+        let request = GetTxRequest { hash };
+        let response = self
+            .rpc_client()
+            .await?
+            .abci_query(
+                Some(ABCI_GET_TX_PATH.to_string()),
+                request.encode_to_vec(),
+                ABCI_REQUEST_HEIGHT,
+                ABCI_REQUEST_PROVE,
+            )
+            .await?;
+
+        let tx = GetTxResponse::decode(response.value.as_slice())?;
+
+        match tx.tx_response {
+            Some(tx_response) => {
+                let height = u64::try_from(tx_response.height).map_to_mm(|_| {
+                    TendermintCoinRpcError::InvalidResponse(format!(
+                        "Tx {} has invalid height {}",
+                        request.hash, tx_response.height
+                    ))
+                })?;
+                Ok(Some(height))
+            },
+            None => Ok(None),
+        }
+    }
+
+    async fn latest_block_height(&self) -> MmResult<u64, TendermintCoinRpcError> {
+        let info = self.rpc_client().await?.abci_info().await?;
+        Ok(info.response.last_block_height.into())
+    }
+
+    /// Polls the chain until the transaction is confirmed by `policy.confirmations` blocks.
+    /// Fails with `TendermintCoinRpcError::ConfirmationTimeout` once `policy.timeout_secs` is exceeded.
+    async fn wait_for_tx_confirmations(
+        &self,
+        tx_hash: String,
+        policy: TendermintConfirmationPolicy,
+    ) -> MmResult<(), TendermintCoinRpcError> {
+        let wait_until = now_sec() + policy.timeout_secs;
+
+        loop {
+            if let Some(tx_height) = self.get_tx_height_or_none(tx_hash.clone()).await? {
+                let latest_height = self.latest_block_height().await?;
+                // The block that includes the tx counts as the first confirmation.
+                if latest_height.saturating_sub(tx_height) + 1 >= policy.confirmations {
+                    return Ok(());
+                }
+            }
+
+            let now = now_sec();
+            if now >= wait_until {
+                return MmError::err(TendermintCoinRpcError::ConfirmationTimeout {
+                    tx_hash,
+                    confirmations: policy.confirmations,
+                    timeout_secs: policy.timeout_secs,
+                });
+            }
+
+            // Check once per block, but never sleep past the deadline.
+            let check_every = (self.avg_blocktime as u64).min(wait_until - now).max(1);
+            Timer::sleep(check_every as f64).await;
+        }
+    }
+
     pub(crate) async fn query_htlc(&self, id: String) -> MmResult<QueryHtlcResponse, TendermintCoinRpcError> {
         let htlc_type = HtlcType::from_str(&self.protocol_info.account_prefix).map_err(|_| {
             TendermintCoinRpcError::UnexpectedAccountType {
@@ -2697,7 +2857,7 @@ impl TendermintCoin {
             internal_id,
             kmd_rewards: None,
             transaction_type: TransactionType::StakingDelegation,
-            memo: Some(req.memo),
+            memo: tx_memo_from_body(&req.memo),
         })
     }
 
@@ -2825,7 +2985,7 @@ impl TendermintCoin {
             internal_id,
             kmd_rewards: None,
             transaction_type: TransactionType::RemoveDelegation,
-            memo: Some(req.memo),
+            memo: tx_memo_from_body(&req.memo),
         })
     }
 
@@ -3022,7 +3182,7 @@ impl TendermintCoin {
             internal_id,
             kmd_rewards: None,
             transaction_type: TransactionType::ClaimDelegationRewards,
-            memo: Some(req.memo),
+            memo: tx_memo_from_body(&req.memo),
         })
     }
 
@@ -3397,7 +3557,7 @@ impl MmCoin for TendermintCoin {
                 } else {
                     TransactionType::StandardTransfer
                 },
-                memo: Some(memo),
+                memo: tx_memo_from_body(&memo),
             })
         };
         Box::new(fut.boxed().compat())
@@ -3638,7 +3798,7 @@ impl MmCoin for TendermintCoin {
 
     fn swap_contract_address(&self) -> Option<BytesJson> { None }
 
-    fn fallback_swap_contract(&self) -> Option<BytesJson> { None }
+    fn fallback_swap_contracts(&self) -> Vec<BytesJson> { Vec::new() }
 
     fn mature_confirmations(&self) -> Option<u32> { None }
 
@@ -3714,7 +3874,18 @@ impl MarketCoinOps for TendermintCoin {
 
     fn send_raw_tx(&self, tx: &str) -> Box<dyn Future<Item = String, Error = String> + Send> {
         let tx_bytes = try_fus!(hex::decode(tx));
-        self.send_raw_tx_bytes(&tx_bytes)
+
+        let Some(policy) = self.confirmation_policy else {
+            return self.send_raw_tx_bytes(&tx_bytes);
+        };
+
+        let coin = self.clone();
+        let fut = async move {
+            let tx_hash = try_s!(coin.send_raw_tx_bytes(&tx_bytes).compat().await);
+            try_s!(coin.wait_for_tx_confirmations(tx_hash.clone(), policy).await);
+            Ok(tx_hash)
+        };
+        Box::new(fut.boxed().compat())
     }
 
     /// Consider using `seq_safe_send_raw_tx_bytes` instead.
@@ -4269,15 +4440,27 @@ pub(crate) fn tendermint_tx_internal_id(bytes: &[u8], token_id: Option<BytesJson
     sha256(&bytes).to_vec().into()
 }
 
+/// Returns the memo of a transaction body to be shown in its history record.
+/// Cosmos txs always have a memo field, an empty one means that no memo was set and is represented as `None`.
+pub(crate) fn tx_memo_from_body(memo: &str) -> Option<String> {
+    if memo.is_empty() {
+        None
+    } else {
+        Some(memo.to_owned())
+    }
+}
+
 #[cfg(test)]
 pub mod tendermint_falsecoin_tests {
     use super::*;
+    use crate::tendermint::htlc::CustomTendermintMsgType;
     use crate::DexFeeBurnDestination;
 
     use common::{block_on, wait_until_ms, DEX_FEE_ADDR_RAW_PUBKEY};
     use cosmrs::proto::cosmos::tx::v1beta1::{GetTxRequest, GetTxResponse};
     use crypto::privkey::key_pair_from_seed;
     use mocktopus::mocking::{MockResult, Mockable};
+    use std::sync::atomic::{AtomicUsize, Ordering};
     use std::{mem::discriminant, num::NonZeroUsize};
 
     pub const IRIS_TESTNET_HTLC_PAIR1_SEED: &str = "iris test seed";
@@ -4386,6 +4569,7 @@ pub mod tendermint_falsecoin_tests {
         let conf = TendermintConf {
             avg_blocktime: AVG_BLOCKTIME,
             derivation_path: None,
+            confirmation_policy: None,
         };
 
         let key_pair = key_pair_from_seed(IRIS_TESTNET_HTLC_PAIR1_SEED).unwrap();
@@ -4504,6 +4688,7 @@ pub mod tendermint_falsecoin_tests {
         let conf = TendermintConf {
             avg_blocktime: AVG_BLOCKTIME,
             derivation_path: None,
+            confirmation_policy: None,
         };
 
         let key_pair = key_pair_from_seed(IRIS_TESTNET_HTLC_PAIR1_SEED).unwrap();
@@ -4558,6 +4743,7 @@ pub mod tendermint_falsecoin_tests {
         let conf = TendermintConf {
             avg_blocktime: AVG_BLOCKTIME,
             derivation_path: None,
+            confirmation_policy: None,
         };
 
         let key_pair = key_pair_from_seed(IRIS_TESTNET_HTLC_PAIR1_SEED).unwrap();
@@ -4629,6 +4815,7 @@ pub mod tendermint_falsecoin_tests {
         let conf = TendermintConf {
             avg_blocktime: AVG_BLOCKTIME,
             derivation_path: None,
+            confirmation_policy: None,
         };
 
         let key_pair = key_pair_from_seed(IRIS_TESTNET_HTLC_PAIR1_SEED).unwrap();
@@ -4819,6 +5006,7 @@ pub mod tendermint_falsecoin_tests {
         let conf = TendermintConf {
             avg_blocktime: AVG_BLOCKTIME,
             derivation_path: None,
+            confirmation_policy: None,
         };
 
         let key_pair = key_pair_from_seed(NUCLEUS_TEST_SEED).unwrap();
@@ -4885,6 +5073,7 @@ pub mod tendermint_falsecoin_tests {
         let conf = TendermintConf {
             avg_blocktime: AVG_BLOCKTIME,
             derivation_path: None,
+            confirmation_policy: None,
         };
 
         let key_pair = key_pair_from_seed(IRIS_TESTNET_HTLC_PAIR1_SEED).unwrap();
@@ -4968,6 +5157,7 @@ pub mod tendermint_falsecoin_tests {
         let conf = TendermintConf {
             avg_blocktime: AVG_BLOCKTIME,
             derivation_path: None,
+            confirmation_policy: None,
         };
 
         let key_pair = key_pair_from_seed(IRIS_TESTNET_HTLC_PAIR1_SEED).unwrap();
@@ -5044,6 +5234,7 @@ pub mod tendermint_falsecoin_tests {
         let conf = TendermintConf {
             avg_blocktime: AVG_BLOCKTIME,
             derivation_path: None,
+            confirmation_policy: None,
         };
 
         let key_pair = key_pair_from_seed(IRIS_TESTNET_HTLC_PAIR1_SEED).unwrap();
@@ -5115,6 +5306,7 @@ pub mod tendermint_falsecoin_tests {
         let conf = TendermintConf {
             avg_blocktime: AVG_BLOCKTIME,
             derivation_path: None,
+            confirmation_policy: None,
         };
 
         let ctx = mm2_core::mm_ctx::MmCtxBuilder::default().into_mm_arc();
@@ -5170,6 +5362,7 @@ pub mod tendermint_falsecoin_tests {
         let conf = TendermintConf {
             avg_blocktime: AVG_BLOCKTIME,
             derivation_path: None,
+            confirmation_policy: None,
         };
 
         let ctx = mm2_core::mm_ctx::MmCtxBuilder::default().into_mm_arc();
@@ -5223,6 +5416,233 @@ pub mod tendermint_falsecoin_tests {
         }
     }
 
+    #[test]
+    fn test_wait_for_tx_confirmations_honors_timeout() {
+        const TX_HEIGHT: u64 = 100;
+        const TIMEOUT_SECS: u64 = 2;
+
+        let nodes = vec![RpcNode::for_test(IRIS_TESTNET_RPC_URL)];
+        let protocol_conf = get_iris_protocol();
+
+        let policy = TendermintConfirmationPolicy {
+            confirmations: 3,
+            timeout_secs: TIMEOUT_SECS,
+        };
+        let conf = TendermintConf {
+            avg_blocktime: AVG_BLOCKTIME,
+            derivation_path: None,
+            confirmation_policy: Some(policy),
+        };
+
+        let ctx = mm2_core::mm_ctx::MmCtxBuilder::default().into_mm_arc();
+        let key_pair = key_pair_from_seed(IRIS_TESTNET_HTLC_PAIR1_SEED).unwrap();
+        let tendermint_pair = TendermintKeyPair::new(key_pair.private().secret, *key_pair.public());
+        let activation_policy =
+            TendermintActivationPolicy::with_private_key_policy(TendermintPrivKeyPolicy::Iguana(tendermint_pair));
+
+        let coin = block_on(TendermintCoin::init(
+            &ctx,
+            "IRIS-TEST".to_string(),
+            conf,
+            protocol_conf,
+            nodes,
+            false,
+            activation_policy,
+            Default::default(),
+        ))
+        .unwrap();
+
+        TendermintCoin::get_tx_height_or_none
+            .mock_safe(|_, _| MockResult::Return(Box::pin(async move { Ok(Some(TX_HEIGHT)) })));
+
+        // The chain is stuck at the tx block, so the required confirmations are never reached.
+        TendermintCoin::latest_block_height.mock_safe(|_| MockResult::Return(Box::pin(async move { Ok(TX_HEIGHT) })));
+
+        let err = block_on(coin.wait_for_tx_confirmations("ABCD".to_string(), policy)).unwrap_err();
+        match err.into_inner() {
+            TendermintCoinRpcError::ConfirmationTimeout {
+                tx_hash,
+                confirmations,
+                timeout_secs,
+            } => {
+                assert_eq!(tx_hash, "ABCD");
+                assert_eq!(confirmations, 3);
+                assert_eq!(timeout_secs, TIMEOUT_SECS);
+            },
+            e => panic!("Expected 'ConfirmationTimeout', found {:?}", e),
+        }
+
+        // Two more blocks on top of the tx block give the required 3 confirmations.
+        TendermintCoin::latest_block_height
+            .mock_safe(|_| MockResult::Return(Box::pin(async move { Ok(TX_HEIGHT + 2) })));
+        block_on(coin.wait_for_tx_confirmations("ABCD".to_string(), policy)).unwrap();
+    }
+
+    #[test]
+    fn test_seq_safe_send_uses_cached_sequence() {
+        const NODE_SEQUENCE: u64 = 7;
+
+        let nodes = vec![RpcNode::for_test(IRIS_TESTNET_RPC_URL)];
+        let protocol_conf = get_iris_protocol();
+
+        let conf = TendermintConf {
+            avg_blocktime: AVG_BLOCKTIME,
+            derivation_path: None,
+            confirmation_policy: None,
+        };
+
+        let ctx = mm2_core::mm_ctx::MmCtxBuilder::default().into_mm_arc();
+        let key_pair = key_pair_from_seed(IRIS_TESTNET_HTLC_PAIR1_SEED).unwrap();
+        let tendermint_pair = TendermintKeyPair::new(key_pair.private().secret, *key_pair.public());
+        let activation_policy =
+            TendermintActivationPolicy::with_private_key_policy(TendermintPrivKeyPolicy::Iguana(tendermint_pair));
+
+        let coin = block_on(TendermintCoin::init(
+            &ctx,
+            "IRIS-TEST".to_string(),
+            conf,
+            protocol_conf,
+            nodes,
+            false,
+            activation_policy,
+            Default::default(),
+        ))
+        .unwrap();
+
+        let account_queries = Arc::new(AtomicUsize::new(0));
+        let account_queries_c = account_queries.clone();
+        TendermintCoin::account_info.mock_safe(move |coin, _| {
+            account_queries_c.fetch_add(1, Ordering::Relaxed);
+            let account = BaseAccount {
+                address: coin.account_id.to_string(),
+                pub_key: None,
+                account_number: 1,
+                sequence: NODE_SEQUENCE,
+            };
+            MockResult::Return(Box::pin(async move { Ok(account) }))
+        });
+
+        let broadcasted_sequences = Arc::new(Mutex::new(Vec::new()));
+        let broadcasted_sequences_c = broadcasted_sequences.clone();
+        TendermintCoin::broadcast_tx_bytes.mock_safe(move |_, tx_bytes| {
+            let tx = cosmrs::Tx::from_bytes(&tx_bytes).unwrap();
+            broadcasted_sequences_c
+                .lock()
+                .unwrap()
+                .push(tx.auth_info.signer_infos[0].sequence);
+            MockResult::Return(Box::pin(async move { Ok("TX_HASH".to_string()) }))
+        });
+
+        let tx_payload = Any {
+            type_url: "/cosmos.bank.v1beta1.MsgSend".to_string(),
+            value: Vec::new(),
+        };
+        let fee = Fee::from_amount_and_gas(
+            Coin {
+                denom: coin.protocol_info.denom.clone(),
+                amount: 200_u64.into(),
+            },
+            GAS_LIMIT_DEFAULT,
+        );
+
+        for _ in 0..2 {
+            block_on(coin.seq_safe_send_raw_tx_bytes(tx_payload.clone(), fee.clone(), 0, "")).unwrap();
+        }
+
+        assert_eq!(account_queries.load(Ordering::Relaxed), 1);
+        assert_eq!(*broadcasted_sequences.lock().unwrap(), vec![
+            NODE_SEQUENCE,
+            NODE_SEQUENCE + 1
+        ]);
+    }
+
+    #[test]
+    fn test_send_custom_delegate_msg() {
+        let nodes = vec![RpcNode::for_test(IRIS_TESTNET_RPC_URL)];
+        let protocol_conf = get_iris_protocol();
+
+        let conf = TendermintConf {
+            avg_blocktime: AVG_BLOCKTIME,
+            derivation_path: None,
+            confirmation_policy: None,
+        };
+
+        let ctx = mm2_core::mm_ctx::MmCtxBuilder::default().into_mm_arc();
+        let key_pair = key_pair_from_seed(IRIS_TESTNET_HTLC_PAIR1_SEED).unwrap();
+        let tendermint_pair = TendermintKeyPair::new(key_pair.private().secret, *key_pair.public());
+        let activation_policy =
+            TendermintActivationPolicy::with_private_key_policy(TendermintPrivKeyPolicy::Iguana(tendermint_pair));
+
+        let coin = block_on(TendermintCoin::init(
+            &ctx,
+            "IRIS-TEST".to_string(),
+            conf,
+            protocol_conf,
+            nodes,
+            false,
+            activation_policy,
+            Default::default(),
+        ))
+        .unwrap();
+
+        TendermintCoin::latest_block_height.mock_safe(|_| MockResult::Return(Box::pin(async { Ok(100) })));
+        TendermintCoin::calculate_fee.mock_safe(|coin, _, _, _, _| {
+            let fee = Fee::from_amount_and_gas(
+                Coin {
+                    denom: coin.protocol_info.denom.clone(),
+                    amount: 200_u64.into(),
+                },
+                GAS_LIMIT_DEFAULT,
+            );
+            MockResult::Return(Box::pin(async move { Ok(fee) }))
+        });
+        TendermintCoin::account_info.mock_safe(|coin, _| {
+            let account = BaseAccount {
+                address: coin.account_id.to_string(),
+                pub_key: None,
+                account_number: 1,
+                sequence: 0,
+            };
+            MockResult::Return(Box::pin(async move { Ok(account) }))
+        });
+
+        let broadcasted_txs = Arc::new(Mutex::new(Vec::new()));
+        let broadcasted_txs_c = broadcasted_txs.clone();
+        TendermintCoin::broadcast_tx_bytes.mock_safe(move |_, tx_bytes| {
+            broadcasted_txs_c.lock().unwrap().push(tx_bytes);
+            MockResult::Return(Box::pin(async move { Ok("TX_HASH".to_string()) }))
+        });
+
+        let delegate_msg = MsgDelegate {
+            delegator_address: coin.account_id.clone(),
+            validator_address: AccountId::from_str("iva1svannhv2zaxefq83m7treg078udfk37lpjufkw").unwrap(),
+            amount: Coin {
+                denom: coin.protocol_info.denom.clone(),
+                amount: 1_000_u64.into(),
+            },
+        }
+        .to_any()
+        .unwrap();
+
+        // The message goes through the generic path only, the coin knows nothing about its type.
+        let msg_type = CustomTendermintMsgType::from(delegate_msg.clone());
+        let msg = msg_type.to_any().unwrap();
+        let (tx_hash, _) = block_on(coin.send_custom_msg(msg, "delegate", None, Duration::from_secs(60))).unwrap();
+        assert_eq!(tx_hash, "TX_HASH");
+
+        let broadcasted_txs = broadcasted_txs.lock().unwrap();
+        assert_eq!(broadcasted_txs.len(), 1);
+        let tx = cosmrs::Tx::from_bytes(&broadcasted_txs[0]).unwrap();
+        assert_eq!(tx.body.messages, vec![delegate_msg]);
+        assert_eq!(tx.body.memo, "delegate");
+        assert_eq!(tx.body.timeout_height.value(), 100 + TIMEOUT_HEIGHT_DELTA);
+        assert_eq!(tx.signatures.len(), 1);
+        assert_eq!(
+            MsgDelegate::from_any(&tx.body.messages[0]).unwrap().amount.amount,
+            1_000
+        );
+    }
+
     #[test]
     fn test_generate_account_id() {
         let key_pair = key_pair_from_seed("best seed").unwrap();
@@ -5261,6 +5681,12 @@ pub mod tendermint_falsecoin_tests {
         assert!(parse_expected_sequence_number("check_tx log: account sequence mismatch, expected").is_err());
     }
 
+    #[test]
+    fn test_tx_memo_from_body() {
+        assert_eq!(tx_memo_from_body(""), None);
+        assert_eq!(tx_memo_from_body("104950"), Some("104950".to_owned()));
+    }
+
     #[test]
     fn test_extract_big_decimal_from_dec_coin() {
         let dec_coin = DecCoin {
@@ -5289,6 +5715,7 @@ pub mod tendermint_falsecoin_tests {
         let conf = TendermintConf {
             avg_blocktime: AVG_BLOCKTIME,
             derivation_path: None,
+            confirmation_policy: None,
         };
 
         let ctx = mm2_core::mm_ctx::MmCtxBuilder::default().into_mm_arc();
@@ -5339,6 +5766,7 @@ pub mod tendermint_falsecoin_tests {
         let conf = TendermintConf {
             avg_blocktime: AVG_BLOCKTIME,
             derivation_path: None,
+            confirmation_policy: None,
         };
 
         let ctx = mm2_core::mm_ctx::MmCtxBuilder::default().into_mm_arc();
@@ -5392,6 +5820,7 @@ pub mod tendermint_falsecoin_tests {
         let conf = TendermintConf {
             avg_blocktime: AVG_BLOCKTIME,
             derivation_path: None,
+            confirmation_policy: None,
         };
 
         let key_pair = key_pair_from_seed(IRIS_TESTNET_HTLC_PAIR1_SEED).unwrap();
@@ -1,8 +1,8 @@
 //! Module containing implementation for Tendermint Tokens. They include native assets + IBC
 
 use super::ibc::IBC_GAS_LIMIT_DEFAULT;
-use super::{create_withdraw_msg_as_any, TendermintCoin, TendermintFeeDetails, GAS_LIMIT_DEFAULT, MIN_TX_SATOSHIS,
-            TIMEOUT_HEIGHT_DELTA, TX_DEFAULT_MEMO};
+use super::{create_withdraw_msg_as_any, tx_memo_from_body, TendermintCoin, TendermintFeeDetails, GAS_LIMIT_DEFAULT,
+            MIN_TX_SATOSHIS, TIMEOUT_HEIGHT_DELTA, TX_DEFAULT_MEMO};
 use crate::coin_errors::{AddressFromPubkeyError, ValidatePaymentResult};
 use crate::hd_wallet::HDAddressSelector;
 use crate::utxo::utxo_common::big_decimal_from_sat;
@@ -525,7 +525,7 @@ impl MmCoin for TendermintToken {
                 } else {
                     TransactionType::TokenTransfer(token.token_id())
                 },
-                memo: Some(memo),
+                memo: tx_memo_from_body(&memo),
             })
         };
         Box::new(fut.boxed().compat())
@@ -603,7 +603,7 @@ impl MmCoin for TendermintToken {
 
     fn swap_contract_address(&self) -> Option<BytesJson> { self.platform_coin.swap_contract_address() }
 
-    fn fallback_swap_contract(&self) -> Option<BytesJson> { self.platform_coin.fallback_swap_contract() }
+    fn fallback_swap_contracts(&self) -> Vec<BytesJson> { self.platform_coin.fallback_swap_contracts() }
 
     fn mature_confirmations(&self) -> Option<u32> { None }
 
@@ -1,4 +1,4 @@
-use super::{rpc::*, AllBalancesResult, TendermintCoin, TendermintCommons, TendermintToken};
+use super::{rpc::*, tx_memo_from_body, AllBalancesResult, TendermintCoin, TendermintCommons, TendermintToken};
 
 use crate::my_tx_history_v2::{CoinWithTxHistoryV2, MyTxHistoryErrorV2, MyTxHistoryTarget, TxHistoryStorage};
 use crate::tendermint::htlc::CustomTendermintMsgType;
@@ -968,7 +968,7 @@ where
                             timestamp,
                             kmd_rewards: None,
                             transaction_type,
-                            memo: Some(deserialized_tx.body.memo.clone()),
+                            memo: tx_memo_from_body(&deserialized_tx.body.memo),
                         };
                         tx_details.push(details.clone());
 
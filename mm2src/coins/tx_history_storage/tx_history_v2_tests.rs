@@ -2,9 +2,11 @@
 //! to print all transactions from `../for_tests/tBCH_tx_history_fixtures.json` ordered.
 
 use crate::my_tx_history_v2::{GetHistoryResult, TxHistoryStorage};
+use crate::tendermint::{tendermint_tx_internal_id, TendermintFeeDetails};
 use crate::tx_history_storage::{FilteringAddresses, GetTxHistoryFilters, TxHistoryStorageBuilder, WalletId};
-use crate::{BytesJson, TransactionDetails};
+use crate::{BytesJson, TransactionData, TransactionDetails, TransactionType, TxFeeDetails};
 use common::PagingOptionsEnum;
+use mm2_number::BigDecimal;
 use mm2_test_helpers::for_tests::mm_ctx_with_custom_db;
 use serde_json as json;
 use std::collections::HashMap;
@@ -29,6 +31,38 @@ fn parse_tx_history_map() -> HashMap<String, TransactionDetails> {
 
 fn get_bch_tx_details(internal_id: &str) -> TransactionDetails { BCH_TX_HISTORY_MAP.get(internal_id).unwrap().clone() }
 
+const IRIS_TEST_ADDRESS: &str = "iaa1e0rx87mdj79zejewuc4jg7ql9ud2286g2us8f2";
+
+/// Returns the history record of an IRIS transfer sent by [`IRIS_TEST_ADDRESS`].
+/// Unlike the UTXO transactions, Tendermint transactions can have a memo.
+fn iris_tx_details(tx_hash: &str, memo: Option<&str>) -> TransactionDetails {
+    let tx_hash_bytes = hex::decode(tx_hash).unwrap();
+    let amount: BigDecimal = "0.1".parse().unwrap();
+    let fee_details = TendermintFeeDetails {
+        coin: "IRIS".to_owned(),
+        amount: "0.0252".parse().unwrap(),
+        uamount: 25_200,
+        gas_limit: 125_000,
+    };
+    TransactionDetails {
+        tx: TransactionData::new_signed(tx_hash_bytes.clone().into(), tx_hash.to_owned()),
+        from: vec![IRIS_TEST_ADDRESS.to_owned()],
+        to: vec!["iaa1erfnkjsmalkwtvj44qnfr2drfzdt4n9ldh0kjv".to_owned()],
+        total_amount: amount.clone(),
+        spent_by_me: &amount + &fee_details.amount,
+        received_by_me: BigDecimal::default(),
+        my_balance_change: BigDecimal::default() - &amount - &fee_details.amount,
+        block_height: 19_015_297,
+        timestamp: 1_700_000_000,
+        fee_details: Some(TxFeeDetails::Tendermint(fee_details)),
+        coin: "IRIS".to_owned(),
+        internal_id: tendermint_tx_internal_id(&tx_hash_bytes, None),
+        kmd_rewards: None,
+        transaction_type: TransactionType::StandardTransfer,
+        memo: memo.map(str::to_owned),
+    }
+}
+
 fn wallet_id_for_test(test_name: &str) -> WalletId { WalletId::new(test_name.to_owned()) }
 
 #[track_caller]
@@ -154,6 +188,66 @@ async fn test_get_transaction_impl() {
     assert!(tx.is_none());
 }
 
+async fn test_tx_memo_round_trip_impl() {
+    let wallet_id = wallet_id_for_test("TEST_TX_MEMO_ROUND_TRIP");
+
+    let ctx = mm_ctx_with_custom_db();
+    let storage = TxHistoryStorageBuilder::new(&ctx).build().unwrap();
+
+    storage.init(&wallet_id).await.unwrap();
+
+    let tx_with_memo = iris_tx_details(
+        "A010FC0AA33FC6D597A8635F9D127C0A7B892FAAC72489F4DADD90048CFE9279",
+        Some("exchange deposit 104950"),
+    );
+    let tx_without_memo = iris_tx_details("54FD77054AE311C484CC2EADD4621428BB23D14A9BAAC128B0E7B47422F86EC8", None);
+    // The records stored by older versions have an empty memo instead of `None`.
+    let legacy_tx_without_memo = iris_tx_details(
+        "B5BB46A7D8DB1F5A1C68DC9A7BD3E8C9E6D6F6B3C9D6A1E8A1C9E7B3D5A6C7E8",
+        Some(""),
+    );
+    storage
+        .add_transactions_to_history(&wallet_id, [
+            tx_with_memo.clone(),
+            tx_without_memo.clone(),
+            legacy_tx_without_memo.clone(),
+        ])
+        .await
+        .unwrap();
+
+    let actual = storage
+        .get_tx_from_history(&wallet_id, &tx_with_memo.internal_id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(actual.memo.as_deref(), Some("exchange deposit 104950"));
+    let actual = storage
+        .get_tx_from_history(&wallet_id, &tx_without_memo.internal_id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(actual.memo, None);
+    let actual = storage
+        .get_tx_from_history(&wallet_id, &legacy_tx_without_memo.internal_id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(actual.memo, None);
+
+    let filters = GetTxHistoryFilters::for_address(IRIS_TEST_ADDRESS.to_string());
+    let paging_options = PagingOptionsEnum::PageNumber(NonZeroUsize::new(1).unwrap());
+    let history = storage
+        .get_history(&wallet_id, filters, paging_options, u32::MAX as usize)
+        .await
+        .unwrap()
+        .transactions;
+    let actual = history
+        .iter()
+        .find(|tx| tx.internal_id == tx_with_memo.internal_id)
+        .unwrap();
+    assert_eq!(actual.memo, tx_with_memo.memo);
+}
+
 async fn test_update_transaction_impl() {
     let wallet_id = wallet_id_for_test("TEST_UPDATE_TRANSACTION");
 
@@ -616,6 +710,9 @@ mod native_tests {
     #[test]
     fn test_update_transaction() { block_on(super::test_update_transaction_impl()); }
 
+    #[test]
+    fn test_tx_memo_round_trip() { block_on(super::test_tx_memo_round_trip_impl()); }
+
     #[test]
     fn test_contains_and_get_unconfirmed_transaction() {
         block_on(super::test_contains_and_get_unconfirmed_transaction_impl());
@@ -686,6 +783,9 @@ mod wasm_tests {
     #[wasm_bindgen_test]
     async fn test_update_transaction() { super::test_update_transaction_impl().await; }
 
+    #[wasm_bindgen_test]
+    async fn test_tx_memo_round_trip() { super::test_tx_memo_round_trip_impl().await; }
+
     #[wasm_bindgen_test]
     async fn test_contains_and_get_unconfirmed_transaction() {
         super::test_contains_and_get_unconfirmed_transaction_impl().await;
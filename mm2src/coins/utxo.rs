@@ -95,6 +95,7 @@ use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, AtomicU64};
 use std::sync::{Arc, Mutex, Weak};
 use utxo_builder::UtxoConfBuilder;
+use utxo_common::fee_rate::TxFeeRate;
 use utxo_common::{big_decimal_from_sat, UtxoTxBuilder};
 use utxo_hd_wallet::UtxoHDWallet;
 use utxo_signer::with_key_pair::sign_tx;
@@ -286,7 +287,7 @@ pub enum ActualFeeRate {
 impl ActualFeeRate {
     fn get_tx_fee(&self, tx_size: u64) -> u64 {
         match self {
-            ActualFeeRate::Dynamic(fee_rate) => (fee_rate * tx_size) / KILO_BYTE,
+            ActualFeeRate::Dynamic(fee_rate) => TxFeeRate::from_sat_per_kb(*fee_rate).fee_for_vsize(tx_size),
             // return fee_rate here as swap spend transaction size is always less than 1 kb
             ActualFeeRate::FixedPerKb(fee_rate) => {
                 let tx_size_kb = if tx_size % KILO_BYTE == 0 {
@@ -302,7 +303,7 @@ impl ActualFeeRate {
     /// Return extra tx fee for the change output as p2pkh
     fn get_tx_fee_for_change(&self, tx_size: u64) -> u64 {
         match self {
-            ActualFeeRate::Dynamic(fee_rate) => (*fee_rate * P2PKH_OUTPUT_LEN) / KILO_BYTE,
+            ActualFeeRate::Dynamic(fee_rate) => TxFeeRate::from_sat_per_kb(*fee_rate).fee_for_vsize(P2PKH_OUTPUT_LEN),
             ActualFeeRate::FixedPerKb(fee_rate) => {
                 // take into account the change output if tx_size_kb(tx with change) > tx_size_kb(tx without change)
                 if tx_size % KILO_BYTE + P2PKH_OUTPUT_LEN > KILO_BYTE {
@@ -4,6 +4,7 @@
 mod electrum_rpc;
 pub use electrum_rpc::*;
 
+use crate::utxo::utxo_common::fee_rate::TxFeeRate;
 use crate::utxo::{sat_from_big_decimal, GetBlockHeaderError, GetTxError, NumConversError, NumConversResult};
 use crate::{big_decimal_from_sat_unsigned, MyAddressError, RpcTransportEventHandlerShared};
 use chain::{OutPoint, Transaction as UtxoTx, TransactionInput, TxHashAlgo};
@@ -900,14 +901,14 @@ impl UtxoRpcClientOps for NativeClient {
         match fee_method {
             EstimateFeeMethod::Standard => Box::new(self.estimate_fee(n_blocks).map(move |fee| {
                 if fee > 0.00001 {
-                    (fee * 10.0_f64.powf(decimals as f64)) as u64
+                    TxFeeRate::from_coin_per_kb(fee, decimals).sat_per_kb()
                 } else {
                     1000
                 }
             })),
             EstimateFeeMethod::SmartFee => Box::new(self.estimate_smart_fee(mode, n_blocks).map(move |res| {
                 if res.fee_rate > 0.00001 {
-                    (res.fee_rate * 10.0_f64.powf(decimals as f64)) as u64
+                    TxFeeRate::from_coin_per_kb(res.fee_rate, decimals).sat_per_kb()
                 } else {
                     1000
                 }
@@ -10,6 +10,7 @@ use super::rpc_responses::*;
 
 use crate::utxo::rpc_clients::ConcurrentRequestMap;
 use crate::utxo::utxo_block_header_storage::BlockHeaderStorage;
+use crate::utxo::utxo_common::fee_rate::TxFeeRate;
 use crate::utxo::{output_script, output_script_p2pk, GetBlockHeaderError, GetConfirmedTxError, GetTxHeightError,
                   ScripthashNotification};
 use crate::RpcTransportEventHandler;
@@ -998,7 +999,7 @@ impl UtxoRpcClientOps for ElectrumClient {
     ) -> UtxoRpcFut<u64> {
         Box::new(self.estimate_fee(mode, n_blocks).map(move |fee| {
             if fee > 0.00001 {
-                (fee * 10.0_f64.powf(decimals as f64)) as u64
+                TxFeeRate::from_coin_per_kb(fee, decimals).sat_per_kb()
             } else {
                 1000
             }
@@ -24,7 +24,7 @@ impl SimplePaymentVerification for ElectrumClient {
         try_spv_proof_until: u64,
     ) -> Result<ConfirmedTransactionInfo, SPVError> {
         if tx.outputs.is_empty() {
-            return Err(SPVError::InvalidVout);
+            return Err(SPVError::MalformedInput("Transaction has no outputs".to_string()));
         }
 
         let tx_hash = tx.hash().reversed();
@@ -52,6 +52,7 @@ impl SimplePaymentVerification for ElectrumClient {
             vout: serialize_list(&tx.outputs).take(),
             index: merkle_branch.pos as u64,
             intermediate_nodes,
+            height,
         };
 
         proof.validate(&validated_header)?;
@@ -70,6 +70,7 @@ use utxo_signer::with_key_pair::{calc_and_sign_sighash, p2sh_spend, signature_ha
                                  SIGHASH_SINGLE};
 use utxo_signer::UtxoSignerOps;
 
+pub mod fee_rate;
 pub mod utxo_tx_history_v2_common;
 
 pub const DEFAULT_FEE_VOUT: usize = 0;
@@ -4528,7 +4529,7 @@ pub fn swap_contract_address() -> Option<BytesJson> { None }
 
 /// Fallback swap contract address is not used by standard UTXO coins.
 #[inline]
-pub fn fallback_swap_contract() -> Option<BytesJson> { None }
+pub fn fallback_swap_contracts() -> Vec<BytesJson> { Vec::new() }
 
 /// Convert satoshis to BigDecimal amount of coin units
 #[inline]
@@ -0,0 +1,113 @@
+//! Conversions between the fee rate units used across UTXO coins.
+//!
+//! Daemons and electrum servers report fee rates in coins per kilobyte, wallets usually display them in satoshis per
+//! virtual byte and LDK expects satoshis per 1000 weight units. [`TxFeeRate`] keeps the rate in satoshis per 1000
+//! virtual bytes, which is the unit used by [`crate::utxo::ActualFeeRate`], and converts to/from the other units.
+
+/// The number of weight units in one virtual byte.
+const WITNESS_SCALE_FACTOR: u64 = 4;
+
+/// A transaction fee rate stored as satoshis per 1000 virtual bytes (equal to bytes for non-segwit transactions).
+#[derive(Clone, Copy, Debug, Default, Eq, Ord, PartialEq, PartialOrd)]
+pub struct TxFeeRate {
+    sat_per_kb: u64,
+}
+
+impl TxFeeRate {
+    /// Creates a fee rate from satoshis per 1000 virtual bytes.
+    #[inline]
+    pub const fn from_sat_per_kb(sat_per_kb: u64) -> Self { TxFeeRate { sat_per_kb } }
+
+    /// Creates a fee rate from satoshis per virtual byte. Saturates at `u64::MAX` sat/kB.
+    #[inline]
+    pub const fn from_sat_per_vb(sat_per_vb: u64) -> Self {
+        TxFeeRate {
+            sat_per_kb: sat_per_vb.saturating_mul(1000),
+        }
+    }
+
+    /// Creates a fee rate from coins per kilobyte as returned by `estimatefee`/`estimatesmartfee` and electrum's
+    /// `blockchain.estimatefee`. The fractional satoshi part is truncated, negative and non-finite rates become zero.
+    #[inline]
+    pub fn from_coin_per_kb(coin_per_kb: f64, decimals: u8) -> Self {
+        TxFeeRate {
+            sat_per_kb: (coin_per_kb * 10.0_f64.powi(decimals as i32)) as u64,
+        }
+    }
+
+    /// Satoshis per 1000 virtual bytes.
+    #[inline]
+    pub const fn sat_per_kb(&self) -> u64 { self.sat_per_kb }
+
+    /// Satoshis per virtual byte, rounded up so that a transaction paying this rate is never below the original one.
+    #[inline]
+    pub const fn sat_per_vb(&self) -> u64 { ceil_div(self.sat_per_kb, 1000) }
+
+    /// Satoshis per 1000 weight units, rounded up so that a transaction paying this rate is never below the original
+    /// one, e.g. 1 sat/vB is exactly 250 sat/kWU while 1001 sat/kB becomes 251 sat/kWU.
+    #[inline]
+    pub const fn sat_per_kwu(&self) -> u64 { ceil_div(self.sat_per_kb, WITNESS_SCALE_FACTOR) }
+
+    /// The fee for a transaction of `vsize` virtual bytes. The fractional satoshi part is truncated,
+    /// matching the fee calculation used for the dynamic fee rates so far.
+    #[inline]
+    pub const fn fee_for_vsize(&self, vsize: u64) -> u64 { self.sat_per_kb.saturating_mul(vsize) / 1000 }
+}
+
+#[inline]
+const fn ceil_div(dividend: u64, divisor: u64) -> u64 {
+    dividend / divisor + if dividend % divisor == 0 { 0 } else { 1 }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_standard_conversions() {
+        let rate = TxFeeRate::from_sat_per_vb(1);
+        assert_eq!(rate.sat_per_kb(), 1000);
+        assert_eq!(rate.sat_per_vb(), 1);
+        assert_eq!(rate.sat_per_kwu(), 250);
+        assert_eq!(rate, TxFeeRate::from_sat_per_kb(1000));
+
+        let rate = TxFeeRate::from_sat_per_kb(25_000);
+        assert_eq!(rate.sat_per_vb(), 25);
+        assert_eq!(rate.sat_per_kwu(), 6250);
+        assert_eq!(rate.fee_for_vsize(226), 5650);
+
+        // 0.00001 BTC/kB is the usual minimum relay fee.
+        let rate = TxFeeRate::from_coin_per_kb(0.00001, 8);
+        assert_eq!(rate.sat_per_kb(), 1000);
+        let rate = TxFeeRate::from_coin_per_kb(0.00012345, 8);
+        assert_eq!(rate.sat_per_kb(), 12345);
+    }
+
+    #[test]
+    fn test_boundary_rounding() {
+        // Lossy conversions round up so the resulting rate is never lower than the original one.
+        let rate = TxFeeRate::from_sat_per_kb(1001);
+        assert_eq!(rate.sat_per_vb(), 2);
+        assert_eq!(rate.sat_per_kwu(), 251);
+
+        let rate = TxFeeRate::from_sat_per_kb(999);
+        assert_eq!(rate.sat_per_vb(), 1);
+        assert_eq!(rate.sat_per_kwu(), 250);
+
+        let rate = TxFeeRate::from_sat_per_kb(1);
+        assert_eq!(rate.sat_per_vb(), 1);
+        assert_eq!(rate.sat_per_kwu(), 1);
+        // While the fee itself is truncated.
+        assert_eq!(rate.fee_for_vsize(999), 0);
+        assert_eq!(rate.fee_for_vsize(1000), 1);
+
+        let rate = TxFeeRate::default();
+        assert_eq!(rate.sat_per_vb(), 0);
+        assert_eq!(rate.sat_per_kwu(), 0);
+        assert_eq!(rate.fee_for_vsize(1000), 0);
+
+        assert_eq!(TxFeeRate::from_sat_per_vb(u64::MAX).sat_per_kb(), u64::MAX);
+        assert_eq!(TxFeeRate::from_coin_per_kb(-1., 8).sat_per_kb(), 0);
+        assert_eq!(TxFeeRate::from_coin_per_kb(f64::NAN, 8).sat_per_kb(), 0);
+    }
+}
@@ -6,13 +6,15 @@ use crate::z_coin::z_coin_errors::ZcoinStorageError;
 use crate::z_coin::ZcoinConsensusParams;
 
 use common::async_blocking;
-use db_common::sqlite::rusqlite::{params, Connection};
+use db_common::sqlite::rusqlite::{params, Connection, Error as SqlError};
 use db_common::sqlite::{query_single_row, run_optimization_pragmas, rusqlite};
 use itertools::Itertools;
 use mm2_core::mm_ctx::MmArc;
+use mm2_core::sql_connection_pool::{PooledConnection, SqliteConnPool};
 use mm2_err_handle::prelude::*;
 use protobuf::Message;
-use std::sync::{Arc, Mutex};
+use std::path::Path;
+use std::sync::Arc;
 use zcash_client_backend::data_api::error::Error as ChainError;
 use zcash_client_backend::proto::compact_formats::CompactBlock;
 use zcash_client_sqlite::error::{SqliteClientError as ZcashClientError, SqliteClientError};
@@ -43,64 +45,72 @@ impl From<ChainError<NoteId>> for ZcoinStorageError {
     fn from(value: ChainError<NoteId>) -> Self { Self::SqliteError(ZcashClientError::from(value)) }
 }
 
+/// The blocks are written by the sync loop only, the extra connection serves the concurrent reads.
+#[cfg(not(test))]
+const BLOCK_DB_POOL_SIZE: usize = 2;
+
+fn open_block_db_connection(path: &Path) -> Result<Connection, SqlError> {
+    let conn = Connection::open(path)?;
+    run_optimization_pragmas(&conn)?;
+    Ok(conn)
+}
+
 impl BlockDbImpl {
     #[cfg(not(test))]
     pub async fn new(ctx: &MmArc, ticker: String) -> ZcoinStorageRes<Self> {
         let path = ctx.global_dir().join(format!("{}_cache.db", ticker));
-        async_blocking(move || {
+        let db = async_blocking(move || {
             mm2_io::fs::create_parents(&path).map_err(|err| ZcoinStorageError::IoError(err.to_string()))?;
-            let conn = Connection::open(path).map_to_mm(|err| ZcoinStorageError::DbError(err.to_string()))?;
-            let conn = Arc::new(Mutex::new(conn));
-            let conn_lock = conn.lock().unwrap();
-            run_optimization_pragmas(&conn_lock).map_to_mm(|err| ZcoinStorageError::DbError(err.to_string()))?;
-            conn_lock
-                .execute(
-                    "CREATE TABLE IF NOT EXISTS compactblocks (
-            height INTEGER PRIMARY KEY,
-            data BLOB NOT NULL
-        )",
-                    [],
-                )
-                .map_to_mm(|err| ZcoinStorageError::DbError(err.to_string()))?;
-            drop(conn_lock);
-
-            Ok(Self { db: conn, ticker })
+            SqliteConnPool::with_opener(BLOCK_DB_POOL_SIZE, move || open_block_db_connection(&path))
+                .mm_err(|err| ZcoinStorageError::DbError(err.to_string()))
         })
-        .await
+        .await?;
+
+        Self::init(db, ticker).await
     }
 
     #[cfg(test)]
-    pub(crate) async fn new(ctx: &MmArc, ticker: String) -> ZcoinStorageRes<Self> {
-        let ctx = ctx.clone();
-        async_blocking(move || {
-            let conn = ctx
-                .sqlite_connection
-                .get()
-                .cloned()
-                .unwrap_or_else(|| Arc::new(Mutex::new(Connection::open_in_memory().unwrap())));
-            let conn_lock = conn.lock().unwrap();
-            run_optimization_pragmas(&conn_lock).map_err(|err| ZcoinStorageError::DbError(err.to_string()))?;
-            conn_lock
-                .execute(
-                    "CREATE TABLE IF NOT EXISTS compactblocks (
+    pub(crate) async fn new(_ctx: &MmArc, ticker: String) -> ZcoinStorageRes<Self> {
+        // Every in-memory connection is a separate database, so the pool must hold a single one.
+        let db = SqliteConnPool::with_opener(1, || open_block_db_connection(Path::new(":memory:")))
+            .mm_err(|err| ZcoinStorageError::DbError(err.to_string()))?;
+
+        Self::init(db, ticker).await
+    }
+
+    async fn init(db: SqliteConnPool, ticker: String) -> ZcoinStorageRes<Self> {
+        db.run(|conn| {
+            conn.execute(
+                "CREATE TABLE IF NOT EXISTS compactblocks (
             height INTEGER PRIMARY KEY,
             data BLOB NOT NULL
         )",
-                    [],
-                )
-                .map_to_mm(|err| ZcoinStorageError::DbError(err.to_string()))?;
-            drop(conn_lock);
-
-            Ok(BlockDbImpl { db: conn, ticker })
+                [],
+            )
         })
         .await
+        .mm_err(|err| ZcoinStorageError::DbError(err.to_string()))?
+        .map_to_mm(|err| ZcoinStorageError::DbError(err.to_string()))?;
+
+        Ok(BlockDbImpl {
+            db: Arc::new(db),
+            ticker,
+        })
+    }
+
+    /// Waits for an idle connection without blocking an executor thread.
+    async fn connection(&self) -> ZcoinStorageRes<PooledConnection> {
+        self.db
+            .acquire()
+            .await
+            .mm_err(|err| ZcoinStorageError::DbError(err.to_string()))
     }
 
     pub(crate) async fn get_latest_block(&self) -> ZcoinStorageRes<u32> {
-        let db = self.db.clone();
+        let conn = self.connection().await?;
         Ok(async_blocking(move || {
             query_single_row(
-                &db.lock().unwrap(),
+                &conn,
                 "SELECT height FROM compactblocks ORDER BY height DESC LIMIT 1",
                 [],
                 |row| row.get(0),
@@ -112,10 +122,9 @@ impl BlockDbImpl {
     }
 
     pub(crate) async fn insert_block(&self, height: u32, cb_bytes: Vec<u8>) -> ZcoinStorageRes<usize> {
-        let db = self.db.clone();
+        let conn = self.connection().await?;
         async_blocking(move || {
-            let db = db.lock().unwrap();
-            let insert = db
+            let insert = conn
                 .prepare("INSERT INTO compactblocks (height, data) VALUES (?, ?)")
                 .map_to_mm(|err| ZcoinStorageError::AddToStorageErr(err.to_string()))?
                 .execute(params![height, cb_bytes])
@@ -127,25 +136,20 @@ impl BlockDbImpl {
     }
 
     pub(crate) async fn rewind_to_height(&self, height: BlockHeight) -> ZcoinStorageRes<usize> {
-        let db = self.db.clone();
+        let conn = self.connection().await?;
         async_blocking(move || {
-            db.lock()
-                .unwrap()
-                .execute("DELETE from compactblocks WHERE height > ?1", [u32::from(height)])
+            conn.execute("DELETE from compactblocks WHERE height > ?1", [u32::from(height)])
                 .map_to_mm(|err| ZcoinStorageError::RemoveFromStorageErr(err.to_string()))
         })
         .await
     }
 
     pub(crate) async fn get_earliest_block(&self) -> ZcoinStorageRes<u32> {
-        let db = self.db.clone();
+        let conn = self.connection().await?;
         Ok(async_blocking(move || {
-            query_single_row(
-                &db.lock().unwrap(),
-                "SELECT MIN(height) from compactblocks",
-                [],
-                |row| row.get::<_, Option<u32>>(0),
-            )
+            query_single_row(&conn, "SELECT MIN(height) from compactblocks", [], |row| {
+                row.get::<_, Option<u32>>(0)
+            })
         })
         .await
         .map_to_mm(|err| ZcoinStorageError::GetFromStorageError(err.to_string()))?
@@ -158,11 +162,10 @@ impl BlockDbImpl {
         from_height: BlockHeight,
         limit: Option<u32>,
     ) -> ZcoinStorageRes<Vec<rusqlite::Result<CompactBlockRow>>> {
-        let db = self.db.clone();
+        let conn = self.connection().await?;
         async_blocking(move || {
             // Fetch the CompactBlocks we need to scan
-            let db = db.lock().unwrap();
-            let mut stmt_blocks = db
+            let mut stmt_blocks = conn
                 .prepare(
                     "SELECT height, data FROM compactblocks WHERE height > ? ORDER BY height ASC \
         LIMIT ?",
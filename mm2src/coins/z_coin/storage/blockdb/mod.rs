@@ -2,8 +2,8 @@
 pub(crate) mod blockdb_sql_storage;
 
 #[cfg(not(target_arch = "wasm32"))]
-use db_common::sqlite::rusqlite::Connection;
-#[cfg(not(target_arch = "wasm32"))] use std::sync::{Arc, Mutex};
+use mm2_core::sql_connection_pool::SqliteConnPool;
+#[cfg(not(target_arch = "wasm32"))] use std::sync::Arc;
 
 #[cfg(target_arch = "wasm32")]
 pub(crate) mod blockdb_idb_storage;
@@ -15,7 +15,7 @@ use blockdb_idb_storage::BlockDbInner;
 #[derive(Clone)]
 pub struct BlockDbImpl {
     #[cfg(not(target_arch = "wasm32"))]
-    pub db: Arc<Mutex<Connection>>,
+    pub db: Arc<SqliteConnPool>,
     #[cfg(target_arch = "wasm32")]
     pub db: SharedDb<BlockDbInner>,
     ticker: String,
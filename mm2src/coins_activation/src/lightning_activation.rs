@@ -11,7 +11,7 @@ use coins::lightning::ln_p2p::{connect_to_ln_nodes_loop, init_peer_manager, ln_n
 use coins::lightning::ln_platform::Platform;
 use coins::lightning::ln_storage::LightningStorage;
 use coins::lightning::ln_utils::{get_open_channels_nodes_addresses, init_channel_manager, init_db, init_keys_manager,
-                                 init_persister, PAYMENT_RETRY_ATTEMPTS};
+                                 init_persister, nodes_addresses_encryption_key, PAYMENT_RETRY_ATTEMPTS};
 use coins::lightning::{InvoicePayer, LightningCoin};
 use coins::utxo::utxo_standard::UtxoStandardCoin;
 use coins::utxo::UtxoCommonOps;
@@ -111,6 +111,10 @@ pub struct LightningActivationParams {
     pub payment_retries: Option<usize>,
     // Node's backup path for channels and other data that requires backup.
     pub backup_path: Option<String>,
+    // Whether to encrypt the file storing the addresses of the channels' counterparties.
+    // The encryption key is derived from the platform coin's private key. Defaults to false.
+    #[serde(default)]
+    pub encrypt_nodes_addresses: bool,
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -127,6 +131,8 @@ pub struct LightningValidatedParams {
     pub payment_retries: Option<usize>,
     // Node's backup path for channels and other data that requires backup.
     pub backup_path: Option<String>,
+    // Whether to encrypt the file storing the addresses of the channels' counterparties.
+    pub encrypt_nodes_addresses: bool,
 }
 
 #[derive(Clone, Debug, Deserialize, Display, Serialize, SerializeErrorType)]
@@ -287,6 +293,7 @@ impl InitL2ActivationOps for LightningCoin {
             node_color,
             payment_retries: activation_params.payment_retries,
             backup_path: activation_params.backup_path,
+            encrypt_nodes_addresses: activation_params.encrypt_nodes_addresses,
         })
     }
 
@@ -360,7 +367,20 @@ async fn start_lightning(
     let node_id = node_id.to_string();
 
     // Initialize Persister
-    let persister = init_persister(ctx, &node_id, conf.ticker.clone(), params.backup_path).await?;
+    let nodes_addresses_key = if params.encrypt_nodes_addresses {
+        Some(nodes_addresses_encryption_key(&platform)?)
+    } else {
+        None
+    };
+    let persister = init_persister(
+        ctx,
+        &node_id,
+        conf.ticker.clone(),
+        params.backup_path,
+        nodes_addresses_key,
+        conf.monitor_fsync_policy,
+    )
+    .await?;
 
     // Initialize the P2PGossipSync. This is used for providing routes to send payments over
     task_handle.update_in_progress_status(LightningInProgressStatus::ReadingNetworkGraphFromFile)?;
@@ -377,7 +397,14 @@ async fn start_lightning(
     ));
 
     // Initialize DB
-    let db = init_db(ctx, &node_id, conf.ticker.clone()).await?;
+    let db = init_db(
+        ctx,
+        &node_id,
+        conf.ticker.clone(),
+        conf.db_wal_mode,
+        conf.db_busy_timeout(),
+    )
+    .await?;
 
     // Initialize the ChannelManager
     task_handle.update_in_progress_status(LightningInProgressStatus::InitializingChannelManager)?;
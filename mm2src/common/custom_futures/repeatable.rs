@@ -23,6 +23,7 @@
 use crate::executor::Timer;
 use crate::number_type_casting::SafeTypeCastingNumbers;
 use crate::{now_ms, wait_until_ms};
+use futures::future::BoxFuture;
 use futures::FutureExt;
 use log::warn;
 use std::fmt;
@@ -95,9 +96,31 @@ macro_rules! try_or_ready_err {
 
 const DEFAULT_REPEAT_EVERY: Duration = Duration::from_secs(1);
 
-pub trait FactoryTrait<F>: Fn() -> F {}
+/// Creates a new future for every attempt.
+pub trait FactoryTrait<F> {
+    /// Creates the future for the given 0-based `attempt` index.
+    fn create(&self, attempt: usize) -> F;
+}
+
+impl<Factory, F> FactoryTrait<F> for Factory
+where
+    Factory: Fn() -> F,
+{
+    #[inline]
+    fn create(&self, _attempt: usize) -> F { self() }
+}
 
-impl<Factory, F> FactoryTrait<F> for Factory where Factory: Fn() -> F {}
+/// A factory that receives the 0-based index of the attempt it creates the future for.
+/// Use [`Repeatable::with_attempt`] to construct a `Repeatable` with such a factory.
+pub struct WithAttempt<Factory>(pub Factory);
+
+impl<Factory, F> FactoryTrait<F> for WithAttempt<Factory>
+where
+    Factory: Fn(usize) -> F,
+{
+    #[inline]
+    fn create(&self, attempt: usize) -> F { (self.0)(attempt) }
+}
 
 pub trait RepeatableTrait<T, E>: Future<Output = Action<T, E>> + Unpin {}
 
@@ -107,6 +130,10 @@ pub(crate) trait InspectErrorTrait<E>: 'static + Fn(&E) + Send {}
 
 impl<F: 'static + Fn(&E) + Send, E> InspectErrorTrait<E> for F {}
 
+pub(crate) trait InspectOkTrait<T>: 'static + Fn(&T) + Send {}
+
+impl<F: 'static + Fn(&T) + Send, T> InspectOkTrait<T> for F {}
+
 #[derive(Clone, Debug, PartialEq)]
 pub enum RepeatError<E> {
     TimeoutExpired {
@@ -119,24 +146,35 @@ pub enum RepeatError<E> {
         /// An error occurred during the last attempt.
         error: E,
     },
+    /// The cancellation future specified with [`Repeatable::cancel_on`] fired before the future succeeded.
+    Cancelled {
+        /// An error occurred during the last attempt.
+        error: E,
+    },
 }
 
 impl<E> RepeatError<E> {
     pub fn error(&self) -> &E {
         match self {
-            RepeatError::TimeoutExpired { error, .. } | RepeatError::AttemptsExceed { error, .. } => error,
+            RepeatError::TimeoutExpired { error, .. }
+            | RepeatError::AttemptsExceed { error, .. }
+            | RepeatError::Cancelled { error } => error,
         }
     }
 
     pub fn into_error(self) -> E {
         match self {
-            RepeatError::TimeoutExpired { error, .. } | RepeatError::AttemptsExceed { error, .. } => error,
+            RepeatError::TimeoutExpired { error, .. }
+            | RepeatError::AttemptsExceed { error, .. }
+            | RepeatError::Cancelled { error } => error,
         }
     }
 
     fn timeout(until_ms: u64, error: E) -> Self { RepeatError::TimeoutExpired { until_ms, error } }
 
     fn attempts(attempts: usize, error: E) -> Self { RepeatError::AttemptsExceed { attempts, error } }
+
+    fn cancelled(error: E) -> Self { RepeatError::Cancelled { error } }
 }
 
 impl<E: fmt::Display> fmt::Display for RepeatError<E> {
@@ -151,6 +189,9 @@ impl<E: fmt::Display> fmt::Display for RepeatError<E> {
             RepeatError::AttemptsExceed { attempts, error } => {
                 write!(f, "Error {error} on retrying the future after {attempts} attempts")
             },
+            RepeatError::Cancelled { error } => {
+                write!(f, "Retrying the future was cancelled. Error: {error}")
+            },
         }
     }
 }
@@ -182,13 +223,20 @@ impl<T, E> RetryOnError<T, E> for Result<T, E> {
 /// The result of `repeatable` or `retry_on_err` macros - the first step at the future configuration.
 pub struct Repeatable<Factory, F, T, E> {
     factory: Factory,
+    /// The 0-based index of the active attempt.
+    attempt: usize,
     /// Currently executable future, i.e. an active attempt.
     exec_fut: F,
     /// A timeout future if we're currently waiting for a timeout.
     timeout_fut: Option<Timer>,
+    /// An error occurred during the last attempt, kept while we're waiting for the timeout.
+    last_error: Option<E>,
+    /// An external cancellation future that stops repeating the future once it's ready.
+    cancel_fut: Option<BoxFuture<'static, ()>>,
     until: RepeatUntil,
     repeat_every: Duration,
     inspect_err: Option<Box<dyn InspectErrorTrait<E>>>,
+    inspect_ok: Option<Box<dyn InspectOkTrait<T>>>,
     _phantom: PhantomData<(F, T, E)>,
 }
 
@@ -199,15 +247,19 @@ where
 {
     #[inline]
     pub fn new(factory: Factory) -> Self {
-        let exec_fut = factory();
+        let exec_fut = factory.create(0);
 
         Repeatable {
             factory,
+            attempt: 0,
             exec_fut,
             timeout_fut: None,
+            last_error: None,
+            cancel_fut: None,
             until: RepeatUntil::default(),
             repeat_every: DEFAULT_REPEAT_EVERY,
             inspect_err: None,
+            inspect_ok: None,
             _phantom: Default::default(),
         }
     }
@@ -222,6 +274,16 @@ where
         self
     }
 
+    /// Specifies an inspect handler that does something with the value of the successful attempt.
+    #[inline]
+    pub fn inspect_ok<Inspect>(mut self, inspect: Inspect) -> Self
+    where
+        Inspect: 'static + Fn(&T) + Send,
+    {
+        self.inspect_ok = Some(Box::new(inspect));
+        self
+    }
+
     #[inline]
     pub fn repeat_every(mut self, repeat_every: Duration) -> Self {
         self.repeat_every = repeat_every;
@@ -280,6 +342,19 @@ where
         self.until_ms(until_ms)
     }
 
+    /// Specifies an external cancellation future. If it's ready before the future succeeds
+    /// and before the deadline or attempts are exhausted, [`RepeatError::Cancelled`] is returned.
+    ///
+    /// The cancellation is checked between the attempts, so an ongoing attempt isn't interrupted.
+    #[inline]
+    pub fn cancel_on<Cancel>(mut self, cancel: Cancel) -> Self
+    where
+        Cancel: Future<Output = ()> + Send + 'static,
+    {
+        self.cancel_fut = Some(cancel.boxed());
+        self
+    }
+
     /// Specifies a timeout in milliseconds before that we may try to repeat the future.
     /// Note this method name should differ from [`FutureTimerExt::timeout_ms`].
     #[inline]
@@ -301,6 +376,17 @@ where
     }
 }
 
+impl<Factory, F, T, E> Repeatable<WithAttempt<Factory>, F, T, E>
+where
+    Factory: Fn(usize) -> F,
+    F: RepeatableTrait<T, E>,
+{
+    /// Same as [`Repeatable::new`], but the `factory` receives the 0-based index of the current attempt,
+    /// so the future can adapt to it, e.g. by widening a timeout on later attempts.
+    #[inline]
+    pub fn with_attempt(factory: Factory) -> Self { Repeatable::new(WithAttempt(factory)) }
+}
+
 impl<Factory, F: Unpin, T, E> Unpin for Repeatable<Factory, F, T, E> {}
 
 impl<Factory, F, T, E> Future for Repeatable<Factory, F, T, E>
@@ -312,12 +398,24 @@ where
 
     fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
         loop {
+            // Whichever comes first: the cancellation or the next attempt after the timeout.
+            if self.timeout_fut.is_some() && poll_cancel(&mut self.cancel_fut, cx).is_ready() {
+                if let Some(error) = self.last_error.take() {
+                    return Poll::Ready(Err(RepeatError::cancelled(error)));
+                }
+            }
             if poll_timeout(&mut self.timeout_fut, cx).is_pending() {
                 return Poll::Pending;
             }
+            self.last_error = None;
 
             match self.exec_fut.poll_unpin(cx) {
-                Poll::Ready(Ready(ready)) => return Poll::Ready(Ok(ready)),
+                Poll::Ready(Ready(ready)) => {
+                    if let Some(ref inspect) = self.inspect_ok {
+                        inspect(&ready);
+                    }
+                    return Poll::Ready(Ok(ready));
+                },
                 Poll::Ready(Retry(error)) => {
                     if let Some(ref inspect) = self.inspect_err {
                         inspect(&error);
@@ -340,8 +438,10 @@ where
                         RepeatUntil::Ready => (),
                     }
 
+                    self.last_error = Some(error);
                     // Create a new future attempt.
-                    self.exec_fut = (self.factory)();
+                    self.attempt += 1;
+                    self.exec_fut = self.factory.create(self.attempt);
                     // Reset the timeout future.
                     self.timeout_fut = Some(Timer::sleep(self.repeat_every.as_secs_f64()));
                 },
@@ -392,6 +492,14 @@ fn poll_timeout(timeout_fut: &mut Option<Timer>, cx: &mut Context<'_>) -> Poll<(
     }
 }
 
+/// Returns `Poll::Ready(())` if the cancellation future is ready, or `Poll::Pending` if there is no such future.
+fn poll_cancel(cancel_fut: &mut Option<BoxFuture<'static, ()>>, cx: &mut Context<'_>) -> Poll<()> {
+    match cancel_fut {
+        Some(cancel) => cancel.poll_unpin(cx),
+        None => Poll::Pending,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -503,6 +611,55 @@ mod tests {
         assert_eq!(inspect_counter_c.load(Ordering::Relaxed), FAILED_ATTEMPTS);
     }
 
+    #[test]
+    fn test_attempts_with_attempt_index() {
+        const ATTEMPT_TO_FINISH: usize = 2;
+
+        async fn an_operation_on_attempt(attempt: usize) -> Action<usize, &'static str> {
+            if attempt == ATTEMPT_TO_FINISH {
+                Ready(attempt)
+            } else {
+                Retry("Not ready")
+            }
+        }
+
+        // Succeeds only on the third attempt.
+        let fut = Repeatable::with_attempt(|attempt| Box::pin(an_operation_on_attempt(attempt)))
+            .repeat_every(Duration::from_millis(100))
+            .attempts(5);
+        assert_eq!(block_on(fut), Ok(ATTEMPT_TO_FINISH));
+
+        let fut = Repeatable::with_attempt(|attempt| Box::pin(an_operation_on_attempt(attempt)))
+            .repeat_every(Duration::from_millis(100))
+            .attempts(ATTEMPT_TO_FINISH);
+        assert_eq!(
+            block_on(fut),
+            Err(RepeatError::AttemptsExceed {
+                attempts: ATTEMPT_TO_FINISH,
+                error: "Not ready"
+            })
+        );
+    }
+
+    #[test]
+    fn test_attempts_inspect_ok() {
+        const ATTEMPTS_TO_FINISH: usize = 3;
+
+        let inspected = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let inspected_c = inspected.clone();
+        let counter = AsyncMutex::new(0);
+
+        let fut = repeatable!(async { an_operation(&counter, ATTEMPTS_TO_FINISH).await.retry_on_err() })
+            .repeat_every(Duration::from_millis(100))
+            .inspect_ok(move |ready| inspected.lock().unwrap().push(*ready))
+            .attempts(ATTEMPTS_TO_FINISH);
+
+        let actual = block_on(fut);
+        assert_eq!(actual, Ok(ATTEMPTS_TO_FINISH));
+        // The handler should be called only once with the final value.
+        assert_eq!(*inspected_c.lock().unwrap(), vec![ATTEMPTS_TO_FINISH]);
+    }
+
     #[test]
     #[cfg(not(target_os = "macos"))] // https://github.com/KomodoPlatform/komodo-defi-framework/issues/1712#issuecomment-2669934159
     fn test_until_success() {
@@ -598,6 +755,32 @@ mod tests {
         );
     }
 
+    #[test]
+    #[cfg(not(target_os = "macos"))] // https://github.com/KomodoPlatform/atomicDEX-API/issues/1712
+    fn test_cancelled_before_deadline() {
+        const ATTEMPTS_TO_FINISH: usize = 100;
+        const HIGHEST_TIMEOUT: Duration = Duration::from_millis(800);
+
+        let counter = AsyncMutex::new(0);
+
+        let fut = repeatable!(async { an_operation(&counter, ATTEMPTS_TO_FINISH).await.retry_on_err() })
+            .repeat_every(Duration::from_millis(100))
+            .cancel_on(Timer::sleep(0.35))
+            .with_timeout_secs(5.);
+
+        let before = Instant::now();
+        let actual = block_on(fut);
+        let took = before.elapsed();
+
+        assert_eq!(actual, Err(RepeatError::Cancelled { error: "Not ready" }));
+        // The cancellation should have stopped retrying long before the deadline.
+        assert!(
+            took <= HIGHEST_TIMEOUT,
+            "Expected at most {HIGHEST_TIMEOUT:?}, but took {took:?}"
+        );
+        assert!(*block_on(counter.lock()) < 10);
+    }
+
     /// `Repeatable` future should be executed the only once
     /// if neither [`Repeatable::until`] nor [`Repeatable::attempts`] are specified.
     ///
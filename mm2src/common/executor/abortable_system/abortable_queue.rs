@@ -1,11 +1,13 @@
-use crate::executor::abortable_system::{AbortableSystem, AbortedError, InnerShared, InnerWeak, SystemInner};
+use crate::executor::abortable_system::{AbortableSystem, AbortedError, InnerShared, InnerWeak, SubsystemHandle,
+                                        SystemInner};
 use crate::executor::spawner::{SpawnAbortable, SpawnFuture};
 use crate::executor::{spawn, AbortSettings, Timer};
-use crate::log::{error, LogOnError};
+use crate::log::{error, warn, LogOnError};
 use futures::channel::oneshot;
 use futures::future::{abortable, select, Either};
 use futures::FutureExt;
 use std::future::Future as Future03;
+use std::panic::Location;
 use std::sync::Arc;
 
 const CAPACITY: usize = 1024;
@@ -20,9 +22,23 @@ type FutureId = usize;
 #[derive(Debug, Default)]
 pub struct AbortableQueue {
     inner: InnerShared<QueueInnerState>,
+    /// Whether to warn about the futures that are still alive when the queue is dropped,
+    /// see [`AbortableQueue::with_alive_futures_warning`].
+    warn_alive_futures_on_drop: bool,
 }
 
 impl AbortableQueue {
+    /// Creates a queue that warns about the futures that are still alive when it's dropped without being
+    /// aborted first. This helps to catch futures that never yield, since these can't actually be aborted.
+    ///
+    /// No warning is logged if the queue has been intentionally aborted with [`AbortableSystem::abort_all`].
+    pub fn with_alive_futures_warning() -> Self {
+        AbortableQueue {
+            inner: Default::default(),
+            warn_alive_futures_on_drop: true,
+        }
+    }
+
     /// Returns `WeakSpawner` that will not prevent the spawned futures from being aborted.
     /// This is the only way to create a `'static` instance pointing to the same `QueueInnerState`
     /// that can be passed into spawned futures, since `AbortableQueue` doesn't implement `Clone`.
@@ -34,17 +50,18 @@ impl AbortableQueue {
 }
 
 impl From<InnerShared<QueueInnerState>> for AbortableQueue {
-    fn from(inner: InnerShared<QueueInnerState>) -> Self { AbortableQueue { inner } }
+    fn from(inner: InnerShared<QueueInnerState>) -> Self {
+        AbortableQueue {
+            inner,
+            warn_alive_futures_on_drop: false,
+        }
+    }
 }
 
 impl AbortableSystem for AbortableQueue {
     type Inner = QueueInnerState;
 
     fn __inner(&self) -> InnerShared<Self::Inner> { self.inner.clone() }
-
-    fn __push_subsystem_abort_tx(&self, subsystem_abort_tx: oneshot::Sender<()>) -> Result<(), AbortedError> {
-        self.inner.lock().insert_handle(subsystem_abort_tx).map(|_| ())
-    }
 }
 
 /// `WeakSpawner` doesn't prevent the spawned futures from being aborted.
@@ -63,6 +80,7 @@ pub struct WeakSpawner {
 impl WeakSpawner {
     /// Spawns the `fut` future with the specified abort `settings`.
     /// The future won't be executed if `AbortableQueue` is dropped.
+    #[track_caller]
     fn spawn_with_settings_impl<F>(&self, fut: F, settings: AbortSettings) -> Result<(), AbortedError>
     where
         F: Future03<Output = ()> + Send + 'static,
@@ -157,7 +175,10 @@ impl SpawnAbortable for WeakSpawner {
 pub enum QueueInnerState {
     Ready {
         abort_handlers: Vec<oneshot::Sender<()>>,
+        /// Where the future of the same index in `abort_handlers` was spawned from, for diagnostics.
+        spawn_locations: Vec<&'static Location<'static>>,
         finished_futures: Vec<FutureId>,
+        subsystems: Vec<SubsystemHandle>,
     },
     Aborted,
 }
@@ -166,26 +187,33 @@ impl Default for QueueInnerState {
     fn default() -> Self {
         QueueInnerState::Ready {
             abort_handlers: Vec::with_capacity(CAPACITY),
+            spawn_locations: Vec::with_capacity(CAPACITY),
             finished_futures: Vec::with_capacity(CAPACITY),
+            subsystems: Vec::new(),
         }
     }
 }
 
 impl QueueInnerState {
     /// Inserts the given future `handle`.
+    #[track_caller]
     fn insert_handle(&mut self, handle: oneshot::Sender<()>) -> Result<FutureId, AbortedError> {
-        let (abort_handlers, finished_futures) = match self {
+        let (abort_handlers, spawn_locations, finished_futures) = match self {
             QueueInnerState::Ready {
                 abort_handlers,
+                spawn_locations,
                 finished_futures,
-            } => (abort_handlers, finished_futures),
+                ..
+            } => (abort_handlers, spawn_locations, finished_futures),
             QueueInnerState::Aborted => return Err(AbortedError),
         };
+        let location = Location::caller();
 
         match finished_futures.pop() {
             // We can reuse the given `finished_id`.
             Some(finished_id) if finished_id < abort_handlers.len() => {
                 abort_handlers[finished_id] = handle;
+                spawn_locations[finished_id] = location;
                 // The freed future ID.
                 return Ok(finished_id);
             },
@@ -198,6 +226,7 @@ impl QueueInnerState {
         }
 
         abort_handlers.push(handle);
+        spawn_locations.push(location);
         // Return the last item ID.
         Ok(abort_handlers.len() - 1)
     }
@@ -207,6 +236,7 @@ impl QueueInnerState {
         if let QueueInnerState::Ready {
             finished_futures,
             abort_handlers,
+            ..
         } = self
         {
             // Only mark this ID as finished if a future existed for it and is canceled. We can get false
@@ -219,6 +249,34 @@ impl QueueInnerState {
         }
     }
 
+    /// Returns a warning listing the futures that are still alive, if any.
+    /// A future is alive if it has neither finished nor been aborted yet.
+    fn alive_futures_warning(&self) -> Option<String> {
+        let (abort_handlers, spawn_locations) = match self {
+            QueueInnerState::Ready {
+                abort_handlers,
+                spawn_locations,
+                ..
+            } => (abort_handlers, spawn_locations),
+            QueueInnerState::Aborted => return None,
+        };
+
+        let alive: Vec<_> = abort_handlers
+            .iter()
+            .zip(spawn_locations)
+            .filter(|(handle, _)| !handle.is_canceled())
+            .map(|(_, location)| location.to_string())
+            .collect();
+        if alive.is_empty() {
+            return None;
+        }
+        Some(format!(
+            "Aborting {} futures that are still alive, spawned at: {}",
+            alive.len(),
+            alive.join(", ")
+        ))
+    }
+
     #[cfg(test)]
     fn count_abort_handlers(&self) -> Result<usize, AbortedError> {
         match self {
@@ -236,6 +294,25 @@ impl QueueInnerState {
     }
 }
 
+impl AbortableQueue {
+    /// Returns the warning to log when the queue is dropped, if it was created with
+    /// [`AbortableQueue::with_alive_futures_warning`] and still has alive futures.
+    fn drop_warning(&self) -> Option<String> {
+        if !self.warn_alive_futures_on_drop {
+            return None;
+        }
+        self.inner.lock().alive_futures_warning()
+    }
+}
+
+impl Drop for AbortableQueue {
+    fn drop(&mut self) {
+        if let Some(warning) = self.drop_warning() {
+            warn!("{warning}");
+        }
+    }
+}
+
 impl SystemInner for QueueInnerState {
     fn abort_all(&mut self) -> Result<(), AbortedError> {
         if matches!(self, QueueInnerState::Aborted) {
@@ -247,6 +324,30 @@ impl SystemInner for QueueInnerState {
     }
 
     fn is_aborted(&self) -> bool { matches!(self, QueueInnerState::Aborted) }
+
+    fn has_live_futures(&self) -> bool {
+        match self {
+            QueueInnerState::Ready { abort_handlers, .. } => abort_handlers.iter().any(|handle| !handle.is_canceled()),
+            QueueInnerState::Aborted => false,
+        }
+    }
+
+    fn push_subsystem(&mut self, subsystem: SubsystemHandle) -> Result<(), AbortedError> {
+        match self {
+            QueueInnerState::Ready { subsystems, .. } => {
+                subsystems.push(subsystem);
+                Ok(())
+            },
+            QueueInnerState::Aborted => Err(AbortedError),
+        }
+    }
+
+    fn subsystems(&self) -> &[SubsystemHandle] {
+        match self {
+            QueueInnerState::Ready { subsystems, .. } => subsystems,
+            QueueInnerState::Aborted => &[],
+        }
+    }
 }
 
 #[cfg(test)]
@@ -342,6 +443,43 @@ mod tests {
         assert!(unsafe { F2_FINISHED });
     }
 
+    #[test]
+    fn test_alive_futures_warning() {
+        let abortable_system = AbortableQueue::default();
+        let spawner = abortable_system.weak_spawner();
+
+        spawner.spawn(async {});
+        let spawn_line = line!() + 1;
+        spawner.spawn(futures::future::pending());
+        block_on(Timer::sleep(0.1));
+
+        // Only the pending future should be listed, the finished one isn't alive anymore.
+        let warning = abortable_system.inner.lock().alive_futures_warning().unwrap();
+        assert!(warning.starts_with("Aborting 1 futures"), "{warning}");
+        assert!(warning.contains(&format!("{}:{spawn_line}:", file!())), "{warning}");
+
+        abortable_system.abort_all().unwrap();
+        assert_eq!(abortable_system.inner.lock().alive_futures_warning(), None);
+    }
+
+    #[test]
+    fn test_alive_futures_warning_on_drop() {
+        // The warning is opt-in.
+        let abortable_system = AbortableQueue::default();
+        abortable_system.weak_spawner().spawn(futures::future::pending());
+        assert_eq!(abortable_system.drop_warning(), None);
+
+        let abortable_system = AbortableQueue::with_alive_futures_warning();
+        assert_eq!(abortable_system.drop_warning(), None);
+        abortable_system.weak_spawner().spawn(futures::future::pending());
+        let warning = abortable_system.drop_warning().unwrap();
+        assert!(warning.starts_with("Aborting 1 futures"), "{warning}");
+
+        // The futures have been intentionally aborted, so there is nothing to warn about on drop.
+        abortable_system.abort_all().unwrap();
+        assert_eq!(abortable_system.drop_warning(), None);
+    }
+
     #[test]
     fn test_spawn_after_abort() {
         static mut F1_FINISHED: bool = false;
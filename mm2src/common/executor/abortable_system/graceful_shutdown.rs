@@ -1,4 +1,4 @@
-use crate::executor::abortable_system::{AbortedError, InnerShared, SystemInner};
+use crate::executor::abortable_system::{AbortedError, InnerShared, SubsystemHandle, SystemInner};
 use crate::executor::AbortableSystem;
 use futures::channel::oneshot;
 use futures::FutureExt;
@@ -33,14 +33,13 @@ impl AbortableSystem for GracefulShutdownRegistry {
     type Inner = ShutdownInnerState;
 
     fn __inner(&self) -> InnerShared<Self::Inner> { self.inner.clone() }
-
-    fn __push_subsystem_abort_tx(&self, subsystem_abort_tx: oneshot::Sender<()>) -> Result<(), AbortedError> {
-        self.inner.lock().insert_handle(subsystem_abort_tx)
-    }
 }
 
 pub enum ShutdownInnerState {
-    Ready { abort_handlers: Vec<oneshot::Sender<()>> },
+    Ready {
+        abort_handlers: Vec<oneshot::Sender<()>>,
+        subsystems: Vec<SubsystemHandle>,
+    },
     Aborted,
 }
 
@@ -48,6 +47,7 @@ impl Default for ShutdownInnerState {
     fn default() -> Self {
         ShutdownInnerState::Ready {
             abort_handlers: Vec::new(),
+            subsystems: Vec::new(),
         }
     }
 }
@@ -55,7 +55,7 @@ impl Default for ShutdownInnerState {
 impl ShutdownInnerState {
     fn insert_handle(&mut self, handle: oneshot::Sender<()>) -> Result<(), AbortedError> {
         match self {
-            ShutdownInnerState::Ready { abort_handlers } => {
+            ShutdownInnerState::Ready { abort_handlers, .. } => {
                 abort_handlers.push(handle);
                 Ok(())
             },
@@ -75,4 +75,31 @@ impl SystemInner for ShutdownInnerState {
     }
 
     fn is_aborted(&self) -> bool { matches!(self, ShutdownInnerState::Aborted) }
+
+    /// The registered graceful shutdown listeners are considered as the live futures.
+    fn has_live_futures(&self) -> bool {
+        match self {
+            ShutdownInnerState::Ready { abort_handlers, .. } => {
+                abort_handlers.iter().any(|handle| !handle.is_canceled())
+            },
+            ShutdownInnerState::Aborted => false,
+        }
+    }
+
+    fn push_subsystem(&mut self, subsystem: SubsystemHandle) -> Result<(), AbortedError> {
+        match self {
+            ShutdownInnerState::Ready { subsystems, .. } => {
+                subsystems.push(subsystem);
+                Ok(())
+            },
+            ShutdownInnerState::Aborted => Err(AbortedError),
+        }
+    }
+
+    fn subsystems(&self) -> &[SubsystemHandle] {
+        match self {
+            ShutdownInnerState::Ready { subsystems, .. } => subsystems,
+            ShutdownInnerState::Aborted => &[],
+        }
+    }
 }
@@ -1,5 +1,5 @@
 use crate::executor::spawn;
-use crate::log::LogOnError;
+use crate::log::{debug, LogOnError};
 use futures::channel::oneshot;
 use parking_lot::Mutex as PaMutex;
 use std::fmt;
@@ -53,28 +53,63 @@ pub trait AbortableSystem: From<InnerShared<Self::Inner>> {
     where
         S: AbortableSystem,
     {
-        let (abort_tx, abort_rx) = oneshot::channel();
-        self.__push_subsystem_abort_tx(abort_tx)?;
+        create_subsystem_impl(self, None, log_abort_propagation)
+    }
 
-        let inner_shared = Arc::new(PaMutex::new(S::Inner::default()));
-        let inner_weak = Arc::downgrade(&inner_shared);
+    /// Same as [`AbortableSystem::create_subsystem`], but tags the subsystem with the given `name`,
+    /// which is then referenced when the abort is propagated to the subsystem.
+    fn create_subsystem_named<S>(&self, name: &str) -> Result<S, AbortedError>
+    where
+        S: AbortableSystem,
+    {
+        create_subsystem_impl(self, Some(name.to_owned()), log_abort_propagation)
+    }
 
-        let abort_fut = async move {
-            // Once the `abort_rx` is invoked, we need to abort its all futures.
-            abort_rx.await.ok();
+    /// Walks the tree of the subsystems created from `Self` and reports whether each of them still has live futures.
+    /// The returned root stands for `Self` and has no name. Subsystems that have been dropped already are skipped.
+    fn status(&self) -> SystemStatus { self.__inner().lock().status(None) }
 
-            if let Some(inner_arc) = inner_weak.upgrade() {
-                inner_arc.lock().abort_all().warn_log();
-            }
-        };
+    fn __inner(&self) -> InnerShared<Self::Inner>;
 
-        spawn(abort_fut);
-        Ok(S::from(inner_shared))
+    fn __push_subsystem(&self, subsystem: SubsystemHandle) -> Result<(), AbortedError> {
+        self.__inner().lock().push_subsystem(subsystem)
     }
+}
 
-    fn __inner(&self) -> InnerShared<Self::Inner>;
+fn log_abort_propagation(msg: String) { debug!("{msg}") }
+
+/// Creates a subsystem of `system`, `log_abort` is called with the log line once the abort is propagated
+/// to a named subsystem.
+fn create_subsystem_impl<Sys, S, L>(system: &Sys, name: Option<String>, log_abort: L) -> Result<S, AbortedError>
+where
+    Sys: AbortableSystem + ?Sized,
+    S: AbortableSystem,
+    L: FnOnce(String) + Send + 'static,
+{
+    let (abort_tx, abort_rx) = oneshot::channel();
+    let inner_shared = Arc::new(PaMutex::new(S::Inner::default()));
+    let inner_weak = Arc::downgrade(&inner_shared);
+
+    system.__push_subsystem(SubsystemHandle {
+        name: name.clone(),
+        _abort_tx: abort_tx,
+        inner: inner_weak.clone(),
+    })?;
+
+    let abort_fut = async move {
+        // Once the `abort_rx` is invoked, we need to abort its all futures.
+        abort_rx.await.ok();
 
-    fn __push_subsystem_abort_tx(&self, subsystem_abort_tx: oneshot::Sender<()>) -> Result<(), AbortedError>;
+        if let Some(inner_arc) = inner_weak.upgrade() {
+            if let Some(name) = name {
+                log_abort(format!("Propagating the abort to the '{name}' subsystem"));
+            }
+            inner_arc.lock().abort_all().warn_log();
+        }
+    };
+
+    spawn(abort_fut);
+    Ok(S::from(inner_shared))
 }
 
 pub trait SystemInner: Default + Send + 'static {
@@ -83,6 +118,63 @@ pub trait SystemInner: Default + Send + 'static {
 
     /// Returns whether the system has already been aborted.
     fn is_aborted(&self) -> bool;
+
+    /// Returns whether any of the futures spawned by the system (not by its subsystems) is still alive.
+    fn has_live_futures(&self) -> bool;
+
+    /// Keeps the `subsystem` handle, so the subsystem is aborted once the system is aborted.
+    fn push_subsystem(&mut self, subsystem: SubsystemHandle) -> Result<(), AbortedError>;
+
+    /// Returns the handles of the subsystems created from the system.
+    fn subsystems(&self) -> &[SubsystemHandle];
+}
+
+/// An object-safe part of [`SystemInner`] used to walk the subsystem tree.
+trait SystemStatusInner: Send {
+    fn status(&self, name: Option<String>) -> SystemStatus;
+}
+
+impl<T: SystemInner> SystemStatusInner for T {
+    fn status(&self, name: Option<String>) -> SystemStatus {
+        let subsystems = self
+            .subsystems()
+            .iter()
+            .filter_map(|subsystem| {
+                let inner = subsystem.inner.upgrade()?;
+                let status = inner.lock().status(subsystem.name.clone());
+                Some(status)
+            })
+            .collect();
+        SystemStatus {
+            name,
+            has_live_futures: self.has_live_futures(),
+            subsystems,
+        }
+    }
+}
+
+/// A handle to a subsystem kept by its parent system.
+/// Dropping the handle aborts the subsystem.
+pub struct SubsystemHandle {
+    name: Option<String>,
+    /// Is never sent, the subsystem is aborted once this sender is dropped.
+    _abort_tx: oneshot::Sender<()>,
+    inner: Weak<PaMutex<dyn SystemStatusInner>>,
+}
+
+impl fmt::Debug for SubsystemHandle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SubsystemHandle").field("name", &self.name).finish()
+    }
+}
+
+/// A snapshot of an abortable system and its subsystems, see [`AbortableSystem::status`].
+#[derive(Debug, PartialEq, Serialize)]
+pub struct SystemStatus {
+    /// The name given to [`AbortableSystem::create_subsystem_named`], if any.
+    pub name: Option<String>,
+    pub has_live_futures: bool,
+    pub subsystems: Vec<SystemStatus>,
 }
 
 #[cfg(test)]
@@ -150,4 +242,68 @@ mod tests {
             assert!(!SUB_FINISHED);
         }
     }
+
+    #[test]
+    fn test_named_subsystems_abort_logs() {
+        // Captures the logs of this test only, rather than installing a global logger.
+        let logs = Arc::new(PaMutex::new(Vec::new()));
+        let create_named = |system: &AbortableQueue, name: &str| -> AbortableQueue {
+            let logs = logs.clone();
+            create_subsystem_impl(system, Some(name.to_owned()), move |msg| logs.lock().push(msg)).unwrap()
+        };
+
+        let super_system = AbortableQueue::default();
+        let _coins = create_named(&super_system, "coins");
+        let _swaps = create_named(&super_system, "swaps");
+
+        drop(super_system);
+        block_on(Timer::sleep(0.1));
+
+        let logs = logs.lock();
+        for name in ["coins", "swaps"] {
+            let expected = format!("Propagating the abort to the '{name}' subsystem");
+            assert!(logs.contains(&expected), "{logs:?}");
+        }
+    }
+
+    #[test]
+    fn test_subsystems_status() {
+        fn status(name: Option<&str>, has_live_futures: bool, subsystems: Vec<SystemStatus>) -> SystemStatus {
+            SystemStatus {
+                name: name.map(str::to_owned),
+                has_live_futures,
+                subsystems,
+            }
+        }
+
+        let root = AbortableQueue::default();
+        let coins: AbortableQueue = root.create_subsystem_named("coins").unwrap();
+        let swaps: AbortableQueue = root.create_subsystem_named("swaps").unwrap();
+        let swap: AbortableQueue = swaps.create_subsystem().unwrap();
+        coins.weak_spawner().spawn(futures::future::pending());
+        swap.weak_spawner().spawn(futures::future::pending());
+
+        let expected = status(None, false, vec![
+            status(Some("coins"), true, vec![]),
+            status(Some("swaps"), false, vec![status(None, true, vec![])]),
+        ]);
+        assert_eq!(root.status(), expected);
+
+        // The aborted subsystem has no live futures anymore, but it's still a part of the tree.
+        swap.abort_all().unwrap();
+        let expected = status(None, false, vec![
+            status(Some("coins"), true, vec![]),
+            status(Some("swaps"), false, vec![status(None, false, vec![])]),
+        ]);
+        assert_eq!(root.status(), expected);
+
+        // The dropped subsystem isn't a part of the tree anymore.
+        drop(coins);
+        let expected = status(None, false, vec![status(Some("swaps"), false, vec![status(
+            None,
+            false,
+            vec![],
+        )])]);
+        assert_eq!(root.status(), expected);
+    }
 }
@@ -1,6 +1,5 @@
-use crate::executor::abortable_system::{AbortableSystem, AbortedError, InnerShared, SystemInner};
+use crate::executor::abortable_system::{AbortableSystem, AbortedError, InnerShared, SubsystemHandle, SystemInner};
 use crate::executor::{spawn_abortable, AbortOnDropHandle};
-use futures::channel::oneshot;
 use futures::future::Future as Future03;
 use parking_lot::{Mutex as PaMutex, MutexGuard as PaMutexGuard};
 use std::borrow::Borrow;
@@ -36,10 +35,6 @@ impl<FutureId: FutureIdTrait> AbortableSystem for AbortableSimpleMap<FutureId> {
     type Inner = SimpleMapInnerState<FutureId>;
 
     fn __inner(&self) -> InnerShared<Self::Inner> { self.inner.clone() }
-
-    fn __push_subsystem_abort_tx(&self, subsystem_abort_tx: oneshot::Sender<()>) -> Result<(), AbortedError> {
-        self.inner.lock().insert_subsystem(subsystem_abort_tx)
-    }
 }
 
 impl<FutureId: FutureIdTrait> From<InnerShared<SimpleMapInnerState<FutureId>>> for AbortableSimpleMap<FutureId> {
@@ -49,7 +44,7 @@ impl<FutureId: FutureIdTrait> From<InnerShared<SimpleMapInnerState<FutureId>>> f
 pub enum SimpleMapInnerState<FutureId: FutureIdTrait> {
     Ready {
         futures: HashMap<FutureId, AbortOnDropHandle>,
-        subsystems: Vec<oneshot::Sender<()>>,
+        subsystems: Vec<SubsystemHandle>,
     },
     Aborted,
 }
@@ -83,6 +78,32 @@ impl<FutureId: FutureIdTrait> SystemInner for SimpleMapInnerState<FutureId> {
     }
 
     fn is_aborted(&self) -> bool { matches!(self, SimpleMapInnerState::Aborted) }
+
+    /// The map doesn't remove the futures once they complete, so a spawned future is considered alive
+    /// until it's aborted.
+    fn has_live_futures(&self) -> bool {
+        match self {
+            SimpleMapInnerState::Ready { futures, .. } => !futures.is_empty(),
+            SimpleMapInnerState::Aborted => false,
+        }
+    }
+
+    fn push_subsystem(&mut self, subsystem: SubsystemHandle) -> Result<(), AbortedError> {
+        match self {
+            SimpleMapInnerState::Ready { subsystems, .. } => {
+                subsystems.push(subsystem);
+                Ok(())
+            },
+            SimpleMapInnerState::Aborted => Err(AbortedError),
+        }
+    }
+
+    fn subsystems(&self) -> &[SubsystemHandle] {
+        match self {
+            SimpleMapInnerState::Ready { subsystems, .. } => subsystems,
+            SimpleMapInnerState::Aborted => &[],
+        }
+    }
 }
 
 impl<FutureId: FutureIdTrait> SimpleMapInnerState<FutureId> {
@@ -125,16 +146,6 @@ impl<FutureId: FutureIdTrait> SimpleMapInnerState<FutureId> {
     {
         Ok(self.futures_mut()?.remove(future_id).is_some())
     }
-
-    fn insert_subsystem(&mut self, subsystem_abort_tx: oneshot::Sender<()>) -> Result<(), AbortedError> {
-        match self {
-            SimpleMapInnerState::Ready { subsystems, .. } => {
-                subsystems.push(subsystem_abort_tx);
-                Ok(())
-            },
-            SimpleMapInnerState::Aborted => Err(AbortedError),
-        }
-    }
 }
 
 #[cfg(test)]
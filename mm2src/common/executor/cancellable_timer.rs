@@ -0,0 +1,112 @@
+use super::Timer;
+use futures::task::{AtomicWaker, Context, Poll};
+use futures::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// Tells whether a [`CancellableTimer`] has elapsed or was interrupted by [`TimerCancelHandle::cancel`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TimerOutcome {
+    Elapsed,
+    Cancelled,
+}
+
+#[derive(Default)]
+struct CancelState {
+    cancelled: AtomicBool,
+    waker: AtomicWaker,
+}
+
+/// A [`Timer`] that can be woken early from another task, e.g. to interrupt a backoff on reconnect.
+#[must_use = "futures do nothing unless polled"]
+pub struct CancellableTimer {
+    timer: Timer,
+    state: Arc<CancelState>,
+}
+
+impl CancellableTimer {
+    pub fn sleep(seconds: f64) -> CancellableTimer {
+        CancellableTimer {
+            timer: Timer::sleep(seconds),
+            state: Arc::new(CancelState::default()),
+        }
+    }
+
+    pub fn sleep_ms(ms: u64) -> CancellableTimer {
+        CancellableTimer {
+            timer: Timer::sleep_ms(ms),
+            state: Arc::new(CancelState::default()),
+        }
+    }
+
+    /// Returns a handle that can be moved to another task to cancel this timer.
+    pub fn cancel_handle(&self) -> TimerCancelHandle {
+        TimerCancelHandle {
+            state: self.state.clone(),
+        }
+    }
+}
+
+impl Future for CancellableTimer {
+    type Output = TimerOutcome;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        // Check the flag after registering the waker too, so a concurrent `cancel` isn't missed.
+        if self.state.cancelled.load(Ordering::Acquire) {
+            return Poll::Ready(TimerOutcome::Cancelled);
+        }
+        self.state.waker.register(cx.waker());
+        if self.state.cancelled.load(Ordering::Acquire) {
+            return Poll::Ready(TimerOutcome::Cancelled);
+        }
+
+        Pin::new(&mut self.timer).poll(cx).map(|_| TimerOutcome::Elapsed)
+    }
+}
+
+#[derive(Clone)]
+pub struct TimerCancelHandle {
+    state: Arc<CancelState>,
+}
+
+impl TimerCancelHandle {
+    /// Wakes the timer immediately with [`TimerOutcome::Cancelled`].
+    /// Has no effect if the timer has already elapsed.
+    pub fn cancel(&self) {
+        self.state.cancelled.store(true, Ordering::Release);
+        self.state.waker.wake();
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+    use crate::executor::spawn;
+    use crate::{block_on, now_float};
+
+    #[test]
+    fn test_cancel_from_another_task() {
+        let timer = CancellableTimer::sleep(10.);
+        let handle = timer.cancel_handle();
+
+        let started = now_float();
+        spawn(async move {
+            Timer::sleep(0.1).await;
+            handle.cancel();
+        });
+
+        assert_eq!(block_on(timer), TimerOutcome::Cancelled);
+        let elapsed = now_float() - started;
+        assert!(elapsed >= 0.1 && elapsed < 1., "{}", elapsed);
+    }
+
+    #[test]
+    fn test_elapsed_without_cancel() {
+        let timer = CancellableTimer::sleep_ms(50);
+        // Dropping the handle must not wake the timer.
+        drop(timer.cancel_handle());
+
+        assert_eq!(block_on(timer), TimerOutcome::Elapsed);
+    }
+}
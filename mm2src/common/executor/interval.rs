@@ -0,0 +1,110 @@
+use super::Timer;
+use compatible_time::Instant;
+use std::time::Duration;
+
+impl Timer {
+    /// Creates an [`Interval`] that ticks every `period`, starting one `period` from now.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `period` is zero.
+    pub fn interval(period: Duration) -> Interval {
+        assert!(!period.is_zero(), "Interval period must be non-zero");
+        Interval {
+            period,
+            next_tick: Instant::now() + period,
+        }
+    }
+}
+
+/// A periodic timer for tasks like expiry sweeps or health checks.
+///
+/// Ticks are scheduled at fixed multiples of `period` from the start, so the time spent
+/// by the task between ticks doesn't accumulate as drift.
+/// If the task falls behind by more than a `period`, the missed ticks are skipped rather than fired in a burst.
+pub struct Interval {
+    period: Duration,
+    next_tick: Instant,
+}
+
+impl Interval {
+    /// Waits until the next tick.
+    pub async fn tick(&mut self) {
+        let now = Instant::now();
+        if self.next_tick > now {
+            Timer::sleep((self.next_tick - now).as_secs_f64()).await;
+        }
+
+        self.schedule_next_tick(Instant::now());
+    }
+
+    /// Schedules the tick that follows the current one, given the time `now` when the current one fired.
+    /// The ticks that are already due at `now` are skipped.
+    fn schedule_next_tick(&mut self, now: Instant) {
+        self.next_tick += self.period;
+        if self.next_tick <= now {
+            let missed = (now - self.next_tick).as_nanos() / self.period.as_nanos() + 1;
+            self.next_tick += self.period * missed as u32;
+        }
+    }
+
+    #[inline(always)]
+    pub fn period(&self) -> Duration { self.period }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+    use crate::{block_on, now_float};
+
+    #[test]
+    fn test_interval_doesnt_drift() {
+        const TICKS: u32 = 5;
+        const PERIOD_SECS: f64 = 0.2;
+        const TASK_SECS: f64 = 0.1;
+
+        let started = now_float();
+        block_on(async {
+            let mut interval = Timer::interval(Duration::from_secs_f64(PERIOD_SECS));
+            for _ in 0..TICKS {
+                interval.tick().await;
+                // Simulate the task execution time that a plain loop-and-sleep would add to every period.
+                Timer::sleep(TASK_SECS).await;
+            }
+        });
+        let elapsed = now_float() - started;
+
+        // The last task runs after the last tick. A drifting timer would take `TASK_SECS` longer for every tick
+        // after the first one, twice the margin allowed here.
+        let expected = PERIOD_SECS * TICKS as f64 + TASK_SECS;
+        assert!(elapsed >= expected, "{}", elapsed);
+        assert!(elapsed < expected + PERIOD_SECS, "{}", elapsed);
+    }
+
+    #[test]
+    fn test_interval_schedule_next_tick() {
+        let period = Duration::from_millis(50);
+        let start = Instant::now();
+        let mut interval = Interval {
+            period,
+            next_tick: start + period,
+        };
+
+        // The tick fired on time, the next one is a period later.
+        interval.schedule_next_tick(start + period);
+        assert_eq!(interval.next_tick, start + period * 2);
+
+        // The tick fired late but still before the next one is due, the schedule is kept.
+        interval.schedule_next_tick(start + Duration::from_millis(130));
+        assert_eq!(interval.next_tick, start + period * 3);
+
+        // The task fell behind by more than two periods, the missed ticks are skipped
+        // and the next one is aligned to the original schedule instead of firing in a burst.
+        interval.schedule_next_tick(start + Duration::from_millis(280));
+        assert_eq!(interval.next_tick, start + period * 6);
+
+        // A tick that fires exactly when the next one is due skips it as well.
+        interval.schedule_next_tick(start + period * 7);
+        assert_eq!(interval.next_tick, start + period * 8);
+    }
+}
@@ -1,4 +1,4 @@
-use futures::future::abortable;
+use futures::future::{abortable, Aborted};
 use futures::{Future as Future03, FutureExt};
 
 #[cfg(not(target_arch = "wasm32"))] mod native_executor;
@@ -14,6 +14,12 @@ pub use spawner::{BoxFutureSpawner, SpawnAbortable, SpawnFuture};
 mod abort_on_drop;
 pub use abort_on_drop::AbortOnDropHandle;
 
+mod cancellable_timer;
+pub use cancellable_timer::{CancellableTimer, TimerCancelHandle, TimerOutcome};
+
+mod interval;
+pub use interval::Interval;
+
 #[cfg(target_arch = "wasm32")] mod wasm_executor;
 #[cfg(target_arch = "wasm32")]
 pub use wasm_executor::{spawn, spawn_local, spawn_local_abortable, Timer};
@@ -80,3 +86,45 @@ pub fn spawn_abortable(fut: impl Future03<Output = ()> + Send + 'static) -> Abor
     spawn(abortable.then(|_| futures::future::ready(())));
     AbortOnDropHandle::from(handle)
 }
+
+/// Like [`spawn_abortable`], but passes the future's output to `on_result` once it finishes,
+/// or `Err(Aborted)` if it's aborted, so failures of background tasks don't go unnoticed.
+#[must_use]
+pub fn spawn_with_result<Fut, F>(fut: Fut, on_result: F) -> AbortOnDropHandle
+where
+    Fut: Future03 + Send + 'static,
+    Fut::Output: Send + 'static,
+    F: FnOnce(Result<Fut::Output, Aborted>) + Send + 'static,
+{
+    let (abortable, handle) = abortable(fut);
+    spawn(abortable.map(on_result));
+    AbortOnDropHandle::from(handle)
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+    use crate::block_on;
+    use futures::channel::oneshot;
+
+    #[test]
+    fn test_spawn_with_result_reports_error() {
+        let (tx, rx) = oneshot::channel();
+        let _handle = spawn_with_result(async { Err::<(), _>("Background task failed") }, move |result| {
+            tx.send(result).unwrap();
+        });
+
+        assert_eq!(block_on(rx).unwrap(), Ok(Err("Background task failed")));
+    }
+
+    #[test]
+    fn test_spawn_with_result_reports_abort() {
+        let (tx, rx) = oneshot::channel();
+        let handle = spawn_with_result(Timer::sleep(10.), move |result| {
+            tx.send(result).unwrap();
+        });
+        drop(handle);
+
+        assert_eq!(block_on(rx).unwrap(), Err(Aborted));
+    }
+}
@@ -5,13 +5,10 @@ use crate::EncryptedData;
 use derive_more::Display;
 use mm2_err_handle::prelude::*;
 
-#[allow(dead_code)]
 pub(crate) const ENCRYPTION_PATH: &str = "SLIP-0021/Master encryption key/";
-#[allow(dead_code)]
 pub(crate) const AUTHENTICATION_PATH: &str = "SLIP-0021/Authentication key/";
 
 #[derive(Debug, Display, PartialEq)]
-#[allow(dead_code)]
 pub enum SLIP21Error {
     #[display(fmt = "Error deriving key: {}", _0)]
     KeyDerivationError(String),
@@ -29,7 +26,6 @@ impl From<KeyDerivationError> for SLIP21Error {
 ///
 /// # Returns
 /// `MmResult<EncryptedData, EncryptionError>` - The encrypted data along with metadata for decryption, or an error.
-#[allow(dead_code)]
 pub fn encrypt_with_slip21(
     data: &[u8],
     master_secret: &[u8; 64],
@@ -55,7 +51,6 @@ pub fn encrypt_with_slip21(
 ///
 /// # Returns
 /// `MmResult<Vec<u8>, DecryptionError>` - The decrypted data, or an error.
-#[allow(dead_code)]
 pub fn decrypt_with_slip21(encrypted_data: &EncryptedData, master_secret: &[u8; 64]) -> MmResult<Vec<u8>, SLIP21Error> {
     let (encryption_path, authentication_path) = match &encrypted_data.key_derivation_details {
         KeyDerivationDetails::SLIP0021 {
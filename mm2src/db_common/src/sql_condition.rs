@@ -97,6 +97,24 @@ pub trait SqlCondition: Sized {
         Ok(self)
     }
 
+    /// Add WHERE field LIKE '%substring%' condition with the specified `substring` param.
+    /// For more details see [`SqlBuilder::and_where_like_any`].
+    ///
+    /// Please note the function validates the given `field`,
+    /// and escapes `%`, `_` wildcards within the given `substring`.
+    /// The comparison is case-insensitive for ASCII characters.
+    fn and_where_like_any_param<S>(&mut self, field: S, substring: &str) -> SqlResult<&mut Self>
+    where
+        S: ToValidSqlIdent,
+    {
+        let field = field.to_valid_sql_ident()?;
+        let escaped = substring.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_");
+        let param_id = self.sql_params().push_param(format!("%{}%", escaped));
+        self.sql_builder()
+            .and_where(format!("{} LIKE {} ESCAPE '\\'", field, param_id));
+        Ok(self)
+    }
+
     /// Add OR condition of equal parts to the last WHERE condition.
     /// For more details see [`SqlBuilder::or_where_eq`].
     ///
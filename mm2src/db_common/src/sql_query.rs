@@ -488,6 +488,37 @@ mod tests {
         assert_eq!(actual, expected);
     }
 
+    #[test]
+    fn test_query_where_like_any_param() {
+        let conn = Connection::open_in_memory().unwrap();
+        init_table_for_test(&conn);
+
+        let mut query = SqlQuery::select_from(&conn, "tx_history").unwrap();
+        query
+            .field("tx_hash")
+            .unwrap()
+            .and_where_like_any_param("tx_hash", "HASH_1")
+            .unwrap();
+        assert_eq!(
+            query.clone().sql().unwrap(),
+            "SELECT tx_hash FROM tx_history WHERE tx_hash LIKE :1 ESCAPE '\\';"
+        );
+        assert_eq!(query.params(), &vec!["%HASH\\_1%".to_owned().into()]);
+
+        let actual: Vec<String> = query.query(|row| row.get(0)).unwrap();
+        assert_eq!(actual, vec!["tx_hash_1".to_owned()]);
+
+        // `%` must be treated literally.
+        let mut query = SqlQuery::select_from(&conn, "tx_history").unwrap();
+        query
+            .field("tx_hash")
+            .unwrap()
+            .and_where_like_any_param("tx_hash", "%")
+            .unwrap();
+        let actual: Vec<String> = query.query(|row| row.get(0)).unwrap();
+        assert!(actual.is_empty());
+    }
+
     #[test]
     fn test_query_where_eq_null() {
         const NO_KMD_REWARDS: Option<f64> = None;
@@ -333,6 +333,15 @@ pub fn is_constraint_error(error: &SqlError) -> bool {
     }
 }
 
+/// Checks whether the `error` is caused by the database or the disk being full.
+/// Such an error is transient, the same query can succeed once some space is freed.
+pub fn is_disk_full_error(error: &SqlError) -> bool {
+    match error {
+        SqlError::SqliteFailure(failure, _error) => failure.code == rusqlite::ErrorCode::DiskFull,
+        _ => false,
+    }
+}
+
 pub trait ToValidSqlTable {
     /// Converts `self` to a valid SQL table name or returns an error.
     fn to_valid_sql_table(&self) -> SqlResult<String>;
@@ -453,3 +462,58 @@ where
         Err(validation_error())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn h256_from_text(text: Option<&str>) -> (Result<[u8; 32], SqlError>, Result<Option<[u8; 32]>, SqlError>) {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.query_row("SELECT ?1;", [text], |row| {
+            Ok((
+                h256_slice_from_row::<String>(row, 0),
+                h256_option_slice_from_row::<String>(row, 0),
+            ))
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_h256_slice_from_row() {
+        let hex = "66b7d8e66f3c3d6a7f4d4e0e4c0c2b1b6f8f0a9e8d7c6b5a4f3e2d1c0b0a0908";
+        let (slice, option) = h256_from_text(Some(hex));
+        let expected: [u8; 32] = hex::decode(hex).unwrap().try_into().unwrap();
+        assert_eq!(slice.unwrap(), expected);
+        assert_eq!(option.unwrap(), Some(expected));
+
+        let (slice, option) = h256_from_text(None);
+        slice.unwrap_err();
+        assert_eq!(option.unwrap(), None);
+    }
+
+    #[test]
+    fn test_h256_slice_from_row_invalid() {
+        let wrong_length = "66b7d8e66f3c3d6a";
+        let not_hex = "zzb7d8e66f3c3d6a7f4d4e0e4c0c2b1b6f8f0a9e8d7c6b5a4f3e2d1c0b0a0908";
+        for invalid in [wrong_length, not_hex] {
+            let (slice, option) = h256_from_text(Some(invalid));
+            for err in [slice.unwrap_err(), option.unwrap_err()] {
+                assert!(
+                    matches!(err, SqlError::FromSqlConversionFailure(0, SqlType::Text, _)),
+                    "{invalid}: {err:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_is_disk_full_error() {
+        let disk_full = SqlError::SqliteFailure(rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_FULL), None);
+        assert!(is_disk_full_error(&disk_full));
+        assert!(!is_constraint_error(&disk_full));
+
+        let constraint = SqlError::SqliteFailure(rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_CONSTRAINT), None);
+        assert!(!is_disk_full_error(&constraint));
+        assert!(!is_disk_full_error(&SqlError::QueryReturnedNoRows));
+    }
+}
@@ -1,11 +1,20 @@
 use mm2_err_handle::prelude::{MmError, MmResult};
+use relay_rpc::rpc::params::session::Namespace;
 use serde::{Deserialize, Serialize};
+use serde_json::Value as Json;
+use std::collections::BTreeMap;
 use std::str::FromStr;
 
 use crate::error::WalletConnectError;
+use crate::session::SessionProperties;
 
 pub(crate) const SUPPORTED_PROTOCOL: &str = "irn";
 
+/// The chains a newly settled session starts being active on if no `default_chain_id` is configured
+/// or the session didn't approve it, in the order of preference.
+/// Cosmos goes first as it used to be the only default.
+const ACTIVE_CHAIN_PRIORITY: [WcChain; 2] = [WcChain::Cosmos, WcChain::Eip155];
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum WcChain {
     Eip155,
@@ -105,3 +114,181 @@ impl AsRef<str> for WcRequestMethods {
         }
     }
 }
+
+/// Reads the optional default active chain from the `wallet_connect.default_chain_id` conf field, e.g. `"eip155:1"`.
+pub(crate) fn default_chain_id_from_conf(conf: &Json) -> MmResult<Option<WcChainId>, WalletConnectError> {
+    conf["wallet_connect"]["default_chain_id"]
+        .as_str()
+        .map(WcChainId::try_from_str)
+        .transpose()
+}
+
+/// Checks whether the session with the given `namespaces` and `session_properties` approved the `chain_id`.
+pub(crate) fn is_chain_id_approved(
+    namespaces: &BTreeMap<String, Namespace>,
+    session_properties: Option<&SessionProperties>,
+    chain_id: &WcChainId,
+) -> bool {
+    if let Some(Namespace { chains, .. }) = namespaces.get(chain_id.chain.as_ref()) {
+        match chains {
+            Some(chains) => {
+                if chains.contains(&chain_id.to_string()) {
+                    return true;
+                }
+            },
+            None => {
+                // https://specs.walletconnect.com/2.0/specs/clients/sign/namespaces#13-chains-might-be-omitted-if-the-caip-2-is-defined-in-the-index
+                if let Some(SessionProperties { keys: Some(keys) }) = session_properties {
+                    if keys.iter().any(|k| k.chain_id == chain_id.id) {
+                        return true;
+                    }
+                }
+            },
+        };
+    }
+
+    // https://specs.walletconnect.com/2.0/specs/clients/sign/namespaces#13-chains-might-be-omitted-if-the-caip-2-is-defined-in-the-index
+    namespaces.contains_key(&chain_id.to_string())
+}
+
+/// Returns all the chains approved by the session with the given `namespaces` and `session_properties`.
+fn approved_chain_ids(
+    namespaces: &BTreeMap<String, Namespace>,
+    session_properties: Option<&SessionProperties>,
+) -> Vec<WcChainId> {
+    let mut approved = Vec::new();
+    for (key, namespace) in namespaces {
+        match &namespace.chains {
+            Some(chains) => approved.extend(
+                chains
+                    .iter()
+                    .filter_map(|chain_id| WcChainId::try_from_str(chain_id).ok()),
+            ),
+            // The namespace key is the CAIP-2 chain ID itself.
+            None if key.contains(':') => approved.extend(WcChainId::try_from_str(key).ok()),
+            // The chains are listed in the session properties then.
+            None => {
+                let Ok(chain) = WcChain::from_str(key) else {
+                    continue;
+                };
+                let keys = session_properties.and_then(|properties| properties.keys.as_ref());
+                approved.extend(
+                    keys.into_iter()
+                        .flatten()
+                        .map(|key| chain.derive_chain_id(key.chain_id.clone())),
+                );
+            },
+        }
+    }
+    approved
+}
+
+/// Picks the chain a newly settled session starts being active on.
+///
+/// That's the configured `default_chain_id` if the session approved it, otherwise the first approved chain
+/// of the most preferred chain family, see [`ACTIVE_CHAIN_PRIORITY`].
+pub(crate) fn default_active_chain_id(
+    default_chain_id: Option<&WcChainId>,
+    namespaces: &BTreeMap<String, Namespace>,
+    session_properties: Option<&SessionProperties>,
+) -> Option<WcChainId> {
+    if let Some(default_chain_id) = default_chain_id {
+        if is_chain_id_approved(namespaces, session_properties, default_chain_id) {
+            return Some(default_chain_id.clone());
+        }
+    }
+
+    let approved = approved_chain_ids(namespaces, session_properties);
+    ACTIVE_CHAIN_PRIORITY
+        .iter()
+        .find_map(|chain| approved.iter().find(|chain_id| &chain_id.chain == chain).cloned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn namespaces() -> BTreeMap<String, Namespace> {
+        serde_json::from_value(json!({
+            "cosmos": {
+                "chains": ["cosmos:cosmoshub-4"],
+                "methods": ["cosmos_signDirect"],
+                "events": [],
+                "accounts": []
+            },
+            "eip155": {
+                "chains": ["eip155:1", "eip155:137"],
+                "methods": ["eth_sendTransaction"],
+                "events": ["chainChanged"],
+                "accounts": []
+            }
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_default_chain_id_from_conf() {
+        let conf = json!({ "wallet_connect": { "default_chain_id": "eip155:137" } });
+        let chain_id = default_chain_id_from_conf(&conf).unwrap();
+        assert_eq!(chain_id, Some(WcChainId::new_eip155("137".to_string())));
+
+        assert_eq!(default_chain_id_from_conf(&json!({})).unwrap(), None);
+        default_chain_id_from_conf(&json!({ "wallet_connect": { "default_chain_id": "eip155" } })).unwrap_err();
+    }
+
+    #[test]
+    fn test_default_active_chain_id() {
+        let eth_default = WcChainId::new_eip155("137".to_string());
+        let active = default_active_chain_id(Some(&eth_default), &namespaces(), None);
+        assert_eq!(active, Some(eth_default));
+
+        // Falls back to the preferred chain family if the configured chain wasn't approved.
+        let unapproved = WcChainId::new_eip155("56".to_string());
+        let active = default_active_chain_id(Some(&unapproved), &namespaces(), None);
+        assert_eq!(active, Some(WcChainId::new_cosmos("cosmoshub-4".to_string())));
+
+        assert_eq!(
+            default_active_chain_id(None, &namespaces(), None),
+            Some(WcChainId::new_cosmos("cosmoshub-4".to_string()))
+        );
+        assert_eq!(default_active_chain_id(None, &BTreeMap::new(), None), None);
+    }
+
+    #[test]
+    fn test_default_active_chain_id_omitted_chains() {
+        // The chains are omitted, and the namespaces are keyed by the CAIP-2 chain IDs instead.
+        let namespaces: BTreeMap<String, Namespace> = serde_json::from_value(json!({
+            "eip155:1": {
+                "methods": ["eth_sendTransaction"],
+                "events": [],
+                "accounts": []
+            },
+            "eip155:137": {
+                "methods": ["eth_sendTransaction"],
+                "events": [],
+                "accounts": []
+            }
+        }))
+        .unwrap();
+
+        let eth_default = WcChainId::new_eip155("137".to_string());
+        let active = default_active_chain_id(Some(&eth_default), &namespaces, None);
+        assert_eq!(active, Some(eth_default));
+
+        let unapproved = WcChainId::new_eip155("56".to_string());
+        let active = default_active_chain_id(Some(&unapproved), &namespaces, None);
+        assert_eq!(active, Some(WcChainId::new_eip155("1".to_string())));
+
+        // A namespace without chains doesn't approve every chain of its family.
+        let namespaces: BTreeMap<String, Namespace> = serde_json::from_value(json!({
+            "eip155": {
+                "methods": ["eth_sendTransaction"],
+                "events": [],
+                "accounts": []
+            }
+        }))
+        .unwrap();
+        assert_eq!(default_active_chain_id(Some(&unapproved), &namespaces, None), None);
+    }
+}
@@ -63,3 +63,30 @@ impl ConnectionHandler for Handler {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::channel::mpsc::unbounded;
+
+    #[test]
+    fn test_handler_reports_disconnections() {
+        let (msg_sender, _msg_receiver) = unbounded();
+        let (conn_live_sender, mut conn_live_receiver) = unbounded();
+        let mut handler = Handler::new("test", msg_sender, conn_live_sender);
+
+        // A successful connection isn't reported, the lifecycle task marks the state itself once subscribed.
+        handler.connected();
+        assert!(conn_live_receiver.try_next().is_err());
+
+        // Any disconnection is forwarded so the lifecycle task switches the state to `Disconnected`.
+        handler.disconnected(None);
+        assert_eq!(conn_live_receiver.try_next().unwrap(), Some(None));
+
+        handler.connected();
+        assert!(conn_live_receiver.try_next().is_err());
+
+        handler.disconnected(None);
+        assert_eq!(conn_live_receiver.try_next().unwrap(), Some(None));
+    }
+}
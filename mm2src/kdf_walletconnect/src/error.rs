@@ -15,6 +15,9 @@ pub(crate) const INVALID_UPDATE_REQUEST: i32 = 1003;
 pub(crate) const INVALID_EXTEND_REQUEST: i32 = 1004;
 pub(crate) const INVALID_SESSION_SETTLE_REQUEST: i32 = 1005;
 
+// JSON-RPC error code
+pub(crate) const INVALID_PARAMS: i32 = -32602;
+
 // Unauthorized error codes
 pub(crate) const UNAUTHORIZED_METHOD: i32 = 3001;
 pub(crate) const UNAUTHORIZED_EVENT: i32 = 3002;
@@ -92,6 +95,14 @@ pub enum WalletConnectError {
     ChainIdNotSupported(String),
     #[error("Request timeout error")]
     TimeoutError,
+    #[error("Timed out connecting to the relay after {0}s")]
+    ConnectTimeout(f64),
+    #[error("Invalid connect timeout: {0}, expected a positive number of seconds")]
+    InvalidConnectTimeout(String),
+    #[error("Pairing {0} didn't respond to a ping")]
+    PairingNotResponding(String),
+    #[error("No pending session proposal for pairing {0}")]
+    NoPendingProposal(String),
 }
 
 impl From<Error<PublishError>> for WalletConnectError {
@@ -1,4 +1,4 @@
-use crate::{error::WalletConnectError,
+use crate::{error::{WalletConnectError, INVALID_PARAMS, UNSUPPORTED_METHODS},
             pairing::{reply_pairing_delete_response, reply_pairing_extend_response, reply_pairing_ping_response},
             session::rpc::{delete::reply_session_delete_request,
                            event::handle_session_event,
@@ -9,10 +9,15 @@ use crate::{error::WalletConnectError,
                            update::reply_session_update_request},
             WalletConnectCtxImpl};
 
-use common::log::{info, LogOnError};
+use async_trait::async_trait;
+use common::log::{warn, LogOnError};
+use futures::future::BoxFuture;
 use mm2_err_handle::prelude::*;
 use relay_rpc::domain::{MessageId, Topic};
-use relay_rpc::rpc::{params::ResponseParamsSuccess, Params, Request, Response};
+use relay_rpc::rpc::{params::{ResponseParamsError, ResponseParamsSuccess},
+                     ErrorData, Response};
+use serde::Deserialize;
+use serde_json::Value as Json;
 
 pub(crate) type SessionMessageType = MmResult<SessionMessage, WalletConnectError>;
 
@@ -23,38 +28,320 @@ pub struct SessionMessage {
     pub data: ResponseParamsSuccess,
 }
 
-/// Processes an inbound WalletConnect request and performs the appropriate action based on the request type.
+/// An inbound WalletConnect request with its `params` not parsed yet, as their type depends on the `method`.
+#[derive(Deserialize)]
+pub(crate) struct InboundRequest {
+    id: MessageId,
+    method: String,
+    #[serde(default)]
+    params: Json,
+}
+
+type InboundRequestFut<'a> = BoxFuture<'a, MmResult<(), WalletConnectError>>;
+
+/// Parses the `params` of an inbound request and returns the future handling it.
+/// Fails if the `params` are malformed.
+type InboundRequestHandler =
+    for<'a> fn(&'a WalletConnectCtxImpl, &'a Topic, &'a MessageId, Json) -> serde_json::Result<InboundRequestFut<'a>>;
+
+/// An inbound request method we know of.
+pub(crate) struct InboundMethod {
+    method: &'static str,
+    handler: InboundRequestHandler,
+    /// Wraps the data of an error response to a request of this method.
+    error_response: fn(ErrorData) -> ResponseParamsError,
+}
+
+/// The dispatch table of the inbound request methods, keyed by the method name.
+const INBOUND_METHODS: &[InboundMethod] = &[
+    InboundMethod {
+        method: "wc_sessionPropose",
+        handler: session_propose,
+        error_response: ResponseParamsError::SessionPropose,
+    },
+    InboundMethod {
+        method: "wc_sessionSettle",
+        handler: session_settle,
+        error_response: ResponseParamsError::SessionSettle,
+    },
+    InboundMethod {
+        method: "wc_sessionUpdate",
+        handler: session_update,
+        error_response: ResponseParamsError::SessionUpdate,
+    },
+    InboundMethod {
+        method: "wc_sessionExtend",
+        handler: session_extend,
+        error_response: ResponseParamsError::SessionExtend,
+    },
+    InboundMethod {
+        method: "wc_sessionRequest",
+        handler: session_request,
+        error_response: ResponseParamsError::SessionRequest,
+    },
+    InboundMethod {
+        method: "wc_sessionEvent",
+        handler: session_event,
+        error_response: ResponseParamsError::SessionEvent,
+    },
+    InboundMethod {
+        method: "wc_sessionDelete",
+        handler: session_delete,
+        error_response: ResponseParamsError::SessionDelete,
+    },
+    InboundMethod {
+        method: "wc_sessionPing",
+        handler: session_ping,
+        error_response: ResponseParamsError::SessionPing,
+    },
+    InboundMethod {
+        method: "wc_pairingPing",
+        handler: pairing_ping,
+        error_response: ResponseParamsError::PairingPing,
+    },
+    InboundMethod {
+        method: "wc_pairingDelete",
+        handler: pairing_delete,
+        error_response: ResponseParamsError::PairingDelete,
+    },
+    InboundMethod {
+        method: "wc_pairingExtend",
+        handler: pairing_extend,
+        error_response: ResponseParamsError::PairingExtend,
+    },
+];
+
+fn session_propose<'a>(
+    ctx: &'a WalletConnectCtxImpl,
+    topic: &'a Topic,
+    message_id: &'a MessageId,
+    params: Json,
+) -> serde_json::Result<InboundRequestFut<'a>> {
+    let proposal = serde_json::from_value(params)?;
+    Ok(Box::pin(reply_session_proposal_request(
+        ctx, proposal, topic, message_id,
+    )))
+}
+
+fn session_settle<'a>(
+    ctx: &'a WalletConnectCtxImpl,
+    topic: &'a Topic,
+    _message_id: &'a MessageId,
+    params: Json,
+) -> serde_json::Result<InboundRequestFut<'a>> {
+    let settle = serde_json::from_value(params)?;
+    Ok(Box::pin(reply_session_settle_request(ctx, topic, settle)))
+}
+
+fn session_update<'a>(
+    ctx: &'a WalletConnectCtxImpl,
+    topic: &'a Topic,
+    message_id: &'a MessageId,
+    params: Json,
+) -> serde_json::Result<InboundRequestFut<'a>> {
+    let update = serde_json::from_value(params)?;
+    Ok(Box::pin(reply_session_update_request(ctx, topic, message_id, update)))
+}
+
+fn session_extend<'a>(
+    ctx: &'a WalletConnectCtxImpl,
+    topic: &'a Topic,
+    message_id: &'a MessageId,
+    params: Json,
+) -> serde_json::Result<InboundRequestFut<'a>> {
+    let extend = serde_json::from_value(params)?;
+    Ok(Box::pin(reply_session_extend_request(ctx, topic, message_id, extend)))
+}
+
+// TODO: Implement when integrating KDF as a Dapp.
+fn session_request<'a>(
+    ctx: &'a WalletConnectCtxImpl,
+    topic: &'a Topic,
+    message_id: &'a MessageId,
+    _params: Json,
+) -> serde_json::Result<InboundRequestFut<'a>> {
+    Ok(Box::pin(reply_unsupported_method(
+        ctx,
+        topic,
+        message_id,
+        "wc_sessionRequest",
+    )))
+}
+
+fn session_event<'a>(
+    ctx: &'a WalletConnectCtxImpl,
+    topic: &'a Topic,
+    message_id: &'a MessageId,
+    params: Json,
+) -> serde_json::Result<InboundRequestFut<'a>> {
+    let event = serde_json::from_value(params)?;
+    Ok(Box::pin(handle_session_event(ctx, topic, message_id, event)))
+}
+
+fn session_delete<'a>(
+    ctx: &'a WalletConnectCtxImpl,
+    topic: &'a Topic,
+    message_id: &'a MessageId,
+    params: Json,
+) -> serde_json::Result<InboundRequestFut<'a>> {
+    let delete = serde_json::from_value(params)?;
+    Ok(Box::pin(reply_session_delete_request(ctx, topic, message_id, delete)))
+}
+
+fn session_ping<'a>(
+    ctx: &'a WalletConnectCtxImpl,
+    topic: &'a Topic,
+    message_id: &'a MessageId,
+    _params: Json,
+) -> serde_json::Result<InboundRequestFut<'a>> {
+    Ok(Box::pin(reply_session_ping_request(ctx, topic, message_id)))
+}
+
+fn pairing_ping<'a>(
+    ctx: &'a WalletConnectCtxImpl,
+    topic: &'a Topic,
+    message_id: &'a MessageId,
+    _params: Json,
+) -> serde_json::Result<InboundRequestFut<'a>> {
+    Ok(Box::pin(reply_pairing_ping_response(ctx, topic, message_id)))
+}
+
+fn pairing_delete<'a>(
+    ctx: &'a WalletConnectCtxImpl,
+    topic: &'a Topic,
+    message_id: &'a MessageId,
+    params: Json,
+) -> serde_json::Result<InboundRequestFut<'a>> {
+    let delete = serde_json::from_value(params)?;
+    Ok(Box::pin(reply_pairing_delete_response(ctx, topic, message_id, delete)))
+}
+
+fn pairing_extend<'a>(
+    ctx: &'a WalletConnectCtxImpl,
+    topic: &'a Topic,
+    message_id: &'a MessageId,
+    params: Json,
+) -> serde_json::Result<InboundRequestFut<'a>> {
+    let extend = serde_json::from_value(params)?;
+    Ok(Box::pin(reply_pairing_extend_response(ctx, topic, message_id, extend)))
+}
+
+fn lookup_inbound_method(method: &str) -> Option<&'static InboundMethod> {
+    INBOUND_METHODS
+        .iter()
+        .find(|inbound_method| inbound_method.method == method)
+}
+
+/// Looks the `method` up in the dispatch table.
+/// Returns the "unsupported method" error response to publish back to the peer if we don't know of the method.
+pub(crate) fn find_inbound_method(method: &str) -> Result<&'static InboundMethod, ResponseParamsError> {
+    lookup_inbound_method(method).ok_or_else(|| unsupported_method_error(method))
+}
+
+/// Returns the error response to a request of the `method` with malformed params.
+fn invalid_params_error(inbound_method: &InboundMethod, error: serde_json::Error) -> ResponseParamsError {
+    (inbound_method.error_response)(ErrorData {
+        code: INVALID_PARAMS,
+        message: format!("Invalid params of {}: {error}", inbound_method.method),
+        data: None,
+    })
+}
+
+/// Returns the "unsupported method" error response, wrapped according to the namespace of the `method`
+/// since the peer expects a pairing response to a `wc_pairing*` request.
+fn unsupported_method_error(method: &str) -> ResponseParamsError {
+    let error = ErrorData {
+        code: UNSUPPORTED_METHODS,
+        message: format!("Unsupported method: {method}"),
+        data: None,
+    };
+    match lookup_inbound_method(method) {
+        Some(inbound_method) => (inbound_method.error_response)(error),
+        None if method.starts_with("wc_pairing") => ResponseParamsError::PairingPing(error),
+        None => ResponseParamsError::SessionRequest(error),
+    }
+}
+
+/// The relay side effects of processing an inbound request, abstracted so they can be checked in tests.
+#[async_trait]
+pub(crate) trait InboundRequestRelay {
+    /// Publishes the `error` response to the request with `message_id` back to the peer on the `topic`.
+    async fn publish_response_err(
+        &self,
+        topic: &Topic,
+        error: ResponseParamsError,
+        message_id: &MessageId,
+    ) -> MmResult<(), WalletConnectError>;
+
+    /// Parses the `params` of the request and returns the future handling it with the handler of `inbound_method`.
+    fn dispatch<'a>(
+        &'a self,
+        inbound_method: &'static InboundMethod,
+        topic: &'a Topic,
+        message_id: &'a MessageId,
+        params: Json,
+    ) -> serde_json::Result<InboundRequestFut<'a>>;
+}
+
+#[async_trait]
+impl InboundRequestRelay for WalletConnectCtxImpl {
+    async fn publish_response_err(
+        &self,
+        topic: &Topic,
+        error: ResponseParamsError,
+        message_id: &MessageId,
+    ) -> MmResult<(), WalletConnectError> {
+        WalletConnectCtxImpl::publish_response_err(self, topic, error, message_id).await
+    }
+
+    fn dispatch<'a>(
+        &'a self,
+        inbound_method: &'static InboundMethod,
+        topic: &'a Topic,
+        message_id: &'a MessageId,
+        params: Json,
+    ) -> serde_json::Result<InboundRequestFut<'a>> {
+        (inbound_method.handler)(self, topic, message_id, params)
+    }
+}
+
+/// Processes an inbound WalletConnect request by dispatching it to the handler of its method.
 ///
-/// Handles various session and pairing requests, routing them to their corresponding handlers.
+/// Publishes an error response back to the peer if the method is unknown or the params are malformed.
 pub(crate) async fn process_inbound_request(
-    ctx: &WalletConnectCtxImpl,
-    request: Request,
+    relay: &(impl InboundRequestRelay + Sync),
+    request: InboundRequest,
     topic: &Topic,
 ) -> MmResult<(), WalletConnectError> {
     let message_id = request.id;
-    match request.params {
-        Params::SessionPropose(proposal) => reply_session_proposal_request(ctx, proposal, topic, &message_id).await?,
-        Params::SessionExtend(param) => reply_session_extend_request(ctx, topic, &message_id, param).await?,
-        Params::SessionDelete(param) => reply_session_delete_request(ctx, topic, &message_id, param).await?,
-        Params::SessionPing(()) => reply_session_ping_request(ctx, topic, &message_id).await?,
-        Params::SessionSettle(param) => reply_session_settle_request(ctx, topic, param).await?,
-        Params::SessionUpdate(param) => reply_session_update_request(ctx, topic, &message_id, param).await?,
-        Params::SessionEvent(param) => handle_session_event(ctx, topic, &message_id, param).await?,
-        Params::SessionRequest(_param) => {
-            // TODO: Implement when integrating KDF as a Dapp.
-            return MmError::err(WalletConnectError::NotImplemented);
+    let inbound_method = match find_inbound_method(&request.method) {
+        Ok(inbound_method) => inbound_method,
+        Err(error) => {
+            warn!("[{topic}] Unsupported request method received: {}", request.method);
+            return relay.publish_response_err(topic, error, &message_id).await;
         },
+    };
 
-        Params::PairingPing(_param) => reply_pairing_ping_response(ctx, topic, &message_id).await?,
-        Params::PairingDelete(param) => reply_pairing_delete_response(ctx, topic, &message_id, param).await?,
-        Params::PairingExtend(param) => reply_pairing_extend_response(ctx, topic, &message_id, param).await?,
-        _ => {
-            info!("Unknown request params received.");
-            return MmError::err(WalletConnectError::InvalidRequest);
+    match relay.dispatch(inbound_method, topic, &message_id, request.params) {
+        Ok(handle_fut) => handle_fut.await,
+        Err(e) => {
+            warn!("[{topic}] Invalid params of {} request: {e}", inbound_method.method);
+            let error = invalid_params_error(inbound_method, e);
+            relay.publish_response_err(topic, error, &message_id).await
         },
-    };
+    }
+}
 
-    Ok(())
+/// Publishes an "unsupported method" error back to the peer for a request we have no handler for.
+async fn reply_unsupported_method(
+    ctx: &WalletConnectCtxImpl,
+    topic: &Topic,
+    message_id: &MessageId,
+    method: &str,
+) -> MmResult<(), WalletConnectError> {
+    warn!("[{topic}] Unsupported request method received: {method}");
+    ctx.publish_response_err(topic, unsupported_method_error(method), message_id)
+        .await
 }
 
 /// Processes an inbound WalletConnect response and sends the result to the provided message channel.
@@ -62,6 +349,9 @@ pub(crate) async fn process_inbound_request(
 /// Handles successful responses, errors, and specific session proposal processing.
 pub(crate) async fn process_inbound_response(ctx: &WalletConnectCtxImpl, response: Response, topic: &Topic) {
     let message_id = response.id();
+    // The proposal is either approved or rejected by the peer, so it can't be rejected by us anymore.
+    ctx.pending_proposals.remove_by_message_id(&message_id);
+
     let result = match &response {
         Response::Success(value) => match serde_json::from_value::<ResponseParamsSuccess>(value.result.clone()) {
             Ok(ResponseParamsSuccess::SessionPropose(propose)) => {
@@ -96,3 +386,131 @@ pub(crate) async fn process_inbound_response(ctx: &WalletConnectCtxImpl, respons
         common::log::error!("[{topic}] unrecognized inbound response/message: {response:?}");
     };
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::block_on;
+    use relay_client::MessageIdGenerator;
+    use std::sync::Mutex;
+
+    /// Records the error responses published back to the peer and the requests dispatched to the handlers.
+    #[derive(Default)]
+    struct RecordingRelay {
+        published: Mutex<Vec<(Topic, ResponseParamsError, MessageId)>>,
+        dispatched: Mutex<Vec<&'static str>>,
+    }
+
+    #[async_trait]
+    impl InboundRequestRelay for RecordingRelay {
+        async fn publish_response_err(
+            &self,
+            topic: &Topic,
+            error: ResponseParamsError,
+            message_id: &MessageId,
+        ) -> MmResult<(), WalletConnectError> {
+            self.published.lock().unwrap().push((topic.clone(), error, *message_id));
+            Ok(())
+        }
+
+        fn dispatch<'a>(
+            &'a self,
+            inbound_method: &'static InboundMethod,
+            _topic: &'a Topic,
+            _message_id: &'a MessageId,
+            _params: Json,
+        ) -> serde_json::Result<InboundRequestFut<'a>> {
+            self.dispatched.lock().unwrap().push(inbound_method.method);
+            Ok(Box::pin(async { Ok(()) }))
+        }
+    }
+
+    fn inbound_request(method: &str) -> InboundRequest {
+        InboundRequest {
+            id: MessageIdGenerator::new().next(),
+            method: method.to_string(),
+            params: Json::Null,
+        }
+    }
+
+    fn error_data(error: ResponseParamsError) -> ErrorData {
+        match error {
+            ResponseParamsError::SessionRequest(data)
+            | ResponseParamsError::SessionUpdate(data)
+            | ResponseParamsError::PairingPing(data) => data,
+            error => panic!("Unexpected error response: {error:?}"),
+        }
+    }
+
+    #[test]
+    fn test_unknown_method_error_response() {
+        let relay = RecordingRelay::default();
+        let topic: Topic = "session".to_string().into();
+        let request = inbound_request("wc_unknownMethod");
+        let message_id = request.id;
+
+        block_on(process_inbound_request(&relay, request, &topic)).unwrap();
+
+        assert!(relay.dispatched.lock().unwrap().is_empty());
+        let mut published = relay.published.lock().unwrap();
+        assert_eq!(published.len(), 1);
+        let (published_topic, error, published_id) = published.remove(0);
+        assert_eq!(published_topic, topic);
+        assert_eq!(published_id, message_id);
+        assert!(matches!(error, ResponseParamsError::SessionRequest(_)));
+        let data = error_data(error);
+        assert_eq!(data.code, UNSUPPORTED_METHODS);
+        assert_eq!(data.message, "Unsupported method: wc_unknownMethod");
+
+        // Unknown pairing methods are replied with a pairing response.
+        let Err(error) = find_inbound_method("wc_pairingUnknown") else {
+            panic!("wc_pairingUnknown must not be dispatched");
+        };
+        assert!(matches!(error, ResponseParamsError::PairingPing(_)));
+        assert_eq!(error_data(error).code, UNSUPPORTED_METHODS);
+
+        // Known methods we don't handle yet are replied with the response of the method itself.
+        let error = unsupported_method_error("wc_sessionRequest");
+        assert!(matches!(error, ResponseParamsError::SessionRequest(_)));
+    }
+
+    #[test]
+    fn test_known_method_is_dispatched() {
+        let relay = RecordingRelay::default();
+        let topic: Topic = "session".to_string().into();
+
+        block_on(process_inbound_request(
+            &relay,
+            inbound_request("wc_sessionPing"),
+            &topic,
+        ))
+        .unwrap();
+
+        assert_eq!(*relay.dispatched.lock().unwrap(), vec!["wc_sessionPing"]);
+        assert!(relay.published.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_malformed_params_error_response() {
+        let inbound_method = lookup_inbound_method("wc_sessionUpdate").unwrap();
+        let parse_error = serde_json::from_value::<u8>(Json::Null).unwrap_err();
+
+        let error = invalid_params_error(inbound_method, parse_error);
+        assert!(matches!(error, ResponseParamsError::SessionUpdate(_)));
+        let data = error_data(error);
+        assert_eq!(data.code, INVALID_PARAMS);
+        assert!(data.message.starts_with("Invalid params of wc_sessionUpdate"));
+    }
+
+    #[test]
+    fn test_inbound_request_deserialization() {
+        let payload = r#"{"id":1700000000000000,"jsonrpc":"2.0","method":"wc_sessionPing","params":{}}"#;
+        let request: InboundRequest = serde_json::from_str(payload).unwrap();
+        assert_eq!(request.method, "wc_sessionPing");
+        assert_eq!(serde_json::to_value(request.id).unwrap(), 1700000000000000u64);
+
+        // Responses are not requests.
+        let payload = r#"{"id":1700000000000000,"jsonrpc":"2.0","result":true}"#;
+        assert!(serde_json::from_str::<InboundRequest>(payload).is_err());
+    }
+}
@@ -7,9 +7,12 @@ mod metadata;
 pub mod session;
 mod storage;
 
+pub use session::rpc::event::{AccountsChanged, ChainChanged, WalletConnectEvent};
+pub use session::rpc::propose::RejectionReason;
+
 use crate::connection_handler::{Handler, MAX_BACKOFF};
-use crate::session::rpc::propose::send_proposal_request;
-use chain::{WcChainId, WcRequestMethods, SUPPORTED_PROTOCOL};
+use crate::session::rpc::propose::{reject_session_proposal, send_proposal_request, PendingProposals};
+use chain::{default_chain_id_from_conf, is_chain_id_approved, WcChainId, WcRequestMethods, SUPPORTED_PROTOCOL};
 use common::custom_futures::timeout::FutureTimerExt;
 use common::executor::abortable_queue::AbortableQueue;
 use common::executor::{AbortableSystem, SpawnFuture, Timer};
@@ -17,9 +20,10 @@ use common::log::{debug, error, info, LogOnError};
 use error::WalletConnectError;
 use futures::channel::mpsc::{unbounded, UnboundedReceiver};
 use futures::StreamExt;
-use inbound_message::{process_inbound_request, process_inbound_response, SessionMessageType};
+use inbound_message::{process_inbound_request, process_inbound_response, InboundRequest, SessionMessageType};
 use metadata::{generate_metadata, AUTH_TOKEN_DURATION, AUTH_TOKEN_SUB, PROJECT_ID, RELAY_ADDRESS};
-use mm2_core::mm_ctx::{from_ctx, MmArc};
+use mm2_core::event_dispatcher::{Dispatcher, EventListener};
+use mm2_core::mm_ctx::{from_ctx, MmArc, MmWeak};
 use mm2_err_handle::prelude::*;
 use pairing_api::PairingClient;
 use relay_client::websocket::{connection_event_loop as client_event_loop, Client, PublishedMessage};
@@ -32,25 +36,32 @@ use relay_rpc::rpc::params::{session_request::Request as SessionRequest, IrnMeta
                              RelayProtocolMetadata, RequestParams, ResponseParamsError, ResponseParamsSuccess};
 use relay_rpc::rpc::{ErrorResponse, Payload, Request, Response, SuccessfulResponse};
 use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value as Json;
 use session::rpc::delete::send_session_delete_request;
+use session::rpc::event::WalletConnectEvent;
 use session::{key::SymKeyPair, SessionManager};
-use session::{EncodingAlgo, Session, SessionProperties, FIVE_MINUTES};
+use session::{EncodingAlgo, Session, SessionProperties, SessionPropertiesView, FIVE_MINUTES};
 use std::collections::BTreeSet;
+use std::future::Future;
 use std::ops::Deref;
 use std::sync::{Arc, Mutex};
 use std::time::Duration;
-use storage::SessionStorageDb;
-use storage::WalletConnectStorageOps;
+use storage::{init_storage_with_retry, SessionStorageDb, WalletConnectStorageOps};
 use timed_map::TimedMap;
-use tokio::sync::{oneshot, watch};
+use tokio::sync::{oneshot, watch, RwLock as AsyncRwLock};
 use wc_common::{decode_and_decrypt_type0, encrypt_and_encode, EnvelopeType, SymKey};
 
 const PUBLISH_TIMEOUT_SECS: f64 = 6.;
 const CONNECTION_TIMEOUT_S: f64 = 30.;
+/// The default time to wait for the relay connection (TCP/TLS and websocket handshakes) to be established.
+const DEFAULT_CONNECT_TIMEOUT_S: f64 = 30.;
 
+/// State of the connection to the WalletConnect relay server.
 /// Broadcast by the lifecycle task so every RPC can cheaply await connectivity.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum ConnectionState {
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConnectionState {
     Connecting,
     Connected,
     Disconnected,
@@ -97,8 +108,17 @@ pub struct WalletConnectCtxImpl {
     metadata: Metadata,
     message_id_generator: MessageIdGenerator,
     pending_requests: Mutex<TimedMap<MessageId, oneshot::Sender<SessionMessageType>>>,
+    pending_proposals: PendingProposals,
     abortable_system: AbortableQueue,
     connection_state_rx: watch::Receiver<ConnectionState>,
+    /// The chain a newly settled session starts being active on if the session approved it.
+    /// Configured through `wallet_connect.default_chain_id`, see [`chain::default_active_chain_id`] for the fallback.
+    pub(crate) default_chain_id: Option<WcChainId>,
+    /// Configured through `wallet_connect.connect_timeout_secs`, defaults to [`DEFAULT_CONNECT_TIMEOUT_S`].
+    connect_timeout_secs: f64,
+    /// Weak reference to the MM context the listeners of the emitted events are called with.
+    mm_ctx: MmWeak,
+    event_dispatcher: AsyncRwLock<Dispatcher<WalletConnectEvent>>,
 }
 
 /// A newtype wrapper around a thread-safe reference to `WalletConnectCtxImpl`.
@@ -117,6 +137,8 @@ impl WalletConnectCtx {
             .create_subsystem::<AbortableQueue>()
             .map_to_mm(|err| WalletConnectError::InternalError(err.to_string()))?;
         let storage = SessionStorageDb::new(ctx)?;
+        let default_chain_id = default_chain_id_from_conf(&ctx.conf)?;
+        let connect_timeout_secs = connect_timeout_secs_from_conf(&ctx.conf)?;
         let pairing = PairingClient::new();
         let relay = Relay {
             protocol: SUPPORTED_PROTOCOL.to_string(),
@@ -143,9 +165,14 @@ impl WalletConnectCtx {
             key_pair: SymKeyPair::new(),
             session_manager: SessionManager::new(storage),
             pending_requests: Default::default(),
+            pending_proposals: Default::default(),
             message_id_generator,
             abortable_system,
             connection_state_rx,
+            default_chain_id,
+            connect_timeout_secs,
+            mm_ctx: ctx.weak(),
+            event_dispatcher: Default::default(),
         });
 
         // Spawn the relayer connection lifecycle task.
@@ -179,7 +206,7 @@ impl WalletConnectCtxImpl {
         mut conn_status_rx: UnboundedReceiver<Option<String>>,
         connection_state_tx: watch::Sender<ConnectionState>,
     ) {
-        if let Err(err) = self.session_manager.storage().init().await {
+        if let Err(err) = init_storage_with_retry(|| self.session_manager.storage().init()).await {
             error!("Failed to initialize WalletConnect storage, shutting down: {err:?}");
             connection_state_tx.send(ConnectionState::Disconnected).error_log();
             self.abortable_system.abort_all().error_log();
@@ -218,6 +245,26 @@ impl WalletConnectCtxImpl {
         }
     }
 
+    /// Registers a listener of the [`WalletConnectEvent`]s emitted by the sessions,
+    /// e.g. to refresh the coins activated with a session once the wallet switches its accounts.
+    pub async fn add_event_listener(&self, listener: impl EventListener<Event = WalletConnectEvent>) {
+        self.event_dispatcher.write().await.add_listener(listener);
+    }
+
+    /// Delivers the `event` to the registered listeners. Does nothing if the MM context is already dropped.
+    pub(crate) async fn dispatch_event(&self, event: WalletConnectEvent) {
+        let Some(ctx) = MmArc::from_weak(&self.mm_ctx) else {
+            return;
+        };
+        self.event_dispatcher.read().await.dispatch_async(ctx, event).await;
+    }
+
+    /// Returns the current state of the connection to the relay server.
+    pub fn connection_state(&self) -> ConnectionState { *self.connection_state_rx.borrow() }
+
+    /// Whether the relay server connection is currently established and subscribed.
+    pub fn is_connected(&self) -> bool { self.connection_state() == ConnectionState::Connected }
+
     /// Waits until the current state is `Connected`.
     async fn await_connection(&self) -> MmResult<(), WalletConnectError> {
         let mut rx = self.connection_state_rx.clone();
@@ -245,6 +292,9 @@ impl WalletConnectCtxImpl {
     }
 
     /// Attempt to connect to a wallet connection relay server.
+    ///
+    /// Fails with [`WalletConnectError::ConnectTimeout`] if the connection isn't established in time,
+    /// which the connection lifecycle task treats like any other failed attempt and retries with a backoff.
     pub async fn connect_client(&self) -> MmResult<(), WalletConnectError> {
         let auth = {
             let key = SigningKey::generate(&mut rand::thread_rng());
@@ -255,9 +305,8 @@ impl WalletConnectCtxImpl {
                 .map_to_mm(|err| WalletConnectError::InternalError(err.to_string()))?
         };
         let opts = ConnectionOptions::new(PROJECT_ID, auth).with_address(RELAY_ADDRESS);
-        self.client.connect(&opts).await?;
-
-        Ok(())
+        let connect = async { Ok(self.client.connect(&opts).await?) };
+        with_connect_timeout(connect, self.connect_timeout_secs).await
     }
 
     /// Connects to WalletConnect relayer and re-subscribes to previously active session topics if it's a reconnection.
@@ -329,9 +378,14 @@ impl WalletConnectCtxImpl {
 
         info!("[{}] Inbound message payload={message}", msg.topic);
 
-        match serde_json::from_str(&message)? {
-            Payload::Request(request) => process_inbound_request(self, request, &msg.topic).await?,
-            Payload::Response(response) => process_inbound_response(self, response, &msg.topic).await,
+        // Requests are dispatched by their method, which is parsed first so that the peer gets a response
+        // even if we don't know of the method or the params of the method are malformed.
+        match serde_json::from_str::<InboundRequest>(&message) {
+            Ok(request) => process_inbound_request(self, request, &msg.topic).await?,
+            Err(_) => {
+                let response = serde_json::from_str(&message)?;
+                process_inbound_response(self, response, &msg.topic).await
+            },
         }
 
         debug!("[{}] Inbound message was handled successfully", msg.topic);
@@ -397,10 +451,20 @@ impl WalletConnectCtxImpl {
         &self,
         topic: &Topic,
         param: RequestParams,
+    ) -> MmResult<(oneshot::Receiver<SessionMessageType>, Duration), WalletConnectError> {
+        let message_id = self.message_id_generator.next();
+        self.publish_request_with_id(topic, param, message_id).await
+    }
+
+    /// Same as [`WalletConnectCtxImpl::publish_request`], but publishes the request with the given `message_id`.
+    pub(crate) async fn publish_request_with_id(
+        &self,
+        topic: &Topic,
+        param: RequestParams,
+        message_id: MessageId,
     ) -> MmResult<(oneshot::Receiver<SessionMessageType>, Duration), WalletConnectError> {
         let irn_metadata = param.irn_metadata();
         let ttl = irn_metadata.ttl;
-        let message_id = self.message_id_generator.next();
         let request = Request::new(message_id, param.into());
 
         self.publish_payload(topic, irn_metadata, Payload::Request(request))
@@ -482,15 +546,16 @@ impl WalletConnectCtxImpl {
 
     /// Checks if the current session is connected to a Ledger device.
     /// NOTE: for COSMOS chains only.
-    pub fn is_ledger_connection(&self, session_topic: &str) -> bool {
+    pub fn is_ledger_connection(&self, session_topic: &str) -> bool { self.session_properties(session_topic).is_ledger }
+
+    /// Returns a structured view over the properties of the session with the given topic.
+    /// Falls back to the default (empty) view if there is no such session or it has no properties.
+    pub fn session_properties(&self, session_topic: &str) -> SessionPropertiesView {
         let session_topic = session_topic.into();
         self.session_manager
             .get_session(&session_topic)
-            .and_then(|session| session.session_properties)
-            .and_then(|props| props.keys.as_ref().cloned())
-            .and_then(|keys| keys.first().cloned())
-            .map(|key| key.is_nano_ledger)
-            .unwrap_or(false)
+            .map(|session| session.properties())
+            .unwrap_or_default()
     }
 
     /// Checks if the current session is connected via Keplr wallet.
@@ -509,26 +574,7 @@ impl WalletConnectCtxImpl {
         session: &Session,
         chain_id: &WcChainId,
     ) -> MmResult<(), WalletConnectError> {
-        if let Some(Namespace { chains, .. }) = session.namespaces.get(chain_id.chain.as_ref()) {
-            match chains {
-                Some(chains) => {
-                    if chains.contains(&chain_id.to_string()) {
-                        return Ok(());
-                    }
-                },
-                None => {
-                    // https://specs.walletconnect.com/2.0/specs/clients/sign/namespaces#13-chains-might-be-omitted-if-the-caip-2-is-defined-in-the-index
-                    if let Some(SessionProperties { keys: Some(keys) }) = &session.session_properties {
-                        if keys.iter().any(|k| k.chain_id == chain_id.id) {
-                            return Ok(());
-                        }
-                    }
-                },
-            };
-        }
-
-        // https://specs.walletconnect.com/2.0/specs/clients/sign/namespaces#13-chains-might-be-omitted-if-the-caip-2-is-defined-in-the-index
-        if session.namespaces.contains_key(&chain_id.to_string()) {
+        if is_chain_id_approved(&session.namespaces, session.session_properties.as_ref(), chain_id) {
             return Ok(());
         }
 
@@ -664,6 +710,42 @@ impl WalletConnectCtxImpl {
     pub async fn drop_session(&self, topic: &Topic) -> MmResult<(), WalletConnectError> {
         send_session_delete_request(self, topic).await
     }
+
+    /// Rejects the session proposal sent on the pairing `topic` that hasn't been responded to yet,
+    /// e.g. if the user backs out before approving it in the wallet.
+    /// The pairing is deleted and unsubscribed from afterwards.
+    pub async fn reject_proposal(&self, topic: &Topic, reason: RejectionReason) -> MmResult<(), WalletConnectError> {
+        reject_session_proposal(self, topic, reason).await
+    }
+
+    /// Pings the pairing `topic` and waits for the pong.
+    /// Used to detect a dead pairing before proposing a session on it.
+    pub async fn ping_pairing(&self, topic: &Topic) -> MmResult<(), WalletConnectError> {
+        pairing::send_pairing_ping_request(self, topic).await
+    }
+}
+
+/// Reads the optional relay connect timeout from the `wallet_connect.connect_timeout_secs` conf field.
+fn connect_timeout_secs_from_conf(conf: &Json) -> MmResult<f64, WalletConnectError> {
+    let value = &conf["wallet_connect"]["connect_timeout_secs"];
+    if value.is_null() {
+        return Ok(DEFAULT_CONNECT_TIMEOUT_S);
+    }
+
+    match value.as_f64() {
+        Some(secs) if secs > 0. && Duration::try_from_secs_f64(secs).is_ok() => Ok(secs),
+        _ => MmError::err(WalletConnectError::InvalidConnectTimeout(value.to_string())),
+    }
+}
+
+async fn with_connect_timeout<F>(connect: F, timeout_secs: f64) -> MmResult<(), WalletConnectError>
+where
+    F: Future<Output = MmResult<(), WalletConnectError>>,
+{
+    Box::pin(connect)
+        .timeout_secs(timeout_secs)
+        .await
+        .map_to_mm(|_| WalletConnectError::ConnectTimeout(timeout_secs))?
 }
 
 fn find_account_in_namespace<'a>(accounts: &'a BTreeSet<String>, chain_id: &'a str) -> Option<String> {
@@ -676,3 +758,106 @@ fn find_account_in_namespace<'a>(accounts: &'a BTreeSet<String>, chain_id: &'a s
         }
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::block_on;
+    use futures::future;
+
+    #[test]
+    fn test_connect_timeout_secs_from_conf() {
+        use serde_json::json;
+
+        assert_eq!(
+            connect_timeout_secs_from_conf(&json!({})).unwrap(),
+            DEFAULT_CONNECT_TIMEOUT_S
+        );
+        let conf = json!({ "wallet_connect": { "connect_timeout_secs": 5.5 } });
+        assert_eq!(connect_timeout_secs_from_conf(&conf).unwrap(), 5.5);
+
+        for invalid in [json!(0), json!(-1), json!("30"), json!(1e300)] {
+            let conf = json!({ "wallet_connect": { "connect_timeout_secs": invalid } });
+            let err = connect_timeout_secs_from_conf(&conf).unwrap_err();
+            assert!(matches!(err.into_inner(), WalletConnectError::InvalidConnectTimeout(_)));
+        }
+    }
+
+    #[test]
+    fn test_connect_timeout() {
+        // A relay that never completes the handshake.
+        let hanging_connect = future::pending::<MmResult<(), WalletConnectError>>();
+        let err = block_on(with_connect_timeout(hanging_connect, 0.1)).unwrap_err();
+        assert!(matches!(err.into_inner(), WalletConnectError::ConnectTimeout(_)));
+    }
+
+    #[test]
+    #[cfg(not(target_arch = "wasm32"))]
+    fn test_try_init_with_eip155_default_chain_id() {
+        use crate::session::rpc::settle::reply_session_settle_request;
+        use crate::storage::session_storage_tests::sample_test_session;
+        use db_common::async_sql_conn::AsyncConnection;
+        use futures::lock::Mutex as AsyncMutex;
+        use mm2_core::mm_ctx::MmCtxBuilder;
+        use serde_json::json;
+
+        let conf = json!({ "wallet_connect": { "default_chain_id": "eip155:137" } });
+        let ctx = MmCtxBuilder::new().with_conf(conf).into_mm_arc();
+        let conn = block_on(AsyncConnection::open_in_memory()).unwrap();
+        ctx.async_sqlite_connection
+            .set(Arc::new(AsyncMutex::new(conn)))
+            .ok()
+            .unwrap();
+
+        let wallet_connect = WalletConnectCtx::try_init(&ctx).unwrap();
+        let eth_default = WcChainId::new_eip155("137".to_string());
+        assert_eq!(wallet_connect.default_chain_id, Some(eth_default.clone()));
+
+        let storage = wallet_connect.session_manager.storage();
+        block_on(storage.init()).unwrap();
+        let session = sample_test_session(&wallet_connect);
+        let topic = session.topic.clone();
+        block_on(storage.save_session(&session)).unwrap();
+        // The wallet approves both cosmos and eip155 chains.
+        let settle = serde_json::from_value(json!({
+            "relay": session.relay,
+            "controller": session.controller,
+            "namespaces": {
+                "cosmos": {
+                    "chains": ["cosmos:cosmoshub-4"],
+                    "methods": ["cosmos_signDirect"],
+                    "events": [],
+                    "accounts": []
+                },
+                "eip155": {
+                    "chains": ["eip155:1", "eip155:137"],
+                    "methods": ["eth_sendTransaction"],
+                    "events": [],
+                    "accounts": []
+                }
+            },
+            "expiry": session.expiry,
+        }))
+        .unwrap();
+        wallet_connect.session_manager.add_session(session);
+        block_on(reply_session_settle_request(&wallet_connect, &topic, settle)).unwrap();
+
+        // The settled session starts being active on the configured chain.
+        let session = wallet_connect.session_manager.get_session(&topic).unwrap();
+        assert_eq!(session.get_active_chain_id(), &Some(eth_default.clone()));
+
+        // The other approved chains are only validated, the active chain isn't switched until the wallet
+        // can be notified of it with a chainChanged event.
+        let cosmos = WcChainId::new_cosmos("cosmoshub-4".to_string());
+        block_on(wallet_connect.validate_update_active_chain_id(&topic.to_string(), &cosmos)).unwrap();
+        let session = wallet_connect.session_manager.get_session(&topic).unwrap();
+        assert_eq!(session.get_active_chain_id(), &Some(eth_default));
+    }
+
+    #[test]
+    fn test_connect_error_is_not_a_timeout() {
+        let failing_connect = future::ready(MmError::err(WalletConnectError::ClientError("refused".to_string())));
+        let err = block_on(with_connect_timeout(failing_connect, 1.)).unwrap_err();
+        assert!(matches!(err.into_inner(), WalletConnectError::ClientError(_)));
+    }
+}
@@ -1,14 +1,21 @@
+use crate::inbound_message::SessionMessageType;
 use crate::session::{WcRequestResponseResult, THIRTY_DAYS};
 use crate::{error::WalletConnectError, WalletConnectCtxImpl};
 
 use chrono::Utc;
-use mm2_err_handle::prelude::MmResult;
+use common::custom_futures::timeout::FutureTimerExt;
+use mm2_err_handle::prelude::*;
 use relay_rpc::domain::MessageId;
 use relay_rpc::rpc::params::pairing_ping::PairingPingRequest;
 use relay_rpc::rpc::params::{RelayProtocolMetadata, RequestParams};
 use relay_rpc::{domain::Topic,
                 rpc::params::{pairing_delete::PairingDeleteRequest, pairing_extend::PairingExtendRequest,
                               ResponseParamsSuccess}};
+use std::time::Duration;
+use tokio::sync::oneshot;
+
+/// The maximum time to wait for a pong after pinging a pairing.
+const PAIRING_PING_TIMEOUT_SECS: u64 = 30;
 
 pub(crate) async fn reply_pairing_ping_response(
     ctx: &WalletConnectCtxImpl,
@@ -46,3 +53,81 @@ pub(crate) async fn reply_pairing_delete_response(
 
     Ok(())
 }
+
+/// Pings the pairing `topic` and waits for the pong.
+pub(crate) async fn send_pairing_ping_request(
+    ctx: &WalletConnectCtxImpl,
+    topic: &Topic,
+) -> MmResult<(), WalletConnectError> {
+    let param = RequestParams::PairingPing(PairingPingRequest {});
+    let (rx, ttl) = ctx.publish_request(topic, param).await?;
+    let timeout = ttl.min(Duration::from_secs(PAIRING_PING_TIMEOUT_SECS));
+    wait_for_pairing_pong(topic, rx, timeout).await
+}
+
+async fn wait_for_pairing_pong(
+    topic: &Topic,
+    rx: oneshot::Receiver<SessionMessageType>,
+    timeout: Duration,
+) -> MmResult<(), WalletConnectError> {
+    // The pong is a bare `true` result which is deserialized as whichever boolean variant of the untagged
+    // `ResponseParamsSuccess` comes first, so any successful response is the pong (like for session pings).
+    rx.timeout(timeout)
+        .await
+        .map_to_mm(|_| WalletConnectError::PairingNotResponding(topic.to_string()))?
+        .map_to_mm(|err| WalletConnectError::InternalError(err.to_string()))??;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::inbound_message::SessionMessage;
+    use common::block_on;
+    use relay_client::MessageIdGenerator;
+
+    /// Builds the pong the way `process_inbound_response` does from the `result` of a success response.
+    fn pong(topic: &Topic, result: serde_json::Value) -> SessionMessageType {
+        Ok(SessionMessage {
+            message_id: MessageIdGenerator::new().next(),
+            topic: topic.clone(),
+            data: serde_json::from_value::<ResponseParamsSuccess>(result).unwrap(),
+        })
+    }
+
+    #[test]
+    fn test_wait_for_pairing_pong() {
+        let topic: Topic = "pairing_topic".to_string().into();
+        let (tx, rx) = oneshot::channel();
+        tx.send(pong(&topic, serde_json::json!(true))).unwrap();
+
+        block_on(wait_for_pairing_pong(&topic, rx, Duration::from_secs(1))).unwrap();
+    }
+
+    #[test]
+    fn test_wait_for_pairing_pong_timeout() {
+        let topic: Topic = "pairing_topic".to_string().into();
+        // Keep the sender alive so that the pong never arrives.
+        let (_tx, rx) = oneshot::channel();
+
+        let err = block_on(wait_for_pairing_pong(&topic, rx, Duration::from_millis(100))).unwrap_err();
+        match err.into_inner() {
+            WalletConnectError::PairingNotResponding(not_responding) => assert_eq!(not_responding, topic.to_string()),
+            err => panic!("Expected PairingNotResponding, found {err:?}"),
+        }
+    }
+
+    #[test]
+    fn test_wait_for_pairing_pong_error_response() {
+        let topic: Topic = "pairing_topic".to_string().into();
+        let (tx, rx) = oneshot::channel();
+        tx.send(MmError::err(WalletConnectError::UnSuccessfulResponse(
+            "pairing not found".to_owned(),
+        )))
+        .unwrap();
+
+        let err = block_on(wait_for_pairing_pong(&topic, rx, Duration::from_secs(1))).unwrap_err();
+        assert!(matches!(err.into_inner(), WalletConnectError::UnSuccessfulResponse(_)));
+    }
+}
@@ -70,6 +70,35 @@ pub struct SessionProperties {
     pub keys: Option<Vec<KeyInfo>>,
 }
 
+impl SessionProperties {
+    /// Returns a structured view over the shared keys.
+    pub fn view(&self) -> SessionPropertiesView {
+        let keys = self.keys.as_deref().unwrap_or_default();
+        let primary_key = keys.first();
+        SessionPropertiesView {
+            is_ledger: primary_key.map_or(false, |key| key.is_nano_ledger),
+            is_keystone: primary_key.map_or(false, |key| key.is_keystone),
+            key_algos: keys.iter().map(|key| key.algo.clone()).collect(),
+            bech32_addresses: keys.iter().map(|key| key.bech32_address.clone()).collect(),
+            ethereum_hex_addresses: keys.iter().map(|key| key.ethereum_hex_address.clone()).collect(),
+        }
+    }
+}
+
+/// A structured view over [`SessionProperties`].
+/// Defaults to no keys and no hardware wallet if the session has no properties at all.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct SessionPropertiesView {
+    /// Whether the primary (first) key is held by a Ledger device.
+    pub is_ledger: bool,
+    /// Whether the primary (first) key is held by a Keystone device.
+    pub is_keystone: bool,
+    /// The signing algorithm of each key.
+    pub key_algos: Vec<String>,
+    pub bech32_addresses: Vec<String>,
+    pub ethereum_hex_addresses: Vec<String>,
+}
+
 fn deserialize_keys_from_string<'de, D>(deserializer: D) -> Result<Option<Vec<KeyInfo>>, D::Error>
 where
     D: Deserializer<'de>,
@@ -201,6 +230,14 @@ impl Session {
 
     /// Sets the active chain ID for the current session.
     pub fn set_active_chain_id(&mut self, chain_id: WcChainId) { self.active_chain_id = Some(chain_id); }
+
+    /// Returns a structured view over the session properties.
+    pub fn properties(&self) -> SessionPropertiesView {
+        self.session_properties
+            .as_ref()
+            .map(SessionProperties::view)
+            .unwrap_or_default()
+    }
 }
 
 /// Internal implementation of session management.
@@ -373,4 +410,49 @@ mod tests {
         let deserialized: SessionProperties = serde_json::from_str(&serialized).unwrap();
         assert_eq!(original, deserialized);
     }
+
+    #[test]
+    fn test_session_properties_view() {
+        let ledger_key = KeyInfo {
+            is_nano_ledger: true,
+            ..create_sample_key_info()
+        };
+        let eth_key = KeyInfo {
+            chain_id: "test-chain-2".to_string(),
+            algo: "ethsecp256k1".to_string(),
+            bech32_address: "bech32_test_address_2".to_string(),
+            ethereum_hex_address: "0xtest_eth_address_2".to_string(),
+            is_keystone: true,
+            ..create_sample_key_info()
+        };
+        let properties = SessionProperties {
+            keys: Some(vec![ledger_key, eth_key]),
+        };
+
+        let view = properties.view();
+        assert!(view.is_ledger);
+        // Only the primary key defines the hardware wallet type.
+        assert!(!view.is_keystone);
+        assert_eq!(view.key_algos, vec!["secp256k1", "ethsecp256k1"]);
+        assert_eq!(view.bech32_addresses, vec![
+            "bech32_test_address",
+            "bech32_test_address_2"
+        ]);
+        assert_eq!(view.ethereum_hex_addresses, vec![
+            "0xtest_eth_address",
+            "0xtest_eth_address_2"
+        ]);
+    }
+
+    #[test]
+    fn test_session_properties_view_fallback() {
+        let properties = SessionProperties { keys: None };
+        assert_eq!(properties.view(), SessionPropertiesView::default());
+
+        let view = SessionProperties { keys: Some(vec![]) }.view();
+        assert!(!view.is_ledger && !view.is_keystone);
+        assert!(view.key_algos.is_empty());
+        assert!(view.bech32_addresses.is_empty());
+        assert!(view.ethereum_hex_addresses.is_empty());
+    }
 }
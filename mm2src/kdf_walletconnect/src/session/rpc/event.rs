@@ -1,12 +1,96 @@
 use crate::{chain::{WcChain, WcChainId},
             error::{WalletConnectError, UNSUPPORTED_CHAINS},
+            storage::WalletConnectStorageOps,
             WalletConnectCtxImpl};
 
-use common::log::{error, info};
+use common::log::{info, warn};
+use mm2_core::event_dispatcher::EventUniqueId;
 use mm2_err_handle::prelude::*;
 use relay_rpc::{domain::{MessageId, Topic},
                 rpc::{params::{session_event::SessionEventRequest, ResponseParamsError},
                       ErrorData}};
+use std::any::TypeId;
+use std::collections::BTreeSet;
+
+/// Internal events about the changes of the WalletConnect sessions,
+/// delivered to the listeners registered with [`WalletConnectCtxImpl::add_event_listener`].
+#[derive(Clone, Debug, PartialEq)]
+pub enum WalletConnectEvent {
+    AccountsChanged(AccountsChanged),
+    ChainChanged(ChainChanged),
+}
+
+impl EventUniqueId for WalletConnectEvent {
+    fn event_id(&self) -> TypeId {
+        match self {
+            WalletConnectEvent::AccountsChanged(_) => AccountsChanged::event_id(),
+            WalletConnectEvent::ChainChanged(_) => ChainChanged::event_id(),
+        }
+    }
+}
+
+/// The wallet switched the accounts of the session `topic` on the `chain_id` chain.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AccountsChanged {
+    pub topic: Topic,
+    pub chain_id: WcChainId,
+    /// The new CAIP-10 accounts of the chain, e.g. `eip155:1:0xab16a96d359ec26a11e2c2b3d8f8b8942d5bfcdb`.
+    pub accounts: Vec<String>,
+}
+
+impl AccountsChanged {
+    pub fn event_id() -> TypeId { TypeId::of::<AccountsChanged>() }
+}
+
+/// The wallet switched the session `topic` to the `chain_id` chain, which is now the active chain of the session.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ChainChanged {
+    pub topic: Topic,
+    pub chain_id: WcChainId,
+}
+
+impl ChainChanged {
+    pub fn event_id() -> TypeId { TypeId::of::<ChainChanged>() }
+}
+
+/// Makes `new_chain` the active chain of the session `topic` and emits [`ChainChanged`].
+/// Returns `false` without switching if the session doesn't support `new_chain`.
+async fn switch_active_chain(
+    ctx: &WalletConnectCtxImpl,
+    topic: &Topic,
+    new_chain: WcChainId,
+) -> MmResult<bool, WalletConnectError> {
+    let session = {
+        let mut sessions = ctx.session_manager.write();
+        let session = sessions
+            .get_mut(topic)
+            .ok_or(MmError::new(WalletConnectError::SessionError(
+                "No active WalletConnect session found".to_string(),
+            )))?;
+        if session.get_active_chain_id().as_ref() == Some(&new_chain) {
+            return Ok(true);
+        }
+        if let Err(err) = ctx.validate_chain_id(session, &new_chain) {
+            warn!("[{topic}] Wallet switched to an unsupported chain, keeping the active one: {err}");
+            return Ok(false);
+        }
+        session.set_active_chain_id(new_chain.clone());
+        session.clone()
+    };
+
+    ctx.session_manager
+        .storage()
+        .update_session(&session)
+        .await
+        .mm_err(|err| WalletConnectError::StorageError(err.to_string()))?;
+
+    let event = ChainChanged {
+        topic: topic.clone(),
+        chain_id: new_chain,
+    };
+    ctx.dispatch_event(WalletConnectEvent::ChainChanged(event)).await;
+    Ok(true)
+}
 
 pub async fn handle_session_event(
     ctx: &WalletConnectCtxImpl,
@@ -32,15 +116,9 @@ pub async fn handle_session_event(
 
             ctx.validate_chain_id(&session, &chain_id)?;
 
-            if session.get_active_chain_id().as_ref().map_or(false, |c| c == &chain_id) {
-                return Ok(());
-            };
-
-            // check if if new chain_id is supported.
             let new_id = serde_json::from_value::<u32>(event.event.data)?;
             let new_chain = chain_id.chain.derive_chain_id(new_id.to_string());
-            if let Err(err) = ctx.validate_chain_id(&session, &new_chain) {
-                error!("[{topic}] {err:?}");
+            if !switch_active_chain(ctx, topic, new_chain).await? {
                 let error_data = ErrorData {
                     code: UNSUPPORTED_CHAINS,
                     message: "Unsupported chain id".to_string(),
@@ -48,20 +126,47 @@ pub async fn handle_session_event(
                 };
                 let params = ResponseParamsError::SessionEvent(error_data);
                 ctx.publish_response_err(topic, params, message_id).await?;
-            } else {
-                {
-                    ctx.session_manager
-                        .write()
-                        .get_mut(topic)
-                        .ok_or(MmError::new(WalletConnectError::SessionError(
-                            "No active WalletConnect session found".to_string(),
-                        )))?
-                        .set_active_chain_id(chain_id);
-                }
             };
         },
         "accountsChanged" => {
-            // TODO: Handle accountsChanged event logic.
+            let addresses = serde_json::from_value::<Vec<String>>(event.event.data)?;
+            let accounts: Vec<String> = addresses
+                .iter()
+                .map(|address| format!("{chain_id}:{address}"))
+                .collect();
+
+            let session = {
+                let mut sessions = ctx.session_manager.write();
+                let session = sessions
+                    .get_mut(topic)
+                    .ok_or(MmError::new(WalletConnectError::SessionError(
+                        "No active WalletConnect session found".to_string(),
+                    )))?;
+                ctx.validate_chain_id(session, &chain_id)?;
+
+                let namespace = session.namespaces.get_mut(chain_id.chain.as_ref()).ok_or(MmError::new(
+                    WalletConnectError::ChainIdNotSupported(chain_id.to_string()),
+                ))?;
+                // Only the accounts of the given chain are replaced, the other chains of the namespace keep theirs.
+                let chain_prefix = format!("{chain_id}:");
+                let namespace_accounts = namespace.accounts.get_or_insert_with(BTreeSet::new);
+                namespace_accounts.retain(|account| !account.starts_with(&chain_prefix));
+                namespace_accounts.extend(accounts.iter().cloned());
+                session.clone()
+            };
+
+            ctx.session_manager
+                .storage()
+                .update_session(&session)
+                .await
+                .mm_err(|err| WalletConnectError::StorageError(err.to_string()))?;
+
+            let event = AccountsChanged {
+                topic: topic.clone(),
+                chain_id,
+                accounts,
+            };
+            ctx.dispatch_event(WalletConnectEvent::AccountsChanged(event)).await;
         },
         _ => {
             // TODO: Handle other event logic.,
@@ -71,3 +176,159 @@ pub async fn handle_session_event(
     info!("[{topic}] {event_name} event handled successfully");
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    common::cfg_wasm32! {
+        use wasm_bindgen_test::*;
+        wasm_bindgen_test::wasm_bindgen_test_configure!(run_in_browser);
+    }
+    use super::*;
+    use crate::storage::session_storage_tests::sample_test_session;
+    use crate::WalletConnectCtx;
+    use async_trait::async_trait;
+    use common::cross_test;
+    use mm2_core::event_dispatcher::EventListener;
+    use mm2_core::mm_ctx::MmArc;
+    use mm2_test_helpers::for_tests::mm_ctx_with_custom_async_db;
+    use relay_client::MessageIdGenerator;
+    use serde_json::{json, Value as Json};
+    use std::sync::{Arc, Mutex};
+
+    /// Records the events of the `desired` type.
+    struct RecordingListener {
+        desired: TypeId,
+        received: Arc<Mutex<Vec<WalletConnectEvent>>>,
+    }
+
+    #[async_trait]
+    impl EventListener for RecordingListener {
+        type Event = WalletConnectEvent;
+
+        async fn process_event_async(&self, _ctx: MmArc, event: Self::Event) {
+            self.received.lock().unwrap().push(event);
+        }
+
+        fn get_desired_events(&self) -> Vec<TypeId> { vec![self.desired] }
+
+        fn listener_id(&self) -> &'static str { "recording_listener" }
+    }
+
+    /// Initializes a WalletConnect context with a stored session approving the given `namespaces`,
+    /// and a listener recording the events of the `desired` type.
+    /// The returned MM context must be kept alive for the listener to be called.
+    async fn init_with_session(
+        namespaces: Json,
+        active_chain_id: Option<WcChainId>,
+        desired: TypeId,
+    ) -> (MmArc, WalletConnectCtx, Topic, Arc<Mutex<Vec<WalletConnectEvent>>>) {
+        let mm_ctx = mm_ctx_with_custom_async_db().await;
+        let wc_ctx = WalletConnectCtx::try_init(&mm_ctx).unwrap();
+        wc_ctx.session_manager.storage().init().await.unwrap();
+        let received = Arc::new(Mutex::new(Vec::new()));
+        wc_ctx
+            .add_event_listener(RecordingListener {
+                desired,
+                received: received.clone(),
+            })
+            .await;
+
+        let mut session = sample_test_session(&wc_ctx);
+        session.namespaces = serde_json::from_value(namespaces).unwrap();
+        session.active_chain_id = active_chain_id;
+        let topic = session.topic.clone();
+        wc_ctx.session_manager.storage().save_session(&session).await.unwrap();
+        wc_ctx.session_manager.add_session(session);
+        (mm_ctx, wc_ctx, topic, received)
+    }
+
+    cross_test!(accounts_changed_event_test, {
+        let namespaces = json!({
+            "eip155": {
+                "chains": ["eip155:1", "eip155:137"],
+                "methods": ["eth_sendTransaction"],
+                "events": ["accountsChanged"],
+                "accounts": ["eip155:1:0xold", "eip155:137:0xpolygon"]
+            }
+        });
+        let (_mm_ctx, wc_ctx, topic, received) = init_with_session(namespaces, None, AccountsChanged::event_id()).await;
+
+        let event: SessionEventRequest = serde_json::from_value(json!({
+            "event": { "name": "accountsChanged", "data": ["0xnew"] },
+            "chainId": "eip155:1"
+        }))
+        .unwrap();
+        handle_session_event(&wc_ctx, &topic, &MessageIdGenerator::new().next(), event)
+            .await
+            .unwrap();
+
+        let expected_accounts: BTreeSet<String> = ["eip155:1:0xnew".to_string(), "eip155:137:0xpolygon".to_string()]
+            .into_iter()
+            .collect();
+        let session = wc_ctx.session_manager.get_session(&topic).unwrap();
+        assert_eq!(session.namespaces["eip155"].accounts, Some(expected_accounts.clone()));
+        let stored = wc_ctx
+            .session_manager
+            .storage()
+            .get_session(&topic)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored.namespaces["eip155"].accounts, Some(expected_accounts));
+
+        let expected_event = WalletConnectEvent::AccountsChanged(AccountsChanged {
+            topic,
+            chain_id: WcChainId::new_eip155("1".to_string()),
+            accounts: vec!["eip155:1:0xnew".to_string()],
+        });
+        assert_eq!(*received.lock().unwrap(), vec![expected_event]);
+    });
+
+    cross_test!(chain_changed_event_test, {
+        let namespaces = json!({
+            "eip155": {
+                "chains": ["eip155:1", "eip155:137"],
+                "methods": ["eth_sendTransaction"],
+                "events": ["chainChanged"],
+                "accounts": ["eip155:1:0xabc", "eip155:137:0xabc"]
+            }
+        });
+        let ethereum = WcChainId::new_eip155("1".to_string());
+        let (_mm_ctx, wc_ctx, topic, received) =
+            init_with_session(namespaces, Some(ethereum), ChainChanged::event_id()).await;
+
+        // The wallet switches from Ethereum to Polygon, which is supported by the session.
+        let event: SessionEventRequest = serde_json::from_value(json!({
+            "event": { "name": "chainChanged", "data": 137 },
+            "chainId": "eip155:1"
+        }))
+        .unwrap();
+        handle_session_event(&wc_ctx, &topic, &MessageIdGenerator::new().next(), event)
+            .await
+            .unwrap();
+
+        let polygon = WcChainId::new_eip155("137".to_string());
+        let session = wc_ctx.session_manager.get_session(&topic).unwrap();
+        assert_eq!(session.get_active_chain_id(), &Some(polygon.clone()));
+        let stored = wc_ctx
+            .session_manager
+            .storage()
+            .get_session(&topic)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored.get_active_chain_id(), &Some(polygon.clone()));
+        let expected_event = WalletConnectEvent::ChainChanged(ChainChanged {
+            topic: topic.clone(),
+            chain_id: polygon.clone(),
+        });
+        assert_eq!(*received.lock().unwrap(), vec![expected_event.clone()]);
+
+        // BSC is not a part of the session, so the active chain stays the same.
+        let bsc = WcChainId::new_eip155("56".to_string());
+        assert!(!switch_active_chain(&wc_ctx, &topic, bsc).await.unwrap());
+        let session = wc_ctx.session_manager.get_session(&topic).unwrap();
+        assert_eq!(session.get_active_chain_id(), &Some(polygon));
+        assert_eq!(*received.lock().unwrap(), vec![expected_event]);
+    });
+}
@@ -1,17 +1,154 @@
 use super::settle::send_session_settle_request;
 use crate::storage::WalletConnectStorageOps;
-use crate::{error::WalletConnectError,
+use crate::{error::{WalletConnectError, UNSUPPORTED_CHAINS, UNSUPPORTED_EVENTS, UNSUPPORTED_METHODS, USER_REJECTED,
+                    USER_REJECTED_CHAINS, USER_REJECTED_EVENTS, USER_REJECTED_METHODS},
             metadata::generate_metadata,
-            session::{Session, SessionKey, SessionType, THIRTY_DAYS},
+            session::{Session, SessionKey, SessionType, FIVE_MINUTES, THIRTY_DAYS},
             WalletConnectCtxImpl};
 
+use async_trait::async_trait;
 use chrono::Utc;
+use common::log::LogOnError;
 use mm2_err_handle::map_to_mm::MapToMmResult;
 use mm2_err_handle::prelude::*;
+use relay_rpc::rpc::params::pairing_delete::PairingDeleteRequest;
 use relay_rpc::rpc::params::session::ProposeNamespaces;
+use relay_rpc::rpc::ErrorData;
 use relay_rpc::{domain::{MessageId, Topic},
                 rpc::params::{session_propose::{Proposer, SessionProposeRequest, SessionProposeResponse},
                               RequestParams, ResponseParamsSuccess}};
+use std::sync::Mutex;
+use std::time::Duration;
+use timed_map::TimedMap;
+
+/// Why a session proposal is rejected.
+/// https://specs.walletconnect.com/2.0/specs/clients/sign/error-codes
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RejectionReason {
+    UserRejected,
+    UserRejectedChains,
+    UserRejectedMethods,
+    UserRejectedEvents,
+    UnsupportedChains,
+    UnsupportedMethods,
+    UnsupportedEvents,
+}
+
+impl RejectionReason {
+    fn error_data(self) -> ErrorData {
+        let (code, message) = match self {
+            RejectionReason::UserRejected => (USER_REJECTED, "User rejected"),
+            RejectionReason::UserRejectedChains => (USER_REJECTED_CHAINS, "User rejected chains"),
+            RejectionReason::UserRejectedMethods => (USER_REJECTED_METHODS, "User rejected methods"),
+            RejectionReason::UserRejectedEvents => (USER_REJECTED_EVENTS, "User rejected events"),
+            RejectionReason::UnsupportedChains => (UNSUPPORTED_CHAINS, "Unsupported chains"),
+            RejectionReason::UnsupportedMethods => (UNSUPPORTED_METHODS, "Unsupported methods"),
+            RejectionReason::UnsupportedEvents => (UNSUPPORTED_EVENTS, "Unsupported events"),
+        };
+        ErrorData {
+            code,
+            message: message.to_owned(),
+            data: None,
+        }
+    }
+}
+
+/// Session proposals sent by us that haven't been responded to yet.
+/// Like the pending requests, they expire after a while if the peer never responds.
+#[derive(Default)]
+pub(crate) struct PendingProposals(Mutex<PendingProposalsInner>);
+
+#[derive(Default)]
+struct PendingProposalsInner {
+    by_topic: TimedMap<Topic, MessageId>,
+    by_message_id: TimedMap<MessageId, Topic>,
+}
+
+impl PendingProposals {
+    fn insert(&self, pairing_topic: Topic, message_id: MessageId) {
+        let expiry = Duration::from_secs(FIVE_MINUTES);
+        let mut inner = self.0.lock().expect("pending proposals lock shouldn't fail!");
+        // Forget the previous proposal sent on the same topic, if any.
+        if let Some(previous_id) = inner.by_topic.remove(&pairing_topic) {
+            inner.by_message_id.remove(&previous_id);
+        }
+        inner
+            .by_topic
+            .insert_expirable(pairing_topic.clone(), message_id, expiry);
+        inner.by_message_id.insert_expirable(message_id, pairing_topic, expiry);
+    }
+
+    /// Removes the proposal sent on the `pairing_topic` and returns its message ID.
+    fn remove(&self, pairing_topic: &Topic) -> MmResult<MessageId, WalletConnectError> {
+        let mut inner = self.0.lock().expect("pending proposals lock shouldn't fail!");
+        let message_id = inner
+            .by_topic
+            .remove(pairing_topic)
+            .or_mm_err(|| WalletConnectError::NoPendingProposal(pairing_topic.to_string()))?;
+        inner.by_message_id.remove(&message_id);
+        Ok(message_id)
+    }
+
+    /// Removes the proposal the `message_id` response is for, if any.
+    pub(crate) fn remove_by_message_id(&self, message_id: &MessageId) {
+        let mut inner = self.0.lock().expect("pending proposals lock shouldn't fail!");
+        if let Some(pairing_topic) = inner.by_message_id.remove(message_id) {
+            inner.by_topic.remove(&pairing_topic);
+        }
+    }
+
+    #[cfg(test)]
+    fn contains(&self, pairing_topic: &Topic) -> bool {
+        let inner = self.0.lock().expect("pending proposals lock shouldn't fail!");
+        inner.by_topic.get(pairing_topic).is_some()
+    }
+}
+
+/// The relay side effects of rejecting a session proposal, abstracted so they can be checked in tests.
+#[async_trait]
+trait ProposalRejectionRelay {
+    /// Sends a `wc_pairingDelete` request with the rejection `reason` to the peer on the `pairing_topic`.
+    async fn send_pairing_delete(
+        &self,
+        pairing_topic: &Topic,
+        reason: RejectionReason,
+    ) -> MmResult<(), WalletConnectError>;
+
+    async fn unsubscribe(&self, pairing_topic: &Topic) -> MmResult<(), WalletConnectError>;
+
+    /// Stops awaiting the response to the proposal with `proposal_id` and deletes the pairing.
+    fn forget_pairing(&self, pairing_topic: &Topic, proposal_id: &MessageId);
+}
+
+#[async_trait]
+impl ProposalRejectionRelay for WalletConnectCtxImpl {
+    async fn send_pairing_delete(
+        &self,
+        pairing_topic: &Topic,
+        reason: RejectionReason,
+    ) -> MmResult<(), WalletConnectError> {
+        let error = reason.error_data();
+        let param = RequestParams::PairingDelete(PairingDeleteRequest {
+            code: error.code.into(),
+            message: error.message,
+        });
+        self.publish_request(pairing_topic, param).await?;
+        Ok(())
+    }
+
+    async fn unsubscribe(&self, pairing_topic: &Topic) -> MmResult<(), WalletConnectError> {
+        self.client.unsubscribe(pairing_topic.clone()).await?;
+        Ok(())
+    }
+
+    fn forget_pairing(&self, pairing_topic: &Topic, proposal_id: &MessageId) {
+        self.pending_requests
+            .lock()
+            .expect("pending request lock shouldn't fail!")
+            .remove(proposal_id);
+        self.pairing.delete(pairing_topic);
+    }
+}
 
 /// Creates a new session proposal from topic and metadata.
 pub(crate) async fn send_proposal_request(
@@ -30,11 +167,49 @@ pub(crate) async fn send_proposal_request(
         required_namespaces,
         optional_namespaces: Some(optional_namespaces),
     });
-    let _ = ctx.publish_request(topic, session_proposal).await?;
+
+    // Register the proposal before publishing it, so that a quick response doesn't race with the registration.
+    let message_id = ctx.message_id_generator.next();
+    ctx.pending_proposals.insert(topic.clone(), message_id);
+    if let Err(err) = ctx.publish_request_with_id(topic, session_proposal, message_id).await {
+        ctx.pending_proposals.remove_by_message_id(&message_id);
+        return Err(err);
+    }
 
     Ok(())
 }
 
+/// Rejects the session proposal sent on the `pairing_topic` by deleting the pairing with the peer,
+/// then unsubscribes from the pairing topic.
+/// Returns [`WalletConnectError::NoPendingProposal`] if there is no proposal awaiting a response on the topic.
+pub(crate) async fn reject_session_proposal(
+    ctx: &WalletConnectCtxImpl,
+    pairing_topic: &Topic,
+    reason: RejectionReason,
+) -> MmResult<(), WalletConnectError> {
+    reject_pending_proposal(ctx, &ctx.pending_proposals, pairing_topic, reason).await
+}
+
+async fn reject_pending_proposal(
+    relay: &(impl ProposalRejectionRelay + Sync),
+    pending_proposals: &PendingProposals,
+    pairing_topic: &Topic,
+    reason: RejectionReason,
+) -> MmResult<(), WalletConnectError> {
+    let proposal_id = pending_proposals.remove(pairing_topic)?;
+    // The pairing delete is encrypted with the pairing key, so it's published before the pairing is forgotten.
+    let deleted = relay.send_pairing_delete(pairing_topic, reason).await;
+
+    // Clean up the pairing and the subscription even if the peer couldn't be notified.
+    relay.forget_pairing(pairing_topic, &proposal_id);
+    relay
+        .unsubscribe(pairing_topic)
+        .await
+        .error_log_with_msg(&format!("[{pairing_topic}] Unable to unsubscribe from the pairing"));
+
+    deleted
+}
+
 /// Process session proposal request
 /// https://specs.walletconnect.com/2.0/specs/clients/sign/session-proposal
 pub async fn reply_session_proposal_request(
@@ -150,3 +325,152 @@ pub(crate) async fn process_session_propose_response(
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::block_on;
+    use relay_client::MessageIdGenerator;
+
+    /// Records the relay side effects of a rejection.
+    /// Like the real relay, it can only publish on the topics of the pairings it still knows the keys of.
+    #[derive(Default)]
+    struct RecordingRelay {
+        pairings: Mutex<Vec<Topic>>,
+        pairing_deletes: Mutex<Vec<(Topic, RejectionReason)>>,
+        unsubscribed: Mutex<Vec<Topic>>,
+        forgotten: Mutex<Vec<(Topic, MessageId)>>,
+    }
+
+    impl RecordingRelay {
+        fn with_pairing(pairing_topic: &Topic) -> Self {
+            RecordingRelay {
+                pairings: Mutex::new(vec![pairing_topic.clone()]),
+                ..Default::default()
+            }
+        }
+    }
+
+    #[async_trait]
+    impl ProposalRejectionRelay for RecordingRelay {
+        async fn send_pairing_delete(
+            &self,
+            pairing_topic: &Topic,
+            reason: RejectionReason,
+        ) -> MmResult<(), WalletConnectError> {
+            if !self.pairings.lock().unwrap().contains(pairing_topic) {
+                return MmError::err(WalletConnectError::InternalError(format!(
+                    "topic sym_key not found: {pairing_topic}"
+                )));
+            }
+            self.pairing_deletes
+                .lock()
+                .unwrap()
+                .push((pairing_topic.clone(), reason));
+            Ok(())
+        }
+
+        async fn unsubscribe(&self, pairing_topic: &Topic) -> MmResult<(), WalletConnectError> {
+            self.unsubscribed.lock().unwrap().push(pairing_topic.clone());
+            Ok(())
+        }
+
+        fn forget_pairing(&self, pairing_topic: &Topic, proposal_id: &MessageId) {
+            self.pairings.lock().unwrap().retain(|topic| topic != pairing_topic);
+            self.forgotten
+                .lock()
+                .unwrap()
+                .push((pairing_topic.clone(), *proposal_id));
+        }
+    }
+
+    #[test]
+    fn test_reject_pending_proposal() {
+        let pairing_topic: Topic = "pairing".to_string().into();
+        let relay = RecordingRelay::with_pairing(&pairing_topic);
+        let proposals = PendingProposals::default();
+        let proposal_id = MessageIdGenerator::new().next();
+        proposals.insert(pairing_topic.clone(), proposal_id);
+
+        block_on(reject_pending_proposal(
+            &relay,
+            &proposals,
+            &pairing_topic,
+            RejectionReason::UserRejected,
+        ))
+        .unwrap();
+
+        assert!(!proposals.contains(&pairing_topic));
+        assert_eq!(*relay.pairing_deletes.lock().unwrap(), vec![(
+            pairing_topic.clone(),
+            RejectionReason::UserRejected
+        )]);
+        assert_eq!(*relay.unsubscribed.lock().unwrap(), vec![pairing_topic.clone()]);
+        assert_eq!(*relay.forgotten.lock().unwrap(), vec![(
+            pairing_topic.clone(),
+            proposal_id
+        )]);
+
+        // The proposal can't be rejected twice.
+        let err = block_on(reject_pending_proposal(
+            &relay,
+            &proposals,
+            &pairing_topic,
+            RejectionReason::UserRejected,
+        ))
+        .unwrap_err();
+        assert!(matches!(err.into_inner(), WalletConnectError::NoPendingProposal(_)));
+        assert_eq!(relay.unsubscribed.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_reject_pending_proposal_publishes_before_forgetting_pairing() {
+        let pairing_topic: Topic = "pairing".to_string().into();
+        let relay = RecordingRelay::with_pairing(&pairing_topic);
+        let proposals = PendingProposals::default();
+        proposals.insert(pairing_topic.clone(), MessageIdGenerator::new().next());
+
+        block_on(reject_pending_proposal(
+            &relay,
+            &proposals,
+            &pairing_topic,
+            RejectionReason::UserRejected,
+        ))
+        .unwrap();
+        assert_eq!(relay.pairing_deletes.lock().unwrap().len(), 1);
+
+        // The pairing key is gone once the pairing is forgotten, so nothing can be published on it anymore.
+        let err = block_on(relay.send_pairing_delete(&pairing_topic, RejectionReason::UserRejected)).unwrap_err();
+        assert!(matches!(err.into_inner(), WalletConnectError::InternalError(_)));
+    }
+
+    #[test]
+    fn test_pending_proposals() {
+        let proposals = PendingProposals::default();
+        let ids = MessageIdGenerator::new();
+        let (rejected_topic, responded_topic): (Topic, Topic) =
+            ("rejected".to_string().into(), "responded".to_string().into());
+        let (rejected_id, responded_id) = (ids.next(), ids.next());
+        proposals.insert(rejected_topic.clone(), rejected_id);
+        proposals.insert(responded_topic.clone(), responded_id);
+
+        // Rejecting the proposal takes it out of the pending ones.
+        assert_eq!(proposals.remove(&rejected_topic).unwrap(), rejected_id);
+        let err = proposals.remove(&rejected_topic).unwrap_err();
+        match err.into_inner() {
+            WalletConnectError::NoPendingProposal(topic) => assert_eq!(topic, rejected_topic.to_string()),
+            err => panic!("Expected NoPendingProposal, found {err:?}"),
+        }
+
+        // A proposal the peer has responded to can't be rejected anymore.
+        proposals.remove_by_message_id(&responded_id);
+        assert!(proposals.remove(&responded_topic).is_err());
+    }
+
+    #[test]
+    fn test_rejection_reason_error_data() {
+        let error = RejectionReason::UserRejected.error_data();
+        assert_eq!(error.code, USER_REJECTED);
+        assert_eq!(RejectionReason::UnsupportedChains.error_data().code, UNSUPPORTED_CHAINS);
+    }
+}
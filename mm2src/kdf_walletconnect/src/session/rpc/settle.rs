@@ -1,3 +1,4 @@
+use crate::chain::default_active_chain_id;
 use crate::session::{EncodingAlgo, Session, SessionProperties};
 use crate::storage::WalletConnectStorageOps;
 use crate::{error::WalletConnectError, WalletConnectCtxImpl};
@@ -56,6 +57,13 @@ pub(crate) async fn reply_session_settle_request(
         session.controller = settle.controller;
         session.relay = settle.relay;
         session.expiry = settle.expiry;
+        if session.active_chain_id.is_none() {
+            session.active_chain_id = default_active_chain_id(
+                ctx.default_chain_id.as_ref(),
+                &session.namespaces,
+                session.session_properties.as_ref(),
+            );
+        }
 
         session.clone()
     };
@@ -1,6 +1,9 @@
+use std::future::Future;
 use std::ops::Deref;
 
 use async_trait::async_trait;
+use common::log::warn;
+use common::retry_on_err;
 use mm2_core::mm_ctx::MmArc;
 use mm2_err_handle::prelude::MmResult;
 use mm2_err_handle::prelude::*;
@@ -11,6 +14,11 @@ use crate::{error::WalletConnectError, session::Session};
 #[cfg(target_arch = "wasm32")] pub(crate) mod indexed_db;
 #[cfg(not(target_arch = "wasm32"))] pub(crate) mod sqlite;
 
+/// How many times the session storage initialization is attempted before giving up.
+const STORAGE_INIT_ATTEMPTS: usize = 5;
+/// The delay between the session storage initialization attempts.
+const STORAGE_INIT_RETRY_INTERVAL_MS: u64 = 500;
+
 #[async_trait]
 pub(crate) trait WalletConnectStorageOps {
     type Error: std::fmt::Debug + NotMmError + NotEqual + Send;
@@ -44,6 +52,27 @@ impl SessionStorageDb {
     }
 }
 
+/// Runs the storage `init` until it succeeds, at most [`STORAGE_INIT_ATTEMPTS`] times,
+/// since the storage may be temporarily unavailable at startup (e.g. IndexedDB of a fresh browser profile).
+/// Returns [`WalletConnectError::StorageError`] with the last error if every attempt fails.
+pub(crate) async fn init_storage_with_retry<Init, Fut, E>(init: Init) -> MmResult<(), WalletConnectError>
+where
+    Init: Fn() -> Fut,
+    Fut: Future<Output = MmResult<(), E>>,
+    E: std::fmt::Debug + NotMmError + 'static,
+{
+    retry_on_err!(async { init().await })
+        .attempts(STORAGE_INIT_ATTEMPTS)
+        .repeat_every_ms(STORAGE_INIT_RETRY_INTERVAL_MS)
+        .inspect_err(log_storage_init_error::<E>)
+        .await
+        .map_err(|err| MmError::new(WalletConnectError::StorageError(format!("{:?}", err.into_error()))))
+}
+
+fn log_storage_init_error<E: std::fmt::Debug + NotMmError>(err: &MmError<E>) {
+    warn!("Failed to initialize WalletConnect storage: {err:?}");
+}
+
 #[cfg(test)]
 pub(crate) mod session_storage_tests {
     common::cfg_wasm32! {
@@ -58,9 +87,12 @@ pub(crate) mod session_storage_tests {
                 session::{Session, SessionType},
                 WalletConnectCtx};
 
-    use super::WalletConnectStorageOps;
+    use super::{init_storage_with_retry, WalletConnectStorageOps, STORAGE_INIT_ATTEMPTS};
+    use crate::error::WalletConnectError;
+    use mm2_err_handle::prelude::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
 
-    fn sample_test_session(wc_ctx: &WalletConnectCtx) -> Session {
+    pub(crate) fn sample_test_session(wc_ctx: &WalletConnectCtx) -> Session {
         let session_key = SessionKey {
             sym_key: [
                 115, 159, 247, 31, 199, 84, 88, 59, 158, 252, 98, 225, 51, 125, 201, 239, 142, 34, 9, 201, 128, 114,
@@ -199,4 +231,27 @@ pub(crate) mod session_storage_tests {
         assert_eq!(modified_sample_session, db_session);
         assert_eq!(100, db_session.expiry);
     });
+
+    cross_test!(init_storage_with_retry_test, {
+        let attempts = AtomicUsize::new(0);
+        init_storage_with_retry(|| async {
+            if attempts.fetch_add(1, Ordering::Relaxed) < 2 {
+                return MmError::err(WalletConnectError::InternalError("storage is not ready".into()));
+            }
+            Ok(())
+        })
+        .await
+        .unwrap();
+        assert_eq!(attempts.load(Ordering::Relaxed), 3);
+
+        let attempts = AtomicUsize::new(0);
+        let err = init_storage_with_retry(|| async {
+            attempts.fetch_add(1, Ordering::Relaxed);
+            MmError::<WalletConnectError>::err(WalletConnectError::InternalError("storage is broken".into()))
+        })
+        .await
+        .unwrap_err();
+        assert!(matches!(err.get_inner(), WalletConnectError::StorageError(_)));
+        assert_eq!(attempts.load(Ordering::Relaxed), STORAGE_INIT_ATTEMPTS);
+    });
 }
@@ -1,8 +1,9 @@
 use super::RepresentH256;
 use hash::H256;
 use hex::FromHex;
-use merkle_root::merkle_root;
+use merkle_root::{merkle_node_hash, merkle_root};
 use ser::deserialize;
+use std::fmt;
 use {BlockHeader, Transaction};
 
 #[derive(Debug, PartialEq, Clone, Serializable, Deserializable)]
@@ -11,6 +12,29 @@ pub struct Block {
     pub transactions: Vec<Transaction>,
 }
 
+#[derive(Debug, PartialEq)]
+pub enum BlockError {
+    /// The merkle root in the block header doesn't match the merkle root of the block transactions.
+    MerkleRootMismatch { expected: H256, actual: H256 },
+    /// The transactions list has been mutated to produce the merkle root of another list (CVE-2012-2459),
+    /// e.g. by duplicating the last transactions.
+    MutatedMerkleTree,
+}
+
+impl fmt::Display for BlockError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BlockError::MerkleRootMismatch { expected, actual } => write!(
+                f,
+                "Block header merkle root {} doesn't match the transactions merkle root {}",
+                expected.reversed(),
+                actual.reversed()
+            ),
+            BlockError::MutatedMerkleTree => write!(f, "Block transactions merkle tree is mutated"),
+        }
+    }
+}
+
 impl From<&'static str> for Block {
     fn from(s: &'static str) -> Self { deserialize(&s.from_hex::<Vec<u8>>().unwrap() as &[u8]).unwrap() }
 }
@@ -33,6 +57,23 @@ impl Block {
         merkle_root(&hashes)
     }
 
+    /// Checks that the merkle root in the block header matches the block transactions
+    /// and that the transactions list isn't mutated (CVE-2012-2459).
+    pub fn check_merkle_root(&self) -> Result<(), BlockError> {
+        let hashes = self.transactions.iter().map(|tx| tx.hash()).collect::<Vec<H256>>();
+        let actual = merkle_root(&hashes);
+        if actual != self.block_header.merkle_root_hash {
+            return Err(BlockError::MerkleRootMismatch {
+                expected: self.block_header.merkle_root_hash,
+                actual,
+            });
+        }
+        if is_merkle_tree_mutated(hashes) {
+            return Err(BlockError::MutatedMerkleTree);
+        }
+        Ok(())
+    }
+
     /// Returns block's witness merkle root.
     pub fn witness_merkle_root(&self) -> H256 {
         let hashes = match self.transactions.split_first() {
@@ -53,12 +94,31 @@ impl Block {
     pub fn hash(&self) -> H256 { self.block_header.hash() }
 }
 
+/// Checks whether any level of the merkle tree built from `hashes` has two equal sibling nodes.
+///
+/// Since the last node of an odd-width level is paired with itself, a list with duplicated trailing hashes
+/// (e.g. `[a, b, c, c]` instead of `[a, b, c]`) has the same merkle root as the original list.
+/// Like Bitcoin Core, any equal siblings are treated as a mutation.
+fn is_merkle_tree_mutated(mut hashes: Vec<H256>) -> bool {
+    while hashes.len() > 1 {
+        if hashes.chunks_exact(2).any(|pair| pair[0] == pair[1]) {
+            return true;
+        }
+        hashes = hashes
+            .chunks(2)
+            .map(|pair| merkle_node_hash(&pair[0], pair.get(1).unwrap_or(&pair[0])))
+            .collect();
+    }
+    false
+}
+
 #[cfg(test)]
 mod tests {
-    use super::Block;
+    use super::{Block, BlockError};
     use hash::H256;
     use hex::FromHex;
     use ser::deserialize;
+    use Transaction;
 
     // Block 80000
     // https://blockchain.info/rawblock/000000000043a8c0fd1d6f726790caa2a406010d19efd2780db27bdbbd93baf6
@@ -73,4 +133,54 @@ mod tests {
         assert_eq!(block.merkle_root(), merkle_root);
         assert_eq!(block.hash(), hash);
     }
+
+    #[test]
+    fn test_check_merkle_root() {
+        // Block 80000
+        // https://blockchain.info/rawblock/000000000043a8c0fd1d6f726790caa2a406010d19efd2780db27bdbbd93baf6
+        let header_bytes: Vec<u8> = "01000000ba8b9cda965dd8e536670f9ddec10e53aab14b20bacad27b9137190000000000190760b278fe7b8565fda3b968b918d5fd997f993b23674c0af3b6fde300b38f33a5914ce6ed5b1b01e32f57".from_hex().unwrap();
+        let transactions = vec![
+            "01000000010000000000000000000000000000000000000000000000000000000000000000ffffffff0704e6ed5b1b014effffffff0100f2052a01000000434104b68a50eaa0287eff855189f949c1c6e5f58b37c88231373d8a59809cbae83059cc6469d65c665ccfd1cfeb75c6e8e19413bba7fbff9bc762419a76d87b16086eac00000000".into(),
+            "0100000001a6b97044d03da79c005b20ea9c0e1a6d9dc12d9f7b91a5911c9030a439eed8f5000000004948304502206e21798a42fae0e854281abd38bacd1aeed3ee3738d9e1446618c4571d1090db022100e2ac980643b0b82c0e88ffdfec6b64e3e6ba35e7ba5fdd7d5d6cc8d25c6b241501ffffffff0100f2052a010000001976a914404371705fa9bd789a2fcd52d2c580b65d35549d88ac00000000".into(),
+        ];
+        let mut block = Block::new(deserialize(header_bytes.as_slice()).unwrap(), transactions);
+        let merkle_root = H256::from_reversed_str("8fb300e3fdb6f30a4c67233b997f99fdd518b968b9a3fd65857bfe78b2600719");
+        assert_eq!(block.block_header.merkle_root_hash, merkle_root);
+        block.check_merkle_root().unwrap();
+
+        // Tamper with the amount of the second transaction.
+        block.transactions[1].outputs[0].value += 1;
+        let actual = block.merkle_root();
+        assert_ne!(actual, merkle_root);
+        assert_eq!(
+            block.check_merkle_root(),
+            Err(BlockError::MerkleRootMismatch {
+                expected: merkle_root,
+                actual,
+            })
+        );
+    }
+
+    #[test]
+    fn test_check_merkle_root_mutated() {
+        // Block 80000 transactions plus a modified copy of the second one, to have an odd number of transactions.
+        let header_bytes: Vec<u8> = "01000000ba8b9cda965dd8e536670f9ddec10e53aab14b20bacad27b9137190000000000190760b278fe7b8565fda3b968b918d5fd997f993b23674c0af3b6fde300b38f33a5914ce6ed5b1b01e32f57".from_hex().unwrap();
+        let mut transactions: Vec<Transaction> = vec![
+            "01000000010000000000000000000000000000000000000000000000000000000000000000ffffffff0704e6ed5b1b014effffffff0100f2052a01000000434104b68a50eaa0287eff855189f949c1c6e5f58b37c88231373d8a59809cbae83059cc6469d65c665ccfd1cfeb75c6e8e19413bba7fbff9bc762419a76d87b16086eac00000000".into(),
+            "0100000001a6b97044d03da79c005b20ea9c0e1a6d9dc12d9f7b91a5911c9030a439eed8f5000000004948304502206e21798a42fae0e854281abd38bacd1aeed3ee3738d9e1446618c4571d1090db022100e2ac980643b0b82c0e88ffdfec6b64e3e6ba35e7ba5fdd7d5d6cc8d25c6b241501ffffffff0100f2052a010000001976a914404371705fa9bd789a2fcd52d2c580b65d35549d88ac00000000".into(),
+        ];
+        let mut third = transactions[1].clone();
+        third.outputs[0].value += 1;
+        transactions.push(third);
+
+        let mut block = Block::new(deserialize(header_bytes.as_slice()).unwrap(), transactions);
+        block.block_header.merkle_root_hash = block.merkle_root();
+        block.check_merkle_root().unwrap();
+
+        // Duplicating the last transaction keeps the merkle root the same.
+        let last = block.transactions[2].clone();
+        block.transactions.push(last);
+        assert_eq!(block.merkle_root(), block.block_header.merkle_root_hash);
+        assert_eq!(block.check_merkle_root(), Err(BlockError::MutatedMerkleTree));
+    }
 }
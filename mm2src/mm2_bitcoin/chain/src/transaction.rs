@@ -24,6 +24,13 @@ const WITNESS_MARKER: u8 = 0;
 const WITNESS_FLAG: u8 = 1;
 /// Maximum supported list size (inputs, outputs, etc.)
 const MAX_LIST_SIZE: usize = 8192;
+// The script opcodes needed to parse OP_RETURN outputs.
+// `chain` can't depend on the `script` crate as it depends on `chain` itself.
+const OP_PUSHBYTES_75: u8 = 0x4b;
+const OP_PUSHDATA1: u8 = 0x4c;
+const OP_PUSHDATA2: u8 = 0x4d;
+const OP_PUSHDATA4: u8 = 0x4e;
+const OP_RETURN: u8 = 0x6a;
 
 #[derive(Clone, Copy, Debug, Default, Deserializable, Eq, Hash, PartialEq, Serializable)]
 pub struct OutPoint {
@@ -332,6 +339,48 @@ impl Transaction {
     pub fn first_output(&self) -> Result<&TransactionOutput, TxHasNoOutputs> {
         self.outputs.first().ok_or(TxHasNoOutputs {})
     }
+
+    /// Returns the data pushed after OP_RETURN in the `vout` output, concatenated if there are several pushes.
+    /// Returns `None` if there is no such output, it isn't an OP_RETURN output or its pushes are malformed.
+    ///
+    /// Note that the data is arbitrary binary data, use [`Transaction::op_return_memo_lossy`] to read it as text.
+    pub fn op_return_data(&self, vout: usize) -> Option<Vec<u8>> {
+        let script = &self.outputs.get(vout)?.script_pubkey;
+        match script.split_first() {
+            Some((&OP_RETURN, pushes)) => parse_pushed_data(pushes),
+            _ => None,
+        }
+    }
+
+    /// Returns the OP_RETURN data of the `vout` output as UTF-8 text, replacing invalid sequences
+    /// with `U+FFFD REPLACEMENT CHARACTER`.
+    pub fn op_return_memo_lossy(&self, vout: usize) -> Option<String> {
+        self.op_return_data(vout)
+            .map(|data| String::from_utf8_lossy(&data).into_owned())
+    }
+}
+
+/// Concatenates the data of a script consisting only of data pushes.
+fn parse_pushed_data(mut script: &[u8]) -> Option<Vec<u8>> {
+    let mut data = Vec::new();
+    while let Some((&opcode, rest)) = script.split_first() {
+        let (len, rest) = match opcode {
+            0..=OP_PUSHBYTES_75 => (opcode as usize, rest),
+            OP_PUSHDATA1 => (*rest.first()? as usize, &rest[1..]),
+            OP_PUSHDATA2 if rest.len() >= 2 => (u16::from_le_bytes([rest[0], rest[1]]) as usize, &rest[2..]),
+            OP_PUSHDATA4 if rest.len() >= 4 => (
+                u32::from_le_bytes([rest[0], rest[1], rest[2], rest[3]]) as usize,
+                &rest[4..],
+            ),
+            _ => return None,
+        };
+        if rest.len() < len {
+            return None;
+        }
+        data.extend_from_slice(&rest[..len]);
+        script = &rest[len..];
+    }
+    Some(data)
 }
 
 impl Serializable for TransactionInput {
@@ -1103,6 +1152,36 @@ mod tests {
         assert_eq!(actual, expected);
     }
 
+    #[test]
+    fn test_op_return_data() {
+        // https://blockchain.info/rawtx/5a4ebf66822b0b2d56bd9dc64ece0bc38ee7844a23ff1d7320a88c5fdb2ad3e2
+        let mut t: Transaction = "0100000001a6b97044d03da79c005b20ea9c0e1a6d9dc12d9f7b91a5911c9030a439eed8f5000000004948304502206e21798a42fae0e854281abd38bacd1aeed3ee3738d9e1446618c4571d1090db022100e2ac980643b0b82c0e88ffdfec6b64e3e6ba35e7ba5fdd7d5d6cc8d25c6b241501ffffffff0100f2052a010000001976a914404371705fa9bd789a2fcd52d2c580b65d35549d88ac00000000".into();
+        // OP_RETURN <"hello world">
+        t.outputs.push(TransactionOutput {
+            value: 0,
+            script_pubkey: "6a0b68656c6c6f20776f726c64".into(),
+        });
+        // OP_RETURN OP_PUSHDATA1 <0xff 0xfe>
+        t.outputs.push(TransactionOutput {
+            value: 0,
+            script_pubkey: "6a4c02fffe".into(),
+        });
+
+        // P2PKH output.
+        assert_eq!(t.op_return_data(0), None);
+        assert_eq!(t.op_return_data(1), Some(b"hello world".to_vec()));
+        assert_eq!(t.op_return_memo_lossy(1), Some("hello world".to_owned()));
+        // Binary data doesn't panic when read as text.
+        assert_eq!(t.op_return_data(2), Some(vec![0xff, 0xfe]));
+        assert_eq!(t.op_return_memo_lossy(2), Some("\u{FFFD}\u{FFFD}".to_owned()));
+        // No such output.
+        assert_eq!(t.op_return_data(3), None);
+
+        // The push is longer than the remaining script.
+        t.outputs[2].script_pubkey = "6a0b68656c6c6f".into();
+        assert_eq!(t.op_return_data(2), None);
+    }
+
     #[test]
     fn test_from_tx_to_ext_tx() {
         // https://live.blockcypher.com/btc-testnet/tx/2be90e03abb4d5328bf7e9467ca9c571aef575837b55f1253119b87e85ccb94f/
@@ -13,6 +13,7 @@ pub enum Error {
     InvalidAddress,
     FailedKeyGeneration,
     WitnessHashMismatched,
+    InvalidTweak,
 }
 
 impl fmt::Display for Error {
@@ -28,6 +29,7 @@ impl fmt::Display for Error {
             Error::InvalidAddress => "Invalid Address",
             Error::FailedKeyGeneration => "Key generation failed",
             Error::WitnessHashMismatched => "Witness hash mismatched",
+            Error::InvalidTweak => "Invalid Tweak",
         };
 
         msg.fmt(f)
@@ -40,6 +42,7 @@ impl From<SecpError> for Error {
             SecpError::InvalidPublicKey => Error::InvalidPublic,
             SecpError::InvalidSecretKey => Error::InvalidSecret,
             SecpError::InvalidMessage => Error::InvalidMessage,
+            SecpError::InvalidTweak => Error::InvalidTweak,
             _ => Error::InvalidSignature,
         }
     }
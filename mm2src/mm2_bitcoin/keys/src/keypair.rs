@@ -5,7 +5,7 @@ use crypto::ChecksumType;
 use hash::{H264, H520};
 use secp256k1::{PublicKey, SecretKey};
 use std::fmt;
-use {Error, Private, Public, Secret};
+use {Error, Network, Private, Public, Secret};
 
 #[derive(Clone, Copy, Default, PartialEq)]
 pub struct KeyPair {
@@ -77,13 +77,19 @@ impl KeyPair {
         }
     }
 
-    pub fn random_compressed() -> Self {
+    pub fn random_compressed() -> Self { Self::random_compressed_with_prefix(0) }
+
+    /// Generates a random compressed key pair whose private key is exported to WIF with the `network` prefix.
+    /// The secret is drawn from the thread-local CSPRNG.
+    pub fn random(network: Network) -> Self { Self::random_compressed_with_prefix(network.wif_prefix()) }
+
+    fn random_compressed_with_prefix(prefix: u8) -> Self {
         let secp_secret = SecretKey::new(&mut rand::thread_rng());
         let pub_key = PublicKey::from_secret_key(&SECP_SIGN, &secp_secret);
 
         KeyPair {
             private: Private {
-                prefix: 0,
+                prefix,
                 secret: (*secp_secret.as_ref()).into(),
                 compressed: true,
                 checksum_type: Default::default(),
@@ -97,6 +103,7 @@ impl KeyPair {
 mod tests {
     use super::KeyPair;
     use crypto::dhash256;
+    use {Error, Network, Private};
 
     /// Tests from:
     /// https://github.com/bitcoin/bitcoin/blob/a6a860796a44a2805a58391a009ba22752f64e32/src/test/key_tests.cpp
@@ -161,4 +168,29 @@ mod tests {
         assert!(check_verify(SECRET_2C, message, SIGN_2));
         assert!(!check_verify(SECRET_2C, b"", SIGN_2));
     }
+
+    #[test]
+    fn test_random_keypair_wif_round_trip() {
+        for network in [Network::Mainnet, Network::Testnet, Network::Komodo].iter() {
+            let key_pair = KeyPair::random(*network);
+            let wif = key_pair.private().to_wif();
+
+            let private = Private::from_wif(&wif).unwrap();
+            assert_eq!(private.prefix, network.wif_prefix());
+            assert!(private.compressed);
+            assert_eq!(private, *key_pair.private());
+            assert_eq!(KeyPair::from_private(private).unwrap(), key_pair);
+        }
+
+        assert_ne!(KeyPair::random(Network::Mainnet), KeyPair::random(Network::Mainnet));
+    }
+
+    #[test]
+    fn test_from_wif_invalid_checksum() {
+        let wif = KeyPair::random(Network::Komodo).private().to_wif();
+        let mut data = bs58::decode(&wif).into_vec().unwrap();
+        *data.last_mut().unwrap() ^= 1;
+        let tampered = bs58::encode(data).into_string();
+        assert_eq!(Private::from_wif(&tampered), Err(Error::InvalidChecksum));
+    }
 }
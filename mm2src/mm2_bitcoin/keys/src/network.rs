@@ -4,3 +4,14 @@ pub enum Network {
     Testnet,
     Komodo,
 }
+
+impl Network {
+    /// The WIF (Wallet Import Format) prefix of private keys used on this network.
+    pub fn wif_prefix(&self) -> u8 {
+        match self {
+            Network::Mainnet => 128,
+            Network::Testnet => 239,
+            Network::Komodo => 188,
+        }
+    }
+}
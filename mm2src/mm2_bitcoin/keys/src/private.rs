@@ -53,6 +53,23 @@ impl Private {
         out.insert(0, byte);
         Ok(out.into())
     }
+
+    /// Returns `self + tweak (mod n)`, keeping the network prefix, compression and checksum type.
+    /// Fails with [`Error::InvalidTweak`] if the tweak overflows the curve order or the result is zero.
+    pub fn add_tweak(&self, tweak: &Secret) -> Result<Private, Error> {
+        let mut secret_key = SecretKey::from_slice(&*self.secret)?;
+        secret_key.add_assign(&**tweak)?;
+        let mut secret = Secret::default();
+        secret.copy_from_slice(&secret_key[..]);
+        Ok(Private { secret, ..*self })
+    }
+
+    /// Encodes the key in WIF (base58check of the prefix, secret and optional compression flag).
+    pub fn to_wif(&self) -> String { self.to_string() }
+
+    /// Decodes a WIF encoded key, failing with [`Error::InvalidChecksum`] if the base58check checksum doesn't match
+    /// or with [`Error::InvalidPrivate`] if the payload isn't a valid private key.
+    pub fn from_wif(wif: &str) -> Result<Private, Error> { wif.parse() }
 }
 
 impl DisplayLayout for Private {
@@ -132,6 +149,7 @@ impl From<&'static str> for Private {
 mod tests {
     use super::{ChecksumType, Private};
     use hash::H256;
+    use {Error, KeyPair, Secret};
 
     #[test]
     fn test_private_to_string() {
@@ -245,4 +263,64 @@ mod tests {
             "VFqZrZNzkJEk29Kzp87J7eXDuQFMh1UsqYcMmi9bfdAZ522nz1mv".to_owned()
         );
     }
+
+    #[test]
+    fn test_add_tweak() {
+        let private = Private {
+            prefix: 128,
+            secret: H256::from_reversed_str("063377054c25f98bc538ac8dd2cf9064dd5d253a725ece0628a34e2f84803bd5"),
+            compressed: true,
+            checksum_type: ChecksumType::DSHA256,
+        };
+        let tweak = H256::from("4f3c2a1b0e9d8c7b6a5f4e3d2c1b0a99887766554433221100ffeeddccbbaa01");
+        let public = *KeyPair::from_private(private).unwrap().public();
+
+        let tweaked_private = private.add_tweak(&tweak).unwrap();
+        assert_eq!(tweaked_private.prefix, private.prefix);
+        assert_eq!(tweaked_private.compressed, private.compressed);
+        assert_ne!(tweaked_private.secret, private.secret);
+
+        // (priv + tweak) * G == pub + tweak * G
+        let expected_public = *KeyPair::from_private(tweaked_private).unwrap().public();
+        assert_eq!(public.add_tweak(&tweak).unwrap(), expected_public);
+    }
+
+    #[test]
+    fn test_add_tweak_edge_cases() {
+        let private = Private {
+            prefix: 128,
+            secret: H256::from_reversed_str("063377054c25f98bc538ac8dd2cf9064dd5d253a725ece0628a34e2f84803bd5"),
+            compressed: true,
+            checksum_type: ChecksumType::DSHA256,
+        };
+        let public = *KeyPair::from_private(private).unwrap().public();
+
+        // A tweak that is not less than the curve order is invalid.
+        let overflowing_tweak = Secret::from([0xff; 32]);
+        assert_eq!(private.add_tweak(&overflowing_tweak), Err(Error::InvalidTweak));
+        assert_eq!(public.add_tweak(&overflowing_tweak), Err(Error::InvalidTweak));
+
+        // `priv + (n - priv)` is zero and `pub + (n - priv) * G` is the point at infinity.
+        let order = H256::from("fffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364141");
+        let negated = H256::from(sub_be(&order.take(), &private.secret.take()));
+        assert_eq!(private.add_tweak(&negated), Err(Error::InvalidTweak));
+        assert_eq!(public.add_tweak(&negated), Err(Error::InvalidTweak));
+    }
+
+    /// Big-endian `a - b` for `a >= b`.
+    fn sub_be(a: &[u8; 32], b: &[u8; 32]) -> [u8; 32] {
+        let mut result = [0u8; 32];
+        let mut borrow = 0i16;
+        for i in (0..32).rev() {
+            let mut diff = a[i] as i16 - b[i] as i16 - borrow;
+            borrow = if diff < 0 {
+                diff += 256;
+                1
+            } else {
+                0
+            };
+            result[i] = diff as u8;
+        }
+        result
+    }
 }
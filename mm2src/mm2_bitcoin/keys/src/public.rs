@@ -1,4 +1,4 @@
-use crate::SECP_VERIFY;
+use crate::{Secret, SECP_VERIFY};
 use crypto::dhash160;
 use hash::{H160, H264, H520};
 use hex::ToHex;
@@ -83,6 +83,18 @@ impl Public {
 
     #[inline(always)]
     pub fn to_secp256k1_pubkey(&self) -> Result<PublicKey, SecpError> { PublicKey::from_slice(self.deref()) }
+
+    /// Returns `self + tweak * G`, keeping the compressed/uncompressed format.
+    /// Fails with [`Error::InvalidTweak`] if the tweak overflows the curve order or the result is the point at infinity.
+    pub fn add_tweak(&self, tweak: &Secret) -> Result<Public, Error> {
+        let mut pubkey = self.to_secp256k1_pubkey()?;
+        pubkey.add_exp_assign(&SECP_VERIFY, &**tweak)?;
+        let public = match self {
+            Public::Compressed(_) => Public::Compressed(pubkey.serialize().into()),
+            Public::Normal(_) => Public::Normal(pubkey.serialize_uncompressed().into()),
+        };
+        Ok(public)
+    }
 }
 
 impl Deref for Public {
@@ -38,12 +38,14 @@ pub enum SPVError {
         fmt = "When validating a `BitcoinHeader`, the `prevhash` field does not match the parent hash found in the raw header"
     )]
     WrongPrevHash,
-    #[display(fmt = "A `vin` (transaction input vector) is malformatted")]
-    InvalidVin,
-    #[display(fmt = "A `vout` (transaction output vector) is malformatted or empty")]
-    InvalidVout,
-    #[display(fmt = "merkle proof connecting the `tx_id_le` to the `confirming_header`")]
-    BadMerkleProof,
+    /// The proof data couldn't be parsed (bad vin/vout encoding, wrong node length, etc.).
+    /// This points to a client bug or a corrupted fetch, so fetching the data again may succeed.
+    #[display(fmt = "Malformed SPV proof input: {}", _0)]
+    MalformedInput(String),
+    /// The proof is well-formed but doesn't prove the inclusion of the transaction in the block.
+    /// The data provider should not be trusted, refetching the same data won't help.
+    #[display(fmt = "Invalid SPV proof: {}", _0)]
+    ProofInvalid(String),
     #[display(fmt = "Unable to get merkle tree from network or storage for {coin}: {err}")]
     UnableToGetMerkle { coin: String, err: String },
     #[display(fmt = "Unable to retrieve block height / block height is zero: {}", _0)]
@@ -93,7 +95,10 @@ impl<'a> MerkleArray<'a> {
         if slice.len() % 32 == 0 {
             Ok(Self(slice))
         } else {
-            Err(SPVError::BadMerkleProof)
+            Err(SPVError::MalformedInput(format!(
+                "Merkle nodes length {} is not a multiple of 32",
+                slice.len()
+            )))
         }
     }
 }
@@ -106,7 +111,10 @@ impl MerkleArray<'_> {
     fn index(&self, index: usize) -> Result<H256, SPVError> {
         let to_index = (index + 1) * 32;
         if self.0.len() < to_index {
-            return Err(SPVError::BadMerkleProof);
+            return Err(SPVError::MalformedInput(format!(
+                "Merkle node {} is out of bounds",
+                index
+            )));
         }
         let mut digest = H256::default();
         digest.as_mut().copy_from_slice(&self.0[index * 32..to_index]);
@@ -283,7 +291,10 @@ fn verify_hash256_merkle(
     }
 
     if current != merkle_root {
-        return Err(SPVError::BadMerkleProof);
+        return Err(SPVError::ProofInvalid(format!(
+            "Computed merkle root {} doesn't match the header merkle root {}",
+            current, merkle_root
+        )));
     }
 
     Ok(())
@@ -500,12 +511,7 @@ mod tests {
                 }
 
                 let index = inputs.get("index").unwrap().as_u64().unwrap();
-                let expected = case
-                    .output
-                    .as_bool()
-                    .unwrap()
-                    .then_some(())
-                    .ok_or(SPVError::BadMerkleProof);
+                let expected = case.output.as_bool().unwrap();
 
                 // extract root and txid
                 let mut root = H256::default();
@@ -522,10 +528,12 @@ mod tests {
 
                 println!("{:?} {:?} {:?} {:?}", root, txid, proof, proof.len());
 
-                assert_eq!(
-                    verify_hash256_merkle(txid, root, &MerkleArray::new(&proof).unwrap(), index),
-                    expected
-                );
+                let result = verify_hash256_merkle(txid, root, &MerkleArray::new(&proof).unwrap(), index);
+                if expected {
+                    result.unwrap();
+                } else {
+                    assert!(matches!(result, Err(SPVError::ProofInvalid(_))), "{:?}", result);
+                }
             }
         })
     }
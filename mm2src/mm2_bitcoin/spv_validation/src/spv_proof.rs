@@ -1,10 +1,17 @@
 use crate::helpers_validation::{merkle_prove, validate_vin, validate_vout, SPVError};
 use chain::BlockHeader;
+use hex::{FromHex, FromHexError, ToHex};
 use primitives::hash::H256;
+use serde::{Deserialize, Serialize};
+use std::convert::TryFrom;
+use std::str::FromStr;
 
 pub const TRY_SPV_PROOF_INTERVAL: u64 = 10;
 
-#[derive(PartialEq, Clone)]
+/// Can be (de)serialized to cache a proof, e.g. on disk, so that the Merkle data doesn't need to be re-fetched.
+/// A deserialized proof must still be validated against a validated header using [`SPVProof::validate`].
+#[derive(PartialEq, Clone, Debug, Deserialize, Serialize)]
+#[serde(into = "SPVProofJson", try_from = "SPVProofJson")]
 pub struct SPVProof {
     /// The tx id
     pub tx_id: H256,
@@ -16,6 +23,51 @@ pub struct SPVProof {
     pub index: u64,
     /// The intermediate nodes (digests between leaf and root)
     pub intermediate_nodes: Vec<H256>,
+    /// The height of the block containing the transaction
+    pub height: u64,
+}
+
+/// The serialized form of [`SPVProof`] where all the hashes and serialized data are hex encoded.
+#[derive(Deserialize, Serialize)]
+struct SPVProofJson {
+    tx_id: String,
+    vin: String,
+    vout: String,
+    index: u64,
+    intermediate_nodes: Vec<String>,
+    height: u64,
+}
+
+impl From<SPVProof> for SPVProofJson {
+    fn from(proof: SPVProof) -> Self {
+        SPVProofJson {
+            tx_id: proof.tx_id.to_string(),
+            vin: proof.vin.to_hex(),
+            vout: proof.vout.to_hex(),
+            index: proof.index,
+            intermediate_nodes: proof.intermediate_nodes.iter().map(H256::to_string).collect(),
+            height: proof.height,
+        }
+    }
+}
+
+impl TryFrom<SPVProofJson> for SPVProof {
+    type Error = FromHexError;
+
+    fn try_from(json: SPVProofJson) -> Result<Self, Self::Error> {
+        Ok(SPVProof {
+            tx_id: H256::from_str(&json.tx_id)?,
+            vin: json.vin.from_hex()?,
+            vout: json.vout.from_hex()?,
+            index: json.index,
+            intermediate_nodes: json
+                .intermediate_nodes
+                .iter()
+                .map(|node| H256::from_str(node))
+                .collect::<Result<_, _>>()?,
+            height: json.height,
+        })
+    }
 }
 
 /// Checks validity of an entire SPV Proof against a previously validated UTXO header retrieved from storage
@@ -26,17 +78,22 @@ pub struct SPVProof {
 ///
 /// # Errors
 ///
-/// * Errors if any of the SPV Proof elements are invalid.
+/// * [`SPVError::MalformedInput`] if the `vin`, `vout` or merkle nodes can't be parsed.
+/// * [`SPVError::ProofInvalid`] if the merkle proof doesn't connect `tx_id` to the header merkle root.
 ///
 /// # Notes
 /// Re-write with our own types based on `bitcoin_spv::std_types::SPVProof::validate`
 impl SPVProof {
     pub fn validate(&self, validated_header: &BlockHeader) -> Result<(), SPVError> {
         if !validate_vin(self.vin.as_slice()) {
-            return Err(SPVError::InvalidVin);
+            return Err(SPVError::MalformedInput(
+                "`vin` (transaction input vector) is malformatted".to_string(),
+            ));
         }
         if !validate_vout(self.vout.as_slice()) {
-            return Err(SPVError::InvalidVout);
+            return Err(SPVError::MalformedInput(
+                "`vout` (transaction output vector) is malformatted or empty".to_string(),
+            ));
         }
         merkle_prove(
             self.tx_id,
@@ -45,18 +102,29 @@ impl SPVProof {
             self.index,
         )
     }
+
+    /// Returns the number of confirmations of the proven transaction given the current `tip_height`,
+    /// i.e. the number of blocks mined on top of the block containing the transaction.
+    ///
+    /// * A transaction buried below the tip has `tip_height - self.height` confirmations, which is positive.
+    /// * A transaction mined in the tip block has 0 confirmations.
+    /// * A transaction above the tip gets a negative number. This means the tip is stale or the transaction
+    ///   block was reorganized out.
+    ///
+    /// So a result `<= 0` means the transaction must not be treated as confirmed yet.
+    pub fn confirmations(&self, tip_height: u64) -> i64 { tip_height as i64 - self.height as i64 }
 }
 
 #[cfg(test)]
 mod spv_proof_tests {
+    use crate::helpers_validation::SPVError;
     use crate::spv_proof::SPVProof;
     use chain::{BlockHeader, Transaction};
     use hex::FromHex;
     use primitives::hash::H256;
     use serialization::{deserialize, serialize_list};
 
-    #[test]
-    fn test_validate() {
+    fn test_spv_proof() -> (SPVProof, BlockHeader) {
         // https://live.blockcypher.com/btc-testnet/block/000000000000004d36632fda8180ff16855d606e5515aab0750d9d4fe55fe7d6/
         let header_hex = "0000602002bf77bbb098f90f149430c314e71ef4e2671ea5e04a2503e0000000000000000406ffb54f2925360aae81bd3199f456928bbe6ae83a877902da9d9ffb08215da0ba3161ffff001a545a850b";
         let header_bytes: Vec<u8> = header_hex.from_hex().unwrap();
@@ -80,7 +148,77 @@ mod spv_proof_tests {
             vout: serialize_list(&tx.outputs).take(),
             index: 1,
             intermediate_nodes,
+            height: 2_090_175,
         };
+        (spv_proof, validated_header)
+    }
+
+    #[test]
+    fn test_validate() {
+        let (spv_proof, validated_header) = test_spv_proof();
         spv_proof.validate(&validated_header).unwrap()
     }
+
+    #[test]
+    fn test_validate_malformed_input() {
+        let (spv_proof, validated_header) = test_spv_proof();
+
+        let mut truncated_vin = spv_proof.clone();
+        truncated_vin.vin.pop();
+        let err = truncated_vin.validate(&validated_header).unwrap_err();
+        assert!(matches!(err, SPVError::MalformedInput(_)), "{:?}", err);
+
+        let mut empty_vout = spv_proof;
+        empty_vout.vout = vec![0];
+        let err = empty_vout.validate(&validated_header).unwrap_err();
+        assert!(matches!(err, SPVError::MalformedInput(_)), "{:?}", err);
+    }
+
+    #[test]
+    fn test_validate_proof_invalid() {
+        let (spv_proof, validated_header) = test_spv_proof();
+
+        let mut wrong_index = spv_proof.clone();
+        wrong_index.index = 2;
+        let err = wrong_index.validate(&validated_header).unwrap_err();
+        assert!(matches!(err, SPVError::ProofInvalid(_)), "{:?}", err);
+
+        let mut wrong_node = spv_proof;
+        wrong_node.intermediate_nodes[0] = H256::default();
+        let err = wrong_node.validate(&validated_header).unwrap_err();
+        assert!(matches!(err, SPVError::ProofInvalid(_)), "{:?}", err);
+    }
+
+    #[test]
+    fn test_confirmations() {
+        let (spv_proof, _) = test_spv_proof();
+        let height = spv_proof.height;
+
+        // Deeply buried.
+        assert_eq!(spv_proof.confirmations(height + 100), 100);
+        // Mined in the tip block.
+        assert_eq!(spv_proof.confirmations(height), 0);
+        // Above the tip, e.g. after a reorg or if the tip is stale.
+        assert_eq!(spv_proof.confirmations(height - 1), -1);
+        assert_eq!(spv_proof.confirmations(height - 5), -5);
+    }
+
+    #[test]
+    fn test_serde_round_trip() {
+        let (spv_proof, validated_header) = test_spv_proof();
+
+        let json = serde_json::to_value(&spv_proof).unwrap();
+        assert_eq!(json["tx_id"], spv_proof.tx_id.to_string());
+        assert_eq!(json["index"], 1);
+        assert_eq!(json["height"], spv_proof.height);
+        assert_eq!(json["intermediate_nodes"].as_array().unwrap().len(), 8);
+
+        let deserialized: SPVProof = serde_json::from_value(json.clone()).unwrap();
+        assert_eq!(deserialized, spv_proof);
+        deserialized.validate(&validated_header).unwrap();
+
+        let mut invalid = json;
+        invalid["vin"] = "not hex".into();
+        serde_json::from_value::<SPVProof>(invalid).unwrap_err();
+    }
 }
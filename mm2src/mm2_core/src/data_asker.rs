@@ -97,6 +97,25 @@ impl MmCtx {
             Err(_) => MmError::err(AskForDataError::Timeout),
         }
     }
+
+    /// Same as [`MmCtx::ask_for_data`], but falls back to the `default` value if no data is provided
+    /// within the `timeout`, so that the caller doesn't fail if there is no data provider (e.g. no GUI) to answer.
+    pub async fn ask_for_data_or_default<Input, Output>(
+        &self,
+        data_type: &str,
+        data: Input,
+        timeout: Duration,
+        default: Output,
+    ) -> Result<Output, MmError<AskForDataError>>
+    where
+        Input: Serialize,
+        Output: DeserializeOwned,
+    {
+        match self.ask_for_data(data_type, data, timeout).await {
+            Err(e) if matches!(e.get_inner(), AskForDataError::Timeout) => Ok(default),
+            result => result,
+        }
+    }
 }
 
 #[derive(Clone, Debug, Deserialize)]
@@ -141,6 +160,7 @@ pub async fn send_asked_data_rpc(
 
 #[cfg(test)]
 mod tests {
+    use super::AskForDataError;
     use crate::mm_ctx::MmCtxBuilder;
     use common::block_on;
     use common::executor::Timer;
@@ -149,6 +169,19 @@ mod tests {
     use serde_json::json;
     use std::thread;
 
+    #[test]
+    fn test_ask_for_data_timeout() {
+        let ctx = MmCtxBuilder::new().into_mm_arc();
+        let timeout = Duration::from_millis(100);
+
+        // Nobody is going to answer.
+        let err = block_on(ctx.ask_for_data::<_, String>("TEST", serde_json::Value::Null, timeout)).unwrap_err();
+        assert!(matches!(err.get_inner(), AskForDataError::Timeout), "{err}");
+
+        let output = block_on(ctx.ask_for_data_or_default("TEST", serde_json::Value::Null, timeout, 42)).unwrap();
+        assert_eq!(output, 42);
+    }
+
     #[test]
     fn simulate_ask_and_send_data() {
         let ctx = MmCtxBuilder::new().into_mm_arc();
@@ -1,17 +1,32 @@
 use crate::mm_ctx::MmArc;
 use async_trait::async_trait;
+use common::executor::SpawnFuture;
 use std::any::TypeId;
 use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
 
-pub type Listeners<EventType> = Vec<Box<dyn EventListener<Event = EventType>>>;
+pub type Listeners<EventType> = Vec<Arc<dyn EventListener<Event = EventType>>>;
 pub type DispatchTable = HashMap<TypeId, Vec<usize>>;
 
+/// How the [`Dispatcher`] delivers the events to a listener.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum DeliveryMode {
+    /// The event is processed inline: [`Dispatcher::dispatch_async`] waits for the listener to process it
+    /// before delivering it to the next listener. The listener receives the events in the order they are dispatched.
+    #[default]
+    Sync,
+    /// The event is processed in a future spawned on the `ctx` executor, so a slow listener doesn't stall the others.
+    /// There is no guarantee of the order in which the listener processes the events.
+    Spawned,
+}
+
 #[async_trait]
 pub trait EventListener: 'static + Send + Sync {
     type Event;
     async fn process_event_async(&self, ctx: MmArc, event: Self::Event);
     fn get_desired_events(&self) -> Vec<TypeId>;
     fn listener_id(&self) -> &'static str;
+    fn delivery_mode(&self) -> DeliveryMode { DeliveryMode::Sync }
 }
 
 pub trait EventUniqueId {
@@ -39,7 +54,7 @@ where
 
 impl<EventType: 'static> Dispatcher<EventType>
 where
-    EventType: Clone + EventUniqueId,
+    EventType: Clone + EventUniqueId + Send,
 {
     pub fn add_listener(&mut self, listen: impl EventListener<Event = EventType>) {
         if self.listeners_id.contains(listen.listener_id()) {
@@ -48,16 +63,27 @@ where
         self.listeners_id.insert(listen.listener_id().to_string());
         let id = self.listeners.len();
         let events = listen.get_desired_events();
-        self.listeners.push(Box::new(listen));
+        self.listeners.push(Arc::new(listen));
         for ev in events {
             self.dispatch_table.entry(ev).or_default().push(id);
         }
     }
 
+    /// Delivers the event to the interested listeners according to their [`DeliveryMode`].
     pub async fn dispatch_async(&self, ctx: MmArc, ev: EventType) {
         if let Some(interested) = self.dispatch_table.get(&ev.event_id()) {
             for id in interested.iter().copied() {
-                self.listeners[id].process_event_async(ctx.clone(), ev.clone()).await;
+                let listener = &self.listeners[id];
+                match listener.delivery_mode() {
+                    DeliveryMode::Sync => listener.process_event_async(ctx.clone(), ev.clone()).await,
+                    DeliveryMode::Spawned => {
+                        let listener = listener.clone();
+                        let fut_ctx = ctx.clone();
+                        let ev = ev.clone();
+                        ctx.spawner()
+                            .spawn(async move { listener.process_event_async(fut_ctx, ev).await });
+                    },
+                }
             }
         }
     }
@@ -68,13 +94,16 @@ where
 
 #[cfg(test)]
 mod event_dispatcher_tests {
-    use crate::event_dispatcher::{Dispatcher, EventListener, EventUniqueId};
+    use crate::event_dispatcher::{DeliveryMode, Dispatcher, EventListener, EventUniqueId};
     use crate::mm_ctx::{MmArc, MmCtxBuilder};
     use async_trait::async_trait;
     use common::block_on;
+    use common::executor::Timer;
     use std::any::TypeId;
     use std::ops::Deref;
+    use std::sync::atomic::{AtomicBool, Ordering};
     use std::sync::Arc;
+    use std::time::{Duration, Instant};
     use uuid::Uuid;
 
     #[derive(Clone, Default)]
@@ -145,4 +174,72 @@ mod event_dispatcher_tests {
         dispatcher.add_listener(res);
         assert_eq!(dispatcher.nb_listeners(), 1);
     }
+
+    /// Takes a second to process an event, then marks it as processed.
+    struct SlowListener {
+        processed: Arc<AtomicBool>,
+    }
+
+    #[async_trait]
+    impl EventListener for SlowListener {
+        type Event = AppEvents;
+
+        async fn process_event_async(&self, _ctx: MmArc, _event: Self::Event) {
+            Timer::sleep(1.).await;
+            self.processed.store(true, Ordering::SeqCst);
+        }
+
+        fn get_desired_events(&self) -> Vec<TypeId> { vec![EventSwapStatusChanged::event_id()] }
+
+        fn listener_id(&self) -> &'static str { "slow_listener" }
+
+        fn delivery_mode(&self) -> DeliveryMode { DeliveryMode::Spawned }
+    }
+
+    /// Marks the event as processed immediately.
+    struct FastListener {
+        processed: Arc<AtomicBool>,
+    }
+
+    #[async_trait]
+    impl EventListener for FastListener {
+        type Event = AppEvents;
+
+        async fn process_event_async(&self, _ctx: MmArc, _event: Self::Event) {
+            self.processed.store(true, Ordering::SeqCst);
+        }
+
+        fn get_desired_events(&self) -> Vec<TypeId> { vec![EventSwapStatusChanged::event_id()] }
+
+        fn listener_id(&self) -> &'static str { "fast_listener" }
+    }
+
+    #[test]
+    fn test_spawned_listener_does_not_delay_others() {
+        let slow_processed = Arc::new(AtomicBool::new(false));
+        let fast_processed = Arc::new(AtomicBool::new(false));
+
+        let mut dispatcher: Dispatcher<AppEvents> = Default::default();
+        // The slow listener is registered first, so it would delay the fast one if it was processed inline.
+        dispatcher.add_listener(SlowListener {
+            processed: slow_processed.clone(),
+        });
+        dispatcher.add_listener(FastListener {
+            processed: fast_processed.clone(),
+        });
+
+        let ctx = MmCtxBuilder::new().into_mm_arc();
+        let event = AppEvents::EventSwapStatusChanged(EventSwapStatusChanged::default());
+        let before = Instant::now();
+        block_on(dispatcher.dispatch_async(ctx.clone(), event));
+        let took = before.elapsed();
+
+        assert!(took < Duration::from_millis(500), "Dispatching took {took:?}");
+        assert!(fast_processed.load(Ordering::SeqCst));
+        assert!(!slow_processed.load(Ordering::SeqCst));
+
+        // The slow listener still processes the event in the background.
+        block_on(Timer::sleep(1.5));
+        assert!(slow_processed.load(Ordering::SeqCst));
+    }
 }
@@ -1,12 +1,17 @@
 #[cfg(target_arch = "wasm32")] use derive_more::Display;
 #[cfg(target_arch = "wasm32")] use rand::{thread_rng, Rng};
+#[cfg(target_arch = "wasm32")]
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+#[cfg(target_arch = "wasm32")] use std::str::FromStr;
 
 pub mod data_asker;
 pub mod event_dispatcher;
 pub mod mm_ctx;
+#[cfg(not(target_arch = "wasm32"))] pub mod sql_connection_pool;
 
+/// Serialized as its `Display` string, i.e. `"MAIN"` or `"TEST_{id}"`, and parsed back with `FromStr`.
 #[cfg(target_arch = "wasm32")]
-#[derive(Clone, Copy, Display, PartialEq, Default)]
+#[derive(Clone, Copy, Debug, Display, PartialEq, Default)]
 pub enum DbNamespaceId {
     #[display(fmt = "MAIN")]
     #[default]
@@ -25,3 +30,51 @@ impl DbNamespaceId {
     #[inline(always)]
     pub fn for_test_with_id(id: u64) -> DbNamespaceId { DbNamespaceId::Test(id) }
 }
+
+#[cfg(target_arch = "wasm32")]
+impl FromStr for DbNamespaceId {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == "MAIN" {
+            return Ok(DbNamespaceId::Main);
+        }
+        s.strip_prefix("TEST_")
+            .and_then(|id| id.parse().ok())
+            .map(DbNamespaceId::Test)
+            .ok_or_else(|| format!("Invalid DB namespace ID: '{s}'"))
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+impl Serialize for DbNamespaceId {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> { serializer.collect_str(self) }
+}
+
+#[cfg(target_arch = "wasm32")]
+impl<'de> Deserialize<'de> for DbNamespaceId {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        DbNamespaceId::from_str(&s).map_err(de::Error::custom)
+    }
+}
+
+#[cfg(all(test, target_arch = "wasm32"))]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    #[wasm_bindgen_test]
+    fn test_db_namespace_id_serde() {
+        for (namespace, json) in [
+            (DbNamespaceId::Main, "\"MAIN\""),
+            (DbNamespaceId::for_test_with_id(42), "\"TEST_42\""),
+        ] {
+            assert_eq!(serde_json::to_string(&namespace).unwrap(), json);
+            assert_eq!(serde_json::from_str::<DbNamespaceId>(json).unwrap(), namespace);
+        }
+
+        serde_json::from_str::<DbNamespaceId>("\"TEST_\"").unwrap_err();
+        serde_json::from_str::<DbNamespaceId>("\"OTHER\"").unwrap_err();
+    }
+}
@@ -149,6 +149,7 @@ pub struct MmCtx {
     /// The abortable system is pinned to the `MmCtx` context.
     /// It's used to spawn futures that can be aborted immediately or after a timeout
     /// on the [`MmArc::stop`] function call.
+    /// Warns about the futures that are still alive if the context is dropped without being stopped.
     pub abortable_system: AbortableQueue,
     /// The abortable system is pinned to the `MmCtx` context.
     /// It's used to register listeners that will wait for graceful shutdown.
@@ -211,7 +212,7 @@ impl MmCtx {
             mm_version: "".into(),
             datetime: "".into(),
             mm_init_ctx: Mutex::new(None),
-            abortable_system: AbortableQueue::default(),
+            abortable_system: AbortableQueue::with_alive_futures_warning(),
             graceful_shutdown_registry: graceful_shutdown::GracefulShutdownRegistry::default(),
             #[cfg(target_arch = "wasm32")]
             db_namespace: DbNamespaceId::Main,
@@ -461,7 +462,13 @@ impl MmCtx {
 
     /// Returns the event streaming configuration in use.
     pub fn event_streaming_configuration(&self) -> Option<EventStreamingConfiguration> {
-        serde_json::from_value(self.conf["event_streaming_configuration"].clone()).ok()
+        let config = &self.conf["event_streaming_configuration"];
+        if config.is_null() {
+            return None;
+        }
+        serde_json::from_value(config.clone())
+            .map_err(|e| log::error!("Invalid 'event_streaming_configuration': {e}"))
+            .ok()
     }
 
     /// Returns the cloneable `WeakSpawner`.
@@ -897,6 +904,13 @@ impl MmCtxBuilder {
             ctx.conf = conf;
         }
 
+        if let Some(max_streamers) = ctx
+            .event_streaming_configuration()
+            .and_then(|config| config.max_streamers_per_client)
+        {
+            ctx.event_stream_manager = StreamingManager::with_client_quota(max_streamers);
+        }
+
         #[cfg(target_arch = "wasm32")]
         {
             ctx.db_namespace = self.db_namespace;
@@ -0,0 +1,292 @@
+//! A fixed-size pool of SQLite connections.
+//!
+//! Unlike `Arc<Mutex<Connection>>`, connections are acquired asynchronously: a caller waits for
+//! an idle connection without blocking an executor thread, and only then moves the connection
+//! into `async_blocking` to run the actual queries.
+
+use common::async_blocking;
+use common::log::warn;
+use db_common::sqlite::rusqlite::{Connection, Error as SqlError};
+use derive_more::Display;
+use futures::channel::mpsc::{self, UnboundedReceiver, UnboundedSender};
+use futures::lock::Mutex as AsyncMutex;
+use futures::StreamExt;
+use mm2_err_handle::prelude::*;
+use std::ops::{Deref, DerefMut};
+use std::path::Path;
+use std::sync::Arc;
+
+/// Unlike `SELECT 1`, this query reads the database header, so it also detects corrupted or replaced files.
+const HEALTH_CHECK_QUERY: &str = "SELECT count(*) FROM sqlite_master;";
+
+type OpenConnectionFn = Arc<dyn Fn() -> Result<Connection, SqlError> + Send + Sync>;
+
+#[derive(Debug, Display)]
+pub enum SqlConnPoolError {
+    #[display(fmt = "Pool size must be greater than zero")]
+    EmptyPool,
+    #[display(fmt = "Error opening SQLite connection: {}", _0)]
+    OpenError(String),
+}
+
+impl From<SqlError> for SqlConnPoolError {
+    fn from(e: SqlError) -> Self { SqlConnPoolError::OpenError(e.to_string()) }
+}
+
+pub struct SqliteConnPool {
+    idle_tx: UnboundedSender<Connection>,
+    /// Callers queue on this lock while waiting for an idle connection.
+    idle_rx: AsyncMutex<UnboundedReceiver<Connection>>,
+    size: usize,
+    open_connection: OpenConnectionFn,
+    validate_on_checkout: bool,
+}
+
+impl SqliteConnPool {
+    /// Opens `size` connections to the database at the given `path`.
+    pub fn open<P: AsRef<Path>>(path: P, size: usize) -> MmResult<SqliteConnPool, SqlConnPoolError> {
+        let path = path.as_ref().to_path_buf();
+        SqliteConnPool::with_opener(size, move || Connection::open(&path))
+    }
+
+    /// Creates a pool of `size` connections opened by `open_connection`.
+    /// The same function is used to replace the connections that fail validation on checkout.
+    pub fn with_opener<F>(size: usize, open_connection: F) -> MmResult<SqliteConnPool, SqlConnPoolError>
+    where
+        F: Fn() -> Result<Connection, SqlError> + Send + Sync + 'static,
+    {
+        if size == 0 {
+            return MmError::err(SqlConnPoolError::EmptyPool);
+        }
+
+        let (idle_tx, idle_rx) = mpsc::unbounded();
+        for _ in 0..size {
+            idle_tx
+                .unbounded_send(open_connection()?)
+                .expect("The receiver is alive while the pool is being created");
+        }
+
+        Ok(SqliteConnPool {
+            idle_tx,
+            idle_rx: AsyncMutex::new(idle_rx),
+            size,
+            open_connection: Arc::new(open_connection),
+            validate_on_checkout: false,
+        })
+    }
+
+    /// Enables a health check of every connection before it's handed out.
+    /// Unhealthy connections are discarded and transparently replaced with freshly opened ones.
+    /// Disabled by default as it costs an extra query per checkout.
+    pub fn validate_on_checkout(mut self, enable: bool) -> SqliteConnPool {
+        self.validate_on_checkout = enable;
+        self
+    }
+
+    #[inline(always)]
+    pub fn size(&self) -> usize { self.size }
+
+    /// Waits until a connection is idle and returns it.
+    /// The connection goes back to the pool when the returned guard is dropped.
+    pub async fn acquire(&self) -> MmResult<PooledConnection, SqlConnPoolError> {
+        let connection = self
+            .idle_rx
+            .lock()
+            .await
+            .next()
+            .await
+            .expect("The pool keeps its own sender alive");
+
+        let connection = if self.validate_on_checkout {
+            let open_connection = self.open_connection.clone();
+            match async_blocking(move || ensure_healthy(connection, open_connection)).await {
+                Ok(connection) => connection,
+                Err((stale_connection, e)) => {
+                    // Keep the pool size, the next checkout will try to replace the connection again.
+                    self.idle_tx.unbounded_send(stale_connection).ok();
+                    return MmError::err(e.into());
+                },
+            }
+        } else {
+            connection
+        };
+
+        Ok(PooledConnection {
+            connection: Some(connection),
+            idle_tx: self.idle_tx.clone(),
+        })
+    }
+
+    /// Acquires a connection and runs `f` with it on the blocking thread pool.
+    pub async fn run<F, R>(&self, f: F) -> MmResult<R, SqlConnPoolError>
+    where
+        F: FnOnce(&mut Connection) -> R + Send + 'static,
+        R: Send + 'static,
+    {
+        let mut connection = self.acquire().await?;
+        Ok(async_blocking(move || f(&mut connection)).await)
+    }
+}
+
+/// Returns the given connection if it passes the health check, or a freshly opened one otherwise.
+fn ensure_healthy(
+    connection: Connection,
+    open_connection: OpenConnectionFn,
+) -> Result<Connection, (Connection, SqlError)> {
+    match connection.query_row(HEALTH_CHECK_QUERY, [], |_| Ok(())) {
+        Ok(()) => Ok(connection),
+        Err(e) => {
+            warn!("Replacing unhealthy SQLite connection: {}", e);
+            open_connection().map_err(|e| (connection, e))
+        },
+    }
+}
+
+/// A connection checked out of [`SqliteConnPool`].
+pub struct PooledConnection {
+    connection: Option<Connection>,
+    idle_tx: UnboundedSender<Connection>,
+}
+
+impl Deref for PooledConnection {
+    type Target = Connection;
+
+    fn deref(&self) -> &Self::Target { self.connection.as_ref().expect("Connection is taken on drop only") }
+}
+
+impl DerefMut for PooledConnection {
+    fn deref_mut(&mut self) -> &mut Self::Target { self.connection.as_mut().expect("Connection is taken on drop only") }
+}
+
+impl Drop for PooledConnection {
+    fn drop(&mut self) {
+        if let Some(connection) = self.connection.take() {
+            // The send fails only if the pool itself was dropped, then the connection is closed.
+            self.idle_tx.unbounded_send(connection).ok();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::block_on;
+    use common::executor::Timer;
+    use futures::future::join_all;
+    use std::path::PathBuf;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    const POOL_SIZE: usize = 2;
+
+    fn in_memory_pool(size: usize) -> SqliteConnPool {
+        SqliteConnPool::with_opener(size, Connection::open_in_memory).unwrap()
+    }
+
+    /// Creates a single connection pool whose first connection is opened to a file that can be corrupted later,
+    /// while all the replacements are in-memory. Returns the file path and the number of opened connections.
+    fn corruptible_pool(validate_on_checkout: bool) -> (SqliteConnPool, PathBuf, Arc<AtomicUsize>) {
+        let path = std::env::temp_dir().join(format!("sql_conn_pool_{}.db", uuid::Uuid::new_v4()));
+        let opened = Arc::new(AtomicUsize::new(0));
+
+        let path_c = path.clone();
+        let opened_c = opened.clone();
+        let pool = SqliteConnPool::with_opener(1, move || match opened_c.fetch_add(1, Ordering::SeqCst) {
+            0 => Connection::open(&path_c),
+            _ => Connection::open_in_memory(),
+        })
+        .unwrap()
+        .validate_on_checkout(validate_on_checkout);
+
+        block_on(pool.run(|conn| conn.execute_batch("CREATE TABLE t (v INTEGER);")))
+            .unwrap()
+            .unwrap();
+        std::fs::write(&path, b"not a database".repeat(512)).unwrap();
+
+        (pool, path, opened)
+    }
+
+    #[test]
+    fn test_empty_pool() {
+        let err = SqliteConnPool::with_opener(0, Connection::open_in_memory)
+            .err()
+            .unwrap();
+        assert!(matches!(err.into_inner(), SqlConnPoolError::EmptyPool));
+    }
+
+    #[test]
+    fn test_acquire_more_than_pool_size() {
+        const REQUESTS: usize = POOL_SIZE * 4;
+
+        let pool = in_memory_pool(POOL_SIZE);
+        let in_use = AtomicUsize::new(0);
+        let max_in_use = AtomicUsize::new(0);
+
+        let requests = (0..REQUESTS).map(|i| {
+            let pool = &pool;
+            let in_use = &in_use;
+            let max_in_use = &max_in_use;
+            async move {
+                let connection = pool.acquire().await.unwrap();
+                let now_in_use = in_use.fetch_add(1, Ordering::SeqCst) + 1;
+                max_in_use.fetch_max(now_in_use, Ordering::SeqCst);
+
+                // Hold the connection for a while so that other requests have to queue.
+                Timer::sleep(0.05).await;
+                let value: usize = connection.query_row("SELECT ?1", [i], |row| row.get(0)).unwrap();
+
+                in_use.fetch_sub(1, Ordering::SeqCst);
+                value
+            }
+        });
+
+        let values = block_on(join_all(requests));
+        assert_eq!(values, (0..REQUESTS).collect::<Vec<_>>());
+        assert_eq!(max_in_use.load(Ordering::SeqCst), POOL_SIZE);
+
+        // All the connections are returned to the pool.
+        let connections = block_on(join_all((0..POOL_SIZE).map(|_| pool.acquire())));
+        assert!(connections.iter().all(|conn| conn.is_ok()));
+    }
+
+    #[test]
+    fn test_run_on_blocking_pool() {
+        let pool = in_memory_pool(1);
+
+        block_on(pool.run(|conn| conn.execute_batch("CREATE TABLE t (v INTEGER); INSERT INTO t VALUES (42);")))
+            .unwrap()
+            .unwrap();
+        let value: i64 = block_on(pool.run(|conn| conn.query_row("SELECT v FROM t", [], |row| row.get(0))))
+            .unwrap()
+            .unwrap();
+        assert_eq!(value, 42);
+    }
+
+    #[test]
+    fn test_checkout_replaces_unhealthy_connection() {
+        let (pool, path, opened) = corruptible_pool(true);
+
+        let conn = block_on(pool.acquire()).unwrap();
+        assert_eq!(opened.load(Ordering::SeqCst), 2);
+        // The fresh in-memory connection doesn't have the table created through the stale one.
+        let tables: i64 = conn
+            .query_row("SELECT count(*) FROM sqlite_master", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(tables, 0);
+
+        drop(conn);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_checkout_without_validation() {
+        let (pool, path, opened) = corruptible_pool(false);
+
+        let conn = block_on(pool.acquire()).unwrap();
+        assert_eq!(opened.load(Ordering::SeqCst), 1);
+        conn.query_row("SELECT count(*) FROM sqlite_master", [], |row| row.get::<_, i64>(0))
+            .unwrap_err();
+
+        drop(conn);
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -1,4 +1,5 @@
 use serde::Deserialize;
+use std::num::NonZeroUsize;
 
 #[derive(Deserialize)]
 #[serde(default)]
@@ -7,6 +8,11 @@ use serde::Deserialize;
 pub struct EventStreamingConfiguration {
     pub worker_path: String,
     pub access_control_allow_origin: String,
+    /// The maximum number of events buffered for the wasm worker before the new ones start being dropped.
+    /// Must be greater than zero.
+    pub worker_buffer_capacity: NonZeroUsize,
+    /// The maximum number of streamers a single client can have active at the same time. Unlimited if not set.
+    pub max_streamers_per_client: Option<usize>,
 }
 
 impl Default for EventStreamingConfiguration {
@@ -14,6 +20,26 @@ impl Default for EventStreamingConfiguration {
         Self {
             worker_path: "event_streaming_worker.js".to_string(),
             access_control_allow_origin: "*".to_string(),
+            worker_buffer_capacity: NonZeroUsize::new(1024).expect("1024 is not zero"),
+            max_streamers_per_client: None,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_zero_worker_buffer_capacity_is_rejected() {
+        let config: EventStreamingConfiguration = serde_json::from_value(json!({})).unwrap();
+        assert_eq!(config.worker_buffer_capacity.get(), 1024);
+
+        let config: EventStreamingConfiguration =
+            serde_json::from_value(json!({ "worker_buffer_capacity": 16 })).unwrap();
+        assert_eq!(config.worker_buffer_capacity.get(), 16);
+
+        serde_json::from_value::<EventStreamingConfiguration>(json!({ "worker_buffer_capacity": 0 })).unwrap_err();
+    }
+}
@@ -1,5 +1,20 @@
 use crate::StreamerId;
-use serde_json::Value as Json;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value as Json};
+
+/// The format events are serialized in before being sent to a client.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EventFormat {
+    #[default]
+    Json,
+    /// A more compact binary format for high-frequency streams and bandwidth-constrained clients.
+    ///
+    /// Only binary transports, i.e. the wasm `postMessage` to the event streaming worker, send MessagePack frames.
+    /// SSE is a text protocol, so the streamers can't be activated with this format on native targets.
+    #[serde(rename = "messagepack")]
+    MessagePack,
+}
 
 // Note `Event` shouldn't be `Clone`able, but rather Arc/Rc wrapped and then shared.
 // This is only for testing.
@@ -35,6 +50,9 @@ impl Event {
         }
     }
 
+    /// Creates the marker sent to a client in place of the `dropped` events it was too slow to receive.
+    pub fn lagged(dropped: usize) -> Self { Self::new(StreamerId::Lagged, json!({ "dropped": dropped })) }
+
     /// Returns whether this event is an error or not
     #[inline(always)]
     pub fn is_error(&self) -> bool { self.error }
@@ -48,4 +66,57 @@ impl Event {
         let prefix = if self.error { "ERROR:" } else { "" };
         (format!("{prefix}{}", self.streamer_id), &self.message)
     }
+
+    /// Returns the `{"_type": .., "message": ..}` payload that is sent to the clients.
+    pub fn payload(&self) -> Json {
+        let (event_type, message) = self.get();
+        json!({
+            "_type": event_type,
+            "message": message,
+        })
+    }
+
+    /// Serializes the payload of the event in the given `format`.
+    pub fn encode(&self, format: EventFormat) -> Result<Vec<u8>, String> {
+        let payload = self.payload();
+        match format {
+            EventFormat::Json => serde_json::to_vec(&payload).map_err(|e| e.to_string()),
+            EventFormat::MessagePack => rmp_serde::to_vec(&payload).map_err(|e| e.to_string()),
+        }
+    }
+}
+
+#[cfg(any(test, target_arch = "wasm32"))]
+mod tests {
+    use super::*;
+
+    use common::{cfg_wasm32, cross_test};
+    cfg_wasm32! {
+        use wasm_bindgen_test::*;
+        wasm_bindgen_test::wasm_bindgen_test_configure!(run_in_browser);
+    }
+
+    cross_test!(test_encode_event, {
+        let event = Event::new(
+            StreamerId::Balance {
+                coin: "RICK".to_string(),
+            },
+            json!({ "address": "RRVJBpPDqaLGVxg4vzVsTnsW6PvHXmWGkP", "balance": { "spendable": "1.5" } }),
+        );
+        let expected = json!({
+            "_type": "BALANCE:RICK",
+            "message": { "address": "RRVJBpPDqaLGVxg4vzVsTnsW6PvHXmWGkP", "balance": { "spendable": "1.5" } },
+        });
+
+        let json_frame = event.encode(EventFormat::Json).unwrap();
+        assert_eq!(serde_json::from_slice::<Json>(&json_frame).unwrap(), expected);
+
+        let msgpack_frame = event.encode(EventFormat::MessagePack).unwrap();
+        assert_ne!(msgpack_frame, json_frame);
+        assert_eq!(rmp_serde::from_slice::<Json>(&msgpack_frame).unwrap(), expected);
+
+        let error_event = Event::err(StreamerId::SwapStatus, json!("error"));
+        let decoded: Json = rmp_serde::from_slice(&error_event.encode(EventFormat::MessagePack).unwrap()).unwrap();
+        assert_eq!(decoded, json!({ "_type": "ERROR:SWAP_STATUS", "message": "error" }));
+    });
 }
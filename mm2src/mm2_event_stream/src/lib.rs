@@ -6,7 +6,7 @@ pub mod streamer_ids;
 
 // Re-export important types.
 pub use configuration::EventStreamingConfiguration;
-pub use event::Event;
-pub use manager::{StreamingManager, StreamingManagerError};
+pub use event::{Event, EventFormat};
+pub use manager::{ActiveStreamerInfo, ClientEvent, StreamingManager, StreamingManagerError};
 pub use streamer::{Broadcaster, EventStreamer, NoDataIn, StreamHandlerInput};
 pub use streamer_ids::StreamerId;
@@ -1,18 +1,26 @@
 use std::any::Any;
 use std::collections::{HashMap, HashSet};
+use std::num::NonZeroUsize;
 use std::ops::{Deref, DerefMut};
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 
 use crate::streamer::spawn;
-use crate::{Event, EventStreamer, StreamerId};
+use crate::{Event, EventFormat, EventStreamer, StreamerId};
 use common::executor::abortable_queue::WeakSpawner;
-use common::log::{error, LogOnError};
+use common::log::error;
 
 use common::on_drop_callback::OnDropCallback;
 use futures::channel::mpsc::UnboundedSender;
 use futures::channel::oneshot;
 use parking_lot::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+use serde::Serialize;
+use serde_json::Value as Json;
 use tokio::sync::mpsc;
+use tokio::sync::mpsc::error::TrySendError;
+
+/// The number of events buffered for a client by default before the new ones start being dropped.
+const DEFAULT_CLIENT_CAPACITY: usize = 1024;
 
 /// The errors that could originate from the streaming manager.
 #[derive(Debug)]
@@ -31,6 +39,10 @@ pub enum StreamingManagerError {
     ClientExists,
     /// The client is already listening to the streamer.
     ClientAlreadyListening,
+    /// The client is already listening to the maximum number of streamers allowed per client.
+    QuotaExceeded { max: usize },
+    /// There is no parent streamer with the given ID to attach a child streamer to.
+    ParentNotFound,
 }
 
 #[derive(Debug)]
@@ -41,14 +53,25 @@ struct StreamerInfo {
     clients: HashSet<u64>,
     /// The shutdown handle of the streamer.
     shutdown: oneshot::Sender<()>,
+    /// The configuration the streamer was activated with.
+    config: Option<Json>,
+    /// The streamers this streamer is a child of. The events of a child streamer are also broadcasted
+    /// to the clients of its parents, and a child streamer is kept alive as long as it has a parent.
+    parents: HashSet<StreamerId>,
 }
 
 impl StreamerInfo {
-    fn new(data_in: Option<UnboundedSender<Box<dyn Any + Send>>>, shutdown: oneshot::Sender<()>) -> Self {
+    fn new(
+        data_in: Option<UnboundedSender<Box<dyn Any + Send>>>,
+        shutdown: oneshot::Sender<()>,
+        config: Option<Json>,
+    ) -> Self {
         Self {
             data_in,
             clients: HashSet::new(),
             shutdown,
+            config,
+            parents: HashSet::new(),
         }
     }
 
@@ -56,56 +79,162 @@ impl StreamerInfo {
 
     fn remove_client(&mut self, client_id: &u64) { self.clients.remove(client_id); }
 
+    fn is_unused(&self) -> bool { self.clients.is_empty() && self.parents.is_empty() }
+
     fn is_down(&self) -> bool { self.shutdown.is_canceled() }
 }
 
+/// An event queued for a client along with the format the client asked to receive it in.
+pub struct ClientEvent {
+    event: Arc<Event>,
+    format: EventFormat,
+}
+
+impl ClientEvent {
+    /// The format the event should be serialized in for the client.
+    pub fn format(&self) -> EventFormat { self.format }
+
+    /// Serializes the event payload in the format the client asked for.
+    pub fn encode(&self) -> Result<Vec<u8>, String> { self.event.encode(self.format) }
+}
+
+impl Deref for ClientEvent {
+    type Target = Event;
+    fn deref(&self) -> &Self::Target { &self.event }
+}
+
 #[derive(Debug)]
 struct ClientInfo {
-    /// The streamers the client is listening to.
-    listening_to: HashSet<StreamerId>,
+    /// The streamers the client is listening to, along with the format it receives their events in.
+    listening_to: HashMap<StreamerId, EventFormat>,
     /// The communication/stream-out channel to the client.
     // NOTE: Here we are using `tokio`'s `mpsc` because the one in `futures` have some extra feature
     // (ref: https://users.rust-lang.org/t/why-does-try-send-from-crate-futures-require-mut-self/100389).
     // This feature is aimed towards the multi-producer case (which we don't use) and requires a mutable
     // reference on `try_send` calls. This will require us to put the channel in a mutex and degrade the
     // broadcasting performance.
-    channel: mpsc::Sender<Arc<Event>>,
+    channel: mpsc::Sender<ClientEvent>,
+    /// The number of events dropped since the last one the client received, because its channel was full.
+    dropped: AtomicUsize,
 }
 
 impl ClientInfo {
-    fn new(channel: mpsc::Sender<Arc<Event>>) -> Self {
+    fn new(channel: mpsc::Sender<ClientEvent>) -> Self {
         Self {
-            listening_to: HashSet::new(),
+            listening_to: HashMap::new(),
             channel,
+            dropped: AtomicUsize::new(0),
         }
     }
 
-    fn add_streamer(&mut self, streamer_id: StreamerId) { self.listening_to.insert(streamer_id); }
+    fn add_streamer(&mut self, streamer_id: StreamerId, format: EventFormat) {
+        self.listening_to.insert(streamer_id, format);
+    }
 
     fn remove_streamer(&mut self, streamer_id: &StreamerId) { self.listening_to.remove(streamer_id); }
 
-    fn listens_to(&self, streamer_id: &StreamerId) -> bool { self.listening_to.contains(streamer_id) }
+    fn listens_to(&self, streamer_id: &StreamerId) -> bool { self.listening_to.contains_key(streamer_id) }
+
+    /// The format the client receives the events of `streamer_id` in, JSON if it isn't listening to it.
+    fn format_for(&self, streamer_id: &StreamerId) -> EventFormat {
+        self.listening_to.get(streamer_id).copied().unwrap_or_default()
+    }
 
-    fn send_event(&self, event: Arc<Event>) {
+    fn is_at_quota(&self, max_streamers: Option<usize>) -> bool {
+        max_streamers.map_or(false, |max| self.listening_to.len() >= max)
+    }
+
+    fn send_event(&self, event: Arc<Event>, format: EventFormat) {
         // Only `try_send` here. If the channel is full (client is slow), the message
         // will be dropped and the client won't receive it.
         // This avoids blocking the broadcast to other receivers.
-        self.channel.try_send(event).error_log();
+        // The dropped events are counted and reported to the client with a `LAGGED` marker ahead of
+        // the next event that fits into the channel.
+        let dropped = self.dropped.swap(0, Ordering::Relaxed);
+        if dropped > 0 {
+            let marker = ClientEvent {
+                event: Arc::new(Event::lagged(dropped)),
+                format,
+            };
+            if let Err(e) = self.channel.try_send(marker) {
+                self.on_send_error(e, dropped + 1);
+                return;
+            }
+        }
+        if let Err(e) = self.channel.try_send(ClientEvent { event, format }) {
+            self.on_send_error(e, 1);
+        }
+    }
+
+    /// Counts the `dropped` events if the channel is full, the client is gone otherwise.
+    fn on_send_error(&self, error: TrySendError<ClientEvent>, dropped: usize) {
+        match error {
+            TrySendError::Full(_) => {
+                self.dropped.fetch_add(dropped, Ordering::Relaxed);
+            },
+            TrySendError::Closed(_) => error!("Couldn't send an event to a closed client channel"),
+        }
     }
 }
 
+/// An active streamer as seen by one of the clients listening to it.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct ActiveStreamerInfo {
+    pub streamer_id: StreamerId,
+    /// The kind of the streamer, see [`StreamerId::kind`].
+    pub kind: &'static str,
+    pub client_id: u64,
+    /// The configuration the streamer was activated with, if it has any.
+    pub config: Option<Json>,
+}
+
 #[derive(Default, Debug)]
 struct StreamingManagerInner {
     /// A map from streamer IDs to their communication channels (if present) and shutdown handles.
     streamers: HashMap<StreamerId, StreamerInfo>,
     /// An inverse map from client IDs to the streamers they are listening to and the communication channel with the client.
     clients: HashMap<u64, ClientInfo>,
+    /// The maximum number of streamers a single client can listen to at the same time. Unlimited if `None`.
+    max_streamers_per_client: Option<usize>,
+}
+
+impl StreamingManagerInner {
+    /// Terminates the streamer with `streamer_id` if no clients nor parents are left for it,
+    /// and then does the same for its children that were only kept alive by it.
+    fn remove_streamer_if_unused(&mut self, streamer_id: &StreamerId) {
+        if !self.streamers.get(streamer_id).map_or(false, StreamerInfo::is_unused) {
+            return;
+        }
+        self.streamers.remove(streamer_id);
+        self.detach_children(streamer_id);
+    }
+
+    /// Detaches the children of the (already removed) streamer `parent_id` and terminates the unused ones.
+    fn detach_children(&mut self, parent_id: &StreamerId) {
+        let children: Vec<_> = self
+            .streamers
+            .iter_mut()
+            .filter_map(|(child_id, info)| info.parents.remove(parent_id).then(|| child_id.clone()))
+            .collect();
+        for child_id in children {
+            self.remove_streamer_if_unused(&child_id);
+        }
+    }
 }
 
 #[derive(Clone, Default, Debug)]
 pub struct StreamingManager(Arc<RwLock<StreamingManagerInner>>);
 
 impl StreamingManager {
+    /// Creates a streaming manager that allows each client to listen to at most `max_streamers_per_client` streamers.
+    pub fn with_client_quota(max_streamers_per_client: usize) -> Self {
+        let inner = StreamingManagerInner {
+            max_streamers_per_client: Some(max_streamers_per_client),
+            ..Default::default()
+        };
+        Self(Arc::new(RwLock::new(inner)))
+    }
+
     /// Returns a read guard over the streaming manager.
     fn read(&self) -> RwLockReadGuard<StreamingManagerInner> { self.0.read() }
 
@@ -118,6 +247,18 @@ impl StreamingManager {
         client_id: u64,
         streamer: impl EventStreamer,
         spawner: WeakSpawner,
+    ) -> Result<StreamerId, StreamingManagerError> {
+        self.add_with_format(client_id, streamer, spawner, EventFormat::default())
+            .await
+    }
+
+    /// Same as [`StreamingManager::add`], but the client receives the events of the streamer serialized in `format`.
+    pub async fn add_with_format(
+        &self,
+        client_id: u64,
+        streamer: impl EventStreamer,
+        spawner: WeakSpawner,
+        format: EventFormat,
     ) -> Result<StreamerId, StreamingManagerError> {
         let streamer_id = streamer.streamer_id();
         // Remove the streamer if it died for some reason.
@@ -133,6 +274,12 @@ impl StreamingManager {
                 Some(client_info) if client_info.listens_to(&streamer_id) => {
                     return Err(StreamingManagerError::ClientAlreadyListening);
                 },
+                // The client can't listen to any more streamers.
+                Some(client_info) if client_info.is_at_quota(this.max_streamers_per_client) => {
+                    return Err(StreamingManagerError::QuotaExceeded {
+                        max: this.max_streamers_per_client.unwrap_or_default(),
+                    });
+                },
                 _ => (),
             }
 
@@ -142,24 +289,32 @@ impl StreamingManager {
                 streamer_info.add_client(client_id);
                 // Register the streamer as listened-to by the client.
                 if let Some(client_info) = this.clients.get_mut(&client_id) {
-                    client_info.add_streamer(streamer_id.clone());
+                    client_info.add_streamer(streamer_id.clone(), format);
                 }
                 return Ok(streamer_id);
             }
         }
 
         // Spawn a new streamer.
+        let config = streamer.config();
         let (shutdown, data_in) = spawn(streamer, spawner, self.clone())
             .await
             .map_err(StreamingManagerError::SpawnError)?;
-        let streamer_info = StreamerInfo::new(data_in, shutdown);
+        let streamer_info = StreamerInfo::new(data_in, shutdown, config);
 
         // Note that we didn't hold the lock while spawning the streamer (potentially a long operation).
         // This means we can't assume either that the client still exists at this point or
         // that the streamer still doesn't exist.
         let mut this = self.write();
+        let max_streamers = this.max_streamers_per_client;
         if let Some(client_info) = this.clients.get_mut(&client_id) {
-            client_info.add_streamer(streamer_id.clone());
+            // The client might have reached its quota through another request while we were spawning.
+            if !client_info.listens_to(&streamer_id) && client_info.is_at_quota(max_streamers) {
+                return Err(StreamingManagerError::QuotaExceeded {
+                    max: max_streamers.unwrap_or_default(),
+                });
+            }
+            client_info.add_streamer(streamer_id.clone(), format);
             this.streamers
                 .entry(streamer_id.clone())
                 .or_insert(streamer_info)
@@ -172,6 +327,91 @@ impl StreamingManager {
         Ok(streamer_id)
     }
 
+    /// Spawns and adds a new streamer `streamer` as a child of the streamer with `parent_id`.
+    ///
+    /// Events of the child streamer are broadcasted to the clients of the parent streamer, which allows
+    /// a single streamer to aggregate the events of many others (e.g. the balances of all enabled coins)
+    /// without the client listening to (and counting towards its quota) each of them separately.
+    /// The child streamer is terminated once it's detached from all its parents and has no clients.
+    pub async fn add_child(
+        &self,
+        parent_id: &StreamerId,
+        streamer: impl EventStreamer,
+        spawner: WeakSpawner,
+    ) -> Result<StreamerId, StreamingManagerError> {
+        let streamer_id = streamer.streamer_id();
+        // Remove the streamer if it died for some reason.
+        self.remove_streamer_if_down(&streamer_id);
+
+        {
+            let mut this = self.write();
+            if !this.streamers.contains_key(parent_id) {
+                return Err(StreamingManagerError::ParentNotFound);
+            }
+            // If a streamer is already up and running, we won't spawn another one.
+            if let Some(streamer_info) = this.streamers.get_mut(&streamer_id) {
+                streamer_info.parents.insert(parent_id.clone());
+                return Ok(streamer_id);
+            }
+        }
+
+        let config = streamer.config();
+        let (shutdown, data_in) = spawn(streamer, spawner, self.clone())
+            .await
+            .map_err(StreamingManagerError::SpawnError)?;
+        let streamer_info = StreamerInfo::new(data_in, shutdown, config);
+
+        // The parent might have been removed while we were spawning the streamer.
+        let mut this = self.write();
+        if !this.streamers.contains_key(parent_id) {
+            return Err(StreamingManagerError::ParentNotFound);
+        }
+        this.streamers
+            .entry(streamer_id.clone())
+            .or_insert(streamer_info)
+            .parents
+            .insert(parent_id.clone());
+        Ok(streamer_id)
+    }
+
+    /// Detaches the child streamer with `child_id` from the parent streamer with `parent_id`.
+    ///
+    /// The child streamer is terminated if it has no other parents nor clients.
+    pub fn remove_child(&self, parent_id: &StreamerId, child_id: &StreamerId) -> Result<(), StreamingManagerError> {
+        let mut this = self.write();
+        let child_info = this
+            .streamers
+            .get_mut(child_id)
+            .ok_or(StreamingManagerError::StreamerNotFound)?;
+        child_info.parents.remove(parent_id);
+        this.remove_streamer_if_unused(child_id);
+        Ok(())
+    }
+
+    /// Returns the IDs of the running child streamers of the streamer with `parent_id`.
+    ///
+    /// Children that died by themselves (e.g. their coin was disabled) are detached from the parent.
+    pub fn children(&self, parent_id: &StreamerId) -> Result<Vec<StreamerId>, StreamingManagerError> {
+        let mut this = self.write();
+        if !this.streamers.contains_key(parent_id) {
+            return Err(StreamingManagerError::StreamerNotFound);
+        }
+        let (dead, mut children): (Vec<_>, Vec<_>) = this
+            .streamers
+            .iter()
+            .filter(|(_, info)| info.parents.contains(parent_id))
+            .map(|(child_id, info)| (child_id.clone(), info.is_down()))
+            .partition(|(_, is_down)| *is_down);
+        for (child_id, _) in dead {
+            if let Some(info) = this.streamers.get_mut(&child_id) {
+                info.parents.remove(parent_id);
+            }
+            this.remove_streamer_if_unused(&child_id);
+        }
+        children.sort_by_cached_key(|(child_id, _)| child_id.to_string());
+        Ok(children.into_iter().map(|(child_id, _)| child_id).collect())
+    }
+
     /// Sends data to a streamer with `streamer_id`.
     pub fn send<T: Send + 'static>(&self, streamer_id: &StreamerId, data: T) -> Result<(), StreamingManagerError> {
         let this = self.read();
@@ -221,13 +461,85 @@ impl StreamingManager {
             .remove_client(&client_id);
 
         // If there are no more listening clients, terminate the streamer.
-        if this.streamers.get(streamer_id).map(|info| info.clients.len()) == Some(0) {
-            this.streamers.remove(streamer_id);
-        }
+        this.remove_streamer_if_unused(streamer_id);
         Ok(())
     }
 
-    /// Broadcasts some event to clients listening to it.
+    /// Stops streaming from all the streamers of the given `kind` (see [`StreamerId::kind`]) to the client with `client_id`.
+    ///
+    /// The `kind` is matched case-insensitively. Returns the IDs of the stopped streamers.
+    pub fn stop_kind(&self, client_id: u64, kind: &str) -> Result<Vec<StreamerId>, StreamingManagerError> {
+        let mut this = self.write();
+        let client_info = this
+            .clients
+            .get_mut(&client_id)
+            .ok_or(StreamingManagerError::UnknownClient)?;
+        let mut stopped: Vec<_> = client_info
+            .listening_to
+            .keys()
+            .filter(|streamer_id| streamer_id.kind().eq_ignore_ascii_case(kind))
+            .cloned()
+            .collect();
+        for streamer_id in &stopped {
+            client_info.remove_streamer(streamer_id);
+        }
+
+        for streamer_id in &stopped {
+            if let Some(streamer_info) = this.streamers.get_mut(streamer_id) {
+                streamer_info.remove_client(&client_id);
+            }
+            // If there are no more listening clients, terminate the streamer.
+            this.remove_streamer_if_unused(streamer_id);
+        }
+        stopped.sort_by_cached_key(|streamer_id| streamer_id.to_string());
+        Ok(stopped)
+    }
+
+    /// Returns an entry for every (streamer, client) pair that is currently active.
+    ///
+    /// Streamers are registered here when activated and de-registered when stopped or when their last client is removed.
+    pub fn active_streamers(&self) -> Vec<ActiveStreamerInfo> {
+        let this = self.read();
+        let mut active: Vec<_> = this
+            .streamers
+            .iter()
+            .flat_map(|(streamer_id, info)| {
+                info.clients.iter().map(move |client_id| ActiveStreamerInfo {
+                    streamer_id: streamer_id.clone(),
+                    kind: streamer_id.kind(),
+                    client_id: *client_id,
+                    config: info.config.clone(),
+                })
+            })
+            .collect();
+        active.sort_by_cached_key(|info| (info.client_id, info.streamer_id.to_string()));
+        active
+    }
+
+    /// Returns the active streamers the client with `client_id` is listening to.
+    pub fn client_streamers(&self, client_id: u64) -> Result<Vec<ActiveStreamerInfo>, StreamingManagerError> {
+        let this = self.read();
+        let client_info = this
+            .clients
+            .get(&client_id)
+            .ok_or(StreamingManagerError::UnknownClient)?;
+        let mut active: Vec<_> = client_info
+            .listening_to
+            .keys()
+            .filter_map(|streamer_id| {
+                this.streamers.get(streamer_id).map(|info| ActiveStreamerInfo {
+                    streamer_id: streamer_id.clone(),
+                    kind: streamer_id.kind(),
+                    client_id,
+                    config: info.config.clone(),
+                })
+            })
+            .collect();
+        active.sort_by_cached_key(|info| info.streamer_id.to_string());
+        Ok(active)
+    }
+
+    /// Broadcasts some event to clients listening to it (or to any of its parent streamers).
     ///
     /// In contrast to `StreamingManager::send`, which sends some data to a streamer,
     /// this method broadcasts an event to the listening *clients* directly, independently
@@ -235,13 +547,28 @@ impl StreamingManager {
     pub fn broadcast(&self, event: Event) {
         let event = Arc::new(event);
         let this = self.read();
-        if let Some(client_ids) = this.streamers.get(event.origin()).map(|info| &info.clients) {
-            client_ids.iter().for_each(|client_id| {
-                if let Some(info) = this.clients.get(client_id) {
-                    info.send_event(event.clone());
-                }
-            });
+        let Some(streamer_info) = this.streamers.get(event.origin()) else {
+            return;
         };
+        // Maps each recipient client to the streamer it receives the event through (to pick the right format).
+        // A client listening to both the streamer and one of its parents receives the event only once.
+        let mut recipients = HashMap::new();
+        for parent_id in streamer_info.parents.iter() {
+            if let Some(parent_info) = this.streamers.get(parent_id) {
+                recipients.extend(parent_info.clients.iter().map(|client_id| (client_id, parent_id)));
+            }
+        }
+        recipients.extend(
+            streamer_info
+                .clients
+                .iter()
+                .map(|client_id| (client_id, event.origin())),
+        );
+        recipients.into_iter().for_each(|(client_id, via)| {
+            if let Some(info) = this.clients.get(client_id) {
+                info.send_event(event.clone(), info.format_for(via));
+            }
+        });
     }
 
     /// Broadcasts (actually just *sends* in this case) some event to a specific client.
@@ -252,7 +579,10 @@ impl StreamingManager {
         self.read()
             .clients
             .get(&client_id)
-            .map(|info| info.send_event(event))
+            .map(|info| {
+                let format = info.format_for(event.origin());
+                info.send_event(event, format)
+            })
             .ok_or(StreamingManagerError::UnknownClient)
     }
 
@@ -260,19 +590,30 @@ impl StreamingManager {
     pub fn broadcast_all(&self, event: Event) {
         let event = Arc::new(event);
         self.read().clients.values().for_each(|info| {
-            info.send_event(event.clone());
+            info.send_event(event.clone(), info.format_for(event.origin()));
         });
     }
 
     /// Creates a new client and returns the event receiver for this client.
     pub fn new_client(&self, client_id: u64) -> Result<ClientHandle, StreamingManagerError> {
+        let capacity = NonZeroUsize::new(DEFAULT_CLIENT_CAPACITY).expect("The default capacity is not zero");
+        self.new_client_with_capacity(client_id, capacity)
+    }
+
+    /// Registers a new client that buffers at most `capacity` events. Once the buffer is full, the new events
+    /// are dropped and the client receives a `LAGGED` event with the number of the dropped events instead.
+    pub fn new_client_with_capacity(
+        &self,
+        client_id: u64,
+        capacity: NonZeroUsize,
+    ) -> Result<ClientHandle, StreamingManagerError> {
         let mut this = self.write();
         if this.clients.contains_key(&client_id) {
             return Err(StreamingManagerError::ClientExists);
         }
         // Note that events queued in the channel are `Arc<` shared.
         // So a 1024 long buffer isn't actually heavy on memory.
-        let (tx, rx) = mpsc::channel(1024);
+        let (tx, rx) = mpsc::channel(capacity.get());
         let client_info = ClientInfo::new(tx);
         this.clients.insert(client_id, client_info);
         let manager = self.clone();
@@ -293,16 +634,14 @@ impl StreamingManager {
             .remove(&client_id)
             .ok_or(StreamingManagerError::UnknownClient)?;
         // Remove the client from all the streamers it was listening to.
-        for streamer_id in client_info.listening_to {
+        for streamer_id in client_info.listening_to.into_keys() {
             if let Some(streamer_info) = this.streamers.get_mut(&streamer_id) {
                 streamer_info.remove_client(&client_id);
             } else {
                 error!("Client {client_id} was listening to a non-existent streamer {streamer_id}. This is a bug!");
             }
             // If there are no more listening clients, terminate the streamer.
-            if this.streamers.get(&streamer_id).map(|info| info.clients.len()) == Some(0) {
-                this.streamers.remove(&streamer_id);
-            }
+            this.remove_streamer_if_unused(&streamer_id);
         }
         Ok(())
     }
@@ -330,6 +669,8 @@ impl StreamingManager {
                 info.remove_streamer(streamer_id);
             }
         }
+        // Its children can't be reached through it anymore.
+        this.detach_children(streamer_id);
     }
 }
 
@@ -337,13 +678,13 @@ impl StreamingManager {
 /// the client when dropped.
 /// So this handle must live as long as the client is connected.
 pub struct ClientHandle {
-    rx: mpsc::Receiver<Arc<Event>>,
+    rx: mpsc::Receiver<ClientEvent>,
     _on_drop_callback: OnDropCallback,
 }
 
 /// Deref the handle to the receiver inside for ease of use.
 impl Deref for ClientHandle {
-    type Target = mpsc::Receiver<Arc<Event>>;
+    type Target = mpsc::Receiver<ClientEvent>;
     fn deref(&self) -> &Self::Target { &self.rx }
 }
 
@@ -355,7 +696,8 @@ impl DerefMut for ClientHandle {
 #[cfg(any(test, target_arch = "wasm32"))]
 mod tests {
     use super::*;
-    use crate::streamer::test_utils::{InitErrorStreamer, PeriodicStreamer, ReactiveStreamer};
+    use crate::streamer::test_utils::{ConfiguredStreamer, InitErrorStreamer, PeriodicStreamer, PeriodicStreamerWithId,
+                                      ReactiveStreamer};
 
     use common::executor::{abortable_queue::AbortableQueue, AbortableSystem, Timer};
     use common::{cfg_wasm32, cross_test};
@@ -395,6 +737,44 @@ mod tests {
         ));
     });
 
+    cross_test!(test_slow_client_gets_lag_marker, {
+        let manager = StreamingManager::default();
+        let mut client = manager
+            .new_client_with_capacity(1, NonZeroUsize::new(2).unwrap())
+            .unwrap();
+        let event = |i: u32| {
+            Event::new(
+                StreamerId::ForTesting {
+                    test_streamer: "test".to_string(),
+                },
+                json!(i),
+            )
+        };
+
+        // The client doesn't read, so only the first two events fit into its buffer.
+        for i in 0..5 {
+            manager.broadcast_all(event(i));
+        }
+        assert_eq!(*client.try_recv().unwrap(), event(0));
+        assert_eq!(*client.try_recv().unwrap(), event(1));
+        assert!(client.try_recv().is_err());
+
+        // The marker is sent ahead of the next event, and the count is reset after it.
+        manager.broadcast_all(event(5));
+        manager.broadcast_all(event(6));
+        assert_eq!(*client.try_recv().unwrap(), Event::lagged(3));
+        assert_eq!(*client.try_recv().unwrap(), event(5));
+        // No room was left for the 6th event.
+        assert!(client.try_recv().is_err());
+        manager.broadcast_all(event(7));
+        let marker = client.try_recv().unwrap();
+        assert_eq!(
+            marker.payload(),
+            json!({ "_type": "LAGGED", "message": { "dropped": 1 } })
+        );
+        assert_eq!(*client.try_recv().unwrap(), event(7));
+    });
+
     cross_test!(test_broadcast_all, {
         // Create a manager and add register two clients with it.
         let manager = StreamingManager::default();
@@ -555,4 +935,260 @@ mod tests {
             .unwrap()
             .listens_to(&streamer_id));
     });
+
+    cross_test!(test_active_streamers, {
+        let manager = StreamingManager::default();
+        let system = AbortableQueue::default();
+        let client_id = 1;
+        let _client = manager.new_client(client_id).unwrap();
+
+        let balance_id = StreamerId::Balance {
+            coin: "RICK".to_string(),
+        };
+        let balance_config = json!({ "stream_interval_seconds": 10.0 });
+        let balance_streamer = ConfiguredStreamer {
+            streamer_id: balance_id.clone(),
+            config: Some(balance_config.clone()),
+        };
+        let swap_streamer = ConfiguredStreamer {
+            streamer_id: StreamerId::SwapStatus,
+            config: None,
+        };
+        manager
+            .add(client_id, balance_streamer, system.weak_spawner())
+            .await
+            .unwrap();
+        manager
+            .add(client_id, swap_streamer, system.weak_spawner())
+            .await
+            .unwrap();
+
+        let expected = vec![
+            ActiveStreamerInfo {
+                streamer_id: balance_id.clone(),
+                kind: "BALANCE",
+                client_id,
+                config: Some(balance_config),
+            },
+            ActiveStreamerInfo {
+                streamer_id: StreamerId::SwapStatus,
+                kind: "SWAP_STATUS",
+                client_id,
+                config: None,
+            },
+        ];
+        assert_eq!(manager.active_streamers(), expected);
+        assert_eq!(manager.client_streamers(client_id).unwrap(), expected);
+        assert!(matches!(
+            manager.client_streamers(2),
+            Err(StreamingManagerError::UnknownClient)
+        ));
+
+        // Disabling a streamer de-registers it.
+        manager.stop(client_id, &balance_id).unwrap();
+        assert_eq!(manager.active_streamers(), expected[1..]);
+    });
+
+    cross_test!(test_stop_kind, {
+        let manager = StreamingManager::default();
+        let system = AbortableQueue::default();
+        let client_id = 1;
+        let _client = manager.new_client(client_id).unwrap();
+
+        let balance_id = |coin: &str| StreamerId::Balance { coin: coin.to_string() };
+        for streamer_id in [balance_id("RICK"), balance_id("MORTY"), StreamerId::SwapStatus] {
+            let streamer = ConfiguredStreamer {
+                streamer_id,
+                config: None,
+            };
+            manager.add(client_id, streamer, system.weak_spawner()).await.unwrap();
+        }
+
+        let stopped = manager.stop_kind(client_id, "balance").unwrap();
+        assert_eq!(stopped, vec![balance_id("MORTY"), balance_id("RICK")]);
+
+        // Only the streamer of the other kind is left.
+        let active: Vec<_> = manager
+            .client_streamers(client_id)
+            .unwrap()
+            .into_iter()
+            .map(|info| info.streamer_id)
+            .collect();
+        assert_eq!(active, vec![StreamerId::SwapStatus]);
+        assert!(manager.read().streamers.get(&balance_id("RICK")).is_none());
+
+        // Nothing is left to stop.
+        assert!(manager.stop_kind(client_id, "BALANCE").unwrap().is_empty());
+        assert!(matches!(
+            manager.stop_kind(2, "BALANCE"),
+            Err(StreamingManagerError::UnknownClient)
+        ));
+    });
+
+    cross_test!(test_client_quota, {
+        const MAX_STREAMERS: usize = 2;
+
+        let manager = StreamingManager::with_client_quota(MAX_STREAMERS);
+        let system = AbortableQueue::default();
+        let client_id = 1;
+        let _client = manager.new_client(client_id).unwrap();
+
+        let balance_streamer = |coin: &str| ConfiguredStreamer {
+            streamer_id: StreamerId::Balance { coin: coin.to_string() },
+            config: None,
+        };
+        // Activate streamers up to the quota.
+        for coin in ["RICK", "MORTY"] {
+            manager
+                .add(client_id, balance_streamer(coin), system.weak_spawner())
+                .await
+                .unwrap();
+        }
+
+        // One more streamer is beyond the quota.
+        let error = manager
+            .add(client_id, balance_streamer("KMD"), system.weak_spawner())
+            .await
+            .unwrap_err();
+        assert!(matches!(error, StreamingManagerError::QuotaExceeded {
+            max: MAX_STREAMERS
+        }));
+        // The rejected streamer shouldn't be left running.
+        assert!(manager
+            .read()
+            .streamers
+            .get(&StreamerId::Balance {
+                coin: "KMD".to_string()
+            })
+            .is_none());
+
+        // Disabling a streamer frees up the quota.
+        manager
+            .stop(client_id, &StreamerId::Balance {
+                coin: "RICK".to_string(),
+            })
+            .unwrap();
+        manager
+            .add(client_id, balance_streamer("KMD"), system.weak_spawner())
+            .await
+            .unwrap();
+    });
+
+    cross_test!(test_child_streamers, {
+        let manager = StreamingManager::with_client_quota(1);
+        let system = AbortableQueue::default();
+        let client_id = 1;
+        let mut client = manager.new_client(client_id).unwrap();
+
+        let parent_id = StreamerId::AllBalances;
+        let parent_streamer = ConfiguredStreamer {
+            streamer_id: parent_id.clone(),
+            config: None,
+        };
+        let rick_id = StreamerId::Balance {
+            coin: "RICK".to_string(),
+        };
+        let morty_id = StreamerId::Balance {
+            coin: "MORTY".to_string(),
+        };
+
+        // A child can't be attached to a parent that isn't running.
+        let error = manager
+            .add_child(
+                &parent_id,
+                PeriodicStreamerWithId(rick_id.clone()),
+                system.weak_spawner(),
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(error, StreamingManagerError::ParentNotFound));
+
+        manager
+            .add(client_id, parent_streamer, system.weak_spawner())
+            .await
+            .unwrap();
+        manager
+            .add_child(
+                &parent_id,
+                PeriodicStreamerWithId(rick_id.clone()),
+                system.weak_spawner(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(manager.children(&parent_id).unwrap(), vec![rick_id.clone()]);
+
+        // The events of the child reach the client listening to the parent.
+        Timer::sleep(0.15).await;
+        let event = client.try_recv().unwrap();
+        assert_eq!(event.origin(), &rick_id);
+
+        // Children added later (e.g. a newly enabled coin) start streaming too, and don't count towards the quota.
+        manager
+            .add_child(
+                &parent_id,
+                PeriodicStreamerWithId(morty_id.clone()),
+                system.weak_spawner(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(manager.children(&parent_id).unwrap(), vec![
+            morty_id.clone(),
+            rick_id.clone()
+        ]);
+        Timer::sleep(0.15).await;
+        let mut origins = HashSet::new();
+        while let Ok(event) = client.try_recv() {
+            origins.insert(event.origin().clone());
+        }
+        assert_eq!(origins, HashSet::from([rick_id.clone(), morty_id.clone()]));
+
+        // Detaching a child terminates it.
+        manager.remove_child(&parent_id, &rick_id).unwrap();
+        assert!(manager.read().streamers.get(&rick_id).is_none());
+        assert_eq!(manager.children(&parent_id).unwrap(), vec![morty_id.clone()]);
+
+        // Stopping the parent terminates its children.
+        manager.stop(client_id, &parent_id).unwrap();
+        assert!(manager.read().streamers.get(&parent_id).is_none());
+        assert!(manager.read().streamers.get(&morty_id).is_none());
+    });
+
+    cross_test!(test_event_format, {
+        let manager = StreamingManager::default();
+        let system = AbortableQueue::default();
+        let (json_client_id, msgpack_client_id) = (1, 2);
+        let mut json_client = manager.new_client(json_client_id).unwrap();
+        let mut msgpack_client = manager.new_client(msgpack_client_id).unwrap();
+
+        let streamer_id = manager
+            .add(json_client_id, ReactiveStreamer, system.weak_spawner())
+            .await
+            .unwrap();
+        manager
+            .add_with_format(
+                msgpack_client_id,
+                ReactiveStreamer,
+                system.weak_spawner(),
+                EventFormat::MessagePack,
+            )
+            .await
+            .unwrap();
+
+        manager.send(&streamer_id, "hello".to_string()).unwrap();
+        Timer::sleep(0.1).await;
+        let expected = json!({ "_type": streamer_id.to_string(), "message": "hello" });
+
+        // Both clients receive the same event, each in the format it asked for.
+        let event = json_client.try_recv().unwrap();
+        assert_eq!(event.format(), EventFormat::Json);
+        assert_eq!(
+            serde_json::from_slice::<Json>(&event.encode().unwrap()).unwrap(),
+            expected
+        );
+
+        let event = msgpack_client.try_recv().unwrap();
+        assert_eq!(event.format(), EventFormat::MessagePack);
+        let frame = event.encode().unwrap();
+        assert_eq!(rmp_serde::from_slice::<Json>(&frame).unwrap(), expected);
+    });
 }
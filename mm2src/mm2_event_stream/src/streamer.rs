@@ -7,6 +7,7 @@ use common::log::{error, info};
 use async_trait::async_trait;
 use futures::channel::{mpsc, oneshot};
 use futures::{future, select, FutureExt, Stream, StreamExt};
+use serde_json::Value as Json;
 
 /// A marker to indicate that the event streamer doesn't take any input data.
 pub struct NoDataIn;
@@ -27,6 +28,9 @@ where
     /// No other event streamer should have the same identifier.
     fn streamer_id(&self) -> StreamerId;
 
+    /// Returns the configuration the streamer was activated with, for introspection purposes.
+    fn config(&self) -> Option<Json> { None }
+
     /// Event handler that is responsible for broadcasting event data to the streaming channels.
     ///
     /// `ready_tx` is a oneshot sender that is used to send the initialization status of the event.
@@ -106,12 +110,32 @@ where
 }
 
 /// A wrapper around `StreamingManager` to only expose the `broadcast` method.
-pub struct Broadcaster(StreamingManager);
+pub struct Broadcaster(BroadcastTarget);
+
+enum BroadcastTarget {
+    Manager(StreamingManager),
+    Channel(mpsc::UnboundedSender<Event>),
+}
 
 impl Broadcaster {
-    pub fn new(inner: StreamingManager) -> Self { Self(inner) }
+    pub fn new(inner: StreamingManager) -> Self { Self(BroadcastTarget::Manager(inner)) }
+
+    /// Creates a broadcaster that forwards the events to the returned receiver instead of broadcasting them.
+    ///
+    /// Useful for wrapping a streamer to process (e.g. throttle) its events before they are broadcast.
+    pub fn channel() -> (Self, mpsc::UnboundedReceiver<Event>) {
+        let (tx, rx) = mpsc::unbounded();
+        (Self(BroadcastTarget::Channel(tx)), rx)
+    }
 
-    pub fn broadcast(&self, event: Event) { self.0.broadcast(event); }
+    pub fn broadcast(&self, event: Event) {
+        match &self.0 {
+            BroadcastTarget::Manager(manager) => manager.broadcast(event),
+            BroadcastTarget::Channel(tx) => {
+                tx.unbounded_send(event).ok();
+            },
+        }
+    }
 }
 
 #[cfg(any(test, target_arch = "wasm32"))]
@@ -149,6 +173,29 @@ pub mod test_utils {
         }
     }
 
+    /// Same as [`PeriodicStreamer`] but broadcasts under the given streamer ID.
+    pub struct PeriodicStreamerWithId(pub StreamerId);
+
+    #[async_trait]
+    impl EventStreamer for PeriodicStreamerWithId {
+        type DataInType = NoDataIn;
+
+        fn streamer_id(&self) -> StreamerId { self.0.clone() }
+
+        async fn handle(
+            self,
+            broadcaster: Broadcaster,
+            ready_tx: oneshot::Sender<Result<(), String>>,
+            _: impl StreamHandlerInput<Self::DataInType>,
+        ) {
+            ready_tx.send(Ok(())).unwrap();
+            loop {
+                broadcaster.broadcast(Event::new(self.streamer_id(), json!("hello")));
+                Timer::sleep(0.1).await;
+            }
+        }
+    }
+
     /// A test event streamer that broadcasts an event whenever it receives a new message through `data_rx`.
     pub struct ReactiveStreamer;
 
@@ -199,6 +246,31 @@ pub mod test_utils {
             ready_tx.send(Err("error".to_string())).unwrap();
         }
     }
+
+    /// A test event streamer that impersonates any streamer ID and configuration, but never broadcasts anything.
+    pub struct ConfiguredStreamer {
+        pub streamer_id: StreamerId,
+        pub config: Option<Json>,
+    }
+
+    #[async_trait]
+    impl EventStreamer for ConfiguredStreamer {
+        type DataInType = NoDataIn;
+
+        fn streamer_id(&self) -> StreamerId { self.streamer_id.clone() }
+
+        fn config(&self) -> Option<Json> { self.config.clone() }
+
+        async fn handle(
+            self,
+            _: Broadcaster,
+            ready_tx: oneshot::Sender<Result<(), String>>,
+            _: impl StreamHandlerInput<Self::DataInType>,
+        ) {
+            ready_tx.send(Ok(())).unwrap();
+            future::pending::<()>().await;
+        }
+    }
 }
 
 #[cfg(any(test, target_arch = "wasm32"))]
@@ -6,6 +6,8 @@ const NETWORK: &str = "NETWORK";
 const HEARTBEAT: &str = "HEARTBEAT";
 const SWAP_STATUS: &str = "SWAP_STATUS";
 const ORDER_STATUS: &str = "ORDER_STATUS";
+const ALL_BALANCES: &str = "ALL_BALANCES";
+const LAGGED: &str = "LAGGED";
 
 const TASK_PREFIX: &str = "TASK:";
 const BALANCE_PREFIX: &str = "BALANCE:";
@@ -22,6 +24,10 @@ pub enum StreamerId {
     Heartbeat,
     SwapStatus,
     OrderStatus,
+    /// Aggregates the [`StreamerId::Balance`] streamers of all the enabled coins.
+    AllBalances,
+    /// Not a streamer, marks the events a client was too slow to receive, see [`crate::Event::lagged`].
+    Lagged,
     Task {
         task_id: u64, // TODO: should be TaskId (from rpc_task)
     },
@@ -53,6 +59,8 @@ impl fmt::Display for StreamerId {
             StreamerId::Heartbeat => write!(f, "{}", HEARTBEAT),
             StreamerId::SwapStatus => write!(f, "{}", SWAP_STATUS),
             StreamerId::OrderStatus => write!(f, "{}", ORDER_STATUS),
+            StreamerId::AllBalances => write!(f, "{}", ALL_BALANCES),
+            StreamerId::Lagged => write!(f, "{}", LAGGED),
             StreamerId::Task { task_id } => write!(f, "{}{}", TASK_PREFIX, task_id),
             StreamerId::Balance { coin } => write!(f, "{}{}", BALANCE_PREFIX, coin),
             StreamerId::TxHistory { coin } => write!(f, "{}{}", TX_HISTORY_PREFIX, coin),
@@ -65,6 +73,28 @@ impl fmt::Display for StreamerId {
     }
 }
 
+impl StreamerId {
+    /// Returns the kind of the streamer, i.e. the ID without any coin/task/topic specific suffix.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            StreamerId::Network => NETWORK,
+            StreamerId::Heartbeat => HEARTBEAT,
+            StreamerId::SwapStatus => SWAP_STATUS,
+            StreamerId::OrderStatus => ORDER_STATUS,
+            StreamerId::AllBalances => ALL_BALANCES,
+            StreamerId::Lagged => LAGGED,
+            StreamerId::Task { .. } => "TASK",
+            StreamerId::Balance { .. } => "BALANCE",
+            StreamerId::TxHistory { .. } => "TX_HISTORY",
+            StreamerId::FeeEstimation { .. } => "FEE_ESTIMATION",
+            StreamerId::DataNeeded { .. } => "DATA_NEEDED",
+            StreamerId::OrderbookUpdate { .. } => "ORDERBOOK_UPDATE",
+            #[cfg(any(test, target_arch = "wasm32"))]
+            StreamerId::ForTesting { .. } => "TEST_STREAMER",
+        }
+    }
+}
+
 impl Serialize for StreamerId {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
@@ -97,6 +127,8 @@ impl<'de> Deserialize<'de> for StreamerId {
                     HEARTBEAT => Ok(StreamerId::Heartbeat),
                     SWAP_STATUS => Ok(StreamerId::SwapStatus),
                     ORDER_STATUS => Ok(StreamerId::OrderStatus),
+                    ALL_BALANCES => Ok(StreamerId::AllBalances),
+                    LAGGED => Ok(StreamerId::Lagged),
                     v if v.starts_with(TASK_PREFIX) => Ok(StreamerId::Task {
                         task_id: v[TASK_PREFIX.len()..].parse().map_err(de::Error::custom)?,
                     }),
@@ -11,6 +11,8 @@ pub(crate) mod storage;
 pub const MAX_ACCOUNT_NAME_LENGTH: usize = 255;
 pub const MAX_ACCOUNT_DESCRIPTION_LENGTH: usize = 600;
 pub const MAX_TICKER_LENGTH: usize = 255;
+/// The default maximum number of coins that can be activated for one account.
+pub const DEFAULT_MAX_ACCOUNT_COINS: usize = 1000;
 
 pub(crate) type HwPubkey = H160Json;
 
@@ -102,6 +104,11 @@ pub struct AccountInfo {
     pub(crate) name: String,
     pub(crate) description: String,
     pub(crate) balance_usd: BigDecimal,
+    /// The account creation timestamp in seconds.
+    /// Set by [`AccountStorage::upload_account`] if it's `0`, and `0` for the accounts added before the field was introduced.
+    ///
+    /// [`AccountStorage::upload_account`]: crate::account::storage::AccountStorage::upload_account
+    pub(crate) created_at: u64,
 }
 
 #[derive(Debug, PartialEq, Serialize)]
@@ -1,7 +1,9 @@
 use crate::account::storage::{AccountStorage, AccountStorageBuilder, AccountStorageError, AccountStorageResult};
-use crate::account::{AccountId, AccountInfo, AccountWithCoins, AccountWithEnabledFlag, EnabledAccountId, HwPubkey};
+use crate::account::{AccountId, AccountInfo, AccountType, AccountWithCoins, AccountWithEnabledFlag, EnabledAccountId,
+                     HwPubkey};
 use mm2_number::BigDecimal;
-use mm2_test_helpers::for_tests::mm_ctx_with_custom_db;
+use mm2_test_helpers::for_tests::{mm_ctx_with_custom_db, mm_ctx_with_custom_db_with_conf};
+use serde_json::json;
 use std::collections::{BTreeMap, BTreeSet};
 
 const HD_0_ACCOUNT: AccountId = AccountId::HD { account_idx: 0 };
@@ -35,6 +37,7 @@ fn accounts_for_test() -> Vec<AccountInfo> {
             name: format!("Account {}", i),
             description: format!("Description {}", i),
             balance_usd: BigDecimal::from(i as u64),
+            created_at: 1_700_000_000 + i as u64,
         })
         .collect()
 }
@@ -68,6 +71,19 @@ async fn fill_storage(storage: &dyn AccountStorage, accounts: Vec<AccountInfo>)
     Ok(())
 }
 
+fn test_try_from_tuple_zero_hw_pubkey_impl() {
+    let error = AccountId::try_from_tuple(AccountType::HW, 0, HwPubkey::const_default())
+        .expect_err("'AccountId::try_from_tuple' should have failed due to a zero device pubkey");
+    match error.into_inner() {
+        AccountStorageError::ErrorDeserializing(_) => (),
+        other => panic!("Expected 'ErrorDeserializing' error, found: {}", other),
+    }
+
+    let device_pubkey = HwPubkey::from("1549128bbfb33b997949b4105b6a6371c998e212");
+    let actual = AccountId::try_from_tuple(AccountType::HW, 0, device_pubkey).unwrap();
+    assert_eq!(actual, AccountId::HW { device_pubkey });
+}
+
 async fn test_init_collection_impl() {
     let ctx = mm_ctx_with_custom_db();
     let storage = AccountStorageBuilder::new(&ctx).build().unwrap();
@@ -193,6 +209,46 @@ async fn test_set_name_desc_balance_impl() {
     }
 }
 
+async fn test_find_accounts_by_name_impl() {
+    let ctx = mm_ctx_with_custom_db();
+    let storage = AccountStorageBuilder::new(&ctx).build().unwrap();
+    storage.init().await.unwrap();
+
+    let names = ["Savings", "Trading", "My SAVINGS backup", "Ärger wallet"];
+    let accounts: Vec<_> = accounts_for_test()
+        .into_iter()
+        .zip(names.iter())
+        .map(|(account, name)| AccountInfo {
+            name: name.to_string(),
+            ..account
+        })
+        .collect();
+    fill_storage(storage.as_ref(), accounts.clone()).await.unwrap();
+
+    let actual = storage.find_accounts_by_name("saving").await.unwrap();
+    let mut expected = vec![accounts[0].clone(), accounts[2].clone()];
+    expected.sort_by(|a, b| a.account_id.cmp(&b.account_id));
+    assert_eq!(actual, expected);
+
+    let actual = storage.find_accounts_by_name("unknown").await.unwrap();
+    assert!(actual.is_empty());
+
+    // Only ASCII letters are case-insensitive, the same on every storage backend.
+    let actual = storage.find_accounts_by_name("WALLET").await.unwrap();
+    assert_eq!(actual, vec![accounts[3].clone()]);
+    let actual = storage.find_accounts_by_name("ärger").await.unwrap();
+    assert!(actual.is_empty());
+
+    let error = storage
+        .find_accounts_by_name("")
+        .await
+        .expect_err("'AccountStorage::find_accounts_by_name' should have failed due to an empty query");
+    match error.into_inner() {
+        AccountStorageError::EmptySearchQuery => (),
+        other => panic!("Expected 'EmptySearchQuery' error, found: {}", other),
+    }
+}
+
 async fn test_activate_deactivate_coins_impl() {
     let ctx = mm_ctx_with_custom_db();
     let storage = AccountStorageBuilder::new(&ctx).build().unwrap();
@@ -285,6 +341,49 @@ async fn test_activate_deactivate_coins_impl() {
     }
 }
 
+async fn test_activate_coins_limit_impl() {
+    const MAX_COINS: usize = 3;
+
+    let ctx = mm_ctx_with_custom_db_with_conf(Some(json!({ "max_account_coins": MAX_COINS })));
+    let storage = AccountStorageBuilder::new(&ctx).build().unwrap();
+    storage.init().await.unwrap();
+
+    fill_storage(storage.as_ref(), accounts_for_test()).await.unwrap();
+
+    // Activate coins up to the limit, `RICK` is activated twice and should be counted once.
+    storage
+        .activate_coins(AccountId::Iguana, vec!["RICK".to_string(), "MORTY".to_string()])
+        .await
+        .unwrap();
+    storage
+        .activate_coins(AccountId::Iguana, vec!["RICK".to_string(), "KMD".to_string()])
+        .await
+        .unwrap();
+
+    // Try to activate one more coin over the limit.
+    let error = storage
+        .activate_coins(AccountId::Iguana, vec!["BTC".to_string()])
+        .await
+        .expect_err("'AccountStorage::activate_coins' should have failed due to the activated coins limit");
+    match error.into_inner() {
+        AccountStorageError::TooManyCoins { max: MAX_COINS } => (),
+        other => panic!("Expected 'TooManyCoins' error, found: {}", other),
+    }
+
+    // Check if no coins were activated on failure.
+    let actual = storage.load_account_coins(AccountId::Iguana).await.unwrap();
+    let expected = vec!["RICK".to_string(), "MORTY".to_string(), "KMD".to_string()]
+        .into_iter()
+        .collect();
+    assert_eq!(actual, expected);
+
+    // The limit is applied per account.
+    storage
+        .activate_coins(HD_0_ACCOUNT, vec!["BTC".to_string()])
+        .await
+        .unwrap();
+}
+
 async fn test_load_enabled_account_with_coins_impl() {
     let ctx = mm_ctx_with_custom_db();
     let storage = AccountStorageBuilder::new(&ctx).build().unwrap();
@@ -490,6 +589,7 @@ async fn test_delete_account_clears_coins_impl() {
         name: "My iguana".to_string(),
         description: "My description".to_string(),
         balance_usd: BigDecimal::from(123),
+        created_at: 1_700_000_100,
     };
     storage.upload_account(new_iguana.clone()).await.unwrap();
     storage.enable_account(EnabledAccountId::Iguana).await.unwrap();
@@ -514,9 +614,69 @@ async fn test_delete_account_clears_coins_impl() {
     assert_eq!(actual, expected);
 }
 
+async fn test_load_accounts_by_creation_impl() {
+    let ctx = mm_ctx_with_custom_db();
+    let storage = AccountStorageBuilder::new(&ctx).build().unwrap();
+    storage.init().await.unwrap();
+
+    // Upload the accounts in the reversed creation order.
+    let created_at = [300, 100, 200, 100];
+    let accounts: Vec<_> = accounts_for_test()
+        .into_iter()
+        .zip(created_at.iter())
+        .map(|(account, created_at)| AccountInfo {
+            created_at: *created_at,
+            ..account
+        })
+        .collect();
+    fill_storage(storage.as_ref(), accounts.clone()).await.unwrap();
+
+    let actual = storage.load_accounts_by_creation().await.unwrap();
+    // `accounts[1]` goes before `accounts[3]` as they have the same timestamp, but `HD{0} < HW`.
+    let expected = vec![
+        accounts[1].clone(),
+        accounts[3].clone(),
+        accounts[2].clone(),
+        accounts[0].clone(),
+    ];
+    assert_eq!(actual, expected);
+
+    // A newly uploaded account without a timestamp is stamped with the current time, so it goes last.
+    let new_account = AccountInfo {
+        account_id: HD_2_ACCOUNT,
+        name: "New account".to_string(),
+        description: String::new(),
+        balance_usd: BigDecimal::from(0),
+        created_at: 0,
+    };
+    storage.upload_account(new_account).await.unwrap();
+
+    let actual = storage.load_accounts_by_creation().await.unwrap();
+    assert_eq!(actual.len(), expected.len() + 1);
+    let last = actual.last().unwrap();
+    assert_eq!(last.account_id, HD_2_ACCOUNT);
+    assert_ne!(last.created_at, 0);
+}
+
 #[cfg(not(target_arch = "wasm32"))]
 mod native_tests {
+    use crate::account::storage::{AccountStorageBuilder, AccountStorageError};
+    use crate::account::{AccountId, AccountType, EnabledAccountId};
     use common::block_on;
+    use mm2_test_helpers::for_tests::mm_ctx_with_custom_db;
+
+    #[test]
+    fn test_try_from_tuple_zero_hw_pubkey() { super::test_try_from_tuple_zero_hw_pubkey_impl() }
+
+    #[test]
+    fn test_try_from_sql_tuple_short_hw_pubkey() {
+        let error = AccountId::try_from_sql_tuple(AccountType::HW as i64, 0, "1549128bbfb33b99")
+            .expect_err("'AccountId::try_from_sql_tuple' should have failed due to a short device pubkey");
+        match error.into_inner() {
+            AccountStorageError::ErrorDeserializing(_) => (),
+            other => panic!("Expected 'ErrorDeserializing' error, found: {}", other),
+        }
+    }
 
     #[test]
     fn test_init_collection() { block_on(super::test_init_collection_impl()) }
@@ -530,9 +690,15 @@ mod native_tests {
     #[test]
     fn test_set_name_desc_balance() { block_on(super::test_set_name_desc_balance_impl()) }
 
+    #[test]
+    fn test_find_accounts_by_name() { block_on(super::test_find_accounts_by_name_impl()) }
+
     #[test]
     fn test_activate_deactivate_coins() { block_on(super::test_activate_deactivate_coins_impl()) }
 
+    #[test]
+    fn test_activate_coins_limit() { block_on(super::test_activate_coins_limit_impl()) }
+
     #[test]
     fn test_load_enabled_account_with_coins() { block_on(super::test_load_enabled_account_with_coins_impl()) }
 
@@ -544,6 +710,36 @@ mod native_tests {
 
     #[test]
     fn test_delete_account_clears_coins() { block_on(super::test_delete_account_clears_coins_impl()) }
+
+    #[test]
+    fn test_load_enabled_account_deleted_out_of_band() {
+        let ctx = mm_ctx_with_custom_db();
+        let storage = AccountStorageBuilder::new(&ctx).build().unwrap();
+        block_on(storage.init()).unwrap();
+        block_on(super::fill_storage(storage.as_ref(), super::accounts_for_test())).unwrap();
+        block_on(storage.enable_account(EnabledAccountId::Iguana)).unwrap();
+
+        // Delete the enabled account bypassing the storage, so the enabled account table still refers to it.
+        let conn = ctx.sqlite_connection.get().unwrap().lock().unwrap();
+        let deleted = conn
+            .execute("DELETE FROM gui_account WHERE account_type = ?1;", [
+                AccountType::Iguana as i64,
+            ])
+            .unwrap();
+        assert_eq!(deleted, 1);
+        drop(conn);
+
+        let error = block_on(storage.load_enabled_account_with_coins()).expect_err(
+            "'AccountStorage::load_enabled_account_with_coins' should have failed since the enabled account is deleted",
+        );
+        match error.into_inner() {
+            AccountStorageError::Internal(_) => (),
+            other => panic!("Expected 'Internal' error, found: {}", other),
+        }
+    }
+
+    #[test]
+    fn test_load_accounts_by_creation() { block_on(super::test_load_accounts_by_creation_impl()) }
 }
 
 #[cfg(target_arch = "wasm32")]
@@ -552,6 +748,9 @@ mod wasm_tests {
 
     wasm_bindgen_test_configure!(run_in_browser);
 
+    #[wasm_bindgen_test]
+    fn test_try_from_tuple_zero_hw_pubkey() { super::test_try_from_tuple_zero_hw_pubkey_impl() }
+
     #[wasm_bindgen_test]
     async fn test_init_collection() { super::test_init_collection_impl().await }
 
@@ -564,9 +763,15 @@ mod wasm_tests {
     #[wasm_bindgen_test]
     async fn test_set_name_desc_balance() { super::test_set_name_desc_balance_impl().await }
 
+    #[wasm_bindgen_test]
+    async fn test_find_accounts_by_name() { super::test_find_accounts_by_name_impl().await }
+
     #[wasm_bindgen_test]
     async fn test_activate_deactivate_coins() { super::test_activate_deactivate_coins_impl().await }
 
+    #[wasm_bindgen_test]
+    async fn test_activate_coins_limit() { super::test_activate_coins_limit_impl().await }
+
     #[wasm_bindgen_test]
     async fn test_load_enabled_account_with_coins() { super::test_load_enabled_account_with_coins_impl().await }
 
@@ -578,4 +783,7 @@ mod wasm_tests {
 
     #[wasm_bindgen_test]
     async fn test_delete_account_clears_coins() { super::test_delete_account_clears_coins_impl().await }
+
+    #[wasm_bindgen_test]
+    async fn test_load_accounts_by_creation() { super::test_load_accounts_by_creation_impl().await }
 }
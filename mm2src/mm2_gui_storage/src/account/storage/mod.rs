@@ -1,5 +1,5 @@
 use crate::account::{AccountId, AccountInfo, AccountType, AccountWithCoins, AccountWithEnabledFlag, EnabledAccountId,
-                     EnabledAccountType, HwPubkey};
+                     EnabledAccountType, HwPubkey, DEFAULT_MAX_ACCOUNT_COINS};
 use async_trait::async_trait;
 use derive_more::Display;
 use mm2_core::mm_ctx::MmArc;
@@ -27,6 +27,10 @@ pub enum AccountStorageError {
     NoEnabledAccount,
     #[display(fmt = "Account {:?} exists already", _0)]
     AccountExistsAlready(AccountId),
+    #[display(fmt = "Too many activated coins, expected less or equal to {}", max)]
+    TooManyCoins { max: usize },
+    #[display(fmt = "Search query must not be empty")]
+    EmptySearchQuery,
     #[display(fmt = "Error saving changes in accounts storage: {}", _0)]
     ErrorSaving(String),
     #[display(fmt = "Error loading account: {}", _0)]
@@ -72,6 +76,7 @@ impl AccountId {
     ///
     /// The function can expect default values for `account_idx` and/or `device_pubkey`
     /// depending on `account_type`.
+    /// `device_pubkey` of an HW account must not be zero.
     pub(crate) fn try_from_tuple(
         account_type: AccountType,
         account_idx: u32,
@@ -80,6 +85,10 @@ impl AccountId {
         match (account_type, account_idx) {
             (AccountType::Iguana, DEFAULT_ACCOUNT_IDX) if device_pubkey == DEFAULT_DEVICE_PUB => Ok(AccountId::Iguana),
             (AccountType::HD, account_idx) if device_pubkey == DEFAULT_DEVICE_PUB => Ok(AccountId::HD { account_idx }),
+            (AccountType::HW, DEFAULT_ACCOUNT_IDX) if device_pubkey == DEFAULT_DEVICE_PUB => {
+                let error = format!("An invalid HW AccountId: zero device pubkey {:?}", device_pubkey);
+                MmError::err(AccountStorageError::ErrorDeserializing(error))
+            },
             (AccountType::HW, DEFAULT_ACCOUNT_IDX) => Ok(AccountId::HW { device_pubkey }),
             (_, _) => {
                 let error = format!(
@@ -123,19 +132,32 @@ impl EnabledAccountId {
 /// The implementation depends on the target architecture.
 pub(crate) struct AccountStorageBuilder<'a> {
     ctx: &'a MmArc,
+    max_account_coins: usize,
 }
 
 impl<'a> AccountStorageBuilder<'a> {
-    pub fn new(ctx: &'a MmArc) -> Self { AccountStorageBuilder { ctx } }
+    /// The maximum number of coins that can be activated for one account is read from the `max_account_coins`
+    /// config field, [`DEFAULT_MAX_ACCOUNT_COINS`] by default.
+    pub fn new(ctx: &'a MmArc) -> Self {
+        let max_account_coins = ctx.conf["max_account_coins"]
+            .as_u64()
+            .map(|max| max as usize)
+            .unwrap_or(DEFAULT_MAX_ACCOUNT_COINS);
+        AccountStorageBuilder { ctx, max_account_coins }
+    }
 
     #[cfg(not(target_arch = "wasm32"))]
     pub fn build(self) -> AccountStorageResult<AccountStorageBoxed> {
-        sqlite_storage::SqliteAccountStorage::new(self.ctx).map(|storage| -> AccountStorageBoxed { Box::new(storage) })
+        sqlite_storage::SqliteAccountStorage::new(self.ctx, self.max_account_coins)
+            .map(|storage| -> AccountStorageBoxed { Box::new(storage) })
     }
 
     #[cfg(target_arch = "wasm32")]
     pub fn build(self) -> AccountStorageResult<AccountStorageBoxed> {
-        Ok(Box::new(wasm_storage::WasmAccountStorage::new(self.ctx)))
+        Ok(Box::new(wasm_storage::WasmAccountStorage::new(
+            self.ctx,
+            self.max_account_coins,
+        )))
     }
 }
 
@@ -151,6 +173,15 @@ pub(crate) trait AccountStorage: Send + Sync {
     /// Loads accounts from the storage.
     async fn load_accounts(&self) -> AccountStorageResult<BTreeMap<AccountId, AccountInfo>>;
 
+    /// Loads accounts whose names contain the given `query`, sorted by `AccountId`.
+    /// The search is case-insensitive for ASCII letters only, e.g. `"ä"` doesn't match `"Ä"`.
+    /// Returns [`AccountStorageError::EmptySearchQuery`] if `query` is empty.
+    async fn find_accounts_by_name(&self, query: &str) -> AccountStorageResult<Vec<AccountInfo>>;
+
+    /// Loads accounts sorted by their creation timestamp, the oldest first.
+    /// Accounts created at the same time are sorted by `AccountId`.
+    async fn load_accounts_by_creation(&self) -> AccountStorageResult<Vec<AccountInfo>>;
+
     /// Loads accounts from the storage and marks **only** one account as enabled.
     async fn load_accounts_with_enabled_flag(
         &self,
@@ -160,12 +191,14 @@ pub(crate) trait AccountStorage: Send + Sync {
     async fn load_enabled_account_id(&self) -> AccountStorageResult<EnabledAccountId>;
 
     /// Loads an enabled account with activated coins, or returns an error if there is no enabled account yet.
+    /// Returns [`AccountStorageError::Internal`] if the enabled account is not in the accounts table.
     async fn load_enabled_account_with_coins(&self) -> AccountStorageResult<AccountWithCoins>;
 
     /// Checks whether the given account exists in the storage and sets it as an enabled account.
     async fn enable_account(&self, account_id: EnabledAccountId) -> AccountStorageResult<()>;
 
     /// Checks whether the given account doesn't exist in the storage and uploads it.
+    /// Sets [`AccountInfo::created_at`] to the current timestamp if it's not set yet.
     async fn upload_account(&self, account: AccountInfo) -> AccountStorageResult<()>;
 
     /// Checks whether the given account exists in the storage and deletes it.
@@ -181,6 +214,7 @@ pub(crate) trait AccountStorage: Send + Sync {
     async fn set_balance(&self, account_id: AccountId, balance_usd: BigDecimal) -> AccountStorageResult<()>;
 
     /// Puts the given `tickers` coins to the account's activated coins in the storage.
+    /// Returns [`AccountStorageError::TooManyCoins`] if the account would have more activated coins than allowed.
     async fn activate_coins(&self, account_id: AccountId, tickers: Vec<String>) -> AccountStorageResult<()>;
 
     /// Erases the given `tickers` coins from the account's activated coins in the storage.
@@ -3,7 +3,7 @@ use crate::account::{AccountId, AccountInfo, AccountType, AccountWithCoins, Acco
                      EnabledAccountType, HwPubkey, MAX_ACCOUNT_DESCRIPTION_LENGTH, MAX_ACCOUNT_NAME_LENGTH,
                      MAX_TICKER_LENGTH};
 use async_trait::async_trait;
-use common::some_or_return_ok_none;
+use common::{now_sec, some_or_return_ok_none};
 use db_common::foreign_columns;
 use db_common::sql_build::*;
 use db_common::sqlite::rusqlite::types::Type;
@@ -30,6 +30,7 @@ mod account_table {
     pub(super) const NAME: &str = "name";
     pub(super) const DESCRIPTION: &str = "description";
     pub(super) const BALANCE_USD: &str = "balance_usd";
+    pub(super) const CREATED_AT: &str = "created_at";
 
     /// The table PRIMARY KEY name.
     pub(super) const ACCOUNT_ID_PRIMARY_KEY: &str = "account_id_primary";
@@ -89,8 +90,17 @@ impl AccountId {
         device_pubkey: &str,
     ) -> AccountStorageResult<AccountId> {
         let account_type = AccountType::try_from(account_type)?;
-        let device_pubkey =
-            HwPubkey::from_str(device_pubkey).map_to_mm(|e| AccountStorageError::ErrorDeserializing(e.to_string()))?;
+        // `device_pubkey` is stored as a hex string.
+        if device_pubkey.len() != DEVICE_PUBKEY_MAX_LENGTH * 2 {
+            let error = format!(
+                "An invalid device pubkey '{}': expected {} bytes",
+                device_pubkey, DEVICE_PUBKEY_MAX_LENGTH
+            );
+            return MmError::err(AccountStorageError::ErrorDeserializing(error));
+        }
+        let device_pubkey = HwPubkey::from_str(device_pubkey).map_to_mm(|e| {
+            AccountStorageError::ErrorDeserializing(format!("An invalid device pubkey '{}': {}", device_pubkey, e))
+        })?;
         AccountId::try_from_tuple(account_type, account_idx, device_pubkey)
     }
 }
@@ -112,16 +122,19 @@ impl EnabledAccountId {
 
 pub(crate) struct SqliteAccountStorage {
     conn: SqliteConnShared,
+    /// The maximum number of coins that can be activated for one account.
+    max_account_coins: usize,
 }
 
 impl SqliteAccountStorage {
-    pub(crate) fn new(ctx: &MmArc) -> AccountStorageResult<SqliteAccountStorage> {
+    pub(crate) fn new(ctx: &MmArc, max_account_coins: usize) -> AccountStorageResult<SqliteAccountStorage> {
         let shared = ctx
             .sqlite_connection
             .get()
             .or_mm_err(|| AccountStorageError::Internal("'MmCtx::sqlite_connection' is not initialized".to_owned()))?;
         Ok(SqliteAccountStorage {
             conn: Arc::clone(shared),
+            max_account_coins,
         })
     }
 
@@ -144,12 +157,39 @@ impl SqliteAccountStorage {
                 SqlType::Varchar(MAX_ACCOUNT_DESCRIPTION_LENGTH),
             ))
             .column(SqlColumn::new(account_table::BALANCE_USD, SqlType::Varchar(BALANCE_MAX_LENGTH)).not_null())
+            .column(
+                SqlColumn::new(account_table::CREATED_AT, SqlType::Integer)
+                    .not_null()
+                    .default(0i64),
+            )
             .constraint(PrimaryKey::new(account_table::ACCOUNT_ID_PRIMARY_KEY, [
                 account_table::ACCOUNT_TYPE,
                 account_table::ACCOUNT_IDX,
                 account_table::DEVICE_PUBKEY,
             ])?);
-        create_sql.create().map_to_mm(AccountStorageError::from)
+        create_sql.create().map_to_mm(AccountStorageError::from)?;
+        Self::add_created_at_column_if_missing(conn)
+    }
+
+    /// Migrates the account table created before the `created_at` column was introduced.
+    /// The existing accounts get `0` as their creation timestamp.
+    fn add_created_at_column_if_missing(conn: &Connection) -> AccountStorageResult<()> {
+        let table_info = format!("PRAGMA table_info({});", account_table::TABLE_NAME);
+        let columns = conn
+            .prepare(&table_info)?
+            .query_map([], |row| row.get::<_, String>(1))?
+            .collect::<SqlResult<Vec<_>>>()?;
+        if columns.iter().any(|column| column == account_table::CREATED_AT) {
+            return Ok(());
+        }
+
+        let alter_table = format!(
+            "ALTER TABLE {} ADD COLUMN {} INTEGER NOT NULL DEFAULT 0;",
+            account_table::TABLE_NAME,
+            account_table::CREATED_AT
+        );
+        conn.execute(&alter_table, [])?;
+        Ok(())
     }
 
     fn init_account_coins_table(conn: &Connection) -> AccountStorageResult<()> {
@@ -261,7 +301,8 @@ impl SqliteAccountStorage {
             .field(account_table::DEVICE_PUBKEY)?
             .field(account_table::NAME)?
             .field(account_table::DESCRIPTION)?
-            .field(account_table::BALANCE_USD)?;
+            .field(account_table::BALANCE_USD)?
+            .field(account_table::CREATED_AT)?;
 
         let (account_type, account_id, device_pubkey) = account_id.to_sql_tuple();
         query
@@ -281,7 +322,8 @@ impl SqliteAccountStorage {
             .field(account_table::DEVICE_PUBKEY)?
             .field(account_table::NAME)?
             .field(account_table::DESCRIPTION)?
-            .field(account_table::BALANCE_USD)?;
+            .field(account_table::BALANCE_USD)?
+            .field(account_table::CREATED_AT)?;
         let accounts = query
             .query(account_from_row)?
             .into_iter()
@@ -290,6 +332,29 @@ impl SqliteAccountStorage {
         Ok(accounts)
     }
 
+    fn find_accounts_by_name(conn: &Connection, query: &str) -> AccountStorageResult<Vec<AccountInfo>> {
+        let mut sql_query = SqlQuery::select_from(conn, account_table::TABLE_NAME)?;
+        sql_query
+            .field(account_table::ACCOUNT_TYPE)?
+            .field(account_table::ACCOUNT_IDX)?
+            .field(account_table::DEVICE_PUBKEY)?
+            .field(account_table::NAME)?
+            .field(account_table::DESCRIPTION)?
+            .field(account_table::BALANCE_USD)?
+            .field(account_table::CREATED_AT)?
+            .and_where_like_any_param(account_table::NAME, query)?;
+        let mut accounts = sql_query.query(account_from_row)?;
+        accounts.sort_by(|a, b| a.account_id.cmp(&b.account_id));
+        Ok(accounts)
+    }
+
+    fn load_accounts_by_creation(conn: &Connection) -> AccountStorageResult<Vec<AccountInfo>> {
+        // The accounts are sorted by `AccountId` already, so the stable sort keeps that order for the same timestamps.
+        let mut accounts: Vec<_> = Self::load_accounts(conn)?.into_values().collect();
+        accounts.sort_by_key(|account| account.created_at);
+        Ok(accounts)
+    }
+
     fn account_exists(conn: &Connection, account_id: &AccountId) -> AccountStorageResult<bool> {
         let mut query = SqlQuery::select_from(conn, account_table::TABLE_NAME)?;
         query.count(account_table::NAME)?;
@@ -310,6 +375,12 @@ impl SqliteAccountStorage {
     fn upload_account(conn: &Connection, account: AccountInfo) -> AccountStorageResult<()> {
         let mut sql_insert = SqlInsert::new(conn, account_table::TABLE_NAME);
 
+        let created_at = if account.created_at == 0 {
+            now_sec()
+        } else {
+            account.created_at
+        };
+
         let (account_type, account_idx, device_pubkey) = account.account_id.to_sql_tuple();
         sql_insert
             .column(account_table::ACCOUNT_TYPE, account_type)?
@@ -317,7 +388,8 @@ impl SqliteAccountStorage {
             .column_param(account_table::DEVICE_PUBKEY, device_pubkey)?
             .column_param(account_table::NAME, account.name)?
             .column_param(account_table::DESCRIPTION, account.description)?
-            .column_param(account_table::BALANCE_USD, account.balance_usd.to_string())?;
+            .column_param(account_table::BALANCE_USD, account.balance_usd.to_string())?
+            .column(account_table::CREATED_AT, created_at as i64)?;
 
         // A constraint error occurs if there is an account with the same primary key (`account_id`).
         handle_constraint_error(sql_insert.insert(), || {
@@ -399,6 +471,19 @@ impl AccountStorage for SqliteAccountStorage {
         Self::load_accounts(&conn)
     }
 
+    async fn find_accounts_by_name(&self, query: &str) -> AccountStorageResult<Vec<AccountInfo>> {
+        if query.is_empty() {
+            return MmError::err(AccountStorageError::EmptySearchQuery);
+        }
+        let conn = self.lock_conn_mutex()?;
+        Self::find_accounts_by_name(&conn, query)
+    }
+
+    async fn load_accounts_by_creation(&self) -> AccountStorageResult<Vec<AccountInfo>> {
+        let conn = self.lock_conn_mutex()?;
+        Self::load_accounts_by_creation(&conn)
+    }
+
     async fn load_accounts_with_enabled_flag(
         &self,
     ) -> AccountStorageResult<BTreeMap<AccountId, AccountWithEnabledFlag>> {
@@ -508,6 +593,15 @@ impl AccountStorage for SqliteAccountStorage {
         let mut conn = self.lock_conn_mutex()?;
         let transaction = conn.transaction()?;
 
+        // Check if the account won't exceed the activated coins limit.
+        let mut coins = Self::load_account_coins(&transaction, &account_id)?;
+        coins.extend(tickers.iter().cloned());
+        if coins.len() > self.max_account_coins {
+            return MmError::err(AccountStorageError::TooManyCoins {
+                max: self.max_account_coins,
+            });
+        }
+
         for ticker in tickers {
             let mut sql_insert = SqlInsert::new(&transaction, account_coins_table::TABLE_NAME);
 
@@ -577,11 +671,13 @@ fn account_from_row(row: &Row<'_>) -> Result<AccountInfo, SqlError> {
     let name = row.get(3)?;
     let description = row.get(4)?;
     let balance_usd = bigdecimal_from_row(row, 5)?;
+    let created_at: i64 = row.get(6)?;
     Ok(AccountInfo {
         account_id,
         name,
         description,
         balance_usd,
+        created_at: created_at as u64,
     })
 }
 
@@ -2,6 +2,7 @@ use crate::account::storage::{AccountStorage, AccountStorageError, AccountStorag
 use crate::account::{AccountId, AccountInfo, AccountType, AccountWithCoins, AccountWithEnabledFlag, EnabledAccountId,
                      EnabledAccountType, HwPubkey};
 use async_trait::async_trait;
+use common::now_sec;
 use mm2_core::mm_ctx::MmArc;
 use mm2_db::indexed_db::{ConstructibleDb, DbIdentifier, DbInstance, DbLocked, DbTransaction, DbTransactionError,
                          DbUpgrader, IndexedDb, IndexedDbBuilder, InitDbError, InitDbResult, MultiIndex,
@@ -58,12 +59,15 @@ impl AccountId {
 
 pub(crate) struct WasmAccountStorage {
     account_db: SharedDb<AccountDb>,
+    /// The maximum number of coins that can be activated for one account.
+    max_account_coins: usize,
 }
 
 impl WasmAccountStorage {
-    pub fn new(ctx: &MmArc) -> Self {
+    pub fn new(ctx: &MmArc, max_account_coins: usize) -> Self {
         WasmAccountStorage {
             account_db: ConstructibleDb::new_shared_db(ctx).into_shared(),
+            max_account_coins,
         }
     }
 
@@ -168,6 +172,19 @@ impl WasmAccountStorage {
     async fn update_account<F>(&self, account_id: AccountId, f: F) -> AccountStorageResult<()>
     where
         F: FnOnce(&mut AccountTable),
+    {
+        self.try_update_account(account_id, |account| {
+            f(account);
+            Ok(())
+        })
+        .await
+    }
+
+    /// Loads an account by `AccountId`, applies the given fallible `f` function to it,
+    /// and uploads changes to the storage if `f` succeeds.
+    async fn try_update_account<F>(&self, account_id: AccountId, f: F) -> AccountStorageResult<()>
+    where
+        F: FnOnce(&mut AccountTable) -> AccountStorageResult<()>,
     {
         let locked_db = self.lock_db_mutex().await?;
         let transaction = locked_db.inner.transaction().await?;
@@ -178,7 +195,7 @@ impl WasmAccountStorage {
             .get_item_by_unique_multi_index(index_keys)
             .await?
             .or_mm_err(|| AccountStorageError::NoSuchAccount(account_id))?;
-        f(&mut account);
+        f(&mut account)?;
         table.replace_item(item_id, &account).await?;
         Ok(())
     }
@@ -206,6 +223,34 @@ impl AccountStorage for WasmAccountStorage {
         Self::load_accounts(&transaction).await
     }
 
+    async fn find_accounts_by_name(&self, query: &str) -> AccountStorageResult<Vec<AccountInfo>> {
+        if query.is_empty() {
+            return MmError::err(AccountStorageError::EmptySearchQuery);
+        }
+
+        let locked_db = self.lock_db_mutex().await?;
+        let transaction = locked_db.inner.transaction().await?;
+
+        // Only ASCII letters are folded to match the case-insensitivity of SQLite `LIKE` used by the native storage.
+        let query = query.to_ascii_lowercase();
+        let accounts = Self::load_accounts(&transaction)
+            .await?
+            .into_values()
+            .filter(|account| account.name.to_ascii_lowercase().contains(&query))
+            .collect();
+        Ok(accounts)
+    }
+
+    async fn load_accounts_by_creation(&self) -> AccountStorageResult<Vec<AccountInfo>> {
+        let locked_db = self.lock_db_mutex().await?;
+        let transaction = locked_db.inner.transaction().await?;
+
+        // The accounts are sorted by `AccountId` already, so the stable sort keeps that order for the same timestamps.
+        let mut accounts: Vec<_> = Self::load_accounts(&transaction).await?.into_values().collect();
+        accounts.sort_by_key(|account| account.created_at);
+        Ok(accounts)
+    }
+
     async fn load_accounts_with_enabled_flag(
         &self,
     ) -> AccountStorageResult<BTreeMap<AccountId, AccountWithEnabledFlag>> {
@@ -271,7 +316,7 @@ impl AccountStorage for WasmAccountStorage {
         Ok(())
     }
 
-    async fn upload_account(&self, account_info: AccountInfo) -> AccountStorageResult<()> {
+    async fn upload_account(&self, mut account_info: AccountInfo) -> AccountStorageResult<()> {
         let locked_db = self.lock_db_mutex().await?;
         let transaction = locked_db.inner.transaction().await?;
 
@@ -280,6 +325,10 @@ impl AccountStorage for WasmAccountStorage {
             return MmError::err(AccountStorageError::AccountExistsAlready(account_info.account_id));
         }
 
+        if account_info.created_at == 0 {
+            account_info.created_at = now_sec();
+        }
+
         let table = transaction.table::<AccountTable>().await?;
         table.add_item(&AccountTable::from(account_info)).await?;
         Ok(())
@@ -321,8 +370,15 @@ impl AccountStorage for WasmAccountStorage {
     }
 
     async fn activate_coins(&self, account_id: AccountId, tickers: Vec<String>) -> AccountStorageResult<()> {
-        self.update_account(account_id, |account| account.activated_coins.extend(tickers))
-            .await
+        let max_account_coins = self.max_account_coins;
+        self.try_update_account(account_id, |account| {
+            account.activated_coins.extend(tickers);
+            if account.activated_coins.len() > max_account_coins {
+                return MmError::err(AccountStorageError::TooManyCoins { max: max_account_coins });
+            }
+            Ok(())
+        })
+        .await
     }
 
     async fn deactivate_coins(&self, account_id: AccountId, tickers: Vec<String>) -> AccountStorageResult<()> {
@@ -365,6 +421,9 @@ struct AccountTable {
     description: String,
     balance_usd: BigDecimal,
     activated_coins: BTreeSet<String>,
+    /// The items uploaded before the field was introduced are deserialized with `0`.
+    #[serde(default)]
+    created_at: u64,
 }
 
 impl AccountTable {
@@ -413,6 +472,7 @@ impl From<AccountInfo> for AccountTable {
             description: orig.description,
             balance_usd: orig.balance_usd,
             activated_coins: BTreeSet::new(),
+            created_at: orig.created_at,
         }
     }
 }
@@ -426,6 +486,7 @@ impl TryFrom<AccountTable> for AccountInfo {
             name: value.name,
             description: value.description,
             balance_usd: value.balance_usd,
+            created_at: value.created_at,
         })
     }
 }
@@ -20,12 +20,16 @@ pub enum AccountRpcError {
     DescriptionTooLong { max_len: usize },
     #[display(fmt = "Coin ticker is too long, expected shorter or equal to {}", max_len)]
     TickerTooLong { max_len: usize },
+    #[display(fmt = "Too many activated coins, expected less or equal to {}", max)]
+    TooManyCoins { max: usize },
     #[display(fmt = "No such account {:?}", _0)]
     NoSuchAccount(AccountId),
     #[display(fmt = "No enabled account yet. Consider using 'enable_account' RPC")]
     NoEnabledAccount,
     #[display(fmt = "Account {:?} exists already", _0)]
     AccountExistsAlready(AccountId),
+    #[display(fmt = "Search query must not be empty")]
+    EmptySearchQuery,
     #[display(fmt = "Error loading account: {}", _0)]
     ErrorLoadingAccount(String),
     #[display(fmt = "Error saving changes in accounts storage: {}", _0)]
@@ -40,12 +44,14 @@ impl From<AccountStorageError> for AccountRpcError {
             AccountStorageError::NoSuchAccount(account_id) => AccountRpcError::NoSuchAccount(account_id),
             AccountStorageError::NoEnabledAccount => AccountRpcError::NoEnabledAccount,
             AccountStorageError::AccountExistsAlready(account_id) => AccountRpcError::AccountExistsAlready(account_id),
+            AccountStorageError::TooManyCoins { max } => AccountRpcError::TooManyCoins { max },
             AccountStorageError::ErrorDeserializing(e) | AccountStorageError::ErrorLoading(e) => {
                 AccountRpcError::ErrorLoadingAccount(e)
             },
             AccountStorageError::ErrorSaving(e) | AccountStorageError::ErrorSerializing(e) => {
                 AccountRpcError::ErrorSavingAccount(e)
             },
+            AccountStorageError::EmptySearchQuery => AccountRpcError::EmptySearchQuery,
             AccountStorageError::Internal(internal) => AccountRpcError::Internal(internal),
         }
     }
@@ -57,9 +63,11 @@ impl HttpStatusCode for AccountRpcError {
             AccountRpcError::NameTooLong { .. }
             | AccountRpcError::DescriptionTooLong { .. }
             | AccountRpcError::TickerTooLong { .. }
+            | AccountRpcError::TooManyCoins { .. }
             | AccountRpcError::NoSuchAccount(_)
             | AccountRpcError::NoEnabledAccount
-            | AccountRpcError::AccountExistsAlready(_) => StatusCode::BAD_REQUEST,
+            | AccountRpcError::AccountExistsAlready(_)
+            | AccountRpcError::EmptySearchQuery => StatusCode::BAD_REQUEST,
             AccountRpcError::ErrorLoadingAccount(_)
             | AccountRpcError::ErrorSavingAccount(_)
             | AccountRpcError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
@@ -87,6 +95,8 @@ where
             name: orig.name,
             description: orig.description,
             balance_usd: orig.balance_usd,
+            // The creation timestamp is set by the storage on upload.
+            created_at: 0,
         }
     }
 }
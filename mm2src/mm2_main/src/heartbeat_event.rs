@@ -2,9 +2,10 @@ use async_trait::async_trait;
 use common::executor::Timer;
 use futures::channel::oneshot;
 use mm2_event_stream::{Broadcaster, Event, EventStreamer, NoDataIn, StreamHandlerInput, StreamerId};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+use serde_json::Value as Json;
 
-#[derive(Deserialize)]
+#[derive(Deserialize, Serialize)]
 #[serde(deny_unknown_fields, default)]
 pub struct HeartbeatEventConfig {
     /// The time in seconds to wait before sending another ping event.
@@ -33,6 +34,8 @@ impl EventStreamer for HeartbeatEvent {
 
     fn streamer_id(&self) -> StreamerId { StreamerId::Heartbeat }
 
+    fn config(&self) -> Option<Json> { serde_json::to_value(&self.config).ok() }
+
     async fn handle(
         self,
         broadcaster: Broadcaster,
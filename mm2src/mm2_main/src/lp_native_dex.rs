@@ -356,8 +356,11 @@ fn migration_1(_ctx: &MmArc) {}
 #[cfg(target_arch = "wasm32")]
 fn init_wasm_event_streaming(ctx: &MmArc) {
     if let Some(event_streaming_config) = ctx.event_streaming_configuration() {
-        ctx.spawner()
-            .spawn(handle_worker_stream(ctx.clone(), event_streaming_config.worker_path));
+        ctx.spawner().spawn(handle_worker_stream(
+            ctx.clone(),
+            event_streaming_config.worker_path,
+            event_streaming_config.worker_buffer_capacity,
+        ));
     }
 }
 
@@ -22,6 +22,16 @@ impl OrderbookStreamer {
             topic: orderbook_topic_from_base_rel(base, rel),
         }
     }
+
+    /// Returns the UUID of the order an orderbook update event is about.
+    ///
+    /// The updates are deltas of the orderbook, so an update can only replace the previous updates of the same order.
+    pub fn order_uuid(event: &Event) -> Option<String> {
+        let (_, message) = event.get();
+        let order_data = message.get("order_data")?;
+        // A removed item is only its UUID.
+        order_data.get("uuid").unwrap_or(order_data).as_str().map(str::to_owned)
+    }
 }
 
 #[derive(Serialize)]
@@ -13,7 +13,7 @@ use super::{broadcast_my_swap_status, broadcast_p2p_tx_msg, broadcast_swap_msg_e
 use crate::lp_dispatcher::{DispatcherContext, LpEvents};
 use crate::lp_network::subscribe_to_topic;
 use crate::lp_ordermatch::MakerOrderBuilder;
-use crate::lp_swap::swap_events::{SwapStatusEvent, SwapStatusStreamer};
+use crate::lp_swap::swap_events::{SwapFailureContext, SwapStatusEvent, SwapStatusStreamer};
 use crate::lp_swap::swap_v2_common::mark_swap_as_finished;
 use crate::lp_swap::{broadcast_swap_message, taker_payment_spend_duration, MAX_STARTED_AT_DIFF};
 use coins::lp_price::fetch_swap_coins_price;
@@ -198,6 +198,7 @@ pub struct MakerSwapMut {
     maker_payment_refund: Option<TransactionIdentifier>,
     payment_instructions: Option<PaymentInstructions>,
     watcher_reward: bool,
+    last_successful_event: Option<MakerSwapEvent>,
 }
 
 #[cfg(test)]
@@ -286,7 +287,22 @@ impl MakerSwap {
         }
     }
 
+    /// Returns where the swap failed if the `event` is an error event, `None` otherwise.
+    fn failure_context(&self, event: &MakerSwapEvent) -> Option<SwapFailureContext> {
+        if !event.is_error() {
+            return None;
+        }
+        let last_successful = self.r().last_successful_event.clone();
+        Some(SwapFailureContext::new(
+            event,
+            last_successful.as_ref().map(|e| (e, e.status_str())),
+        ))
+    }
+
     fn apply_event(&self, event: MakerSwapEvent) {
+        if event.is_success() {
+            self.w().last_successful_event = Some(event.clone());
+        }
         match event {
             MakerSwapEvent::Started(data) => {
                 self.w().data = data;
@@ -407,6 +423,7 @@ impl MakerSwap {
                 taker_payment_spend_confirmed: false,
                 payment_instructions: None,
                 watcher_reward: false,
+                last_successful_event: None,
             }),
             ctx,
             secret,
@@ -2189,10 +2206,12 @@ pub async fn run_maker_swap(swap: RunMakerSwapInput, ctx: MmArc) {
                         .await;
                     drop(dispatcher);
                     // Send a notification to the swap status streamer about a new event.
+                    let failure = running_swap.failure_context(&event);
                     ctx.event_stream_manager
                         .send_fn(&SwapStatusStreamer::derive_streamer_id(), || SwapStatusEvent::MakerV1 {
                             uuid: running_swap.uuid,
                             event: to_save.clone(),
+                            failure,
                         })
                         .ok();
                     save_my_maker_swap_event(&ctx, &running_swap, to_save)
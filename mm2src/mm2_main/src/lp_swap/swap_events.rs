@@ -7,6 +7,7 @@ use mm2_event_stream::{Broadcaster, Event, EventStreamer, StreamHandlerInput, St
 use async_trait::async_trait;
 use futures::channel::oneshot;
 use futures::StreamExt;
+use serde::Serialize;
 use uuid::Uuid;
 
 pub struct SwapStatusStreamer;
@@ -19,13 +20,64 @@ impl SwapStatusStreamer {
     pub const fn derive_streamer_id() -> StreamerId { StreamerId::SwapStatus }
 }
 
+/// Describes where a legacy swap failed, e.g. "stuck at Taker payment spend confirmed".
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct SwapFailureContext {
+    /// The type of the error event, one of the swap's `error_events`.
+    pub error_event: String,
+    /// The type of the last successful event before the failure.
+    pub last_successful_event: Option<String>,
+    /// The status of the last successful event, e.g. "Taker payment spend confirmed".
+    pub last_successful_status: Option<String>,
+}
+
+impl SwapFailureContext {
+    /// `last_successful` is the last successful event of the swap along with its `status_str`.
+    pub(super) fn new<E: Serialize>(error_event: &E, last_successful: Option<(&E, String)>) -> Self {
+        let (last_successful_event, last_successful_status) = match last_successful {
+            Some((event, status)) => (event_type(event), Some(status.trim_end_matches("...").to_owned())),
+            None => (None, None),
+        };
+        SwapFailureContext {
+            error_event: event_type(error_event).unwrap_or_default(),
+            last_successful_event,
+            last_successful_status,
+        }
+    }
+}
+
+/// Returns the serialized `type` of a legacy swap event, which is the name used in `success_events` and `error_events`.
+fn event_type<E: Serialize>(event: &E) -> Option<String> {
+    serde_json::to_value(event)
+        .ok()?
+        .get("type")?
+        .as_str()
+        .map(ToOwned::to_owned)
+}
+
 #[derive(Serialize)]
 #[serde(tag = "swap_type", content = "swap_data")]
 pub enum SwapStatusEvent {
-    MakerV1 { uuid: Uuid, event: MakerSavedEvent },
-    TakerV1 { uuid: Uuid, event: TakerSavedEvent },
-    MakerV2 { uuid: Uuid, event: MakerSwapEvent },
-    TakerV2 { uuid: Uuid, event: TakerSwapEvent },
+    MakerV1 {
+        uuid: Uuid,
+        event: MakerSavedEvent,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        failure: Option<SwapFailureContext>,
+    },
+    TakerV1 {
+        uuid: Uuid,
+        event: TakerSavedEvent,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        failure: Option<SwapFailureContext>,
+    },
+    MakerV2 {
+        uuid: Uuid,
+        event: MakerSwapEvent,
+    },
+    TakerV2 {
+        uuid: Uuid,
+        event: TakerSwapEvent,
+    },
 }
 
 #[async_trait]
@@ -51,3 +103,56 @@ impl EventStreamer for SwapStatusStreamer {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lp_swap::maker_swap::{MakerSwapEvent as MakerSwapEventV1, MAKER_ERROR_EVENTS};
+    use crate::lp_swap::taker_swap::TakerSwapEvent as TakerSwapEventV1;
+    use crate::lp_swap::SwapError;
+
+    #[test]
+    fn test_maker_failure_context() {
+        let last_successful = MakerSwapEventV1::TakerPaymentSpendConfirmed;
+        let error_event = MakerSwapEventV1::TakerPaymentSpendConfirmFailed(SwapError::from("timeout"));
+        let failure = SwapFailureContext::new(&error_event, Some((&last_successful, last_successful.status_str())));
+        assert!(MAKER_ERROR_EVENTS.contains(&failure.error_event.as_str()));
+        assert_eq!(failure, SwapFailureContext {
+            error_event: "TakerPaymentSpendConfirmFailed".to_owned(),
+            last_successful_event: Some("TakerPaymentSpendConfirmed".to_owned()),
+            last_successful_status: Some("Taker payment spend confirmed".to_owned()),
+        });
+
+        let status_event = SwapStatusEvent::MakerV1 {
+            uuid: Uuid::new_v4(),
+            event: MakerSavedEvent {
+                timestamp: 0,
+                event: error_event,
+            },
+            failure: Some(failure),
+        };
+        let json = serde_json::to_value(status_event).unwrap();
+        assert_eq!(
+            json["swap_data"]["failure"]["last_successful_status"],
+            "Taker payment spend confirmed"
+        );
+    }
+
+    #[test]
+    fn test_taker_event_without_failure() {
+        let status_event = SwapStatusEvent::TakerV1 {
+            uuid: Uuid::new_v4(),
+            event: TakerSavedEvent {
+                timestamp: 0,
+                event: TakerSwapEventV1::MakerPaymentSpendConfirmed,
+            },
+            failure: None,
+        };
+        let json = serde_json::to_value(status_event).unwrap();
+        assert!(json["swap_data"].get("failure").is_none());
+
+        let failure = SwapFailureContext::new(&TakerSwapEventV1::Finished, None);
+        assert_eq!(failure.last_successful_event, None);
+        assert_eq!(failure.last_successful_status, None);
+    }
+}
@@ -12,7 +12,7 @@ use super::{broadcast_my_swap_status, broadcast_swap_message, broadcast_swap_msg
             TransactionIdentifier, INCLUDE_REFUND_FEE, NO_REFUND_FEE, WAIT_CONFIRM_INTERVAL_SEC};
 use crate::lp_network::subscribe_to_topic;
 use crate::lp_ordermatch::TakerOrderBuilder;
-use crate::lp_swap::swap_events::{SwapStatusEvent, SwapStatusStreamer};
+use crate::lp_swap::swap_events::{SwapFailureContext, SwapStatusEvent, SwapStatusStreamer};
 use crate::lp_swap::swap_v2_common::mark_swap_as_finished;
 use crate::lp_swap::taker_restart::get_command_based_on_maker_or_watcher_activity;
 use crate::lp_swap::{broadcast_p2p_tx_msg, broadcast_swap_msg_every_delayed, tx_helper_topic,
@@ -490,10 +490,12 @@ pub async fn run_taker_swap(swap: RunTakerSwapInput, ctx: MmArc) {
                     };
 
                     // Send a notification to the swap status streamer about a new event.
+                    let failure = running_swap.failure_context(&event);
                     ctx.event_stream_manager
                         .send_fn(&SwapStatusStreamer::derive_streamer_id(), || SwapStatusEvent::TakerV1 {
                             uuid: running_swap.uuid,
                             event: to_save.clone(),
+                            failure,
                         })
                         .ok();
                     save_my_taker_swap_event(&ctx, &running_swap, to_save)
@@ -606,6 +608,7 @@ pub struct TakerSwapMut {
     pub watcher_reward: bool,
     reward_amount: Option<BigDecimal>,
     payment_instructions: Option<PaymentInstructions>,
+    last_successful_event: Option<TakerSwapEvent>,
 }
 
 #[cfg(any(test, feature = "run-docker-tests"))]
@@ -832,7 +835,22 @@ impl TakerSwap {
     #[inline]
     fn wait_refund_until(&self) -> u64 { self.r().data.taker_payment_lock + 3700 }
 
+    /// Returns where the swap failed if the `event` is an error event, `None` otherwise.
+    fn failure_context(&self, event: &TakerSwapEvent) -> Option<SwapFailureContext> {
+        if !event.is_error() {
+            return None;
+        }
+        let last_successful = self.r().last_successful_event.clone();
+        Some(SwapFailureContext::new(
+            event,
+            last_successful.as_ref().map(|e| (e, e.status_str())),
+        ))
+    }
+
     pub(crate) fn apply_event(&self, event: TakerSwapEvent) {
+        if event.is_success() {
+            self.w().last_successful_event = Some(event.clone());
+        }
         match event {
             TakerSwapEvent::Started(data) => {
                 self.w().data = data;
@@ -971,6 +989,7 @@ impl TakerSwap {
                 watcher_reward: false,
                 reward_amount: None,
                 payment_instructions: None,
+                last_successful_event: None,
             }),
             ctx,
             #[cfg(any(test, feature = "run-docker-tests"))]
@@ -1221,8 +1240,8 @@ impl TakerSwap {
             .negotiate_swap_contract_addr(maker_data.maker_coin_swap_contract())
         {
             Ok(addr) => addr,
-            Err(e) => match self.maker_coin.fallback_swap_contract() {
-                // try to negotiate using fallback
+            Err(e) => match self.maker_coin.fallback_swap_contracts().into_iter().next() {
+                // try to negotiate using the preferred fallback
                 Some(addr) => Some(addr),
                 None => {
                     return Ok((Some(TakerSwapCommand::Finish), vec![TakerSwapEvent::NegotiateFailed(
@@ -1237,8 +1256,8 @@ impl TakerSwap {
             .negotiate_swap_contract_addr(maker_data.taker_coin_swap_contract())
         {
             Ok(addr) => addr,
-            Err(e) => match self.taker_coin.fallback_swap_contract() {
-                // try to negotiate using fallback
+            Err(e) => match self.taker_coin.fallback_swap_contracts().into_iter().next() {
+                // try to negotiate using the preferred fallback
                 Some(addr) => Some(addr),
                 None => {
                     return Ok((Some(TakerSwapCommand::Finish), vec![TakerSwapEvent::NegotiateFailed(
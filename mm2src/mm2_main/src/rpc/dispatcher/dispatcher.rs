@@ -1,5 +1,5 @@
 use super::streaming_activations;
-use super::wc_commands::{disconnect_session, get_all_sessions, get_session};
+use super::wc_commands::{disconnect_session, get_all_sessions, get_connection_state, get_session};
 use super::{DispatcherError, DispatcherResult, PUBLIC_METHODS};
 use crate::lp_healthcheck::peer_connection_healthcheck_rpc;
 use crate::lp_native_dex::init_hw::{cancel_init_trezor, init_trezor, init_trezor_status, init_trezor_user_action};
@@ -268,6 +268,7 @@ async fn dispatcher_v2(request: MmRpcRequest, ctx: MmArc) -> DispatcherResult<Re
         "wc_get_sessions" => handle_mmrpc(ctx, request, get_all_sessions).await,
         "wc_delete_session" => handle_mmrpc(ctx, request, disconnect_session).await,
         "wc_ping_session" => handle_mmrpc(ctx, request, ping_session).await,
+        "wc_get_connection_state" => handle_mmrpc(ctx, request, get_connection_state).await,
         _ => MmError::err(DispatcherError::NoSuchMethod),
     }
 }
@@ -388,6 +389,7 @@ async fn rpc_streaming_dispatcher(
 ) -> DispatcherResult<Response<Vec<u8>>> {
     match streaming_request.as_str() {
         "balance::enable" => handle_mmrpc(ctx, request, streaming_activations::enable_balance).await,
+        "all_balances::enable" => handle_mmrpc(ctx, request, streaming_activations::enable_all_balances).await,
         "network::enable" => handle_mmrpc(ctx, request, streaming_activations::enable_network).await,
         "heartbeat::enable" => handle_mmrpc(ctx, request, streaming_activations::enable_heartbeat).await,
         "fee_estimator::enable" => handle_mmrpc(ctx, request, streaming_activations::enable_fee_estimation).await,
@@ -396,6 +398,7 @@ async fn rpc_streaming_dispatcher(
         "tx_history::enable" => handle_mmrpc(ctx, request, streaming_activations::enable_tx_history).await,
         "orderbook::enable" => handle_mmrpc(ctx, request, streaming_activations::enable_orderbook).await,
         "disable" => handle_mmrpc(ctx, request, streaming_activations::disable_streamer).await,
+        "disable_by_kind" => handle_mmrpc(ctx, request, streaming_activations::disable_streamers_by_kind).await,
         _ => MmError::err(DispatcherError::NoSuchMethod),
     }
 }
@@ -455,6 +458,7 @@ async fn lightning_dispatcher(
         "channels::update_channel" => handle_mmrpc(ctx, request, channels::update_channel).await,
         "nodes::add_trusted_node" => handle_mmrpc(ctx, request, nodes::add_trusted_node).await,
         "nodes::connect_to_node" => handle_mmrpc(ctx, request, nodes::connect_to_node).await,
+        "nodes::get_persister_health" => handle_mmrpc(ctx, request, nodes::get_persister_health).await,
         "nodes::list_trusted_nodes" => handle_mmrpc(ctx, request, nodes::list_trusted_nodes).await,
         "nodes::remove_trusted_node" => handle_mmrpc(ctx, request, nodes::remove_trusted_node).await,
         "payments::generate_invoice" => handle_mmrpc(ctx, request, payments::generate_invoice).await,
@@ -1,17 +1,25 @@
 //! RPC activation and deactivation for different balance event streamers.
-use super::{EnableStreamingRequest, EnableStreamingResponse};
+use super::{EnableStreamingRequest, EnableStreamingResponse, StreamerQuotaExceeded};
 
+use async_trait::async_trait;
 use coins::eth::eth_balance_events::EthBalanceEventStreamer;
 use coins::tendermint::tendermint_balance_events::TendermintBalanceEventStreamer;
 use coins::utxo::utxo_balance_events::UtxoBalanceEventStreamer;
 use coins::z_coin::z_balance_streaming::ZCoinBalanceEventStreamer;
-use coins::{lp_coinfind, MmCoin, MmCoinEnum};
+use coins::{lp_coinfind, CoinsContext, MmCoin, MmCoinEnum};
+use common::executor::abortable_queue::WeakSpawner;
+use common::executor::Timer;
+use common::log::{error, warn};
 use common::HttpStatusCode;
+use futures::channel::oneshot;
 use http::StatusCode;
-use mm2_core::mm_ctx::MmArc;
+use mm2_core::mm_ctx::{MmArc, MmWeak};
 use mm2_err_handle::{map_to_mm::MapToMmResult, mm_error::MmResult};
+use mm2_event_stream::{Broadcaster, EventFormat, EventStreamer, NoDataIn, StreamHandlerInput, StreamerId,
+                       StreamingManagerError};
 
 use serde_json::Value as Json;
+use std::collections::HashSet;
 
 #[derive(Deserialize)]
 pub struct EnableBalanceStreamingRequest {
@@ -23,6 +31,7 @@ pub struct EnableBalanceStreamingRequest {
 #[serde(tag = "error_type", content = "error_data")]
 pub enum BalanceStreamingRequestError {
     EnableError(String),
+    QuotaExceeded(StreamerQuotaExceeded),
     CoinNotFound,
     CoinNotSupported,
     Internal(String),
@@ -32,6 +41,7 @@ impl HttpStatusCode for BalanceStreamingRequestError {
     fn status_code(&self) -> StatusCode {
         match self {
             BalanceStreamingRequestError::EnableError(_) => StatusCode::BAD_REQUEST,
+            BalanceStreamingRequestError::QuotaExceeded(e) => e.status_code(),
             BalanceStreamingRequestError::CoinNotFound => StatusCode::NOT_FOUND,
             BalanceStreamingRequestError::CoinNotSupported => StatusCode::NOT_IMPLEMENTED,
             BalanceStreamingRequestError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
@@ -39,62 +49,317 @@ impl HttpStatusCode for BalanceStreamingRequestError {
     }
 }
 
-pub async fn enable_balance(
-    ctx: MmArc,
-    req: EnableStreamingRequest<EnableBalanceStreamingRequest>,
-) -> MmResult<EnableStreamingResponse, BalanceStreamingRequestError> {
-    let (client_id, req) = (req.client_id, req.inner);
-    let coin = lp_coinfind(&ctx, &req.coin)
-        .await
-        .map_err(BalanceStreamingRequestError::Internal)?
-        .ok_or(BalanceStreamingRequestError::CoinNotFound)?;
+/// Who the events of a coin balance streamer are streamed to.
+enum BalanceStreamerTarget<'a> {
+    /// A client listening to the coin balance streamer directly, in the given format.
+    Client(u64, EventFormat),
+    /// The clients of a parent streamer aggregating the balance streamers of many coins.
+    Parent(&'a StreamerId),
+}
 
-    match coin {
-        MmCoinEnum::EthCoin(_) => (),
-        MmCoinEnum::ZCoin(_)
-        | MmCoinEnum::UtxoCoin(_)
-        | MmCoinEnum::Bch(_)
-        | MmCoinEnum::QtumCoin(_)
-        | MmCoinEnum::Tendermint(_) => {
-            if req.config.is_some() {
-                Err(BalanceStreamingRequestError::EnableError(
-                    "Invalid config provided. No config needed".to_string(),
-                ))?
-            }
+async fn add_streamer(
+    ctx: &MmArc,
+    target: &BalanceStreamerTarget<'_>,
+    streamer: impl EventStreamer,
+    spawner: WeakSpawner,
+) -> Result<StreamerId, StreamingManagerError> {
+    match target {
+        BalanceStreamerTarget::Client(client_id, format) => {
+            ctx.event_stream_manager
+                .add_with_format(*client_id, streamer, spawner, *format)
+                .await
+        },
+        BalanceStreamerTarget::Parent(parent_id) => {
+            ctx.event_stream_manager.add_child(parent_id, streamer, spawner).await
         },
-        _ => Err(BalanceStreamingRequestError::CoinNotSupported)?,
     }
+}
 
-    let enable_result = match coin {
+async fn add_coin_balance_streamer(
+    ctx: &MmArc,
+    target: BalanceStreamerTarget<'_>,
+    coin: MmCoinEnum,
+    config: Option<Json>,
+) -> MmResult<StreamerId, BalanceStreamingRequestError> {
+    let add_result = match coin {
         MmCoinEnum::UtxoCoin(coin) => {
             let streamer = UtxoBalanceEventStreamer::new(coin.clone().into());
-            ctx.event_stream_manager.add(client_id, streamer, coin.spawner()).await
+            add_streamer(ctx, &target, streamer, coin.spawner()).await
         },
         MmCoinEnum::Bch(coin) => {
             let streamer = UtxoBalanceEventStreamer::new(coin.clone().into());
-            ctx.event_stream_manager.add(client_id, streamer, coin.spawner()).await
+            add_streamer(ctx, &target, streamer, coin.spawner()).await
         },
         MmCoinEnum::QtumCoin(coin) => {
             let streamer = UtxoBalanceEventStreamer::new(coin.clone().into());
-            ctx.event_stream_manager.add(client_id, streamer, coin.spawner()).await
+            add_streamer(ctx, &target, streamer, coin.spawner()).await
         },
         MmCoinEnum::EthCoin(coin) => {
-            let streamer = EthBalanceEventStreamer::try_new(req.config, coin.clone())
+            let streamer = EthBalanceEventStreamer::try_new(config, coin.clone())
                 .map_to_mm(|e| BalanceStreamingRequestError::EnableError(format!("{e:?}")))?;
-            ctx.event_stream_manager.add(client_id, streamer, coin.spawner()).await
+            add_streamer(ctx, &target, streamer, coin.spawner()).await
         },
         MmCoinEnum::ZCoin(coin) => {
             let streamer = ZCoinBalanceEventStreamer::new(coin.clone());
-            ctx.event_stream_manager.add(client_id, streamer, coin.spawner()).await
+            add_streamer(ctx, &target, streamer, coin.spawner()).await
         },
         MmCoinEnum::Tendermint(coin) => {
-            let streamer = TendermintBalanceEventStreamer::new(coin.clone());
-            ctx.event_stream_manager.add(client_id, streamer, coin.spawner()).await
+            let streamer = TendermintBalanceEventStreamer::try_new(config, coin.clone())
+                .map_to_mm(|e| BalanceStreamingRequestError::EnableError(format!("{e:?}")))?;
+            add_streamer(ctx, &target, streamer, coin.spawner()).await
         },
         _ => Err(BalanceStreamingRequestError::CoinNotSupported)?,
     };
 
-    enable_result
+    add_result.map_to_mm(BalanceStreamingRequestError::from)
+}
+
+pub async fn enable_balance(
+    ctx: MmArc,
+    req: EnableStreamingRequest<EnableBalanceStreamingRequest>,
+) -> MmResult<EnableStreamingResponse, BalanceStreamingRequestError> {
+    let (client_id, format, req) = (req.client_id, req.format, req.inner);
+    let coin = lp_coinfind(&ctx, &req.coin)
+        .await
+        .map_err(BalanceStreamingRequestError::Internal)?
+        .ok_or(BalanceStreamingRequestError::CoinNotFound)?;
+
+    match coin {
+        MmCoinEnum::EthCoin(_) | MmCoinEnum::Tendermint(_) => (),
+        MmCoinEnum::ZCoin(_) | MmCoinEnum::UtxoCoin(_) | MmCoinEnum::Bch(_) | MmCoinEnum::QtumCoin(_) => {
+            if req.config.is_some() {
+                Err(BalanceStreamingRequestError::EnableError(
+                    "Invalid config provided. No config needed".to_string(),
+                ))?
+            }
+        },
+        _ => Err(BalanceStreamingRequestError::CoinNotSupported)?,
+    }
+
+    add_coin_balance_streamer(&ctx, BalanceStreamerTarget::Client(client_id, format), coin, req.config)
+        .await
         .map(EnableStreamingResponse::new)
-        .map_to_mm(|e| BalanceStreamingRequestError::EnableError(format!("{e:?}")))
+}
+
+#[derive(Deserialize, Serialize)]
+#[serde(deny_unknown_fields, default)]
+pub struct AllBalancesEventConfig {
+    /// The time in seconds to wait before checking again for enabled/disabled coins.
+    pub sync_interval_seconds: f64,
+}
+
+impl Default for AllBalancesEventConfig {
+    fn default() -> Self {
+        Self {
+            sync_interval_seconds: 5.0,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct EnableAllBalancesStreamingRequest {
+    #[serde(default)]
+    pub config: AllBalancesEventConfig,
+}
+
+#[derive(Serialize)]
+pub struct EnableAllBalancesStreamingResponse {
+    pub streamer_id: StreamerId,
+    /// The coins whose balances are currently streamed.
+    pub coins: Vec<String>,
+}
+
+/// Streams the balances of all the enabled coins under the single [`StreamerId::AllBalances`] ID.
+///
+/// The balance streamer of every enabled coin is attached as a child of this streamer, so the events
+/// (still originating from `BALANCE:{coin}`) are delivered to the clients of this streamer.
+/// Coins enabled or disabled later on are picked up every `sync_interval_seconds`.
+pub struct AllBalancesEvent {
+    ctx: MmWeak,
+    config: AllBalancesEventConfig,
+}
+
+impl AllBalancesEvent {
+    pub fn new(ctx: MmWeak, config: AllBalancesEventConfig) -> Self { Self { ctx, config } }
+}
+
+#[async_trait]
+impl EventStreamer for AllBalancesEvent {
+    type DataInType = NoDataIn;
+
+    fn streamer_id(&self) -> StreamerId { StreamerId::AllBalances }
+
+    fn config(&self) -> Option<Json> { serde_json::to_value(&self.config).ok() }
+
+    async fn handle(
+        self,
+        _: Broadcaster,
+        ready_tx: oneshot::Sender<Result<(), String>>,
+        _: impl StreamHandlerInput<NoDataIn>,
+    ) {
+        ready_tx.send(Ok(())).unwrap();
+
+        loop {
+            Timer::sleep(self.config.sync_interval_seconds).await;
+
+            let Some(ctx) = MmArc::from_weak(&self.ctx) else {
+                return;
+            };
+            sync_all_balances_children(&ctx).await;
+        }
+    }
+}
+
+/// Attaches the balance streamers of the newly enabled coins to the [`StreamerId::AllBalances`] streamer and
+/// detaches the ones of the disabled coins.
+///
+/// Returns the tickers of the coins whose balances are streamed.
+async fn sync_all_balances_children(ctx: &MmArc) -> Vec<String> {
+    let parent_id = StreamerId::AllBalances;
+    let Ok(children) = ctx.event_stream_manager.children(&parent_id) else {
+        return Vec::new();
+    };
+    let streamed: HashSet<String> = children
+        .into_iter()
+        .filter_map(|child_id| match child_id {
+            StreamerId::Balance { coin } => Some(coin),
+            _ => None,
+        })
+        .collect();
+
+    let enabled_coins: Vec<MmCoinEnum> = match CoinsContext::from_ctx(ctx) {
+        Ok(cctx) => cctx
+            .lock_coins()
+            .await
+            .values()
+            .filter(|coin| coin.is_available())
+            .map(|coin| coin.inner.clone())
+            .collect(),
+        Err(e) => {
+            error!("Couldn't get the enabled coins to stream the balances of: {e}");
+            return streamed.into_iter().collect();
+        },
+    };
+    let enabled_tickers: HashSet<&str> = enabled_coins.iter().map(|coin| coin.ticker()).collect();
+
+    for ticker in streamed
+        .iter()
+        .filter(|ticker| !enabled_tickers.contains(ticker.as_str()))
+    {
+        let child_id = StreamerId::Balance { coin: ticker.clone() };
+        ctx.event_stream_manager.remove_child(&parent_id, &child_id).ok();
+    }
+
+    let mut covered = Vec::with_capacity(enabled_coins.len());
+    for coin in enabled_coins {
+        let ticker = coin.ticker().to_string();
+        if streamed.contains(&ticker) {
+            covered.push(ticker);
+            continue;
+        }
+        match add_coin_balance_streamer(ctx, BalanceStreamerTarget::Parent(&parent_id), coin, None).await {
+            Ok(_) => covered.push(ticker),
+            Err(e) => match e.get_inner() {
+                BalanceStreamingRequestError::CoinNotSupported => (),
+                _ => warn!("Couldn't stream the balance of {ticker}: {e}"),
+            },
+        }
+    }
+    covered.sort();
+    covered
+}
+
+pub async fn enable_all_balances(
+    ctx: MmArc,
+    req: EnableStreamingRequest<EnableAllBalancesStreamingRequest>,
+) -> MmResult<EnableAllBalancesStreamingResponse, BalanceStreamingRequestError> {
+    let (client_id, format, req) = (req.client_id, req.format, req.inner);
+    let streamer = AllBalancesEvent::new(ctx.weak(), req.config);
+    let streamer_id = ctx
+        .event_stream_manager
+        .add_with_format(client_id, streamer, ctx.spawner(), format)
+        .await
+        .map_to_mm(BalanceStreamingRequestError::from)?;
+
+    // Attach the currently enabled coins right away instead of waiting for the first sync.
+    let coins = sync_all_balances_children(&ctx).await;
+    Ok(EnableAllBalancesStreamingResponse { streamer_id, coins })
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+    use coins::eth::EthCoin;
+    use coins_activation::platform_for_tests::init_platform_coin_with_tokens_loop;
+    use common::block_on;
+    use common::custom_futures::timeout::FutureTimerExt;
+    use crypto::CryptoCtx;
+    use mm2_core::mm_ctx::MmCtxBuilder;
+    use mm2_test_helpers::for_tests::{eth_sepolia_conf, ETH_SEPOLIA_NODES, ETH_SEPOLIA_SWAP_CONTRACT};
+    use serde_json::json;
+
+    #[test]
+    fn test_all_balances_streams_newly_enabled_coin() {
+        let ctx = MmCtxBuilder::new()
+            .with_conf(json!({ "coins": [eth_sepolia_conf()] }))
+            .into_mm_arc();
+        CryptoCtx::init_with_iguana_passphrase(ctx.clone(), "123").unwrap();
+        let mut client = ctx.event_stream_manager.new_client(1).unwrap();
+
+        let req = serde_json::from_value(json!({
+            "client_id": 1,
+            "config": { "sync_interval_seconds": 0.1 }
+        }))
+        .unwrap();
+        let response = block_on(enable_all_balances(ctx.clone(), req)).unwrap();
+        assert_eq!(response.streamer_id, StreamerId::AllBalances);
+        assert!(response.coins.is_empty());
+
+        let nodes: Vec<_> = ETH_SEPOLIA_NODES.iter().map(|url| json!({ "url": url })).collect();
+        block_on(init_platform_coin_with_tokens_loop::<EthCoin>(
+            ctx.clone(),
+            serde_json::from_value(json!({
+                "ticker": "ETH",
+                "rpc_mode": "Default",
+                "nodes": nodes,
+                "swap_contract_address": ETH_SEPOLIA_SWAP_CONTRACT,
+                "erc20_tokens_requests": [],
+                "priv_key_policy": { "type": "ContextPrivKey" }
+            }))
+            .unwrap(),
+        ))
+        .unwrap();
+
+        // The coin is picked up by the next sync and its first balance is streamed to the client.
+        let eth_balance_id = StreamerId::Balance {
+            coin: "ETH".to_string(),
+        };
+        let next_eth_balance = Box::pin(async {
+            loop {
+                let event = client.recv().await.expect("The client channel is never closed");
+                if event.origin() == &eth_balance_id {
+                    return event;
+                }
+            }
+        });
+        let event = block_on(next_eth_balance.timeout_secs(60.))
+            .expect("The balance of the newly enabled coin should be streamed");
+        assert!(!event.is_error(), "{:?}", event.get());
+        assert_eq!(event.get().1[0]["ticker"], "ETH");
+        assert_eq!(
+            ctx.event_stream_manager.children(&StreamerId::AllBalances).unwrap(),
+            vec![eth_balance_id]
+        );
+        assert_eq!(block_on(sync_all_balances_children(&ctx)), vec!["ETH".to_string()]);
+
+        // The balance of a disabled coin isn't streamed anymore.
+        let eth = block_on(lp_coinfind(&ctx, "ETH")).unwrap().unwrap();
+        block_on(CoinsContext::from_ctx(&ctx).unwrap().remove_coin(eth));
+        assert!(block_on(sync_all_balances_children(&ctx)).is_empty());
+        assert!(ctx
+            .event_stream_manager
+            .children(&StreamerId::AllBalances)
+            .unwrap()
+            .is_empty());
+    }
 }
@@ -1,6 +1,6 @@
 //! The module for handling any event streaming deactivation requests.
 //!
-//! All event streamers are deactivated using the streamer ID only.
+//! Event streamers are deactivated using either the streamer ID or the streamer kind.
 
 use common::HttpStatusCode;
 use mm2_core::mm_ctx::MmArc;
@@ -16,6 +16,14 @@ pub struct DisableStreamingRequest {
     pub streamer_id: StreamerId,
 }
 
+/// The request used to deactivate all the streamers of some kind (e.g. `BALANCE`) at once.
+#[derive(Deserialize)]
+pub struct DisableStreamingByKindRequest {
+    pub client_id: u64,
+    /// The streamer kind as in [`StreamerId::kind`], matched case-insensitively.
+    pub kind: String,
+}
+
 /// The success/ok response for any event streaming deactivation request.
 #[derive(Serialize)]
 pub struct DisableStreamingResponse {
@@ -33,6 +41,13 @@ pub enum DisableStreamingRequestError {
     DisableError(String),
 }
 
+/// The success/ok response for the deactivation of streamers by their kind.
+#[derive(Serialize)]
+pub struct DisableStreamingByKindResponse {
+    /// The IDs of the deactivated streamers.
+    pub stopped: Vec<StreamerId>,
+}
+
 impl HttpStatusCode for DisableStreamingRequestError {
     fn status_code(&self) -> StatusCode { StatusCode::BAD_REQUEST }
 }
@@ -49,3 +64,17 @@ pub async fn disable_streamer(
         .map_to_mm(|e| DisableStreamingRequestError::DisableError(format!("{e:?}")))?;
     Ok(DisableStreamingResponse::new())
 }
+
+/// Disables all the streamers of a given kind the client is listening to.
+///
+/// Useful for a client that lost track of its streamer IDs (e.g. after a reconnect).
+pub async fn disable_streamers_by_kind(
+    ctx: MmArc,
+    req: DisableStreamingByKindRequest,
+) -> MmResult<DisableStreamingByKindResponse, DisableStreamingRequestError> {
+    let stopped = ctx
+        .event_stream_manager
+        .stop_kind(req.client_id, &req.kind)
+        .map_to_mm(|e| DisableStreamingRequestError::DisableError(format!("{e:?}")))?;
+    Ok(DisableStreamingByKindResponse { stopped })
+}
@@ -1,5 +1,5 @@
 //! RPC activation and deactivation for different fee estimation streamers.
-use super::{EnableStreamingRequest, EnableStreamingResponse};
+use super::{EnableStreamingRequest, EnableStreamingResponse, StreamerQuotaExceeded};
 
 use coins::eth::fee_estimation::eth_fee_events::{EthFeeEventStreamer, EthFeeStreamingConfig};
 use coins::{lp_coinfind, MmCoin, MmCoinEnum};
@@ -18,6 +18,7 @@ pub struct EnableFeeStreamingRequest {
 #[serde(tag = "error_type", content = "error_data")]
 pub enum FeeStreamingRequestError {
     EnableError(String),
+    QuotaExceeded(StreamerQuotaExceeded),
     CoinNotFound,
     CoinNotSupported,
     Internal(String),
@@ -27,6 +28,7 @@ impl HttpStatusCode for FeeStreamingRequestError {
     fn status_code(&self) -> StatusCode {
         match self {
             FeeStreamingRequestError::EnableError(_) => StatusCode::BAD_REQUEST,
+            FeeStreamingRequestError::QuotaExceeded(e) => e.status_code(),
             FeeStreamingRequestError::CoinNotFound => StatusCode::NOT_FOUND,
             FeeStreamingRequestError::CoinNotSupported => StatusCode::NOT_IMPLEMENTED,
             FeeStreamingRequestError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
@@ -38,7 +40,7 @@ pub async fn enable_fee_estimation(
     ctx: MmArc,
     req: EnableStreamingRequest<EnableFeeStreamingRequest>,
 ) -> MmResult<EnableStreamingResponse, FeeStreamingRequestError> {
-    let (client_id, req) = (req.client_id, req.inner);
+    let (client_id, format, req) = (req.client_id, req.format, req.inner);
     let coin = lp_coinfind(&ctx, &req.coin)
         .await
         .map_err(FeeStreamingRequestError::Internal)?
@@ -48,10 +50,10 @@ pub async fn enable_fee_estimation(
         MmCoinEnum::EthCoin(coin) => {
             let eth_fee_estimator_streamer = EthFeeEventStreamer::new(req.config, coin.clone());
             ctx.event_stream_manager
-                .add(client_id, eth_fee_estimator_streamer, coin.spawner())
+                .add_with_format(client_id, eth_fee_estimator_streamer, coin.spawner(), format)
                 .await
                 .map(EnableStreamingResponse::new)
-                .map_to_mm(|e| FeeStreamingRequestError::EnableError(format!("{e:?}")))
+                .map_to_mm(FeeStreamingRequestError::from)
         },
         _ => Err(FeeStreamingRequestError::CoinNotSupported)?,
     }
@@ -1,5 +1,5 @@
 //! RPC activation and deactivation for the heartbeats.
-use super::{EnableStreamingRequest, EnableStreamingResponse};
+use super::{EnableStreamingRequest, EnableStreamingResponse, StreamerQuotaExceeded};
 
 use crate::heartbeat_event::{HeartbeatEvent, HeartbeatEventConfig};
 use common::HttpStatusCode;
@@ -16,21 +16,27 @@ pub struct EnableHeartbeatRequest {
 #[serde(tag = "error_type", content = "error_data")]
 pub enum HeartbeatRequestError {
     EnableError(String),
+    QuotaExceeded(StreamerQuotaExceeded),
 }
 
 impl HttpStatusCode for HeartbeatRequestError {
-    fn status_code(&self) -> StatusCode { StatusCode::BAD_REQUEST }
+    fn status_code(&self) -> StatusCode {
+        match self {
+            HeartbeatRequestError::EnableError(_) => StatusCode::BAD_REQUEST,
+            HeartbeatRequestError::QuotaExceeded(e) => e.status_code(),
+        }
+    }
 }
 
 pub async fn enable_heartbeat(
     ctx: MmArc,
     req: EnableStreamingRequest<EnableHeartbeatRequest>,
 ) -> MmResult<EnableStreamingResponse, HeartbeatRequestError> {
-    let (client_id, req) = (req.client_id, req.inner);
+    let (client_id, format, req) = (req.client_id, req.format, req.inner);
     let heartbeat_streamer = HeartbeatEvent::new(req.config);
     ctx.event_stream_manager
-        .add(client_id, heartbeat_streamer, ctx.spawner())
+        .add_with_format(client_id, heartbeat_streamer, ctx.spawner(), format)
         .await
         .map(EnableStreamingResponse::new)
-        .map_to_mm(|e| HeartbeatRequestError::EnableError(format!("{e:?}")))
+        .map_to_mm(HeartbeatRequestError::from)
 }
@@ -6,6 +6,7 @@ mod network;
 mod orderbook;
 mod orders;
 mod swaps;
+mod throttle;
 mod tx_history;
 
 // Re-exports
@@ -17,9 +18,13 @@ pub use network::*;
 pub use orderbook::*;
 pub use orders::*;
 pub use swaps::*;
+pub use throttle::*;
 pub use tx_history::*;
 
-use mm2_event_stream::StreamerId;
+use common::HttpStatusCode;
+use http::StatusCode;
+use mm2_event_stream::{EventFormat, StreamerId, StreamingManagerError};
+use serde::{de, Deserialize, Deserializer};
 
 /// The general request for enabling any streamer.
 /// `client_id` is common in each request, other data is request-specific.
@@ -28,10 +33,25 @@ pub struct EnableStreamingRequest<T> {
     // If the client ID isn't included, assume it's 0.
     #[serde(default)]
     pub client_id: u64,
+    /// The format the events of the streamer are sent to the client in. Defaults to JSON.
+    #[serde(default, deserialize_with = "deserialize_event_format")]
+    pub format: EventFormat,
     #[serde(flatten)]
     inner: T,
 }
 
+/// Deserializes the format of the events requested by a client.
+/// Only JSON is accepted on native targets, since the events are only sent over SSE there, which is a text protocol.
+fn deserialize_event_format<'de, D: Deserializer<'de>>(deserializer: D) -> Result<EventFormat, D::Error> {
+    let format = EventFormat::deserialize(deserializer)?;
+    if cfg!(not(target_arch = "wasm32")) && format != EventFormat::Json {
+        return Err(de::Error::custom(format!(
+            "The {format:?} event format is only supported by the wasm event streaming worker"
+        )));
+    }
+    Ok(format)
+}
+
 /// The success/ok response for any event streaming activation request.
 #[derive(Serialize)]
 pub struct EnableStreamingResponse {
@@ -45,3 +65,74 @@ pub struct EnableStreamingResponse {
 impl EnableStreamingResponse {
     fn new(streamer_id: StreamerId) -> Self { Self { streamer_id } }
 }
+
+/// The error of any event streaming activation request when the client reached its limit of active streamers.
+#[derive(Display, Serialize)]
+#[display(fmt = "Client reached the limit of {} active streamers", max)]
+pub struct StreamerQuotaExceeded {
+    pub max: usize,
+}
+
+impl HttpStatusCode for StreamerQuotaExceeded {
+    fn status_code(&self) -> StatusCode { StatusCode::TOO_MANY_REQUESTS }
+}
+
+/// Implements `From<StreamingManagerError>` for the errors of the event streaming activation requests.
+/// Each of the errors must have the `EnableError(String)` and `QuotaExceeded(StreamerQuotaExceeded)` variants.
+macro_rules! impl_from_streaming_manager_error {
+    ($($error:ident),+) => {
+        $(
+            impl From<StreamingManagerError> for $error {
+                fn from(e: StreamingManagerError) -> Self {
+                    match e {
+                        StreamingManagerError::QuotaExceeded { max } => {
+                            $error::QuotaExceeded(StreamerQuotaExceeded { max })
+                        },
+                        e => $error::EnableError(format!("{e:?}")),
+                    }
+                }
+            }
+        )+
+    };
+}
+
+impl_from_streaming_manager_error!(
+    BalanceStreamingRequestError,
+    FeeStreamingRequestError,
+    HeartbeatRequestError,
+    NetworkStreamingRequestError,
+    OrderbookStreamingRequestError,
+    OrderStatusStreamingRequestError,
+    SwapStatusStreamingRequestError,
+    TxHistoryStreamingRequestError
+);
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+    use serde_json::{json, Value as Json};
+
+    #[test]
+    fn test_binary_event_format_is_rejected_on_native() {
+        let req: EnableStreamingRequest<Json> = serde_json::from_value(json!({ "client_id": 1 })).unwrap();
+        assert_eq!(req.format, EventFormat::Json);
+        let req: EnableStreamingRequest<Json> =
+            serde_json::from_value(json!({ "client_id": 1, "format": "json" })).unwrap();
+        assert_eq!(req.format, EventFormat::Json);
+
+        let err =
+            serde_json::from_value::<EnableStreamingRequest<Json>>(json!({ "client_id": 1, "format": "messagepack" }))
+                .err()
+                .unwrap();
+        assert!(err.to_string().contains("MessagePack event format"), "{err}");
+        let err = serde_json::from_value::<EnableOrderbookStreamingRequest>(json!({
+            "client_id": 1,
+            "base": "KMD",
+            "rel": "BTC",
+            "format": "messagepack",
+        }))
+        .err()
+        .unwrap();
+        assert!(err.to_string().contains("MessagePack event format"), "{err}");
+    }
+}
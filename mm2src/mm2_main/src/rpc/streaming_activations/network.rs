@@ -1,5 +1,5 @@
 //! RPC activation and deactivation for the network event streamer.
-use super::{EnableStreamingRequest, EnableStreamingResponse};
+use super::{EnableStreamingRequest, EnableStreamingResponse, StreamerQuotaExceeded};
 
 use common::HttpStatusCode;
 use http::StatusCode;
@@ -16,21 +16,27 @@ pub struct EnableNetworkStreamingRequest {
 #[serde(tag = "error_type", content = "error_data")]
 pub enum NetworkStreamingRequestError {
     EnableError(String),
+    QuotaExceeded(StreamerQuotaExceeded),
 }
 
 impl HttpStatusCode for NetworkStreamingRequestError {
-    fn status_code(&self) -> StatusCode { StatusCode::BAD_REQUEST }
+    fn status_code(&self) -> StatusCode {
+        match self {
+            NetworkStreamingRequestError::EnableError(_) => StatusCode::BAD_REQUEST,
+            NetworkStreamingRequestError::QuotaExceeded(e) => e.status_code(),
+        }
+    }
 }
 
 pub async fn enable_network(
     ctx: MmArc,
     req: EnableStreamingRequest<EnableNetworkStreamingRequest>,
 ) -> MmResult<EnableStreamingResponse, NetworkStreamingRequestError> {
-    let (client_id, req) = (req.client_id, req.inner);
+    let (client_id, format, req) = (req.client_id, req.format, req.inner);
     let network_steamer = NetworkEvent::new(req.config, ctx.clone());
     ctx.event_stream_manager
-        .add(client_id, network_steamer, ctx.spawner())
+        .add_with_format(client_id, network_steamer, ctx.spawner(), format)
         .await
         .map(EnableStreamingResponse::new)
-        .map_to_mm(|e| NetworkStreamingRequestError::EnableError(format!("{e:?}")))
+        .map_to_mm(NetworkStreamingRequestError::from)
 }
@@ -1,8 +1,12 @@
 //! RPC activation and deactivation of the orderbook streamer.
-use super::EnableStreamingResponse;
+use super::{deserialize_event_format, EnableStreamingResponse, StreamerQuotaExceeded, ThrottleConfig, ThrottlePolicy,
+            ThrottledStreamer};
 use crate::lp_ordermatch::orderbook_events::OrderbookStreamer;
 use mm2_core::mm_ctx::MmArc;
-use mm2_err_handle::{map_to_mm::MapToMmResult, mm_error::MmResult};
+use mm2_err_handle::{map_to_mm::MapToMmResult,
+                     mm_error::{MmError, MmResult}};
+use mm2_event_stream::{ActiveStreamerInfo, EventFormat, EventStreamer, StreamerId};
+use serde_json::Value as Json;
 
 use common::HttpStatusCode;
 use http::StatusCode;
@@ -12,26 +16,190 @@ pub struct EnableOrderbookStreamingRequest {
     pub client_id: u64,
     pub base: String,
     pub rel: String,
+    #[serde(default, deserialize_with = "deserialize_event_format")]
+    pub format: EventFormat,
+    /// Limits the rate of the orderbook updates sent to the client.
+    ///
+    /// The updates are deltas of the orderbook, so they can only be coalesced per order and the `drop` policy
+    /// is not allowed, since a client missing an update would end up with a diverged orderbook.
+    ///
+    /// The throttle applies to the orderbook streamer of the pair, which is shared by all the clients listening to it.
+    /// So a client can't join an active streamer of the pair with a different throttle.
+    #[serde(default)]
+    pub throttle: Option<ThrottleConfig>,
 }
 
 #[derive(Display, Serialize, SerializeErrorType)]
 #[serde(tag = "error_type", content = "error_data")]
 pub enum OrderbookStreamingRequestError {
     EnableError(String),
+    #[display(fmt = "Invalid throttle config: {}", _0)]
+    InvalidThrottle(String),
+    #[display(
+        fmt = "The orderbook streamer is already active with a different throttle config: {:?}",
+        active_config
+    )]
+    ThrottleConflict {
+        active_config: Option<Json>,
+    },
+    QuotaExceeded(StreamerQuotaExceeded),
 }
 
 impl HttpStatusCode for OrderbookStreamingRequestError {
-    fn status_code(&self) -> StatusCode { StatusCode::BAD_REQUEST }
+    fn status_code(&self) -> StatusCode {
+        match self {
+            OrderbookStreamingRequestError::EnableError(_) | OrderbookStreamingRequestError::InvalidThrottle(_) => {
+                StatusCode::BAD_REQUEST
+            },
+            OrderbookStreamingRequestError::ThrottleConflict { .. } => StatusCode::CONFLICT,
+            OrderbookStreamingRequestError::QuotaExceeded(e) => e.status_code(),
+        }
+    }
 }
 
 pub async fn enable_orderbook(
     ctx: MmArc,
     req: EnableOrderbookStreamingRequest,
 ) -> MmResult<EnableStreamingResponse, OrderbookStreamingRequestError> {
+    if let Some(throttle) = &req.throttle {
+        validate_throttle(throttle)?;
+    }
+
     let order_status_streamer = OrderbookStreamer::new(ctx.clone(), req.base, req.rel);
-    ctx.event_stream_manager
-        .add(req.client_id, order_status_streamer, ctx.spawner())
-        .await
+    let manager = &ctx.event_stream_manager;
+    check_throttle_conflict(
+        &manager.active_streamers(),
+        &order_status_streamer.streamer_id(),
+        req.throttle.as_ref(),
+    )?;
+    let streamer_id = match req.throttle {
+        Some(throttle) => {
+            let throttled_streamer =
+                ThrottledStreamer::with_coalesce_key(order_status_streamer, throttle, OrderbookStreamer::order_uuid);
+            manager
+                .add_with_format(req.client_id, throttled_streamer, ctx.spawner(), req.format)
+                .await
+        },
+        None => {
+            manager
+                .add_with_format(req.client_id, order_status_streamer, ctx.spawner(), req.format)
+                .await
+        },
+    };
+    streamer_id
         .map(EnableStreamingResponse::new)
-        .map_to_mm(|e| OrderbookStreamingRequestError::EnableError(format!("{e:?}")))
+        .map_to_mm(OrderbookStreamingRequestError::from)
+}
+
+/// Checks that the `throttle` is valid and doesn't drop any orderbook updates.
+fn validate_throttle(throttle: &ThrottleConfig) -> MmResult<(), OrderbookStreamingRequestError> {
+    throttle
+        .validate()
+        .map_to_mm(OrderbookStreamingRequestError::InvalidThrottle)?;
+    if throttle.policy == ThrottlePolicy::Drop {
+        return MmError::err(OrderbookStreamingRequestError::InvalidThrottle(
+            "The `drop` policy would lose orderbook updates, use `coalesce` instead".to_owned(),
+        ));
+    }
+    Ok(())
+}
+
+/// Checks that the orderbook streamer with `streamer_id`, if already active, was activated with the same `throttle`,
+/// since the streamer (and its throttle) is shared by all of its clients.
+fn check_throttle_conflict(
+    active_streamers: &[ActiveStreamerInfo],
+    streamer_id: &StreamerId,
+    throttle: Option<&ThrottleConfig>,
+) -> MmResult<(), OrderbookStreamingRequestError> {
+    let Some(active) = active_streamers
+        .iter()
+        .find(|active| &active.streamer_id == streamer_id)
+    else {
+        return Ok(());
+    };
+    let active_throttle = active
+        .config
+        .as_ref()
+        .and_then(|config| config.get("throttle"))
+        .and_then(|throttle| serde_json::from_value::<ThrottleConfig>(throttle.clone()).ok());
+    if active_throttle.as_ref() != throttle {
+        return MmError::err(OrderbookStreamingRequestError::ThrottleConflict {
+            active_config: active.config.clone(),
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lp_ordermatch::orderbook_events::OrderbookItemChangeEvent;
+    use mm2_event_stream::Event;
+    use serde_json::json;
+    use uuid::Uuid;
+
+    #[test]
+    fn test_validate_throttle() {
+        let throttle = |policy| ThrottleConfig {
+            max_events_per_second: 10.,
+            policy,
+        };
+        validate_throttle(&throttle(ThrottlePolicy::Coalesce)).unwrap();
+        let err = validate_throttle(&throttle(ThrottlePolicy::Drop)).unwrap_err();
+        assert!(matches!(
+            err.into_inner(),
+            OrderbookStreamingRequestError::InvalidThrottle(_)
+        ));
+    }
+
+    #[test]
+    fn test_orderbook_updates_coalesce_key() {
+        let streamer_id = OrderbookStreamer::derive_streamer_id("KMD", "BTC");
+        let uuid = Uuid::new_v4();
+
+        let removed = serde_json::to_value(OrderbookItemChangeEvent::RemovedItem(uuid)).unwrap();
+        let removed = Event::new(streamer_id.clone(), removed);
+        assert_eq!(OrderbookStreamer::order_uuid(&removed), Some(uuid.to_string()));
+
+        let updated = json!({ "order_type": "NewOrUpdatedItem", "order_data": { "uuid": uuid, "base": "KMD" } });
+        let updated = Event::new(streamer_id, updated);
+        assert_eq!(OrderbookStreamer::order_uuid(&updated), Some(uuid.to_string()));
+    }
+
+    #[test]
+    fn test_check_throttle_conflict() {
+        let streamer_id = StreamerId::OrderbookUpdate {
+            topic: "orbook/KMD:BTC".to_string(),
+        };
+        let throttle = ThrottleConfig {
+            max_events_per_second: 10.,
+            policy: ThrottlePolicy::Coalesce,
+        };
+        let active = |config| ActiveStreamerInfo {
+            streamer_id: streamer_id.clone(),
+            kind: streamer_id.kind(),
+            client_id: 1,
+            config,
+        };
+
+        // The streamer isn't active yet.
+        check_throttle_conflict(&[], &streamer_id, Some(&throttle)).unwrap();
+
+        let throttled = [active(Some(json!({ "config": null, "throttle": throttle })))];
+        check_throttle_conflict(&throttled, &streamer_id, Some(&throttle)).unwrap();
+        let other_throttle = ThrottleConfig {
+            max_events_per_second: 1.,
+            ..throttle.clone()
+        };
+        let err = check_throttle_conflict(&throttled, &streamer_id, Some(&other_throttle)).unwrap_err();
+        assert!(matches!(
+            err.into_inner(),
+            OrderbookStreamingRequestError::ThrottleConflict { .. }
+        ));
+        check_throttle_conflict(&throttled, &streamer_id, None).unwrap_err();
+
+        let unthrottled = [active(None)];
+        check_throttle_conflict(&unthrottled, &streamer_id, None).unwrap();
+        check_throttle_conflict(&unthrottled, &streamer_id, Some(&throttle)).unwrap_err();
+    }
 }
@@ -1,5 +1,5 @@
 //! RPC activation and deactivation of the order status streamer.
-use super::{EnableStreamingRequest, EnableStreamingResponse};
+use super::{EnableStreamingRequest, EnableStreamingResponse, StreamerQuotaExceeded};
 use crate::lp_ordermatch::order_events::OrderStatusStreamer;
 use mm2_core::mm_ctx::MmArc;
 use mm2_err_handle::{map_to_mm::MapToMmResult, mm_error::MmResult};
@@ -11,10 +11,16 @@ use http::StatusCode;
 #[serde(tag = "error_type", content = "error_data")]
 pub enum OrderStatusStreamingRequestError {
     EnableError(String),
+    QuotaExceeded(StreamerQuotaExceeded),
 }
 
 impl HttpStatusCode for OrderStatusStreamingRequestError {
-    fn status_code(&self) -> StatusCode { StatusCode::BAD_REQUEST }
+    fn status_code(&self) -> StatusCode {
+        match self {
+            OrderStatusStreamingRequestError::EnableError(_) => StatusCode::BAD_REQUEST,
+            OrderStatusStreamingRequestError::QuotaExceeded(e) => e.status_code(),
+        }
+    }
 }
 
 pub async fn enable_order_status(
@@ -23,8 +29,8 @@ pub async fn enable_order_status(
 ) -> MmResult<EnableStreamingResponse, OrderStatusStreamingRequestError> {
     let order_status_streamer = OrderStatusStreamer::new();
     ctx.event_stream_manager
-        .add(req.client_id, order_status_streamer, ctx.spawner())
+        .add_with_format(req.client_id, order_status_streamer, ctx.spawner(), req.format)
         .await
         .map(EnableStreamingResponse::new)
-        .map_to_mm(|e| OrderStatusStreamingRequestError::EnableError(format!("{e:?}")))
+        .map_to_mm(OrderStatusStreamingRequestError::from)
 }
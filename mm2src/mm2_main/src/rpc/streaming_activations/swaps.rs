@@ -1,5 +1,5 @@
 //! RPC activation and deactivation of the swap status streamer.
-use super::{EnableStreamingRequest, EnableStreamingResponse};
+use super::{EnableStreamingRequest, EnableStreamingResponse, StreamerQuotaExceeded};
 use crate::lp_swap::swap_events::SwapStatusStreamer;
 use mm2_core::mm_ctx::MmArc;
 use mm2_err_handle::{map_to_mm::MapToMmResult, mm_error::MmResult};
@@ -11,12 +11,14 @@ use http::StatusCode;
 #[serde(tag = "error_type", content = "error_data")]
 pub enum SwapStatusStreamingRequestError {
     EnableError(String),
+    QuotaExceeded(StreamerQuotaExceeded),
 }
 
 impl HttpStatusCode for SwapStatusStreamingRequestError {
     fn status_code(&self) -> StatusCode {
         match self {
             SwapStatusStreamingRequestError::EnableError(_) => StatusCode::BAD_REQUEST,
+            SwapStatusStreamingRequestError::QuotaExceeded(e) => e.status_code(),
         }
     }
 }
@@ -26,8 +28,8 @@ pub async fn enable_swap_status(
     req: EnableStreamingRequest<()>,
 ) -> MmResult<EnableStreamingResponse, SwapStatusStreamingRequestError> {
     ctx.event_stream_manager
-        .add(req.client_id, SwapStatusStreamer::new(), ctx.spawner())
+        .add_with_format(req.client_id, SwapStatusStreamer::new(), ctx.spawner(), req.format)
         .await
         .map(EnableStreamingResponse::new)
-        .map_to_mm(|e| SwapStatusStreamingRequestError::EnableError(format!("{e:?}")))
+        .map_to_mm(SwapStatusStreamingRequestError::from)
 }
@@ -0,0 +1,348 @@
+//! A rate limiting decorator that can wrap any event streamer.
+use common::executor::Timer;
+use common::now_float;
+use mm2_event_stream::{Broadcaster, Event, EventStreamer, StreamHandlerInput, StreamerId};
+
+use async_trait::async_trait;
+use futures::channel::oneshot;
+use futures::future::{self, Either};
+use futures::StreamExt;
+use serde_json::{json, Value as Json};
+
+/// What to do with the events that exceed the rate limit.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ThrottlePolicy {
+    /// Drop the excess events.
+    Drop,
+    /// Keep only the latest excess event and send it once the rate limit allows.
+    #[default]
+    Coalesce,
+}
+
+/// The lowest allowed [`ThrottleConfig::max_events_per_second`], i.e. at least one event every 100 seconds.
+/// Lower rates would make the interval between events overflow the timer duration.
+pub const MIN_MAX_EVENTS_PER_SECOND: f64 = 0.01;
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct ThrottleConfig {
+    /// The maximum number of events sent per second.
+    pub max_events_per_second: f64,
+    #[serde(default)]
+    pub policy: ThrottlePolicy,
+}
+
+impl ThrottleConfig {
+    /// Checks that the rate limit is within the supported range.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.max_events_per_second.is_nan() || self.max_events_per_second < MIN_MAX_EVENTS_PER_SECOND {
+            return Err(format!(
+                "`max_events_per_second` must be at least {}, got {}",
+                MIN_MAX_EVENTS_PER_SECOND, self.max_events_per_second
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Returns the key of the events that replace each other when coalesced, `None` for the events without one.
+pub type CoalesceKey<T> = fn(&T) -> Option<String>;
+
+/// Decides which events pass the rate limit, given the current time in seconds.
+struct ThrottleState<T> {
+    min_interval: f64,
+    policy: ThrottlePolicy,
+    coalesce_key: CoalesceKey<T>,
+    last_sent: Option<f64>,
+    /// The latest coalesced event of each coalesce key, waiting for the next send slot.
+    pending: Vec<(Option<String>, T)>,
+}
+
+impl<T> ThrottleState<T> {
+    fn new(min_interval: f64, policy: ThrottlePolicy, coalesce_key: CoalesceKey<T>) -> Self {
+        ThrottleState {
+            min_interval,
+            policy,
+            coalesce_key,
+            last_sent: None,
+            pending: Vec::new(),
+        }
+    }
+
+    /// Returns the events that must be sent right away, i.e. the `event` along with the coalesced events of the other
+    /// keys if its send slot has come, otherwise drops or coalesces the `event`.
+    fn on_event(&mut self, event: T, now: f64) -> Vec<T> {
+        let key = (self.coalesce_key)(&event);
+        if self
+            .last_sent
+            .map_or(true, |last_sent| now - last_sent >= self.min_interval)
+        {
+            self.last_sent = Some(now);
+            // The coalesced event of the same key is outdated by this one.
+            self.pending.retain(|(pending_key, _)| pending_key != &key);
+            let mut events: Vec<_> = self.pending.drain(..).map(|(_, event)| event).collect();
+            events.push(event);
+            return events;
+        }
+        if self.policy == ThrottlePolicy::Coalesce {
+            match self.pending.iter_mut().find(|(pending_key, _)| pending_key == &key) {
+                Some((_, pending)) => *pending = event,
+                None => self.pending.push((key, event)),
+            }
+        }
+        Vec::new()
+    }
+
+    /// How long to wait for the next send slot, only if there are coalesced events waiting for it.
+    fn wait_for_pending(&self, now: f64) -> Option<f64> {
+        match self.last_sent {
+            Some(last_sent) if !self.pending.is_empty() => Some((last_sent + self.min_interval - now).max(0.)),
+            _ => None,
+        }
+    }
+
+    /// Returns the coalesced events to send once their send slot has come.
+    fn on_send_slot(&mut self, now: f64) -> Vec<T> {
+        if self.pending.is_empty() {
+            return Vec::new();
+        }
+        self.last_sent = Some(now);
+        self.pending.drain(..).map(|(_, event)| event).collect()
+    }
+}
+
+/// Wraps a streamer `S` and limits the rate of the events it broadcasts according to a [`ThrottleConfig`].
+///
+/// The wrapped streamer keeps its streamer ID, so the throttled streamer replaces it rather than running alongside it.
+pub struct ThrottledStreamer<S> {
+    inner: S,
+    config: ThrottleConfig,
+    coalesce_key: CoalesceKey<Event>,
+}
+
+impl<S: EventStreamer> ThrottledStreamer<S> {
+    /// Throttles a streamer whose every event replaces the previous ones, e.g. a balance or a status snapshot.
+    pub fn new(inner: S, config: ThrottleConfig) -> Self { Self::with_coalesce_key(inner, config, |_| None) }
+
+    /// Throttles a streamer whose events only replace the previous events with the same `coalesce_key`,
+    /// e.g. the updates of the different orders. The coalesced events of all the keys are sent in the next send slot,
+    /// so the rate limit applies to the events of each key rather than to all of them.
+    pub fn with_coalesce_key(inner: S, config: ThrottleConfig, coalesce_key: CoalesceKey<Event>) -> Self {
+        Self {
+            inner,
+            config,
+            coalesce_key,
+        }
+    }
+
+    /// The minimum time in seconds between two consecutive events.
+    fn min_interval(&self) -> f64 { 1. / self.config.max_events_per_second }
+}
+
+#[async_trait]
+impl<S: EventStreamer> EventStreamer for ThrottledStreamer<S> {
+    type DataInType = S::DataInType;
+
+    fn streamer_id(&self) -> StreamerId { self.inner.streamer_id() }
+
+    fn config(&self) -> Option<Json> {
+        let throttle = serde_json::to_value(&self.config).ok()?;
+        match self.inner.config() {
+            Some(Json::Object(mut config)) => {
+                config.insert("throttle".to_owned(), throttle);
+                Some(Json::Object(config))
+            },
+            config => Some(json!({ "config": config, "throttle": throttle })),
+        }
+    }
+
+    async fn handle(
+        self,
+        broadcaster: Broadcaster,
+        ready_tx: oneshot::Sender<Result<(), String>>,
+        data_rx: impl StreamHandlerInput<Self::DataInType>,
+    ) {
+        if let Err(e) = self.config.validate() {
+            ready_tx.send(Err(e)).ok();
+            return;
+        }
+
+        let mut state = ThrottleState::new(self.min_interval(), self.config.policy, self.coalesce_key);
+        let (inner_broadcaster, mut events_rx) = Broadcaster::channel();
+
+        let throttle_loop = async move {
+            loop {
+                let next_slot = state.wait_for_pending(now_float());
+                let flush = async move {
+                    match next_slot {
+                        Some(wait) => Timer::sleep(wait).await,
+                        None => future::pending().await,
+                    }
+                };
+                futures::pin_mut!(flush);
+
+                match future::select(events_rx.next(), flush).await {
+                    Either::Left((Some(event), _)) => {
+                        for event in state.on_event(event, now_float()) {
+                            broadcaster.broadcast(event);
+                        }
+                    },
+                    // The inner streamer has stopped.
+                    Either::Left((None, _)) => break,
+                    Either::Right(_) => {
+                        for event in state.on_send_slot(now_float()) {
+                            broadcaster.broadcast(event);
+                        }
+                    },
+                }
+            }
+        };
+
+        let inner_handle = self.inner.handle(inner_broadcaster, ready_tx, data_rx);
+        futures::pin_mut!(inner_handle, throttle_loop);
+        future::select(inner_handle, throttle_loop).await;
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+    use common::block_on;
+    use common::executor::abortable_queue::AbortableQueue;
+    use mm2_event_stream::{NoDataIn, StreamingManager};
+
+    /// Broadcasts an event every millisecond.
+    struct FastStreamer;
+
+    #[async_trait]
+    impl EventStreamer for FastStreamer {
+        type DataInType = NoDataIn;
+
+        fn streamer_id(&self) -> StreamerId { StreamerId::Heartbeat }
+
+        async fn handle(
+            self,
+            broadcaster: Broadcaster,
+            ready_tx: oneshot::Sender<Result<(), String>>,
+            _: impl StreamHandlerInput<Self::DataInType>,
+        ) {
+            ready_tx.send(Ok(())).unwrap();
+            for i in 0.. {
+                broadcaster.broadcast(Event::new(self.streamer_id(), json!(i)));
+                Timer::sleep(0.001).await;
+            }
+        }
+    }
+
+    /// Feeds an event every 1/1024 of a second for one second to an 8 events per second throttle,
+    /// flushing the coalesced event whenever its send slot comes, and returns the sent events.
+    /// The times are powers of two so that the arithmetic is exact.
+    fn throttle_one_second(policy: ThrottlePolicy) -> Vec<u32> {
+        let mut state = ThrottleState::new(0.125, policy, |_| None);
+        let mut sent = Vec::new();
+        for i in 0..1024 {
+            let now = i as f64 / 1024.;
+            if state.wait_for_pending(now) == Some(0.) {
+                sent.extend(state.on_send_slot(now));
+            }
+            sent.extend(state.on_event(i, now));
+        }
+        sent
+    }
+
+    #[test]
+    fn test_throttle_rate() {
+        let sent = throttle_one_second(ThrottlePolicy::Drop);
+        assert_eq!(sent, (0..8).map(|i| i * 128).collect::<Vec<_>>());
+        // The first event is sent right away, then each slot sends the latest event coalesced before it.
+        let sent = throttle_one_second(ThrottlePolicy::Coalesce);
+        assert_eq!(sent, [0, 127, 255, 383, 511, 639, 767, 895]);
+    }
+
+    #[test]
+    fn test_throttle_coalesces_latest_event() {
+        let mut state = ThrottleState::new(1., ThrottlePolicy::Coalesce, |_| None);
+        assert_eq!(state.on_event(1, 0.), [1]);
+        assert_eq!(state.wait_for_pending(0.2), None);
+        assert!(state.on_event(2, 0.2).is_empty());
+        assert!(state.on_event(3, 0.4).is_empty());
+        assert_eq!(state.wait_for_pending(0.5), Some(0.5));
+        // Only the latest excess event is sent in the next slot.
+        assert_eq!(state.on_send_slot(1.), [3]);
+        assert!(state.on_send_slot(1.).is_empty());
+        assert!(state.on_event(4, 1.5).is_empty());
+
+        let mut state = ThrottleState::new(1., ThrottlePolicy::Drop, |_| None);
+        assert_eq!(state.on_event(1, 0.), [1]);
+        assert!(state.on_event(2, 0.2).is_empty());
+        assert_eq!(state.wait_for_pending(0.2), None);
+        assert_eq!(state.on_event(3, 1.), [3]);
+    }
+
+    #[test]
+    fn test_throttle_coalesces_latest_event_per_key() {
+        // The events are `(key, value)` pairs.
+        fn key(event: &(&'static str, u32)) -> Option<String> { Some(event.0.to_owned()) }
+
+        let mut state = ThrottleState::new(1., ThrottlePolicy::Coalesce, key);
+        assert_eq!(state.on_event(("a", 1), 0.), [("a", 1)]);
+        assert!(state.on_event(("a", 2), 0.2).is_empty());
+        assert!(state.on_event(("b", 1), 0.4).is_empty());
+        assert!(state.on_event(("a", 3), 0.6).is_empty());
+        // The latest event of every key is sent in the next slot.
+        assert_eq!(state.wait_for_pending(0.6), Some(0.4));
+        assert_eq!(state.on_send_slot(1.), [("a", 3), ("b", 1)]);
+        assert!(state.on_send_slot(1.).is_empty());
+
+        // An event that comes in its send slot is sent along with the coalesced events of the other keys,
+        // and outdates the coalesced event of its own key.
+        assert!(state.on_event(("a", 4), 1.5).is_empty());
+        assert!(state.on_event(("b", 2), 1.6).is_empty());
+        assert_eq!(state.on_event(("a", 5), 2.), [("b", 2), ("a", 5)]);
+        assert_eq!(state.wait_for_pending(2.), None);
+    }
+
+    #[test]
+    fn test_throttle_config_validate() {
+        let config = |max_events_per_second| ThrottleConfig {
+            max_events_per_second,
+            policy: ThrottlePolicy::Coalesce,
+        };
+        config(10.).validate().unwrap();
+        config(MIN_MAX_EVENTS_PER_SECOND).validate().unwrap();
+        for invalid in [0., -1., 1e-300, f64::NAN] {
+            config(invalid).validate().unwrap_err();
+        }
+    }
+
+    #[test]
+    fn test_throttled_streamer_rejects_tiny_rate() {
+        block_on(async {
+            let manager = StreamingManager::default();
+            let system = AbortableQueue::default();
+            let _client = manager.new_client(1).unwrap();
+            let config = ThrottleConfig {
+                max_events_per_second: 1e-300,
+                policy: ThrottlePolicy::Drop,
+            };
+            manager
+                .add(1, ThrottledStreamer::new(FastStreamer, config), system.weak_spawner())
+                .await
+                .unwrap_err();
+        })
+    }
+
+    #[test]
+    fn test_throttled_streamer_config() {
+        let config = ThrottleConfig {
+            max_events_per_second: 10.,
+            policy: ThrottlePolicy::Drop,
+        };
+        let streamer = ThrottledStreamer::new(FastStreamer, config);
+        assert_eq!(
+            streamer.config().unwrap(),
+            json!({ "config": null, "throttle": { "max_events_per_second": 10.0, "policy": "drop" } })
+        );
+    }
+}
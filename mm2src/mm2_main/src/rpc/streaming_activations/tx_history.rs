@@ -1,5 +1,5 @@
 //! RPC activation and deactivation for Tx history event streamers.
-use super::{EnableStreamingRequest, EnableStreamingResponse};
+use super::{EnableStreamingRequest, EnableStreamingResponse, StreamerQuotaExceeded};
 
 use coins::utxo::tx_history_events::TxHistoryEventStreamer;
 use coins::z_coin::tx_history_events::ZCoinTxHistoryEventStreamer;
@@ -18,6 +18,7 @@ pub struct EnableTxHistoryStreamingRequest {
 #[serde(tag = "error_type", content = "error_data")]
 pub enum TxHistoryStreamingRequestError {
     EnableError(String),
+    QuotaExceeded(StreamerQuotaExceeded),
     CoinNotFound,
     CoinNotSupported,
     Internal(String),
@@ -27,6 +28,7 @@ impl HttpStatusCode for TxHistoryStreamingRequestError {
     fn status_code(&self) -> StatusCode {
         match self {
             TxHistoryStreamingRequestError::EnableError(_) => StatusCode::BAD_REQUEST,
+            TxHistoryStreamingRequestError::QuotaExceeded(e) => e.status_code(),
             TxHistoryStreamingRequestError::CoinNotFound => StatusCode::NOT_FOUND,
             TxHistoryStreamingRequestError::CoinNotSupported => StatusCode::NOT_IMPLEMENTED,
             TxHistoryStreamingRequestError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
@@ -38,7 +40,7 @@ pub async fn enable_tx_history(
     ctx: MmArc,
     req: EnableStreamingRequest<EnableTxHistoryStreamingRequest>,
 ) -> MmResult<EnableStreamingResponse, TxHistoryStreamingRequestError> {
-    let (client_id, req) = (req.client_id, req.inner);
+    let (client_id, format, req) = (req.client_id, req.format, req.inner);
     let coin = lp_coinfind(&ctx, &req.coin)
         .await
         .map_err(TxHistoryStreamingRequestError::Internal)?
@@ -47,30 +49,40 @@ pub async fn enable_tx_history(
     let enable_result = match coin {
         MmCoinEnum::UtxoCoin(coin) => {
             let streamer = TxHistoryEventStreamer::new(req.coin);
-            ctx.event_stream_manager.add(client_id, streamer, coin.spawner()).await
+            ctx.event_stream_manager
+                .add_with_format(client_id, streamer, coin.spawner(), format)
+                .await
         },
         MmCoinEnum::Bch(coin) => {
             let streamer = TxHistoryEventStreamer::new(req.coin);
-            ctx.event_stream_manager.add(client_id, streamer, coin.spawner()).await
+            ctx.event_stream_manager
+                .add_with_format(client_id, streamer, coin.spawner(), format)
+                .await
         },
         MmCoinEnum::QtumCoin(coin) => {
             let streamer = TxHistoryEventStreamer::new(req.coin);
-            ctx.event_stream_manager.add(client_id, streamer, coin.spawner()).await
+            ctx.event_stream_manager
+                .add_with_format(client_id, streamer, coin.spawner(), format)
+                .await
         },
         MmCoinEnum::Tendermint(coin) => {
             // The tx history streamer is very primitive reactive streamer that only emits new txs.
             // it's logic is exactly the same for utxo coins and tendermint coins as well.
             let streamer = TxHistoryEventStreamer::new(req.coin);
-            ctx.event_stream_manager.add(client_id, streamer, coin.spawner()).await
+            ctx.event_stream_manager
+                .add_with_format(client_id, streamer, coin.spawner(), format)
+                .await
         },
         MmCoinEnum::ZCoin(coin) => {
             let streamer = ZCoinTxHistoryEventStreamer::new(coin.clone());
-            ctx.event_stream_manager.add(client_id, streamer, coin.spawner()).await
+            ctx.event_stream_manager
+                .add_with_format(client_id, streamer, coin.spawner(), format)
+                .await
         },
         _ => Err(TxHistoryStreamingRequestError::CoinNotSupported)?,
     };
 
     enable_result
         .map(EnableStreamingResponse::new)
-        .map_to_mm(|e| TxHistoryStreamingRequestError::EnableError(format!("{e:?}")))
+        .map_to_mm(TxHistoryStreamingRequestError::from)
 }
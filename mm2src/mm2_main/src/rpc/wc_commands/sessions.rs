@@ -1,6 +1,6 @@
 use kdf_walletconnect::session::rpc::send_session_ping_request;
 use kdf_walletconnect::session::SessionRpcInfo;
-use kdf_walletconnect::WalletConnectCtx;
+use kdf_walletconnect::{ConnectionState, WalletConnectCtx};
 use mm2_core::mm_ctx::MmArc;
 use mm2_err_handle::prelude::*;
 use serde::Serialize;
@@ -84,3 +84,24 @@ pub async fn ping_session(ctx: MmArc, req: GetSessionRequest) -> MmResult<Sessio
         result: "Ping successful".to_owned(),
     })
 }
+
+#[derive(Debug, Serialize)]
+pub struct ConnectionStateResponse {
+    pub state: ConnectionState,
+    pub is_connected: bool,
+}
+
+/// `Get relay connection state` RPC command implementation.
+pub async fn get_connection_state(
+    ctx: MmArc,
+    _req: EmptyRpcRequest,
+) -> MmResult<ConnectionStateResponse, WalletConnectRpcError> {
+    let wc_ctx =
+        WalletConnectCtx::from_ctx(&ctx).mm_err(|err| WalletConnectRpcError::InitializationError(err.to_string()))?;
+    let state = wc_ctx.connection_state();
+
+    Ok(ConnectionStateResponse {
+        state,
+        is_connected: state == ConnectionState::Connected,
+    })
+}
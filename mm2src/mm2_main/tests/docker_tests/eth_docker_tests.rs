@@ -384,11 +384,13 @@ fn global_nft_with_random_privkey(
     let node = EthNode {
         url: GETH_RPC_URL.to_string(),
         komodo_proxy: false,
+        ws_ping_interval_secs: None,
+        ws_pong_timeout_secs: None,
     };
     let platform_request = EthActivationV2Request {
         nodes: vec![node],
         rpc_mode: Default::default(),
-        swap_contract_address,
+        swap_contract_address: swap_contract_address.into(),
         swap_v2_contracts: Some(swap_v2_contracts),
         fallback_swap_contract: Some(fallback_swap_contract_address),
         contract_supports_watchers: false,
@@ -458,11 +460,13 @@ fn sepolia_coin_from_privkey(ctx: &MmArc, secret: &'static str, ticker: &str, co
     let node = EthNode {
         url: SEPOLIA_RPC_URL.to_string(),
         komodo_proxy: false,
+        ws_ping_interval_secs: None,
+        ws_pong_timeout_secs: None,
     };
     let platform_request = EthActivationV2Request {
         nodes: vec![node],
         rpc_mode: Default::default(),
-        swap_contract_address: swap_addr.swap_contract_address,
+        swap_contract_address: swap_addr.swap_contract_address.into(),
         swap_v2_contracts: Some(swap_addr.swap_v2_contracts),
         fallback_swap_contract: Some(swap_addr.fallback_swap_contract_address),
         contract_supports_watchers: false,
@@ -1456,11 +1460,13 @@ fn eth_coin_v2_activation_with_random_privkey(
     let node = EthNode {
         url: GETH_RPC_URL.to_string(),
         komodo_proxy: false,
+        ws_ping_interval_secs: None,
+        ws_pong_timeout_secs: None,
     };
     let platform_request = EthActivationV2Request {
         nodes: vec![node],
         rpc_mode: Default::default(),
-        swap_contract_address: swap_addr.swap_contract_address,
+        swap_contract_address: swap_addr.swap_contract_address.into(),
         swap_v2_contracts: Some(swap_addr.swap_v2_contracts),
         fallback_swap_contract: Some(swap_addr.fallback_swap_contract_address),
         contract_supports_watchers: false,
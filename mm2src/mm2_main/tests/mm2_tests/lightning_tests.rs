@@ -383,6 +383,26 @@ fn test_enable_lightning() {
     assert_eq!(enable_lightning_coin.balance.spendable, BigDecimal::from(0));
     assert_eq!(enable_lightning_coin.balance.unspendable, BigDecimal::from(0));
 
+    let health = block_on(mm.rpc(&json!({
+        "userpass": mm.userpass,
+        "method": "lightning::nodes::get_persister_health",
+        "params": {
+            "coin": "tBTC-TEST-lightning",
+        },
+    })))
+    .unwrap();
+    assert_eq!(
+        health.0,
+        StatusCode::OK,
+        "'lightning::nodes::get_persister_health' failed: {}",
+        health.1
+    );
+    let health: Json = json::from_str(&health.1).unwrap();
+    assert_eq!(health["result"]["healthy"], Json::Bool(true));
+    assert_eq!(health["result"]["main_dir_writable"], Json::Bool(true));
+    assert_eq!(health["result"]["db_responsive"], Json::Bool(true));
+    assert_eq!(health["result"]["errors"], json!([]));
+
     // Disable tBTC-TEST-lightning
     let disabled = block_on(mm.rpc(&json! ({
         "userpass": mm.userpass,
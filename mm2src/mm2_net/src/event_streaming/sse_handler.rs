@@ -1,7 +1,6 @@
 use http::header::{ACCESS_CONTROL_ALLOW_ORIGIN, CACHE_CONTROL, CONTENT_TYPE};
 use hyper::{body::Bytes, Body, Request, Response};
 use mm2_core::mm_ctx::MmArc;
-use serde_json::json;
 
 pub const SSE_ENDPOINT: &str = "/event-stream";
 
@@ -35,11 +34,8 @@ pub async fn handle_sse(request: Request<Body>, ctx_h: u32) -> Response<Body> {
         while let Some(event) = rx.recv().await {
             // The event's filter will decide whether to expose the event data to this client or not.
             // This happens based on the events that this client has subscribed to.
-            let (event_type, message) = event.get();
-            let data = json!({
-                "_type": event_type,
-                "message": message,
-            });
+            // SSE is a text protocol, so the events are always sent as JSON regardless of the requested format.
+            let data = event.payload();
 
             yield Ok::<_, hyper::Error>(Bytes::from(format!("data: {data} \n\n")));
         }
@@ -1,5 +1,9 @@
+use common::log::error;
+use js_sys::Uint8Array;
 use mm2_core::mm_ctx::MmArc;
-use serde_json::json;
+use mm2_event_stream::EventFormat;
+use std::num::NonZeroUsize;
+use wasm_bindgen::JsValue;
 use web_sys::SharedWorker;
 
 struct SendableSharedWorker(SharedWorker);
@@ -11,7 +15,18 @@ struct SendableMessagePort(web_sys::MessagePort);
 unsafe impl Send for SendableMessagePort {}
 
 /// Handles broadcasted messages from `mm2_event_stream` continuously for WASM.
-pub async fn handle_worker_stream(ctx: MmArc, worker_path: String) {
+///
+/// At most `buffer_capacity` events are buffered for the worker by the streaming manager. If they aren't posted
+/// fast enough, the new events are dropped and the worker receives a `LAGGED` event with the number of the dropped
+/// events instead.
+///
+/// Events are posted as JSON strings, or as `Uint8Array` frames for the streamers activated with the `messagepack`
+/// format.
+///
+/// Note that `MessagePort::postMessage` never blocks and doesn't report how many messages the worker has yet to
+/// handle. Once posted, the messages queue up in the browser until the worker handles them, which this function
+/// can't observe nor limit.
+pub async fn handle_worker_stream(ctx: MmArc, worker_path: String, buffer_capacity: NonZeroUsize) {
     let worker = SendableSharedWorker(
         SharedWorker::new(&worker_path).unwrap_or_else(|_| {
             panic!(
@@ -28,16 +43,20 @@ pub async fn handle_worker_stream(ctx: MmArc, worker_path: String) {
 
     let event_stream_manager = ctx.event_stream_manager.clone();
     let mut rx = event_stream_manager
-        .new_client(0)
+        .new_client_with_capacity(0, buffer_capacity)
         .expect("A different wasm client is already listening. Only one client is allowed at a time.");
 
     while let Some(event) = rx.recv().await {
-        let (event_type, message) = event.get();
-        let data = json!({
-            "_type": event_type,
-            "message": message,
-        });
-        let message_js = wasm_bindgen::JsValue::from_str(&data.to_string());
+        let message_js = match event.format() {
+            EventFormat::Json => JsValue::from_str(&event.payload().to_string()),
+            EventFormat::MessagePack => match event.encode() {
+                Ok(frame) => Uint8Array::from(frame.as_slice()).into(),
+                Err(e) => {
+                    error!("Couldn't encode an event from {}: {e}", event.origin());
+                    continue;
+                },
+            },
+        };
         port.0.post_message(&message_js)
             .expect("Failed to post a message to the SharedWorker.\n\
             This could be due to the browser being incompatible.\n\
@@ -5,13 +5,21 @@ use crate::transport::SlurpError;
 use bytes::{Buf, BufMut, Bytes, BytesMut};
 use common::{cfg_native, cfg_wasm32};
 use derive_more::Display;
+use futures::ready;
+use futures::stream::{BoxStream, Stream, StreamExt};
 use http::header::{ACCEPT, CONTENT_TYPE};
 use mm2_err_handle::prelude::*;
 use prost::DecodeError;
+use std::marker::PhantomData;
+use std::pin::Pin;
+use std::task::{Context, Poll};
 
 cfg_native! {
     use common::APPLICATION_GRPC_WEB;
+    use common::wio::{drive03, HYPER};
     use crate::transport::slurp_req;
+    use futures::TryStreamExt;
+    use hyper::Body;
 }
 
 cfg_wasm32! {
@@ -23,6 +31,9 @@ cfg_wasm32! {
 #[cfg_attr(target_arch = "wasm32", allow(dead_code))]
 const GRPC_HEADER_SIZE: usize = 5;
 
+/// The most significant bit of the frame flag marks a frame carrying the trailers instead of a message.
+const GRPC_TRAILERS_FLAG: u8 = 0x80;
+
 #[derive(Debug)]
 pub enum EncodeBodyError {
     Encode(prost::EncodeError),
@@ -93,6 +104,168 @@ where
     Ok(msg)
 }
 
+/// A single frame of a gRPC-WEB response body.
+enum GrpcWebFrame {
+    Message(Bytes),
+    Trailers(Bytes),
+}
+
+/// Splits the next complete frame off the front of `buf`.
+/// Returns `None` if `buf` doesn't contain a complete frame yet.
+fn split_frame(buf: &mut BytesMut) -> Option<GrpcWebFrame> {
+    if buf.len() < GRPC_HEADER_SIZE {
+        return None;
+    }
+    let flag = buf[0];
+    let len = u32::from_be_bytes([buf[1], buf[2], buf[3], buf[4]]) as usize;
+    if buf.len() < GRPC_HEADER_SIZE + len {
+        return None;
+    }
+
+    buf.advance(GRPC_HEADER_SIZE);
+    let payload = buf.split_to(len).freeze();
+    if flag & GRPC_TRAILERS_FLAG != 0 {
+        Some(GrpcWebFrame::Trailers(payload))
+    } else {
+        Some(GrpcWebFrame::Message(payload))
+    }
+}
+
+/// The trailing status of a gRPC-WEB response.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GrpcStatus {
+    pub code: u32,
+    pub message: String,
+}
+
+impl GrpcStatus {
+    #[inline(always)]
+    pub fn is_ok(&self) -> bool { self.code == 0 }
+
+    /// Parses the trailers frame payload, which is formatted as HTTP/1 headers, e.g. `grpc-status:0\r\ngrpc-message:\r\n`.
+    fn from_trailers(trailers: &[u8]) -> Result<GrpcStatus, MmError<PostGrpcWebErr>> {
+        let trailers = std::str::from_utf8(trailers)
+            .map_to_mm(|e| PostGrpcWebErr::BadResponse(format!("Invalid trailers: {}", e)))?;
+
+        let mut code = None;
+        let mut message = String::new();
+        for line in trailers.split("\r\n") {
+            let Some((name, value)) = line.split_once(':') else {
+                continue;
+            };
+            match name.trim().to_ascii_lowercase().as_str() {
+                "grpc-status" => {
+                    let status = value.trim().parse().map_to_mm(|e| {
+                        PostGrpcWebErr::BadResponse(format!("Invalid grpc-status '{}': {}", value, e))
+                    })?;
+                    code = Some(status);
+                },
+                "grpc-message" => message = value.trim().to_string(),
+                _ => (),
+            }
+        }
+
+        let code = code.or_mm_err(|| PostGrpcWebErr::BadResponse("Trailers don't contain grpc-status".to_string()))?;
+        Ok(GrpcStatus { code, message })
+    }
+}
+
+/// A server-streaming gRPC-WEB response yielding the decoded messages in the order they arrive.
+///
+/// The stream ends once the trailers are received, the trailing status is then available through [`GrpcWebStream::status`].
+/// A non-OK trailing status is also yielded as [`PostGrpcWebErr::Status`].
+/// Any error (transport, decoding or a truncated body) is yielded once and terminates the stream.
+pub struct GrpcWebStream<Res> {
+    body: BoxStream<'static, Result<Bytes, PostGrpcWebErr>>,
+    buf: BytesMut,
+    status: Option<GrpcStatus>,
+    finished: bool,
+    _response: PhantomData<fn() -> Res>,
+}
+
+impl<Res> GrpcWebStream<Res> {
+    /// Creates a stream decoding the given response body chunks.
+    /// Frames don't have to be aligned with the chunks.
+    pub fn new<S>(body: S) -> Self
+    where
+        S: Stream<Item = Result<Bytes, PostGrpcWebErr>> + Send + 'static,
+    {
+        GrpcWebStream {
+            body: body.boxed(),
+            buf: BytesMut::new(),
+            status: None,
+            finished: false,
+            _response: PhantomData,
+        }
+    }
+
+    /// Returns the trailing status if it has been received already.
+    pub fn status(&self) -> Option<&GrpcStatus> { self.status.as_ref() }
+
+    /// Marks the stream as finished and returns the given `error` as its last item.
+    fn finish_with_err<T>(&mut self, error: PostGrpcWebErr) -> Poll<Option<Result<T, MmError<PostGrpcWebErr>>>> {
+        self.finished = true;
+        Poll::Ready(Some(MmError::err(error)))
+    }
+}
+
+impl<Res> Stream for GrpcWebStream<Res>
+where
+    Res: prost::Message + Default,
+{
+    type Item = Result<Res, MmError<PostGrpcWebErr>>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        loop {
+            if this.finished {
+                return Poll::Ready(None);
+            }
+
+            match split_frame(&mut this.buf) {
+                Some(GrpcWebFrame::Message(payload)) => match Res::decode(payload) {
+                    Ok(msg) => return Poll::Ready(Some(Ok(msg))),
+                    Err(e) => return this.finish_with_err(DecodeBodyError::DecodeError(e).into()),
+                },
+                Some(GrpcWebFrame::Trailers(trailers)) => {
+                    this.finished = true;
+                    let status = match GrpcStatus::from_trailers(&trailers) {
+                        Ok(status) => status,
+                        Err(e) => return Poll::Ready(Some(Err(e))),
+                    };
+                    let result = if status.is_ok() {
+                        None
+                    } else {
+                        Some(MmError::err(PostGrpcWebErr::Status(format!(
+                            "grpc-status: {}, grpc-message: {}",
+                            status.code, status.message
+                        ))))
+                    };
+                    this.status = Some(status);
+                    return Poll::Ready(result);
+                },
+                None => (),
+            }
+
+            match ready!(this.body.poll_next_unpin(cx)) {
+                Some(Ok(chunk)) => this.buf.extend_from_slice(&chunk),
+                Some(Err(e)) => return this.finish_with_err(e),
+                None if this.buf.is_empty() => {
+                    return this.finish_with_err(PostGrpcWebErr::BadResponse(
+                        "The stream ended without trailers".to_string(),
+                    ))
+                },
+                None => {
+                    return this.finish_with_err(PostGrpcWebErr::PayloadTooShort(format!(
+                        "The stream ended in the middle of a frame, {} bytes left",
+                        this.buf.len()
+                    )))
+                },
+            }
+        }
+    }
+}
+
 #[derive(Debug, thiserror::Error, Display)]
 pub enum PostGrpcWebErr {
     DecodeBody(String),
@@ -162,6 +335,43 @@ where
     Ok(reply)
 }
 
+/// Send POST gRPC WEB HTTPS request to a server-streaming method and return the stream of responses
+#[cfg(not(target_arch = "wasm32"))]
+pub async fn post_grpc_web_stream<Req, Res>(url: &str, req: &Req) -> Result<GrpcWebStream<Res>, MmError<PostGrpcWebErr>>
+where
+    Req: prost::Message + Send + 'static,
+    Res: prost::Message + Default + Send + 'static,
+{
+    let request = http::Request::builder()
+        .version(http::Version::HTTP_11)
+        .method(http::Method::POST)
+        .uri(url)
+        .header(CONTENT_TYPE, APPLICATION_GRPC_WEB)
+        .header(ACCEPT, APPLICATION_GRPC_WEB)
+        .body(Body::from(encode_body(req)?))?;
+
+    let uri = url.to_string();
+    let response = drive03(HYPER.request(request))
+        .await
+        .map_to_mm(|e| PostGrpcWebErr::Internal(e.to_string()))?
+        .map_to_mm(|e| PostGrpcWebErr::Transport {
+            uri: uri.clone(),
+            error: e.to_string(),
+        })?;
+    if !response.status().is_success() {
+        return MmError::err(PostGrpcWebErr::BadResponse(format!(
+            "Unexpected HTTP status: {}",
+            response.status()
+        )));
+    }
+
+    let body = response.into_body().map_err(move |e| PostGrpcWebErr::Transport {
+        uri: uri.clone(),
+        error: e.to_string(),
+    });
+    Ok(GrpcWebStream::new(body))
+}
+
 #[cfg(target_arch = "wasm32")]
 pub async fn post_grpc_web<Req, Res>(url: &str, req: &Req) -> Result<Res, MmError<PostGrpcWebErr>>
 where
@@ -182,3 +392,102 @@ where
 
     Ok(reply)
 }
+
+#[cfg(any(test, target_arch = "wasm32"))]
+mod tests {
+    use super::*;
+    use common::{cfg_wasm32, cross_test};
+    use futures::stream;
+
+    cfg_wasm32! {
+        use wasm_bindgen_test::*;
+        wasm_bindgen_test_configure!(run_in_browser);
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    struct TestBlock {
+        #[prost(uint64, tag = "1")]
+        height: u64,
+    }
+
+    fn trailers_frame(trailers: &str) -> Vec<u8> {
+        let mut frame = vec![GRPC_TRAILERS_FLAG];
+        frame.extend_from_slice(&(trailers.len() as u32).to_be_bytes());
+        frame.extend_from_slice(trailers.as_bytes());
+        frame
+    }
+
+    /// Splits the given body into chunks of `chunk_size` bytes, so that the frames span the chunk boundaries.
+    fn mock_body(body: Vec<u8>, chunk_size: usize) -> GrpcWebStream<TestBlock> {
+        let chunks: Vec<_> = body.chunks(chunk_size).map(|c| Ok(Bytes::copy_from_slice(c))).collect();
+        GrpcWebStream::new(stream::iter(chunks))
+    }
+
+    fn blocks_body(heights: &[u64]) -> Vec<u8> {
+        heights
+            .iter()
+            .flat_map(|height| encode_body(&TestBlock { height: *height }).unwrap())
+            .collect()
+    }
+
+    cross_test!(test_grpc_web_stream_decodes_frames_in_order, {
+        let heights = [1, 2, 300, 70000];
+        let mut body = blocks_body(&heights);
+        body.extend(trailers_frame("grpc-status:0\r\ngrpc-message:\r\n"));
+
+        let mut stream = mock_body(body, 3);
+        let mut decoded = Vec::new();
+        while let Some(block) = stream.next().await {
+            decoded.push(block.unwrap().height);
+        }
+        assert_eq!(decoded, heights);
+        assert_eq!(
+            stream.status(),
+            Some(&GrpcStatus {
+                code: 0,
+                message: String::new()
+            })
+        );
+    });
+
+    cross_test!(test_grpc_web_stream_error_status, {
+        let mut body = blocks_body(&[1]);
+        body.extend(trailers_frame("grpc-status: 14\r\ngrpc-message: unavailable\r\n"));
+
+        let mut stream = mock_body(body, 4);
+        assert_eq!(stream.next().await.unwrap().unwrap().height, 1);
+        let err = stream.next().await.unwrap().unwrap_err();
+        assert!(matches!(err.into_inner(), PostGrpcWebErr::Status(_)));
+        assert_eq!(stream.status().unwrap().code, 14);
+        assert!(stream.next().await.is_none());
+    });
+
+    cross_test!(test_grpc_web_stream_mid_stream_error, {
+        let first = Bytes::from(blocks_body(&[1]));
+        let chunks = vec![
+            Ok(first),
+            Err(PostGrpcWebErr::Transport {
+                uri: "http://localhost".to_string(),
+                error: "connection reset".to_string(),
+            }),
+        ];
+        let mut stream: GrpcWebStream<TestBlock> = GrpcWebStream::new(stream::iter(chunks));
+
+        assert_eq!(stream.next().await.unwrap().unwrap().height, 1);
+        let err = stream.next().await.unwrap().unwrap_err();
+        assert!(matches!(err.into_inner(), PostGrpcWebErr::Transport { .. }));
+        assert!(stream.next().await.is_none());
+        assert!(stream.status().is_none());
+    });
+
+    cross_test!(test_grpc_web_stream_truncated_body, {
+        let mut body = blocks_body(&[1, 2]);
+        body.truncate(body.len() - 1);
+
+        let mut stream = mock_body(body, 5);
+        assert_eq!(stream.next().await.unwrap().unwrap().height, 1);
+        let err = stream.next().await.unwrap().unwrap_err();
+        assert!(matches!(err.into_inner(), PostGrpcWebErr::PayloadTooShort(_)));
+        assert!(stream.next().await.is_none());
+    });
+}
@@ -1,13 +1,14 @@
 use common::executor::Timer;
+use common::now_ms;
 use mm2_core::mm_ctx::MmArc;
 use mm2_event_stream::{Broadcaster, Event, EventStreamer, NoDataIn, StreamHandlerInput, StreamerId};
 
 use async_trait::async_trait;
 use futures::channel::oneshot;
-use serde::Deserialize;
-use serde_json::json;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value as Json};
 
-#[derive(Deserialize)]
+#[derive(Deserialize, Serialize)]
 #[serde(deny_unknown_fields, default)]
 pub struct NetworkEventConfig {
     /// The time in seconds to wait after sending network info before sending another one.
@@ -25,6 +26,50 @@ impl Default for NetworkEventConfig {
     }
 }
 
+/// Connectivity changes of this node, sent to the network streamer clients alongside the network info.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum ConnectionStatusEvent {
+    /// The node lost all of its directly connected peers.
+    ConnectionLost { lost_at_ms: u64 },
+    /// The node is connected to peers again after being disconnected for `downtime_ms`.
+    /// Clients should invalidate and refetch any state that could have changed during this gap.
+    ConnectionRestored { restored_at_ms: u64, downtime_ms: u64 },
+}
+
+/// Tracks whether the node is connected to the network and detects disconnect/reconnect transitions.
+#[derive(Default)]
+struct ConnectionMonitor {
+    /// Whether the node was connected the last time it was checked.
+    connected: bool,
+    /// When the connection was lost, if it's currently lost.
+    lost_at_ms: Option<u64>,
+}
+
+impl ConnectionMonitor {
+    /// Updates the connection state and returns an event if the state changed.
+    ///
+    /// No event is returned for the initial connection, since there is no gap for the clients to recover from.
+    fn update(&mut self, connected: bool, now_ms: u64) -> Option<ConnectionStatusEvent> {
+        if connected == self.connected {
+            return None;
+        }
+        self.connected = connected;
+
+        if !connected {
+            self.lost_at_ms = Some(now_ms);
+            return Some(ConnectionStatusEvent::ConnectionLost { lost_at_ms: now_ms });
+        }
+
+        self.lost_at_ms
+            .take()
+            .map(|lost_at_ms| ConnectionStatusEvent::ConnectionRestored {
+                restored_at_ms: now_ms,
+                downtime_ms: now_ms.saturating_sub(lost_at_ms),
+            })
+    }
+}
+
 pub struct NetworkEvent {
     config: NetworkEventConfig,
     ctx: MmArc,
@@ -40,6 +85,8 @@ impl EventStreamer for NetworkEvent {
 
     fn streamer_id(&self) -> StreamerId { StreamerId::Network }
 
+    fn config(&self) -> Option<Json> { serde_json::to_value(&self.config).ok() }
+
     async fn handle(
         self,
         broadcaster: Broadcaster,
@@ -48,6 +95,7 @@ impl EventStreamer for NetworkEvent {
     ) {
         let p2p_ctx = crate::p2p_ctx::P2PContext::fetch_from_mm_arc(&self.ctx);
         let mut previously_sent = json!({});
+        let mut connection_monitor = ConnectionMonitor::default();
 
         ready_tx.send(Ok(())).unwrap();
 
@@ -55,6 +103,10 @@ impl EventStreamer for NetworkEvent {
             let p2p_cmd_tx = p2p_ctx.cmd_tx.lock().clone();
 
             let directly_connected_peers = crate::get_directly_connected_peers(p2p_cmd_tx.clone()).await;
+            if let Some(status_event) = connection_monitor.update(!directly_connected_peers.is_empty(), now_ms()) {
+                let status_data = serde_json::to_value(status_event).expect("Serialization shouldn't fail.");
+                broadcaster.broadcast(Event::new(self.streamer_id(), status_data));
+            }
             let gossip_mesh = crate::get_gossip_mesh(p2p_cmd_tx.clone()).await;
             let gossip_peer_topics = crate::get_gossip_peer_topics(p2p_cmd_tx.clone()).await;
             let gossip_topic_peers = crate::get_gossip_topic_peers(p2p_cmd_tx.clone()).await;
@@ -78,3 +130,35 @@ impl EventStreamer for NetworkEvent {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_connection_lost_and_restored() {
+        let mut monitor = ConnectionMonitor::default();
+
+        // Not connected yet and the initial connection don't produce any events.
+        assert_eq!(monitor.update(false, 1_000), None);
+        assert_eq!(monitor.update(true, 2_000), None);
+        assert_eq!(monitor.update(true, 7_000), None);
+
+        assert_eq!(
+            monitor.update(false, 12_000),
+            Some(ConnectionStatusEvent::ConnectionLost { lost_at_ms: 12_000 })
+        );
+        assert_eq!(monitor.update(false, 17_000), None);
+
+        let restored = monitor.update(true, 42_000).unwrap();
+        assert_eq!(restored, ConnectionStatusEvent::ConnectionRestored {
+            restored_at_ms: 42_000,
+            downtime_ms: 30_000,
+        });
+        assert_eq!(monitor.update(true, 47_000), None);
+
+        let json = serde_json::to_value(restored).unwrap();
+        assert_eq!(json["event"], "connection_restored");
+        assert_eq!(json["downtime_ms"], 30_000);
+    }
+}
@@ -1164,6 +1164,15 @@ pub fn mm_ctx_with_custom_db() -> MmArc {
     MmCtxBuilder::new().with_test_db_namespace().into_mm_arc()
 }
 
+#[cfg(target_arch = "wasm32")]
+pub fn mm_ctx_with_custom_db_with_conf(conf: Option<Json>) -> MmArc {
+    let mut ctx_builder = MmCtxBuilder::new().with_test_db_namespace();
+    if let Some(conf) = conf {
+        ctx_builder = ctx_builder.with_conf(conf);
+    }
+    ctx_builder.into_mm_arc()
+}
+
 #[cfg(not(target_arch = "wasm32"))]
 pub fn mm_ctx_with_custom_db() -> MmArc {
     mm_ctx_with_custom_db_with_conf(None)